@@ -0,0 +1,38 @@
+use std::io;
+use std::path::PathBuf;
+
+use crate::Problem;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{}", path.display())]
+    Elf {
+        path: PathBuf,
+        source: linaro_elf::Error,
+    },
+    #[error("{} is not a directory", .0.display())]
+    NotADirectory(PathBuf),
+    #[error("{} would not load: {}", binary.display(), join(problems))]
+    Unresolved {
+        binary: PathBuf,
+        problems: Vec<Problem>,
+    },
+    #[error("{0} not found in the rootfs")]
+    NotFound(String),
+    #[error("{} has no host programs", .0.display())]
+    NoHostPrograms(PathBuf),
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
+
+fn join(problems: &[Problem]) -> String {
+    let problems: Vec<_> = problems.iter().map(ToString::to_string).collect();
+    problems.join("; ")
+}
@@ -0,0 +1,376 @@
+//! Checks that a binary built against the Linaro sysroot will load on a
+//! device, given the device's root filesystem.
+//!
+//! [`Checker::check`] repeats what the target's dynamic linker does at
+//! startup: it looks for the interpreter, resolves `DT_NEEDED` libraries
+//! transitively through `DT_RPATH`/`DT_RUNPATH`, `ld.so.conf` and the
+//! default directories of the [`Rootfs`], and confirms every symbol version
+//! an object requires (`GLIBC_2.28` from `libc.so.6`, say) is defined by
+//! the library that was found.
+//!
+//! [`stage::stage`] uses the same resolution to copy what binaries load out
+//! of a toolchain's sysroot, with its GCC runtime libraries overlaid (see
+//! [`Rootfs::overlay`]), for device images, and [`preflight::preflight`]
+//! to check a toolchain's own programs against the build host.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use linaro_elf::symbols::{self, VersionNeed};
+use linaro_elf::{Class, ElfInfo};
+use serde::Serialize;
+
+mod error;
+pub mod preflight;
+mod rootfs;
+pub mod stage;
+
+pub use error::Error;
+pub use rootfs::Rootfs;
+
+/// Why a binary would not load on the device.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "problem", rename_all = "kebab-case")]
+pub enum Problem {
+    /// `PT_INTERP` names a file the rootfs does not have.
+    MissingInterpreter { path: String },
+    /// `PT_INTERP` exists but cannot load this binary.
+    IncompatibleInterpreter { path: String, reason: String },
+    /// No loadable library called `name` is on the search path.
+    MissingLibrary {
+        name: String,
+        needed_by: String,
+        /// Files with the right name that were skipped, and why.
+        incompatible: Vec<String>,
+    },
+    /// `library` was found but does not define `version`.
+    MissingVersion {
+        version: String,
+        library: String,
+        needed_by: String,
+        /// The newest version of the same family the library does define,
+        /// e.g. `GLIBC_2.27` when `GLIBC_2.28` is missing.
+        newest: Option<String>,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::MissingInterpreter { path } => write!(f, "interpreter {path} not found"),
+            Problem::IncompatibleInterpreter { path, reason } => {
+                write!(f, "interpreter {path} cannot be used: {reason}")
+            }
+            Problem::MissingLibrary {
+                name,
+                needed_by,
+                incompatible,
+            } => {
+                write!(f, "{name} (needed by {needed_by}) not found")?;
+                if !incompatible.is_empty() {
+                    write!(f, "; skipped {}", incompatible.join(", "))?;
+                }
+                Ok(())
+            }
+            Problem::MissingVersion {
+                version,
+                library,
+                needed_by,
+                newest,
+            } => {
+                write!(
+                    f,
+                    "{library} does not provide {version} (needed by {needed_by})"
+                )?;
+                if let Some(newest) = newest {
+                    write!(f, "; newest is {newest}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A `DT_NEEDED` library and where it was found on the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Resolved {
+    pub name: String,
+    pub path: String,
+}
+
+/// The outcome of checking one binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub binary: PathBuf,
+    pub interpreter: Option<String>,
+    /// Every library the binary loads, directly or not, in load order.
+    pub libraries: Vec<Resolved>,
+    pub problems: Vec<Problem>,
+}
+
+impl Report {
+    pub fn ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Checks binaries against a [`Rootfs`].
+#[derive(Debug, Clone)]
+pub struct Checker {
+    rootfs: Rootfs,
+    install_dir: String,
+    /// Whether `install_dir` is a directory on the build host rather than
+    /// in the rootfs.
+    on_host: bool,
+}
+
+/// A loaded object, as far as the checks are concerned.
+struct Object {
+    /// Target path, or the host path for the binary being checked.
+    name: String,
+    info: ElfInfo,
+    needs: Vec<VersionNeed>,
+    definitions: Vec<String>,
+}
+
+impl Checker {
+    pub fn new(rootfs: Rootfs) -> Self {
+        Self {
+            rootfs,
+            install_dir: "/usr/bin".to_owned(),
+            on_host: false,
+        }
+    }
+
+    /// Sets the directory the binary will be installed in on the target,
+    /// which `$ORIGIN` in its run paths expands to. Defaults to `/usr/bin`.
+    pub fn install_dir(mut self, dir: impl Into<String>) -> Self {
+        self.install_dir = dir.into();
+        self.on_host = false;
+        self
+    }
+
+    /// For a binary that runs where it is, such as a toolchain's own
+    /// programs: sets its absolute directory on the build host, and looks
+    /// libraries its `$ORIGIN` run paths lead to up there rather than in
+    /// the rootfs.
+    pub fn host_dir(mut self, dir: &Path) -> Self {
+        self.install_dir = dir.display().to_string();
+        self.on_host = true;
+        self
+    }
+
+    pub fn rootfs(&self) -> &Rootfs {
+        &self.rootfs
+    }
+
+    pub fn check(&self, binary: &Path) -> Result<Report, Error> {
+        let bytes = fs::read(binary).map_err(|source| Error::Io {
+            path: binary.to_owned(),
+            source,
+        })?;
+        let elf = |source| Error::Elf {
+            path: binary.to_owned(),
+            source,
+        };
+        let root = Object {
+            name: binary.display().to_string(),
+            info: ElfInfo::parse(&bytes).map_err(elf)?,
+            needs: symbols::version_needs(&bytes).map_err(elf)?,
+            definitions: Vec::new(),
+        };
+        let mut problems = Vec::new();
+        let interpreter = root.info.interpreter.clone();
+        if let Some(path) = &interpreter {
+            if let Some(problem) = self.check_interpreter(path, &root.info) {
+                problems.push(problem);
+            }
+        }
+
+        // Breadth-first, as the dynamic linker loads libraries.
+        let mut objects = vec![(root, (self.install_dir.clone(), self.on_host))];
+        let mut loaded: BTreeMap<String, usize> = BTreeMap::new();
+        let mut libraries = Vec::new();
+        let mut queue = VecDeque::from([0]);
+        while let Some(index) = queue.pop_front() {
+            let (object, origin) = &objects[index];
+            let mut found = Vec::new();
+            for name in &object.info.needed {
+                if loaded.contains_key(name) || found.iter().any(|(n, _, _)| n == name) {
+                    continue;
+                }
+                match self.find_library(name, object, origin, &objects[0].0.info) {
+                    Ok((path, library)) => found.push((name.clone(), path, library)),
+                    Err(incompatible) => problems.push(Problem::MissingLibrary {
+                        name: name.clone(),
+                        needed_by: object.name.clone(),
+                        incompatible,
+                    }),
+                }
+            }
+            for (name, (path, on_host), library) in found {
+                let origin = path.rsplit_once('/').map_or("", |(dir, _)| dir).to_owned();
+                let origin = (origin, on_host);
+                libraries.push(Resolved {
+                    name: name.clone(),
+                    path,
+                });
+                loaded.insert(name, objects.len());
+                queue.push_back(objects.len());
+                objects.push((library, origin));
+            }
+        }
+
+        for (object, _) in &objects {
+            for need in &object.needs {
+                let Some(&provider) = loaded.get(&need.file) else {
+                    continue;
+                };
+                let definitions = &objects[provider].0.definitions;
+                for version in &need.versions {
+                    if !definitions.contains(version) {
+                        problems.push(Problem::MissingVersion {
+                            version: version.clone(),
+                            library: objects[provider].0.name.clone(),
+                            needed_by: object.name.clone(),
+                            newest: newest_of_family(version, definitions),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(Report {
+            binary: binary.to_owned(),
+            interpreter,
+            libraries,
+            problems,
+        })
+    }
+
+    fn check_interpreter(&self, path: &str, binary: &ElfInfo) -> Option<Problem> {
+        let Some(host) = self.rootfs.resolve(path).filter(|p| p.is_file()) else {
+            return Some(Problem::MissingInterpreter {
+                path: path.to_owned(),
+            });
+        };
+        let reason = match fs::read(&host).map(|bytes| ElfInfo::parse(&bytes)) {
+            Err(err) => err.to_string(),
+            Ok(Err(_)) => "not an ELF file".to_owned(),
+            Ok(Ok(info)) => incompatibility(&info, binary)?,
+        };
+        Some(Problem::IncompatibleInterpreter {
+            path: path.to_owned(),
+            reason,
+        })
+    }
+
+    /// Searches for `name` on behalf of `object`, whose directory is
+    /// `origin` (on the host if `origin.1`, else on the target). Returns
+    /// where it was found, with whether that is on the host, and its
+    /// contents; or the candidates that were rejected.
+    fn find_library(
+        &self,
+        name: &str,
+        object: &Object,
+        (origin, on_host): &(String, bool),
+        binary: &ElfInfo,
+    ) -> Result<((String, bool), Object), Vec<String>> {
+        let expand = |dir: &String| {
+            let expanded = dir.replace("${ORIGIN}", origin).replace("$ORIGIN", origin);
+            (*on_host && expanded != *dir, expanded)
+        };
+        let info = &object.info;
+        let rpath = if info.runpath.is_empty() {
+            &info.rpath[..]
+        } else {
+            &[]
+        };
+        let candidates: Vec<(bool, String)> = if name.contains('/') {
+            vec![(false, name.to_owned())]
+        } else {
+            rpath
+                .iter()
+                .chain(&info.runpath)
+                .map(expand)
+                .chain(self.rootfs.search_dirs().iter().map(|d| (false, d.clone())))
+                .map(|(on_host, dir)| (on_host, format!("{}/{name}", dir.trim_end_matches('/'))))
+                .collect()
+        };
+
+        let mut incompatible = Vec::new();
+        for (on_host, path) in candidates {
+            let host = if on_host {
+                Some(PathBuf::from(&path))
+            } else {
+                self.rootfs.resolve(&path)
+            };
+            let Some(host) = host.filter(|p| p.is_file()) else {
+                continue;
+            };
+            let Ok(bytes) = fs::read(&host) else {
+                continue;
+            };
+            let parsed = ElfInfo::parse(&bytes).and_then(|info| {
+                Ok((
+                    info,
+                    symbols::version_needs(&bytes)?,
+                    symbols::version_definitions(&bytes)?,
+                ))
+            });
+            match parsed {
+                Err(_) => incompatible.push(format!("{path} (not an ELF file)")),
+                Ok((info, needs, definitions)) => match incompatibility(&info, binary) {
+                    Some(reason) => incompatible.push(format!("{path} ({reason})")),
+                    None => {
+                        return Ok((
+                            (path.clone(), on_host),
+                            Object {
+                                name: path,
+                                info,
+                                needs,
+                                definitions,
+                            },
+                        ))
+                    }
+                },
+            }
+        }
+        Err(incompatible)
+    }
+}
+
+/// Why `library` cannot be loaded into `binary`, if it cannot.
+fn incompatibility(library: &ElfInfo, binary: &ElfInfo) -> Option<String> {
+    if library.machine != binary.machine {
+        return Some(format!(
+            "{} rather than {}",
+            library.machine_name(),
+            binary.machine_name()
+        ));
+    }
+    if library.class != binary.class {
+        let bits = |class| if class == Class::Elf64 { 64 } else { 32 };
+        return Some(format!("{}-bit", bits(library.class)));
+    }
+    None
+}
+
+/// The newest version in `definitions` from the same family as `version`,
+/// e.g. the highest `GLIBC_2.*` for `GLIBC_2.28`.
+fn newest_of_family(version: &str, definitions: &[String]) -> Option<String> {
+    let (family, _) = version.rsplit_once('_')?;
+    let key = |v: &str| -> Option<Vec<u32>> {
+        let (f, number) = v.rsplit_once('_')?;
+        (f == family)
+            .then(|| number.split('.').map(|n| n.parse().ok()).collect())
+            .flatten()
+    };
+    definitions
+        .iter()
+        .filter_map(|d| Some((key(d)?, d)))
+        .max()
+        .map(|(_, d)| d.clone())
+}
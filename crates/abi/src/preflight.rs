@@ -0,0 +1,202 @@
+//! Whether a build host can run a toolchain's own programs.
+//!
+//! Linaro links its host tools against a fairly old glibc, but every
+//! release still needs some minimum, and a musl-based container has no
+//! glibc at all; either way the compiler only fails, with a loader error,
+//! once the build reaches it. [`preflight`] checks every host program of a
+//! toolchain (`bin`, `<target>/bin` and `libexec`) against the host's root
+//! filesystem the way [`Checker`] checks target binaries, and sums up what
+//! they need.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use linaro_elf::symbols;
+use linaro_elf::{ElfInfo, Kind};
+use serde::Serialize;
+
+use crate::{incompatibility, newest_of_family, Checker, Error, Problem, Rootfs};
+
+/// What a toolchain's host programs need and what the host lacks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Preflight {
+    /// Programs and plugins checked, relative to the toolchain.
+    pub binaries: Vec<PathBuf>,
+    /// The newest `GLIBC_x.y` any of them requires.
+    pub required_glibc: Option<String>,
+    /// The newest `GLIBC_x.y` the host's `libc.so.6` defines; `None` if
+    /// the host has no glibc for these programs.
+    pub host_glibc: Option<String>,
+    /// Libraries the programs need from the host, by `DT_NEEDED` name.
+    pub libraries: Vec<String>,
+    pub issues: Vec<Issue>,
+}
+
+/// A problem shared by one or more programs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Issue {
+    /// The problem as found for the first of `binaries`.
+    pub problem: Problem,
+    /// Programs affected, relative to the toolchain.
+    pub binaries: Vec<PathBuf>,
+}
+
+impl Preflight {
+    pub fn ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// A one-line summary for people.
+    pub fn verdict(&self) -> String {
+        if self.ok() {
+            return "the host can run this toolchain".to_owned();
+        }
+        match (&self.required_glibc, &self.host_glibc) {
+            (Some(required), None) => {
+                format!("the host has no glibc these programs can use (musl, or another architecture?); the toolchain needs {required}")
+            }
+            (Some(required), Some(host)) if newer(required, host) => format!(
+                "the host's glibc is too old: it provides up to {host}, the toolchain needs {required}"
+            ),
+            _ => "the host cannot run this toolchain".to_owned(),
+        }
+    }
+}
+
+/// Checks the host programs of the toolchain installed at `toolchain`
+/// against `host`, usually `Rootfs::open("/")`.
+pub fn preflight(toolchain: &Path, host: &Rootfs) -> Result<Preflight, Error> {
+    // `$ORIGIN` must expand to where the programs really are.
+    let toolchain = &fs::canonicalize(toolchain).map_err(Error::io(toolchain))?;
+    let mut binaries = Vec::new();
+    for dir in program_dirs(toolchain)? {
+        collect_programs(toolchain, &dir, &mut binaries)?;
+    }
+    binaries.sort_by(|a, b| a.0.cmp(&b.0));
+    let Some((_, first)) = binaries.first() else {
+        return Err(Error::NoHostPrograms(toolchain.to_owned()));
+    };
+    let host_glibc = host_glibc(host, first);
+
+    let mut required = Vec::new();
+    let mut libraries = BTreeSet::new();
+    let mut issues: Vec<Issue> = Vec::new();
+    for (relative, info) in &binaries {
+        let path = toolchain.join(relative);
+        let bytes = fs::read(&path).map_err(Error::io(&path))?;
+        let needs = symbols::version_needs(&bytes).map_err(|source| Error::Elf {
+            path: path.clone(),
+            source,
+        })?;
+        required.extend(needs.into_iter().flat_map(|n| n.versions));
+        libraries.extend(info.needed.iter().cloned());
+
+        let dir = path.parent().unwrap_or(toolchain);
+        let report = Checker::new(host.clone()).host_dir(dir).check(&path)?;
+        let name = path.display().to_string();
+        for mut problem in report.problems {
+            // Name the program relative to the toolchain, and group the
+            // same problem across programs.
+            if let Problem::MissingLibrary { needed_by, .. }
+            | Problem::MissingVersion { needed_by, .. } = &mut problem
+            {
+                if *needed_by == name {
+                    *needed_by = relative.display().to_string();
+                }
+            }
+            let key = without_binary(&problem, relative);
+            match issues
+                .iter_mut()
+                .find(|i| without_binary(&i.problem, &i.binaries[0]) == key)
+            {
+                Some(issue) => issue.binaries.push(relative.clone()),
+                None => issues.push(Issue {
+                    problem,
+                    binaries: vec![relative.clone()],
+                }),
+            }
+        }
+    }
+
+    Ok(Preflight {
+        binaries: binaries.into_iter().map(|(path, _)| path).collect(),
+        required_glibc: newest_of_family("GLIBC_2.0", &required),
+        host_glibc,
+        libraries: libraries.into_iter().collect(),
+        issues,
+    })
+}
+
+/// `problem` with a `needed_by` naming `binary` blanked out.
+fn without_binary(problem: &Problem, binary: &Path) -> Problem {
+    let mut problem = problem.clone();
+    if let Problem::MissingLibrary { needed_by, .. } | Problem::MissingVersion { needed_by, .. } =
+        &mut problem
+    {
+        if Path::new(needed_by) == binary {
+            needed_by.clear();
+        }
+    }
+    problem
+}
+
+/// Directories holding host programs: `bin`, `libexec` and the binutils
+/// directory `<target>/bin`.
+fn program_dirs(toolchain: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut dirs = vec![toolchain.join("bin"), toolchain.join("libexec")];
+    for entry in fs::read_dir(toolchain).map_err(Error::io(toolchain))? {
+        let entry = entry.map_err(Error::io(toolchain))?;
+        let bin = entry.path().join("bin");
+        if !dirs.contains(&bin) && entry.path() != toolchain.join("libexec") {
+            dirs.push(bin);
+        }
+    }
+    Ok(dirs.into_iter().filter(|d| d.is_dir()).collect())
+}
+
+/// Appends the ELF executables and shared objects below `dir` to `out`.
+/// Symlinks are skipped; they point at programs found elsewhere.
+fn collect_programs(
+    toolchain: &Path,
+    dir: &Path,
+    out: &mut Vec<(PathBuf, ElfInfo)>,
+) -> Result<(), Error> {
+    for entry in fs::read_dir(dir).map_err(Error::io(dir))? {
+        let entry = entry.map_err(Error::io(dir))?;
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(Error::io(&path))?;
+        if file_type.is_dir() {
+            collect_programs(toolchain, &path, out)?;
+        } else if file_type.is_file() {
+            let Ok(info) = ElfInfo::read(&path) else {
+                continue;
+            };
+            if matches!(info.kind, Kind::Executable | Kind::Shared) {
+                let relative = path.strip_prefix(toolchain).unwrap_or(&path).to_owned();
+                out.push((relative, info));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The newest `GLIBC_x.y` of the first `libc.so.6` on the host's search
+/// path that `program` could load.
+fn host_glibc(host: &Rootfs, program: &ElfInfo) -> Option<String> {
+    host.search_dirs().iter().find_map(|dir| {
+        let path = host.resolve(&format!("{}/libc.so.6", dir.trim_end_matches('/')))?;
+        let bytes = fs::read(path).ok()?;
+        let info = ElfInfo::parse(&bytes).ok()?;
+        if incompatibility(&info, program).is_some() {
+            return None;
+        }
+        let definitions = symbols::version_definitions(&bytes).ok()?;
+        newest_of_family("GLIBC_2.0", &definitions)
+    })
+}
+
+/// Whether version `a` is newer than `b`, both of the same family.
+fn newer(a: &str, b: &str) -> bool {
+    newest_of_family(a, &[a.to_owned(), b.to_owned()]).as_deref() == Some(a) && a != b
+}
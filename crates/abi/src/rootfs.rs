@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use linaro_glob::wildcard_match;
+
+use crate::Error;
+
+/// Directories the dynamic linker searches after those from `ld.so.conf`.
+const DEFAULT_DIRS: &[&str] = &["/lib", "/usr/lib", "/lib64", "/usr/lib64"];
+
+/// How many symlinks [`Rootfs::resolve`] follows before giving up.
+pub(crate) const MAX_SYMLINKS: usize = 40;
+
+/// A target root filesystem unpacked on the host.
+#[derive(Debug, Clone)]
+pub struct Rootfs {
+    root: PathBuf,
+    search_dirs: Vec<String>,
+    /// Host directories whose files appear in a target directory, behind
+    /// the rootfs's own.
+    overlays: Vec<(String, PathBuf)>,
+}
+
+impl Rootfs {
+    /// Opens the rootfs at `root`, reading its `/etc/ld.so.conf` (and the
+    /// files it includes) for the library search path.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, Error> {
+        let root = root.into();
+        if !root.is_dir() {
+            return Err(Error::NotADirectory(root));
+        }
+        let mut rootfs = Self {
+            root,
+            search_dirs: Vec::new(),
+            overlays: Vec::new(),
+        };
+        let mut dirs = Vec::new();
+        rootfs.read_ld_so_conf("/etc/ld.so.conf", &mut dirs, 0);
+        dirs.extend(DEFAULT_DIRS.iter().map(|d| d.to_string()));
+        for dir in dirs {
+            if !rootfs.search_dirs.contains(&dir) {
+                rootfs.search_dirs.push(dir);
+            }
+        }
+        Ok(rootfs)
+    }
+
+    /// Makes the files of the host directory `host` appear in the target
+    /// directory `dir` wherever the rootfs has none of that name, as when
+    /// a toolchain's runtime libraries are installed next to its sysroot's.
+    /// `dir`'s parent must exist in the rootfs. `dir` is added to the end
+    /// of the search path if not already on it.
+    pub fn overlay(mut self, dir: impl Into<String>, host: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let dir = format!("/{}", dir.trim_matches('/'));
+        if !self.search_dirs.contains(&dir) {
+            self.search_dirs.push(dir.clone());
+        }
+        self.overlays.push((dir, host.into()));
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The library directories searched, as paths on the target.
+    pub fn search_dirs(&self) -> &[String] {
+        &self.search_dirs
+    }
+
+    /// The host path of the absolute target path `path`, following
+    /// symlinks as the target would, so absolute link targets stay inside
+    /// the rootfs. `None` if it does not exist.
+    pub fn resolve(&self, path: &str) -> Option<PathBuf> {
+        self.locate(path, true)
+    }
+
+    /// Like [`resolve`](Self::resolve), but does not follow `path` itself
+    /// if it is a symlink.
+    pub fn lookup(&self, path: &str) -> Option<PathBuf> {
+        self.locate(path, false)
+    }
+
+    fn locate(&self, path: &str, follow_last: bool) -> Option<PathBuf> {
+        let mut pending: Vec<String> = components(path).rev().collect();
+        // Target components resolved so far, with the host path of each.
+        let mut resolved: Vec<(String, PathBuf)> = Vec::new();
+        let mut links = 0;
+        while let Some(component) = pending.pop() {
+            match component.as_str() {
+                "." => continue,
+                ".." => {
+                    resolved.pop();
+                    continue;
+                }
+                _ => {}
+            }
+            let host = self.child(&resolved, &component)?;
+            let is_link = fs::symlink_metadata(&host).ok()?.file_type().is_symlink();
+            if !is_link || (!follow_last && pending.is_empty()) {
+                resolved.push((component, host));
+                continue;
+            }
+            links += 1;
+            if links > MAX_SYMLINKS {
+                return None;
+            }
+            let target = fs::read_link(&host).ok()?;
+            let target = target.to_str()?;
+            if target.starts_with('/') {
+                resolved.clear();
+            }
+            pending.extend(components(target).rev());
+        }
+        Some(
+            resolved
+                .pop()
+                .map_or_else(|| self.root.clone(), |(_, host)| host),
+        )
+    }
+
+    /// The host path of `component` in the resolved directory `dir`, from
+    /// the rootfs or else an overlay.
+    fn child(&self, dir: &[(String, PathBuf)], component: &str) -> Option<PathBuf> {
+        let parent = dir.last().map_or(&self.root, |(_, host)| host);
+        let host = parent.join(component);
+        if fs::symlink_metadata(&host).is_ok() {
+            return Some(host);
+        }
+        let target: String = dir.iter().map(|(c, _)| format!("/{c}")).collect();
+        let path = format!("{target}/{component}");
+        let target = if target.is_empty() { "/" } else { &target };
+        self.overlays.iter().find_map(|(overlay, host)| {
+            if *overlay == path {
+                // The overlaid directory itself, absent from the rootfs.
+                return Some(host.clone());
+            }
+            let host = host.join(component);
+            (overlay == target && fs::symlink_metadata(&host).is_ok()).then_some(host)
+        })
+    }
+
+    /// Appends the directories listed in the `ld.so.conf`-format file at
+    /// target path `conf` to `dirs`. Unreadable files are ignored, as
+    /// `ldconfig` does.
+    fn read_ld_so_conf(&self, conf: &str, dirs: &mut Vec<String>, depth: usize) {
+        if depth > 8 {
+            return;
+        }
+        let Some(text) = self.resolve(conf).and_then(|p| fs::read_to_string(p).ok()) else {
+            return;
+        };
+        let conf_dir = conf.rsplit_once('/').map_or("/", |(dir, _)| dir);
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if let Some(pattern) = line.strip_prefix("include") {
+                let pattern = pattern.trim();
+                let pattern = if pattern.starts_with('/') {
+                    pattern.to_owned()
+                } else {
+                    format!("{conf_dir}/{pattern}")
+                };
+                for file in self.glob(&pattern) {
+                    self.read_ld_so_conf(&file, dirs, depth + 1);
+                }
+            } else if !line.starts_with("hwcap") {
+                dirs.extend(
+                    line.split(|c: char| c.is_whitespace() || c == ':' || c == ',')
+                        .filter(|d| d.starts_with('/'))
+                        .map(|d| d.trim_end_matches('/').to_owned()),
+                );
+            }
+        }
+    }
+
+    /// Expands a target path whose last component may contain `*` or `?`,
+    /// in sorted order.
+    fn glob(&self, pattern: &str) -> Vec<String> {
+        let (dir, name) = pattern.rsplit_once('/').unwrap_or(("", pattern));
+        if !name.contains(['*', '?']) {
+            return vec![pattern.to_owned()];
+        }
+        let Some(entries) = self.resolve(dir).and_then(|p| fs::read_dir(p).ok()) else {
+            return Vec::new();
+        };
+        let mut matches: Vec<_> = entries
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .filter(|file| wildcard_match(name, file))
+            .map(|file| format!("{dir}/{file}"))
+            .collect();
+        matches.sort();
+        matches
+    }
+}
+
+fn components(path: &str) -> impl DoubleEndedIterator<Item = String> + '_ {
+    path.split('/').filter(|c| !c.is_empty()).map(str::to_owned)
+}
@@ -0,0 +1,152 @@
+//! Copying the libraries binaries need out of a sysroot, for device images
+//! that ship glibc-linked programs.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+
+use linaro_manifest::{sha256_file, Sha256};
+use serde::{Deserialize, Serialize};
+
+use crate::rootfs::MAX_SYMLINKS;
+use crate::{Checker, Error};
+
+/// Name of the manifest written at the top of the staging directory.
+pub const MANIFEST_FILE: &str = ".linaro-prebuilts-staged.json";
+
+/// What [`stage`] wrote, as recorded in [`MANIFEST_FILE`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Staged {
+    /// The binaries the libraries were staged for.
+    pub binaries: Vec<PathBuf>,
+    /// Every file and symlink written, by target path.
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Entry {
+    File {
+        path: String,
+        /// Where it was copied from.
+        source: PathBuf,
+        size: u64,
+        sha256: Sha256,
+    },
+    Symlink {
+        path: String,
+        target: String,
+    },
+}
+
+impl Entry {
+    /// The absolute target path.
+    pub fn path(&self) -> &str {
+        match self {
+            Entry::File { path, .. } | Entry::Symlink { path, .. } => path,
+        }
+    }
+}
+
+/// Copies the interpreter and every library `binaries` load, as found by
+/// `checker`, into `out` at their target paths, recreating the symlinks
+/// that lead to them (`libstdc++.so.6 -> libstdc++.so.6.0.24`). Nothing is
+/// written unless every binary would load.
+pub fn stage(checker: &Checker, binaries: &[PathBuf], out: &Path) -> Result<Staged, Error> {
+    let mut paths = Vec::new();
+    for binary in binaries {
+        let report = checker.check(binary)?;
+        if !report.ok() {
+            return Err(Error::Unresolved {
+                binary: binary.clone(),
+                problems: report.problems,
+            });
+        }
+        paths.extend(report.interpreter);
+        paths.extend(report.libraries.into_iter().map(|l| l.path));
+    }
+
+    // Planned entries by target path, with the host file of each.
+    let rootfs = checker.rootfs();
+    let mut planned: BTreeMap<String, Result<PathBuf, String>> = BTreeMap::new();
+    for path in paths {
+        let mut path = normalize("/", &path);
+        for _ in 0..=MAX_SYMLINKS {
+            if planned.contains_key(&path) {
+                break;
+            }
+            let host = rootfs
+                .lookup(&path)
+                .ok_or_else(|| Error::NotFound(path.clone()))?;
+            let metadata = fs::symlink_metadata(&host).map_err(Error::io(&host))?;
+            if !metadata.file_type().is_symlink() {
+                planned.insert(path, Ok(host));
+                break;
+            }
+            let target = fs::read_link(&host).map_err(Error::io(&host))?;
+            let target = target.to_string_lossy().into_owned();
+            let next = normalize(path.rsplit_once('/').map_or("/", |(dir, _)| dir), &target);
+            planned.insert(path, Err(target));
+            path = next;
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (path, plan) in planned {
+        let dest = out.join(path.trim_start_matches('/'));
+        let parent = dest.parent().expect("target paths are absolute");
+        fs::create_dir_all(parent).map_err(Error::io(parent))?;
+        match fs::remove_file(&dest) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(Error::io(&dest)(err)),
+            _ => {}
+        }
+        entries.push(match plan {
+            Ok(source) => {
+                fs::copy(&source, &dest).map_err(Error::io(&source))?;
+                let (sha256, size) = sha256_file(&dest).map_err(Error::io(&dest))?;
+                Entry::File {
+                    path,
+                    source,
+                    size,
+                    sha256,
+                }
+            }
+            Err(target) => {
+                symlink(&target, &dest).map_err(Error::io(&dest))?;
+                Entry::Symlink { path, target }
+            }
+        });
+    }
+
+    let staged = Staged {
+        binaries: binaries.to_vec(),
+        entries,
+    };
+    let manifest = out.join(MANIFEST_FILE);
+    let text = serde_json::to_string_pretty(&staged).expect("manifests serialize") + "\n";
+    fs::write(&manifest, text).map_err(Error::io(&manifest))?;
+    Ok(staged)
+}
+
+/// The absolute target path `path` refers to from the directory `dir`,
+/// without `.` and `..` components.
+fn normalize(dir: &str, path: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    let joined = if path.starts_with('/') {
+        path.to_owned()
+    } else {
+        format!("{dir}/{path}")
+    };
+    for component in joined.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            _ => components.push(component),
+        }
+    }
+    format!("/{}", components.join("/"))
+}
@@ -0,0 +1,371 @@
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+
+use linaro_abi::stage::{self, Entry, Staged, MANIFEST_FILE};
+use linaro_abi::{Checker, Error, Problem, Rootfs};
+
+mod common;
+
+use common::{fixture, gcc};
+
+/// Host-built stand-ins for a device's libraries and an app linked against
+/// them: `libfoo-1.so` (FOO_1), `libfoo-2.so` (FOO_1 and FOO_2),
+/// `libbar.so.1` (needs libfoo) and `app`, which needs `foo_c@FOO_2` and
+/// finds libbar through `RUNPATH=$ORIGIN/../lib/app`. `None` without a host
+/// GCC.
+fn build(dir: &Path) -> Option<()> {
+    let out = |name: &str| dir.join(name);
+    let soname = "-Wl,-soname,libfoo.so.1";
+    for version in ["1", "2"] {
+        let script = format!(
+            "-Wl,--version-script={}",
+            fixture(&format!("libfoo-{version}.map")).display()
+        );
+        let lib = out(&format!("libfoo-{version}.so"));
+        if !gcc(
+            &["-shared", soname, &script],
+            &lib,
+            &format!("libfoo-{version}.c"),
+        ) {
+            return None;
+        }
+    }
+    fs::copy(out("libfoo-2.so"), out("libfoo.so.1")).unwrap();
+    let link_dir = format!("-L{}", dir.display());
+    let built = gcc(
+        &[
+            "-shared",
+            "-Wl,-soname,libbar.so.1",
+            &link_dir,
+            "-l:libfoo.so.1",
+        ],
+        &out("libbar.so.1"),
+        "libbar.c",
+    ) && gcc(
+        &[
+            "-Wl,-e,main",
+            "-Wl,--enable-new-dtags,-rpath,$ORIGIN/../lib/app",
+            &link_dir,
+            "-l:libfoo.so.1",
+            "-l:libbar.so.1",
+        ],
+        &out("app"),
+        "app.c",
+    );
+    built.then_some(())
+}
+
+/// A rootfs in which `app` loads.
+fn rootfs(dir: &Path, build: &Path) -> PathBuf {
+    let root = dir.join("rootfs");
+    for sub in ["lib", "lib64", "usr/bin", "usr/lib/app", "etc/ld.so.conf.d"] {
+        fs::create_dir_all(root.join(sub)).unwrap();
+    }
+    fs::copy(build.join("libfoo-2.so"), root.join("lib/libfoo-2.so")).unwrap();
+    symlink("/lib/libfoo-2.so", root.join("lib/libfoo.so.1")).unwrap();
+    fs::copy(
+        build.join("libbar.so.1"),
+        root.join("usr/lib/app/libbar.so.1"),
+    )
+    .unwrap();
+    // Any x86-64 ELF file will do as the interpreter.
+    symlink(
+        "../lib/libfoo-2.so",
+        root.join("lib64/ld-linux-x86-64.so.2"),
+    )
+    .unwrap();
+    fs::write(
+        root.join("etc/ld.so.conf"),
+        "# comment\ninclude /etc/ld.so.conf.d/*.conf\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("etc/ld.so.conf.d/vendor.conf"),
+        "/vendor/lib64 /opt/lib\n",
+    )
+    .unwrap();
+    root
+}
+
+fn setup() -> Option<(tempfile::TempDir, PathBuf, PathBuf)> {
+    let (dir, build_dir) = common::setup(build)?;
+    let root = rootfs(dir.path(), &build_dir);
+    Some((dir, build_dir.join("app"), root))
+}
+
+#[test]
+fn reads_ld_so_conf() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("etc/ld.so.conf.d")).unwrap();
+    fs::write(
+        root.join("etc/ld.so.conf"),
+        "include ld.so.conf.d/*.conf\n/usr/local/lib\n",
+    )
+    .unwrap();
+    fs::write(root.join("etc/ld.so.conf.d/b.conf"), "/b\n").unwrap();
+    fs::write(root.join("etc/ld.so.conf.d/a.conf"), "/a:/a2,/lib/\n").unwrap();
+    fs::write(root.join("etc/ld.so.conf.d/skip.txt"), "/skipped\n").unwrap();
+    let rootfs = Rootfs::open(root).unwrap();
+    assert_eq!(
+        rootfs.search_dirs(),
+        [
+            "/a",
+            "/a2",
+            "/lib",
+            "/b",
+            "/usr/local/lib",
+            "/usr/lib",
+            "/lib64",
+            "/usr/lib64"
+        ]
+    );
+}
+
+#[test]
+fn resolves_symlinks_inside_rootfs() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("usr/lib")).unwrap();
+    fs::write(root.join("usr/lib/real.so"), "").unwrap();
+    symlink("usr/lib", root.join("lib")).unwrap();
+    symlink("/lib/real.so", root.join("usr/lib/abs.so")).unwrap();
+    symlink("/", root.join("usr/lib/up")).unwrap();
+    let rootfs = Rootfs::open(root).unwrap();
+    assert_eq!(
+        rootfs.resolve("/lib/abs.so"),
+        Some(root.join("usr/lib/real.so"))
+    );
+    assert_eq!(
+        rootfs.resolve("/usr/lib/up/../../lib/real.so"),
+        Some(root.join("usr/lib/real.so"))
+    );
+    assert_eq!(rootfs.resolve("/lib/missing.so"), None);
+}
+
+#[test]
+fn overlays_host_directories() {
+    let dir = tempfile::tempdir().unwrap();
+    let (root, runtime, tools) = (
+        dir.path().join("root"),
+        dir.path().join("runtime"),
+        dir.path().join("tools"),
+    );
+    for d in [root.join("lib"), runtime.clone(), tools.clone()] {
+        fs::create_dir_all(d).unwrap();
+    }
+    fs::write(root.join("lib/libc.so.6"), "rootfs").unwrap();
+    fs::write(runtime.join("libc.so.6"), "overlay").unwrap();
+    fs::write(runtime.join("libstdc++.so.6.0.24"), "").unwrap();
+    symlink("libstdc++.so.6.0.24", runtime.join("libstdc++.so.6")).unwrap();
+    fs::write(tools.join("helper"), "").unwrap();
+    let rootfs = Rootfs::open(&root)
+        .unwrap()
+        .overlay("/lib/", &runtime)
+        .overlay("/lib/tools", &tools);
+    assert_eq!(
+        rootfs.search_dirs(),
+        ["/lib", "/usr/lib", "/lib64", "/usr/lib64", "/lib/tools"]
+    );
+    // The rootfs's own files come first.
+    assert_eq!(
+        rootfs.resolve("/lib/libc.so.6"),
+        Some(root.join("lib/libc.so.6"))
+    );
+    assert_eq!(
+        rootfs.resolve("/lib/libstdc++.so.6"),
+        Some(runtime.join("libstdc++.so.6.0.24"))
+    );
+    assert_eq!(
+        rootfs.lookup("/lib/libstdc++.so.6"),
+        Some(runtime.join("libstdc++.so.6"))
+    );
+    assert_eq!(
+        rootfs.resolve("/lib/tools/helper"),
+        Some(tools.join("helper"))
+    );
+    assert_eq!(rootfs.resolve("/lib/other"), None);
+}
+
+#[test]
+fn stages_libraries() {
+    let Some((dir, app, root)) = setup() else {
+        eprintln!("skipping: needs an x86-64 host gcc");
+        return;
+    };
+    // libbar comes from a runtime directory outside the rootfs.
+    let runtime = dir.path().join("runtime");
+    fs::create_dir(&runtime).unwrap();
+    fs::rename(
+        root.join("usr/lib/app/libbar.so.1"),
+        runtime.join("libbar.so.1.0"),
+    )
+    .unwrap();
+    symlink("libbar.so.1.0", runtime.join("libbar.so.1")).unwrap();
+    let checker = Checker::new(Rootfs::open(&root).unwrap().overlay("/lib", &runtime));
+
+    let out = dir.path().join("out");
+    let staged = stage::stage(&checker, std::slice::from_ref(&app), &out).unwrap();
+    let entries: Vec<_> = staged
+        .entries
+        .iter()
+        .map(|e| match e {
+            Entry::File { path, source, .. } => (path.as_str(), source.display().to_string()),
+            Entry::Symlink { path, target } => (path.as_str(), format!("-> {target}")),
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("/lib/libbar.so.1", "-> libbar.so.1.0".to_owned()),
+            (
+                "/lib/libbar.so.1.0",
+                runtime.join("libbar.so.1.0").display().to_string()
+            ),
+            (
+                "/lib/libfoo-2.so",
+                root.join("lib/libfoo-2.so").display().to_string()
+            ),
+            ("/lib/libfoo.so.1", "-> /lib/libfoo-2.so".to_owned()),
+            (
+                "/lib64/ld-linux-x86-64.so.2",
+                "-> ../lib/libfoo-2.so".to_owned()
+            ),
+        ]
+    );
+    assert_eq!(
+        fs::read(out.join("lib/libbar.so.1.0")).unwrap(),
+        fs::read(runtime.join("libbar.so.1.0")).unwrap()
+    );
+    assert_eq!(
+        fs::read_link(out.join("lib64/ld-linux-x86-64.so.2")).unwrap(),
+        Path::new("../lib/libfoo-2.so")
+    );
+    let manifest: Staged =
+        serde_json::from_str(&fs::read_to_string(out.join(MANIFEST_FILE)).unwrap()).unwrap();
+    assert_eq!(manifest, staged);
+
+    // The staged tree is itself a rootfs the app loads from.
+    let report = Checker::new(Rootfs::open(&out).unwrap())
+        .check(&app)
+        .unwrap();
+    assert!(report.ok(), "{report:#?}");
+}
+
+#[test]
+fn stages_nothing_for_unloadable_binaries() {
+    let Some((dir, app, root)) = setup() else {
+        eprintln!("skipping: needs an x86-64 host gcc");
+        return;
+    };
+    fs::remove_file(root.join("lib/libfoo.so.1")).unwrap();
+    let out = dir.path().join("out");
+    let err = stage::stage(&Checker::new(Rootfs::open(&root).unwrap()), &[app], &out).unwrap_err();
+    assert!(matches!(err, Error::Unresolved { .. }), "{err}");
+    assert!(err.to_string().contains("libfoo.so.1 (needed by"), "{err}");
+    assert!(!out.exists());
+}
+
+#[test]
+fn accepts_compatible_rootfs() {
+    let Some((_dir, app, root)) = setup() else {
+        eprintln!("skipping: needs an x86-64 host gcc");
+        return;
+    };
+    let report = Checker::new(Rootfs::open(&root).unwrap())
+        .check(&app)
+        .unwrap();
+    assert!(report.ok(), "{report:#?}");
+    assert_eq!(
+        report.interpreter.as_deref(),
+        Some("/lib64/ld-linux-x86-64.so.2")
+    );
+    let libraries: Vec<_> = report
+        .libraries
+        .iter()
+        .map(|l| (l.name.as_str(), l.path.as_str()))
+        .collect();
+    assert_eq!(
+        libraries,
+        [
+            ("libfoo.so.1", "/lib/libfoo.so.1"),
+            ("libbar.so.1", "/usr/bin/../lib/app/libbar.so.1"),
+        ]
+    );
+
+    // $ORIGIN follows the install directory.
+    let report = Checker::new(Rootfs::open(&root).unwrap())
+        .install_dir("/opt/app/bin")
+        .check(&app)
+        .unwrap();
+    assert!(matches!(
+        &report.problems[..],
+        [Problem::MissingLibrary { name, .. }] if name == "libbar.so.1"
+    ));
+}
+
+#[test]
+fn reports_missing_versions() {
+    let Some((dir, app, root)) = setup() else {
+        eprintln!("skipping: needs an x86-64 host gcc");
+        return;
+    };
+    fs::copy(
+        dir.path().join("build/libfoo-1.so"),
+        root.join("lib/libfoo-2.so"),
+    )
+    .unwrap();
+    let report = Checker::new(Rootfs::open(&root).unwrap())
+        .check(&app)
+        .unwrap();
+    assert_eq!(
+        report.problems,
+        [Problem::MissingVersion {
+            version: "FOO_2".into(),
+            library: "/lib/libfoo.so.1".into(),
+            needed_by: app.display().to_string(),
+            newest: Some("FOO_1".into()),
+        }]
+    );
+    assert_eq!(
+        report.problems[0].to_string(),
+        format!(
+            "/lib/libfoo.so.1 does not provide FOO_2 (needed by {}); newest is FOO_1",
+            app.display()
+        )
+    );
+}
+
+#[test]
+fn reports_missing_libraries_and_interpreter() {
+    let Some((_dir, app, root)) = setup() else {
+        eprintln!("skipping: needs an x86-64 host gcc");
+        return;
+    };
+    fs::remove_file(root.join("lib64/ld-linux-x86-64.so.2")).unwrap();
+    fs::write(root.join("usr/lib/app/libbar.so.1"), "not elf").unwrap();
+    fs::remove_file(root.join("lib/libfoo.so.1")).unwrap();
+    let report = Checker::new(Rootfs::open(&root).unwrap())
+        .check(&app)
+        .unwrap();
+    let needed_by = app.display().to_string();
+    assert_eq!(
+        report.problems,
+        [
+            Problem::MissingInterpreter {
+                path: "/lib64/ld-linux-x86-64.so.2".into()
+            },
+            Problem::MissingLibrary {
+                name: "libfoo.so.1".into(),
+                needed_by: needed_by.clone(),
+                incompatible: vec![],
+            },
+            Problem::MissingLibrary {
+                name: "libbar.so.1".into(),
+                needed_by,
+                incompatible: vec!["/usr/bin/../lib/app/libbar.so.1 (not an ELF file)".into()],
+            },
+        ]
+    );
+}
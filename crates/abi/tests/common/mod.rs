@@ -0,0 +1,38 @@
+//! Host-built stand-ins for libraries and programs, compiled from the
+//! sources in `tests/fixtures`, shared by the integration tests.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// Compiles `source` to `out` as a freestanding PIC object, with `args`
+/// for the link.
+pub fn gcc(args: &[&str], out: &Path, source: &str) -> bool {
+    Command::new("gcc")
+        .args(["-fPIC", "-nostdlib", "-o"])
+        .arg(out)
+        .arg(fixture(source))
+        .args(args)
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// A temporary directory whose `build` subdirectory `build` has filled,
+/// and that subdirectory. `None` off x86_64 Linux or when `build` fails
+/// for want of a host GCC.
+pub fn setup(build: impl FnOnce(&Path) -> Option<()>) -> Option<(tempfile::TempDir, PathBuf)> {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return None;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let build_dir = dir.path().join("build");
+    fs::create_dir(&build_dir).unwrap();
+    build(&build_dir)?;
+    Some((dir, build_dir))
+}
@@ -0,0 +1,4 @@
+int foo_c(void);
+int bar(void);
+
+int main(void) { return foo_c() + bar(); }
@@ -0,0 +1,4 @@
+int old_call(void);
+int inflate_stub(void);
+
+int main(void) { return old_call() + inflate_stub(); }
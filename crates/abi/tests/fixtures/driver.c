@@ -0,0 +1,3 @@
+int new_call(void);
+
+int main(void) { return new_call(); }
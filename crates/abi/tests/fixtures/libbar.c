@@ -0,0 +1,3 @@
+int foo_a(void);
+
+int bar(void) { return foo_a() + 1; }
@@ -0,0 +1,2 @@
+int old_call(void) { return 1; }
+int new_call(void) { return 2; }
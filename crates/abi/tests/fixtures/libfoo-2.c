@@ -0,0 +1,3 @@
+int foo_a(void) { return 1; }
+int foo_b(void) { return 2; }
+int foo_c(void) { return 3; }
@@ -0,0 +1 @@
+int inflate_stub(void) { return 3; }
@@ -0,0 +1,228 @@
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+
+use linaro_abi::preflight::preflight;
+use linaro_abi::{Error, Problem, Rootfs};
+
+mod common;
+
+use common::{fixture, gcc};
+
+/// Host-built stand-ins for glibc and a toolchain: `libc-2.17.so` and
+/// `libc-2.28.so` (sonames `libc.so.6`), `libz.so.1`, a driver needing
+/// `new_call@GLIBC_2.28` and a `cc1` needing `old_call@GLIBC_2.17` and
+/// libz. `None` without a host GCC.
+fn build(dir: &Path) -> Option<()> {
+    let out = |name: &str| dir.join(name);
+    for version in ["2.17", "2.28"] {
+        let script = format!(
+            "-Wl,--version-script={}",
+            fixture(&format!("libc-{version}.map")).display()
+        );
+        let lib = out(&format!("libc-{version}.so"));
+        if !gcc(
+            &["-shared", "-Wl,-soname,libc.so.6", &script],
+            &lib,
+            "libc.c",
+        ) {
+            return None;
+        }
+    }
+    fs::copy(out("libc-2.28.so"), out("libc.so.6")).unwrap();
+    let link_dir = format!("-L{}", dir.display());
+    let built = gcc(
+        &["-shared", "-Wl,-soname,libz.so.1"],
+        &out("libz.so.1"),
+        "libz.c",
+    ) && gcc(
+        &["-Wl,-e,main", &link_dir, "-l:libc.so.6"],
+        &out("driver"),
+        "driver.c",
+    ) && gcc(
+        &["-Wl,-e,main", &link_dir, "-l:libc.so.6", "-l:libz.so.1"],
+        &out("cc1"),
+        "cc1.c",
+    );
+    built.then_some(())
+}
+
+/// A toolchain tree with the programs in their usual places, a script, a
+/// symlink and target libraries that must not be checked.
+fn toolchain(dir: &Path, build: &Path) -> PathBuf {
+    let root = dir.join("toolchain");
+    let libexec = root.join("libexec/gcc/aarch64-linux-gnu/7.5.0");
+    let sysroot = root.join("aarch64-linux-gnu/libc/lib");
+    for d in [root.join("bin"), root.join("aarch64-linux-gnu/bin")] {
+        fs::create_dir_all(d).unwrap();
+    }
+    fs::create_dir_all(&libexec).unwrap();
+    fs::create_dir_all(&sysroot).unwrap();
+    fs::copy(build.join("driver"), root.join("bin/aarch64-linux-gnu-gcc")).unwrap();
+    fs::write(
+        root.join("bin/aarch64-linux-gnu-gcc-wrapper"),
+        "#!/bin/sh\n",
+    )
+    .unwrap();
+    fs::copy(build.join("driver"), root.join("aarch64-linux-gnu/bin/ar")).unwrap();
+    symlink(
+        "../../bin/aarch64-linux-gnu-gcc",
+        root.join("aarch64-linux-gnu/bin/gcc"),
+    )
+    .unwrap();
+    fs::copy(build.join("cc1"), libexec.join("cc1")).unwrap();
+    fs::copy(build.join("libc-2.28.so"), sysroot.join("libc.so.6")).unwrap();
+    root
+}
+
+/// A host root filesystem with glibc `version`, and libz if `libz`.
+fn host(dir: &Path, build: &Path, version: &str, libz: bool) -> Rootfs {
+    let root = dir.join(format!("host-{version}"));
+    for sub in ["lib", "lib64"] {
+        fs::create_dir_all(root.join(sub)).unwrap();
+    }
+    fs::copy(
+        build.join(format!("libc-{version}.so")),
+        root.join(format!("lib/libc-{version}.so")),
+    )
+    .unwrap();
+    symlink(format!("libc-{version}.so"), root.join("lib/libc.so.6")).unwrap();
+    symlink(
+        format!("../lib/libc-{version}.so"),
+        root.join("lib64/ld-linux-x86-64.so.2"),
+    )
+    .unwrap();
+    if libz {
+        fs::copy(build.join("libz.so.1"), root.join("lib/libz.so.1")).unwrap();
+    }
+    Rootfs::open(root).unwrap()
+}
+
+fn setup() -> Option<(tempfile::TempDir, PathBuf, PathBuf)> {
+    let (dir, build_dir) = common::setup(build)?;
+    let toolchain = toolchain(dir.path(), &build_dir);
+    Some((dir, build_dir, toolchain))
+}
+
+#[test]
+fn passes_on_a_recent_host() {
+    let Some((dir, build, toolchain)) = setup() else {
+        eprintln!("skipping: needs an x86_64 Linux host with gcc");
+        return;
+    };
+    let report = preflight(&toolchain, &host(dir.path(), &build, "2.28", true)).unwrap();
+    assert_eq!(
+        report.binaries,
+        [
+            Path::new("aarch64-linux-gnu/bin/ar"),
+            Path::new("bin/aarch64-linux-gnu-gcc"),
+            Path::new("libexec/gcc/aarch64-linux-gnu/7.5.0/cc1"),
+        ]
+    );
+    assert_eq!(report.required_glibc.as_deref(), Some("GLIBC_2.28"));
+    assert_eq!(report.host_glibc.as_deref(), Some("GLIBC_2.28"));
+    assert_eq!(report.libraries, ["libc.so.6", "libz.so.1"]);
+    assert!(report.ok(), "{:?}", report.issues);
+}
+
+#[test]
+fn reports_an_old_glibc_and_missing_libraries() {
+    let Some((dir, build, toolchain)) = setup() else {
+        eprintln!("skipping: needs an x86_64 Linux host with gcc");
+        return;
+    };
+    let report = preflight(&toolchain, &host(dir.path(), &build, "2.17", false)).unwrap();
+    assert_eq!(report.host_glibc.as_deref(), Some("GLIBC_2.17"));
+    assert_eq!(
+        report.verdict(),
+        "the host's glibc is too old: it provides up to GLIBC_2.17, \
+         the toolchain needs GLIBC_2.28"
+    );
+    assert_eq!(report.issues.len(), 2, "{:?}", report.issues);
+    let missing_version = &report.issues[0];
+    assert_eq!(
+        missing_version.problem,
+        Problem::MissingVersion {
+            version: "GLIBC_2.28".to_owned(),
+            library: "/lib/libc.so.6".to_owned(),
+            needed_by: "aarch64-linux-gnu/bin/ar".to_owned(),
+            newest: Some("GLIBC_2.17".to_owned()),
+        }
+    );
+    assert_eq!(
+        missing_version.binaries,
+        [
+            Path::new("aarch64-linux-gnu/bin/ar"),
+            Path::new("bin/aarch64-linux-gnu-gcc"),
+        ]
+    );
+    assert!(matches!(
+        &report.issues[1].problem,
+        Problem::MissingLibrary { name, .. } if name == "libz.so.1"
+    ));
+}
+
+#[test]
+fn finds_libraries_next_to_the_toolchain() {
+    let Some((dir, build, toolchain)) = setup() else {
+        eprintln!("skipping: needs an x86_64 Linux host with gcc");
+        return;
+    };
+    // The driver carries its own libz, found through its run path; cc1
+    // still needs the host's.
+    let link_dir = format!("-L{}", build.display());
+    assert!(gcc(
+        &[
+            "-Wl,-e,main",
+            &link_dir,
+            "-l:libc.so.6",
+            "-l:libz.so.1",
+            "-Wl,-rpath,$ORIGIN/../lib",
+        ],
+        &toolchain.join("bin/aarch64-linux-gnu-gcc"),
+        "cc1.c",
+    ));
+    fs::create_dir(toolchain.join("lib")).unwrap();
+    fs::copy(build.join("libz.so.1"), toolchain.join("lib/libz.so.1")).unwrap();
+
+    // The host root is not where the toolchain lives, as with a container
+    // image, and the toolchain is named through a symlink.
+    symlink(&toolchain, dir.path().join("prebuilts")).unwrap();
+    let host = host(dir.path(), &build, "2.28", false);
+    let report = preflight(&dir.path().join("prebuilts"), &host).unwrap();
+    assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+    assert!(matches!(
+        &report.issues[0].problem,
+        Problem::MissingLibrary { name, needed_by, .. }
+            if name == "libz.so.1" && needed_by == "libexec/gcc/aarch64-linux-gnu/7.5.0/cc1"
+    ));
+}
+
+#[test]
+fn reports_a_host_without_glibc() {
+    let Some((dir, _, toolchain)) = setup() else {
+        eprintln!("skipping: needs an x86_64 Linux host with gcc");
+        return;
+    };
+    let root = dir.path().join("musl");
+    fs::create_dir_all(root.join("lib")).unwrap();
+    fs::write(root.join("lib/ld-musl-x86_64.so.1"), "").unwrap();
+    let report = preflight(&toolchain, &Rootfs::open(&root).unwrap()).unwrap();
+    assert_eq!(report.host_glibc, None);
+    assert!(report.verdict().starts_with("the host has no glibc"));
+    assert!(report.issues.iter().any(|i| matches!(
+        &i.problem,
+        Problem::MissingInterpreter { path } if path == "/lib64/ld-linux-x86-64.so.2"
+    )));
+}
+
+#[test]
+fn needs_host_programs() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("bin")).unwrap();
+    fs::write(dir.path().join("bin/gcc"), "#!/bin/sh\n").unwrap();
+    assert!(matches!(
+        preflight(dir.path(), &Rootfs::open(dir.path()).unwrap()),
+        Err(Error::NoHostPrograms(_))
+    ));
+}
@@ -0,0 +1,305 @@
+//! Chunked storage of archives larger than a git host accepts in one file.
+//!
+//! [`split`] cuts an archive into numbered chunks of a fixed size,
+//! `<archive>.part000`, `<archive>.part001`, ..., next to an [`Index`] at
+//! `<archive>.chunks.toml` giving the size and SHA-256 of every chunk and of
+//! the whole archive. The chunks and the index are committed instead of the
+//! archive; [`join`] puts it back together after a checkout, checking each
+//! chunk as it is read and the result before it replaces anything. Both
+//! stream through a fixed-size buffer, so memory use does not grow with the
+//! archive.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use linaro_manifest::{sha256_file, Sha256};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+use crate::verify::Status;
+use crate::Error;
+
+/// Suffix of the index written next to the chunks.
+pub const INDEX_SUFFIX: &str = ".chunks.toml";
+
+/// GitHub warns about files over 50 MiB and rejects those over 100 MiB.
+pub const DEFAULT_CHUNK_SIZE: u64 = 50 << 20;
+
+const HEADER: &str = "# Written by `linaro-prebuilts chunks split`; do not edit.\n";
+
+/// The contents of an index file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Index {
+    /// File name of the archive, e.g.
+    /// `gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz`.
+    pub archive: String,
+    pub size: u64,
+    pub sha256: Sha256,
+    /// The size of every chunk but the last.
+    pub chunk_size: u64,
+    #[serde(rename = "chunk", default)]
+    pub chunks: Vec<Chunk>,
+}
+
+/// One piece of the archive, a file next to the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Chunk {
+    pub file: String,
+    pub size: u64,
+    pub sha256: Sha256,
+}
+
+impl Index {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(Error::io(path))?;
+        let index: Self = toml::from_str(&text).map_err(|e| Error::Index {
+            path: path.to_owned(),
+            reason: e.message().to_owned(),
+        })?;
+        let names = [index.archive.as_str()]
+            .into_iter()
+            .chain(index.chunks.iter().map(|c| c.file.as_str()));
+        for name in names {
+            if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+                return Err(Error::Index {
+                    path: path.to_owned(),
+                    reason: format!("{name:?} is not a file name"),
+                });
+            }
+        }
+        Ok(index)
+    }
+}
+
+/// Path of the index for `archive` in `dir`.
+pub fn index_path(dir: &Path, archive: &str) -> PathBuf {
+    dir.join(format!("{archive}{INDEX_SUFFIX}"))
+}
+
+/// The index files in `dir`, sorted by name.
+pub fn indexes(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).map_err(Error::io(dir))? {
+        let entry = entry.map_err(Error::io(dir))?;
+        let is_index = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|n| n.len() > INDEX_SUFFIX.len() && n.ends_with(INDEX_SUFFIX));
+        if is_index {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Splits the archive at `archive` into chunks of `chunk_size` bytes in
+/// `dir`, and writes their index there. Chunks left in `dir` by an earlier
+/// split of the same archive into more pieces are removed.
+pub fn split(archive: &Path, dir: &Path, chunk_size: u64) -> Result<Index, Error> {
+    if chunk_size == 0 {
+        return Err(Error::ChunkSize);
+    }
+    let name = archive
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| {
+            Error::io(archive)(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not an archive file name",
+            ))
+        })?
+        .to_owned();
+    let file = File::open(archive).map_err(Error::io(archive))?;
+    let size = file.metadata().map_err(Error::io(archive))?.len();
+    let count = size.div_ceil(chunk_size);
+    let width = count.saturating_sub(1).to_string().len().max(3);
+    fs::create_dir_all(dir).map_err(Error::io(dir))?;
+
+    let mut reader = BufReader::new(file);
+    let mut whole = sha2::Sha256::new();
+    let mut chunks = Vec::new();
+    for n in 0..count {
+        let chunk_name = format!("{name}.part{n:0width$}");
+        let path = dir.join(&chunk_name);
+        let out = File::create(&path).map_err(Error::io(&path))?;
+        let mut out = BufWriter::new(out);
+        let mut part = sha2::Sha256::new();
+        let written = copy(
+            (&mut reader).take(chunk_size),
+            |data| {
+                whole.update(data);
+                part.update(data);
+                out.write_all(data)
+            },
+            archive,
+            &path,
+        )?;
+        out.flush().map_err(Error::io(&path))?;
+        chunks.push(Chunk {
+            file: chunk_name,
+            size: written,
+            sha256: digest(part),
+        });
+    }
+    let index = Index {
+        archive: name,
+        size,
+        sha256: digest(whole),
+        chunk_size,
+        chunks,
+    };
+    remove_stale_chunks(dir, &index)?;
+    let path = index_path(dir, &index.archive);
+    let text = HEADER.to_owned() + &toml::to_string(&index).expect("index serializes");
+    fs::write(&path, text).map_err(Error::io(&path))?;
+    Ok(index)
+}
+
+/// What [`join`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Joined(PathBuf),
+    /// The archive was already there with the size and digest the index
+    /// gives.
+    AlreadyJoined(PathBuf),
+}
+
+/// Reassembles the archive described by the index at `index`, from the
+/// chunks next to it, into the same directory. An archive already there is
+/// kept if it matches the index and replaced otherwise. A missing or
+/// corrupt chunk fails with [`Error::Unverified`] naming it, and leaves
+/// nothing behind.
+pub fn join(index: &Path) -> Result<Outcome, Error> {
+    let dir = index.parent().unwrap_or(Path::new("."));
+    let index = Index::load(index)?;
+    let dest = dir.join(&index.archive);
+    if dest.is_file() {
+        let (sha256, size) = sha256_file(&dest).map_err(Error::io(&dest))?;
+        if size == index.size && sha256 == index.sha256 {
+            return Ok(Outcome::AlreadyJoined(dest));
+        }
+    }
+
+    let partial = dir.join(format!(".{}.joining-{}", index.archive, std::process::id()));
+    let result = join_into(dir, &index, &partial);
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result?;
+    fs::rename(&partial, &dest).map_err(Error::io(&dest))?;
+    Ok(Outcome::Joined(dest))
+}
+
+fn join_into(dir: &Path, index: &Index, partial: &Path) -> Result<(), Error> {
+    let out = File::create(partial).map_err(Error::io(partial))?;
+    let mut out = BufWriter::new(out);
+    let mut whole = sha2::Sha256::new();
+    let mut size = 0;
+    for chunk in &index.chunks {
+        let path = dir.join(&chunk.file);
+        let unverified = |status| Error::Unverified {
+            archive: chunk.file.clone(),
+            status,
+        };
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(unverified(Status::Missing))
+            }
+            Err(e) => return Err(Error::io(&path)(e)),
+        };
+        let mut part = sha2::Sha256::new();
+        let read = copy(
+            BufReader::new(file),
+            |data| {
+                whole.update(data);
+                part.update(data);
+                out.write_all(data)
+            },
+            &path,
+            partial,
+        )?;
+        if read != chunk.size {
+            return Err(unverified(Status::SizeMismatch {
+                expected: chunk.size,
+                actual: read,
+            }));
+        }
+        let actual = digest(part);
+        if actual != chunk.sha256 {
+            return Err(unverified(Status::DigestMismatch {
+                expected: chunk.sha256,
+                actual,
+            }));
+        }
+        size += read;
+    }
+    out.flush().map_err(Error::io(partial))?;
+    let unverified = |status| Error::Unverified {
+        archive: index.archive.clone(),
+        status,
+    };
+    if size != index.size {
+        return Err(unverified(Status::SizeMismatch {
+            expected: index.size,
+            actual: size,
+        }));
+    }
+    let actual = digest(whole);
+    if actual != index.sha256 {
+        return Err(unverified(Status::DigestMismatch {
+            expected: index.sha256,
+            actual,
+        }));
+    }
+    Ok(())
+}
+
+/// Streams `reader` into `sink`, returning the number of bytes copied. Read
+/// errors are reported against `from`, write errors against `to`.
+fn copy(
+    mut reader: impl Read,
+    mut sink: impl FnMut(&[u8]) -> io::Result<()>,
+    from: &Path,
+    to: &Path,
+) -> Result<u64, Error> {
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut len = 0u64;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(len),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Error::io(from)(e)),
+        };
+        sink(&buf[..n]).map_err(Error::io(to))?;
+        len += n as u64;
+    }
+}
+
+fn digest(hasher: sha2::Sha256) -> Sha256 {
+    Sha256::from_bytes(hasher.finalize().into())
+}
+
+/// Removes `<archive>.partN` files in `dir` that `index` does not list.
+fn remove_stale_chunks(dir: &Path, index: &Index) -> Result<(), Error> {
+    let prefix = format!("{}.part", index.archive);
+    for entry in fs::read_dir(dir).map_err(Error::io(dir))? {
+        let entry = entry.map_err(Error::io(dir))?;
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let numbered = name
+            .strip_prefix(&prefix)
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+        if numbered && !index.chunks.iter().any(|c| c.file == name) {
+            fs::remove_file(entry.path()).map_err(Error::io(entry.path()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::io;
+use std::path::PathBuf;
+
+use crate::verify::Status;
+
+/// Errors that prevent an operation from completing, as opposed to
+/// verification failures, which are reported as results.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{} is signed but keyring {} does not exist", archive.display(), keyring.display())]
+    MissingKeyring { archive: PathBuf, keyring: PathBuf },
+    #[error("failed to run gpgv: {0}")]
+    Gpgv(io::Error),
+    #[error("archive {archive} failed verification: {status}")]
+    Unverified { archive: String, status: Status },
+    #[error("archive {archive} does not contain a single top-level directory")]
+    Layout { archive: String },
+    #[error("{} exists but has no stamp file; refusing to overwrite it", .0.display())]
+    Unstamped(PathBuf),
+    #[error("malformed stamp {}: {reason}", path.display())]
+    Stamp { path: PathBuf, reason: String },
+    #[error("{} was installed from {installed}; remove it to reinstall", path.display())]
+    StampMismatch { path: PathBuf, installed: String },
+    #[error("{} has been modified since it was installed", .0.display())]
+    Drifted(PathBuf),
+    #[error("malformed chunk index {}: {reason}", path.display())]
+    Index { path: PathBuf, reason: String },
+    #[error("chunk size must be at least one byte")]
+    ChunkSize,
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
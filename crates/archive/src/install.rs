@@ -0,0 +1,264 @@
+//! Unpacking verified archives into the `prebuilts/gcc` layout.
+//!
+//! A toolchain is installed to `<root>/<host>/<triple>/<gcc version>/` with
+//! the archive's top-level directory stripped. Every file and directory gets
+//! a fixed mtime and normalized permissions (0755 for directories and
+//! executables, 0644 otherwise), so two installs of the same archive are
+//! byte-for-byte identical. A stamp file records the archive and a digest of
+//! the unpacked tree; installing again is a no-op while the tree matches the
+//! stamp, and an error once it has drifted.
+
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use filetime::FileTime;
+use linaro_manifest::{sha256_file, Sha256, Toolchain};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+use crate::verify::Verifier;
+use crate::Error;
+
+/// Name of the stamp file written at the top of an installed tree.
+pub const STAMP_FILE: &str = ".linaro-prebuilts-stamp";
+
+/// Contents of the stamp file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stamp {
+    pub archive: String,
+    pub sha256: Sha256,
+    /// Digest of the installed tree as computed by [`tree_digest`].
+    pub tree: Sha256,
+}
+
+/// What [`Installer::install`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Installed(PathBuf),
+    /// The toolchain was already installed and matches its stamp.
+    AlreadyInstalled(PathBuf),
+}
+
+/// Installs toolchains from verified archives.
+#[derive(Debug, Clone)]
+pub struct Installer {
+    verifier: Verifier,
+    root: PathBuf,
+}
+
+impl Installer {
+    /// Creates an installer taking archives through `verifier` and
+    /// installing below `root` (usually `prebuilts/gcc`).
+    pub fn new(verifier: Verifier, root: impl Into<PathBuf>) -> Self {
+        Self {
+            verifier,
+            root: root.into(),
+        }
+    }
+
+    /// Directory `toolchain` is installed to.
+    pub fn install_dir(&self, toolchain: &Toolchain) -> PathBuf {
+        self.root.join(toolchain.install_path())
+    }
+
+    /// Installs `toolchain`, or checks an existing install against its stamp.
+    pub fn install(&self, toolchain: &Toolchain) -> Result<Outcome, Error> {
+        let dest = self.install_dir(toolchain);
+        if dest.symlink_metadata().is_ok() {
+            check_installed(&dest, toolchain)?;
+            return Ok(Outcome::AlreadyInstalled(dest));
+        }
+
+        let verification = self.verifier.verify(toolchain)?;
+        if !verification.status.is_ok() {
+            return Err(Error::Unverified {
+                archive: verification.archive,
+                status: verification.status,
+            });
+        }
+
+        let parent = dest.parent().expect("install dir has a parent");
+        fs::create_dir_all(parent).map_err(Error::io(parent))?;
+        let staging = Staging::create(parent.join(format!(
+            ".{}.partial-{}",
+            toolchain.gcc,
+            std::process::id()
+        )))?;
+
+        let archive = self.verifier.archive_path(toolchain);
+        let file = File::open(&archive).map_err(Error::io(&archive))?;
+        let mut tar = tar::Archive::new(xz2::read::XzDecoder::new(BufReader::new(file)));
+        tar.set_preserve_mtime(false);
+        tar.unpack(&staging.0).map_err(Error::io(&archive))?;
+
+        let top = single_directory(&staging.0).ok_or_else(|| Error::Layout {
+            archive: toolchain.archive.clone(),
+        })?;
+        normalize(&top)?;
+        let stamp = Stamp {
+            archive: toolchain.archive.clone(),
+            sha256: toolchain.sha256,
+            tree: tree_digest(&top)?,
+        };
+        let stamp_path = top.join(STAMP_FILE);
+        let text = toml::to_string(&stamp).expect("stamp serializes");
+        fs::write(&stamp_path, text).map_err(Error::io(&stamp_path))?;
+        normalize_entry(
+            &stamp_path,
+            &stamp_path
+                .symlink_metadata()
+                .map_err(Error::io(&stamp_path))?,
+        )?;
+        set_mtime(&top)?;
+
+        fs::rename(&top, &dest).map_err(Error::io(&dest))?;
+        Ok(Outcome::Installed(dest))
+    }
+}
+
+/// Reads the stamp of the tree installed at `dir`.
+pub fn read_stamp(dir: &Path) -> Result<Stamp, Error> {
+    let path = dir.join(STAMP_FILE);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(Error::Unstamped(dir.to_owned()))
+        }
+        Err(e) => return Err(Error::io(&path)(e)),
+    };
+    toml::from_str(&text).map_err(|e| Error::Stamp {
+        path,
+        reason: e.message().to_owned(),
+    })
+}
+
+/// Checks that the tree at `dir` was installed from `toolchain`'s archive and
+/// has not been modified since.
+pub fn check_installed(dir: &Path, toolchain: &Toolchain) -> Result<Stamp, Error> {
+    let stamp = read_stamp(dir)?;
+    if stamp.archive != toolchain.archive || stamp.sha256 != toolchain.sha256 {
+        return Err(Error::StampMismatch {
+            path: dir.to_owned(),
+            installed: stamp.archive,
+        });
+    }
+    if tree_digest(dir)? != stamp.tree {
+        return Err(Error::Drifted(dir.to_owned()));
+    }
+    Ok(stamp)
+}
+
+/// Accepts deliberate changes to the tree installed at `dir`, such as
+/// pruning: normalizes it as [`Installer::install`] does and records its new
+/// digest in the stamp, so [`check_installed`] passes again.
+pub fn restamp(dir: &Path) -> Result<Stamp, Error> {
+    let mut stamp = read_stamp(dir)?;
+    normalize(dir)?;
+    stamp.tree = tree_digest(dir)?;
+    let path = dir.join(STAMP_FILE);
+    let text = toml::to_string(&stamp).expect("stamp serializes");
+    fs::write(&path, text).map_err(Error::io(&path))?;
+    normalize_entry(&path, &path.symlink_metadata().map_err(Error::io(&path))?)?;
+    set_mtime(dir)?;
+    Ok(stamp)
+}
+
+/// Digest over the paths, types, permissions and contents of the tree at
+/// `dir`, excluding the stamp file. Modification times are ignored.
+pub fn tree_digest(dir: &Path) -> Result<Sha256, Error> {
+    let mut hasher = sha2::Sha256::new();
+    hash_tree(dir, "", &mut hasher)?;
+    Ok(Sha256::from_bytes(hasher.finalize().into()))
+}
+
+fn hash_tree(dir: &Path, prefix: &str, hasher: &mut sha2::Sha256) -> Result<(), Error> {
+    for name in sorted_entries(dir)? {
+        if prefix.is_empty() && name == STAMP_FILE {
+            continue;
+        }
+        let path = dir.join(&name);
+        let relative = format!("{prefix}{name}");
+        let metadata = path.symlink_metadata().map_err(Error::io(&path))?;
+        let mode = metadata.permissions().mode() & 0o7777;
+        if metadata.is_symlink() {
+            let target = fs::read_link(&path).map_err(Error::io(&path))?;
+            hasher.update(format!("l {relative} -> {}\n", target.display()));
+        } else if metadata.is_dir() {
+            hasher.update(format!("d {relative} {mode:o}\n"));
+            hash_tree(&path, &format!("{relative}/"), hasher)?;
+        } else {
+            let (digest, _) = sha256_file(&path).map_err(Error::io(&path))?;
+            hasher.update(format!("f {relative} {mode:o} {digest}\n"));
+        }
+    }
+    Ok(())
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<String>, Error> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir).map_err(Error::io(dir))? {
+        let entry = entry.map_err(Error::io(dir))?;
+        names.push(entry.file_name().to_string_lossy().into_owned());
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Normalizes permissions and mtimes below `dir`, children first so that
+/// directory mtimes are not disturbed afterwards.
+fn normalize(dir: &Path) -> Result<(), Error> {
+    for name in sorted_entries(dir)? {
+        let path = dir.join(name);
+        let metadata = path.symlink_metadata().map_err(Error::io(&path))?;
+        if metadata.is_dir() {
+            normalize(&path)?;
+        }
+        normalize_entry(&path, &metadata)?;
+    }
+    normalize_entry(dir, &dir.symlink_metadata().map_err(Error::io(dir))?)
+}
+
+fn normalize_entry(path: &Path, metadata: &fs::Metadata) -> Result<(), Error> {
+    if !metadata.is_symlink() {
+        let executable = metadata.is_dir() || metadata.permissions().mode() & 0o111 != 0;
+        let mode = if executable { 0o755 } else { 0o644 };
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).map_err(Error::io(path))?;
+    }
+    set_mtime(path)
+}
+
+fn set_mtime(path: &Path) -> Result<(), Error> {
+    let epoch = FileTime::zero();
+    filetime::set_symlink_file_times(path, epoch, epoch).map_err(Error::io(path))
+}
+
+fn single_directory(dir: &Path) -> Option<PathBuf> {
+    let mut entries = fs::read_dir(dir).ok()?;
+    let only = entries.next()?.ok()?;
+    if entries.next().is_some() || !only.file_type().ok()?.is_dir() {
+        return None;
+    }
+    Some(only.path())
+}
+
+/// Scratch directory removed on drop, so failed installs leave nothing
+/// behind.
+struct Staging(PathBuf);
+
+impl Staging {
+    fn create(path: PathBuf) -> Result<Self, Error> {
+        if path.exists() {
+            fs::remove_dir_all(&path).map_err(Error::io(&path))?;
+        }
+        fs::create_dir(&path).map_err(Error::io(&path))?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,12 @@
+//! Handling of the Linaro toolchain archives listed in the manifest.
+//!
+//! Everything in this crate works offline: archives are read from local
+//! directories and signatures are checked with `gpgv` against a keyring
+//! shipped alongside the manifest.
+
+pub mod chunks;
+mod error;
+pub mod install;
+pub mod verify;
+
+pub use error::Error;
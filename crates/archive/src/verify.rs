@@ -0,0 +1,223 @@
+//! Offline verification of archives against the manifest.
+//!
+//! Each archive is checked for presence, size and SHA-256. When a detached
+//! `<archive>.asc` signature sits next to it, the signature is checked with
+//! `gpgv` against the keyring shipped in the repository; no key server is
+//! ever contacted.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use linaro_manifest::{sha256_file, Manifest, Sha256, Toolchain};
+use serde::Serialize;
+
+use crate::Error;
+
+/// Result of verifying one archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Verification {
+    pub archive: String,
+    #[serde(flatten)]
+    pub status: Status,
+}
+
+/// What was found for an archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum Status {
+    /// Size and digest match; `signature` tells whether a signature was
+    /// present and checked.
+    Ok {
+        signature: Signature,
+    },
+    /// The archive is not in the directory.
+    Missing,
+    SizeMismatch {
+        expected: u64,
+        actual: u64,
+    },
+    DigestMismatch {
+        expected: Sha256,
+        actual: Sha256,
+    },
+    /// A signature is present but `gpgv` rejected it.
+    BadSignature {
+        detail: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Signature {
+    Unsigned,
+    Verified,
+}
+
+impl Status {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok { .. })
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ok {
+                signature: Signature::Unsigned,
+            } => f.write_str("ok (unsigned)"),
+            Self::Ok {
+                signature: Signature::Verified,
+            } => f.write_str("ok (signature verified)"),
+            Self::Missing => f.write_str("missing"),
+            Self::SizeMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "size mismatch (expected {expected} bytes, found {actual})"
+                )
+            }
+            Self::DigestMismatch { expected, actual } => {
+                write!(f, "SHA-256 mismatch (expected {expected}, found {actual})")
+            }
+            Self::BadSignature { detail } => write!(f, "bad signature ({detail})"),
+        }
+    }
+}
+
+/// Verifies archives stored in one directory.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    dir: PathBuf,
+    keyring: PathBuf,
+    gpgv: OsString,
+}
+
+impl Verifier {
+    /// Creates a verifier for archives in `dir`, checking signatures against
+    /// `keyring`.
+    pub fn new(dir: impl Into<PathBuf>, keyring: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            keyring: keyring.into(),
+            gpgv: "gpgv".into(),
+        }
+    }
+
+    /// Overrides the `gpgv` program used for signature checks.
+    pub fn gpgv(mut self, program: impl Into<OsString>) -> Self {
+        self.gpgv = program.into();
+        self
+    }
+
+    /// Path of `toolchain`'s archive in the directory.
+    pub fn archive_path(&self, toolchain: &Toolchain) -> PathBuf {
+        self.dir.join(&toolchain.archive)
+    }
+
+    /// Verifies a single archive.
+    pub fn verify(&self, toolchain: &Toolchain) -> Result<Verification, Error> {
+        let path = self.archive_path(toolchain);
+        let status = self.status(&path, toolchain)?;
+        Ok(Verification {
+            archive: toolchain.archive.clone(),
+            status,
+        })
+    }
+
+    /// Verifies every archive in `toolchains`, in order.
+    pub fn verify_all<'a>(
+        &self,
+        toolchains: impl IntoIterator<Item = &'a Toolchain>,
+    ) -> Result<Vec<Verification>, Error> {
+        toolchains.into_iter().map(|t| self.verify(t)).collect()
+    }
+
+    /// Lists `.tar.xz` files in the directory that the manifest does not
+    /// mention, sorted by name.
+    pub fn unlisted(&self, manifest: &Manifest) -> Result<Vec<String>, Error> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir).map_err(Error::io(&self.dir))? {
+            let entry = entry.map_err(Error::io(&self.dir))?;
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if name.ends_with(".tar.xz") && manifest.find_archive(&name).is_none() {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn status(&self, path: &Path, toolchain: &Toolchain) -> Result<Status, Error> {
+        let metadata = match fs::metadata(path) {
+            Ok(m) if m.is_file() => m,
+            Ok(_) => return Ok(Status::Missing),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Status::Missing),
+            Err(e) => return Err(Error::io(path)(e)),
+        };
+        if metadata.len() != toolchain.size {
+            return Ok(Status::SizeMismatch {
+                expected: toolchain.size,
+                actual: metadata.len(),
+            });
+        }
+        let (digest, _) = sha256_file(path).map_err(Error::io(path))?;
+        if digest != toolchain.sha256 {
+            return Ok(Status::DigestMismatch {
+                expected: toolchain.sha256,
+                actual: digest,
+            });
+        }
+
+        let mut signature = path.as_os_str().to_owned();
+        signature.push(".asc");
+        let signature = PathBuf::from(signature);
+        if !signature.is_file() {
+            return Ok(Status::Ok {
+                signature: Signature::Unsigned,
+            });
+        }
+        match self.check_signature(&signature, path)? {
+            None => Ok(Status::Ok {
+                signature: Signature::Verified,
+            }),
+            Some(detail) => Ok(Status::BadSignature { detail }),
+        }
+    }
+
+    /// Runs `gpgv`, returning `None` for a good signature and the reason
+    /// otherwise.
+    fn check_signature(&self, signature: &Path, data: &Path) -> Result<Option<String>, Error> {
+        // gpgv looks up keyrings without a slash in its home directory, so
+        // always hand it an absolute path.
+        let keyring = fs::canonicalize(&self.keyring).map_err(|_| Error::MissingKeyring {
+            archive: data.to_owned(),
+            keyring: self.keyring.clone(),
+        })?;
+        let output = Command::new(&self.gpgv)
+            .arg("--keyring")
+            .arg(&keyring)
+            .arg(signature)
+            .arg(data)
+            .output()
+            .map_err(Error::Gpgv)?;
+        if output.status.success() {
+            return Ok(None);
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // gpgv ends some failures with a multi-line hint; its own
+        // diagnostics are the lines prefixed with its name.
+        let detail = stderr
+            .lines()
+            .rev()
+            .find(|l| l.starts_with("gpgv:"))
+            .map_or_else(
+                || format!("gpgv exited with {}", output.status),
+                |l| l.trim().to_owned(),
+            );
+        Ok(Some(detail))
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::Path;
+
+use linaro_archive::chunks::{index_path, indexes, join, split, Index, Outcome};
+use linaro_archive::verify::Status;
+use linaro_archive::Error;
+use linaro_manifest::sha256_file;
+
+const ARCHIVE: &str = "gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz";
+
+/// Writes 10000 bytes that differ from chunk to chunk.
+fn archive(dir: &Path) -> Vec<u8> {
+    let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    fs::write(dir.join(ARCHIVE), &data).unwrap();
+    data
+}
+
+fn names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn splits_and_joins() {
+    let dir = tempfile::tempdir().unwrap();
+    let data = archive(dir.path());
+    let chunks = dir.path().join("chunks");
+    let index = split(&dir.path().join(ARCHIVE), &chunks, 4096).unwrap();
+    let (sha256, size) = sha256_file(dir.path().join(ARCHIVE)).unwrap();
+    assert_eq!((index.sha256, index.size), (sha256, size));
+    let sizes: Vec<_> = index.chunks.iter().map(|c| c.size).collect();
+    assert_eq!(sizes, [4096, 4096, 1808]);
+    assert_eq!(
+        names(&chunks),
+        [
+            format!("{ARCHIVE}.chunks.toml"),
+            format!("{ARCHIVE}.part000"),
+            format!("{ARCHIVE}.part001"),
+            format!("{ARCHIVE}.part002"),
+        ]
+    );
+    let path = index_path(&chunks, ARCHIVE);
+    assert_eq!(Index::load(&path).unwrap(), index);
+    assert_eq!(indexes(&chunks).unwrap(), [path.as_path()]);
+
+    assert_eq!(join(&path).unwrap(), Outcome::Joined(chunks.join(ARCHIVE)));
+    assert_eq!(fs::read(chunks.join(ARCHIVE)).unwrap(), data);
+    assert_eq!(
+        join(&path).unwrap(),
+        Outcome::AlreadyJoined(chunks.join(ARCHIVE))
+    );
+    // A stale archive, e.g. from before a pull updated the chunks, is
+    // replaced.
+    fs::write(chunks.join(ARCHIVE), b"older release").unwrap();
+    assert_eq!(join(&path).unwrap(), Outcome::Joined(chunks.join(ARCHIVE)));
+    assert_eq!(fs::read(chunks.join(ARCHIVE)).unwrap(), data);
+
+    // Splitting again into fewer pieces removes the chunks no longer
+    // needed.
+    let index = split(&dir.path().join(ARCHIVE), &chunks, 8192).unwrap();
+    assert_eq!(index.chunks.len(), 2);
+    assert!(!chunks.join(format!("{ARCHIVE}.part002")).exists());
+}
+
+#[test]
+fn rejects_missing_and_corrupt_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    archive(dir.path());
+    let chunks = dir.path().join("chunks");
+    let index = split(&dir.path().join(ARCHIVE), &chunks, 4096).unwrap();
+    let path = index_path(&chunks, ARCHIVE);
+    let second = chunks.join(&index.chunks[1].file);
+    let original = fs::read(&second).unwrap();
+
+    let mut corrupt = original.clone();
+    corrupt[100] ^= 1;
+    fs::write(&second, &corrupt).unwrap();
+    assert!(matches!(
+        join(&path),
+        Err(Error::Unverified { archive, status: Status::DigestMismatch { .. } })
+            if archive == index.chunks[1].file
+    ));
+
+    fs::write(&second, &original[..4000]).unwrap();
+    assert!(matches!(
+        join(&path),
+        Err(Error::Unverified {
+            status: Status::SizeMismatch {
+                expected: 4096,
+                actual: 4000
+            },
+            ..
+        })
+    ));
+
+    fs::remove_file(&second).unwrap();
+    assert!(matches!(
+        join(&path),
+        Err(Error::Unverified {
+            status: Status::Missing,
+            ..
+        })
+    ));
+    // Failed joins leave neither the archive nor a partial file.
+    assert_eq!(
+        names(&chunks),
+        [
+            format!("{ARCHIVE}.chunks.toml"),
+            format!("{ARCHIVE}.part000"),
+            format!("{ARCHIVE}.part002"),
+        ]
+    );
+}
+
+#[test]
+fn rejects_bad_indexes_and_chunk_sizes() {
+    let dir = tempfile::tempdir().unwrap();
+    archive(dir.path());
+    assert!(matches!(
+        split(&dir.path().join(ARCHIVE), dir.path(), 0),
+        Err(Error::ChunkSize)
+    ));
+
+    let index = split(&dir.path().join(ARCHIVE), dir.path(), 1 << 20).unwrap();
+    assert_eq!(index.chunks.len(), 1);
+    let path = index_path(dir.path(), ARCHIVE);
+    let text = fs::read_to_string(&path).unwrap();
+    fs::write(&path, text.replace(&index.chunks[0].file, "../elsewhere")).unwrap();
+    assert!(matches!(Index::load(&path), Err(Error::Index { .. })));
+    fs::write(&path, "archive = 1\n").unwrap();
+    assert!(matches!(join(&path), Err(Error::Index { .. })));
+}
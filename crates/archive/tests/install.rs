@@ -0,0 +1,130 @@
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+
+use linaro_archive::install::{read_stamp, Installer, Outcome};
+use linaro_archive::verify::Verifier;
+use linaro_archive::Error;
+use linaro_manifest::{sha256_file, Toolchain};
+
+const STEM: &str = "gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu";
+
+fn add_file(builder: &mut tar::Builder<impl std::io::Write>, path: &str, mode: u32, data: &[u8]) {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(mode);
+    header.set_mtime(1_575_000_000);
+    builder.append_data(&mut header, path, data).unwrap();
+}
+
+fn write_archive(dir: &Path) -> Toolchain {
+    let path = dir.join(format!("{STEM}.tar.xz"));
+    let encoder = xz2::write::XzEncoder::new(fs::File::create(&path).unwrap(), 6);
+    let mut builder = tar::Builder::new(encoder);
+    add_file(
+        &mut builder,
+        &format!("{STEM}/bin/aarch64-linux-gnu-gcc"),
+        0o775,
+        b"#!/bin/sh\n",
+    );
+    add_file(
+        &mut builder,
+        &format!("{STEM}/share/doc/README"),
+        0o664,
+        b"docs\n",
+    );
+    let mut link = tar::Header::new_gnu();
+    link.set_entry_type(tar::EntryType::Symlink);
+    link.set_size(0);
+    builder
+        .append_link(
+            &mut link,
+            format!("{STEM}/bin/aarch64-linux-gnu-cc"),
+            "aarch64-linux-gnu-gcc",
+        )
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let (sha256, size) = sha256_file(&path).unwrap();
+    Toolchain {
+        gcc: "7.5.0".parse().unwrap(),
+        release: "2019.12".parse().unwrap(),
+        target: "aarch64-linux-gnu".parse().unwrap(),
+        host: "x86_64".into(),
+        archive: format!("{STEM}.tar.xz"),
+        size,
+        sha256,
+    }
+}
+
+fn installer(dir: &Path) -> Installer {
+    let verifier = Verifier::new(dir, dir.join("keyring.gpg"));
+    Installer::new(verifier, dir.join("prebuilts/gcc"))
+}
+
+#[test]
+fn installs_normalized_tree_idempotently() {
+    let dir = tempfile::tempdir().unwrap();
+    let toolchain = write_archive(dir.path());
+    let installer = installer(dir.path());
+    let dest = dir
+        .path()
+        .join("prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0");
+
+    assert_eq!(
+        installer.install(&toolchain).unwrap(),
+        Outcome::Installed(dest.clone())
+    );
+    let gcc = fs::metadata(dest.join("bin/aarch64-linux-gnu-gcc")).unwrap();
+    assert_eq!(gcc.permissions().mode() & 0o7777, 0o755);
+    assert_eq!(gcc.mtime(), 0);
+    let readme = fs::metadata(dest.join("share/doc/README")).unwrap();
+    assert_eq!(readme.permissions().mode() & 0o7777, 0o644);
+    assert_eq!(fs::metadata(dest.join("share")).unwrap().mtime(), 0);
+    assert_eq!(
+        fs::read_link(dest.join("bin/aarch64-linux-gnu-cc")).unwrap(),
+        Path::new("aarch64-linux-gnu-gcc")
+    );
+    assert_eq!(read_stamp(&dest).unwrap().archive, toolchain.archive);
+
+    assert_eq!(
+        installer.install(&toolchain).unwrap(),
+        Outcome::AlreadyInstalled(dest.clone())
+    );
+    let leftovers: Vec<_> = fs::read_dir(dest.parent().unwrap()).unwrap().collect();
+    assert_eq!(leftovers.len(), 1);
+}
+
+#[test]
+fn refuses_drifted_or_unstamped_trees() {
+    let dir = tempfile::tempdir().unwrap();
+    let toolchain = write_archive(dir.path());
+    let installer = installer(dir.path());
+    let dest = installer.install_dir(&toolchain);
+
+    installer.install(&toolchain).unwrap();
+    fs::write(dest.join("share/doc/README"), b"edited\n").unwrap();
+    assert!(matches!(
+        installer.install(&toolchain),
+        Err(Error::Drifted(_))
+    ));
+
+    fs::remove_dir_all(&dest).unwrap();
+    fs::create_dir_all(&dest).unwrap();
+    assert!(matches!(
+        installer.install(&toolchain),
+        Err(Error::Unstamped(_))
+    ));
+}
+
+#[test]
+fn refuses_unverified_archives() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut toolchain = write_archive(dir.path());
+    toolchain.size += 1;
+    assert!(matches!(
+        installer(dir.path()).install(&toolchain),
+        Err(Error::Unverified { .. })
+    ));
+    assert!(!dir.path().join("prebuilts").exists());
+}
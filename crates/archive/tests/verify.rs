@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::Path;
+
+use linaro_archive::verify::{Signature, Status, Verifier};
+use linaro_archive::Error;
+use linaro_manifest::{sha256_file, Manifest, Toolchain};
+
+const ARCHIVE: &str = "gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz";
+
+fn toolchain_for(path: &Path) -> Toolchain {
+    let (sha256, size) = sha256_file(path).unwrap();
+    Toolchain {
+        gcc: "7.5.0".parse().unwrap(),
+        release: "2019.12".parse().unwrap(),
+        target: "aarch64-linux-gnu".parse().unwrap(),
+        host: "x86_64".into(),
+        archive: ARCHIVE.into(),
+        size,
+        sha256,
+    }
+}
+
+fn setup() -> (tempfile::TempDir, Toolchain) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(ARCHIVE);
+    fs::write(&path, b"not really an xz archive").unwrap();
+    let toolchain = toolchain_for(&path);
+    (dir, toolchain)
+}
+
+#[cfg(unix)]
+fn stub_gpgv(dir: &Path, exit: i32) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join(format!("gpgv-{exit}"));
+    fs::write(
+        &path,
+        format!("#!/bin/sh\necho 'gpgv: BAD signature' >&2\nexit {exit}\n"),
+    )
+    .unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn reports_ok_missing_and_mismatches() {
+    let (dir, toolchain) = setup();
+    let verifier = Verifier::new(dir.path(), dir.path().join("keyring.gpg"));
+    assert_eq!(
+        verifier.verify(&toolchain).unwrap().status,
+        Status::Ok {
+            signature: Signature::Unsigned
+        }
+    );
+
+    let mut wrong_size = toolchain.clone();
+    wrong_size.size += 1;
+    assert!(matches!(
+        verifier.verify(&wrong_size).unwrap().status,
+        Status::SizeMismatch { actual, .. } if actual == toolchain.size
+    ));
+
+    let mut wrong_digest = toolchain.clone();
+    wrong_digest.sha256 = "00".repeat(32).parse().unwrap();
+    assert!(matches!(
+        verifier.verify(&wrong_digest).unwrap().status,
+        Status::DigestMismatch { actual, .. } if actual == toolchain.sha256
+    ));
+
+    fs::remove_file(dir.path().join(ARCHIVE)).unwrap();
+    assert_eq!(verifier.verify(&toolchain).unwrap().status, Status::Missing);
+}
+
+#[cfg(unix)]
+#[test]
+fn checks_detached_signatures_with_gpgv() {
+    let (dir, toolchain) = setup();
+    fs::write(dir.path().join(format!("{ARCHIVE}.asc")), b"signature").unwrap();
+
+    let verifier = Verifier::new(dir.path(), dir.path().join("keyring.gpg"));
+    assert!(matches!(
+        verifier.verify(&toolchain),
+        Err(Error::MissingKeyring { .. })
+    ));
+
+    fs::write(dir.path().join("keyring.gpg"), b"keys").unwrap();
+    let good = verifier.clone().gpgv(stub_gpgv(dir.path(), 0));
+    assert_eq!(
+        good.verify(&toolchain).unwrap().status,
+        Status::Ok {
+            signature: Signature::Verified
+        }
+    );
+
+    let bad = verifier.gpgv(stub_gpgv(dir.path(), 1));
+    assert_eq!(
+        bad.verify(&toolchain).unwrap().status,
+        Status::BadSignature {
+            detail: "gpgv: BAD signature".into()
+        }
+    );
+}
+
+#[test]
+fn lists_archives_missing_from_the_manifest() {
+    let (dir, toolchain) = setup();
+    fs::write(dir.path().join("extra.tar.xz"), b"").unwrap();
+    fs::write(dir.path().join("notes.txt"), b"").unwrap();
+    let manifest = Manifest {
+        toolchains: vec![toolchain],
+        ..Manifest::new()
+    };
+    let verifier = Verifier::new(dir.path(), dir.path().join("keyring.gpg"));
+    assert_eq!(verifier.unlisted(&manifest).unwrap(), ["extra.tar.xz"]);
+}
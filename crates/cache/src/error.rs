@@ -0,0 +1,35 @@
+use std::io;
+use std::path::PathBuf;
+
+use linaro_manifest::Sha256;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{what}: expected SHA-256 {expected}, got {actual}")]
+    DigestMismatch {
+        what: String,
+        expected: Sha256,
+        actual: Sha256,
+    },
+    #[error("{what}: expected {expected} bytes, got {actual}")]
+    SizeMismatch {
+        what: String,
+        expected: u64,
+        actual: u64,
+    },
+    #[error("invalid mirror `{0}`: expected a directory or an http:// URL")]
+    BadMirror(String),
+    #[error("{0} not found on the mirror")]
+    NotFound(String),
+    #[error("{url}: {reason}")]
+    Http { url: String, reason: String },
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
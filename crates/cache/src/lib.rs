@@ -0,0 +1,312 @@
+//! A content-addressed cache of toolchain archives, and the mirrors that
+//! fill it.
+//!
+//! Archives are stored by SHA-256, so the same tarball is kept once however
+//! many checkouts or manifests refer to it:
+//!
+//! ```text
+//! <cache>/sha256/ab/ab12…ef        the archive
+//! <cache>/sha256/ab/ab12…ef.asc    its detached signature, if any
+//! <cache>/names/<archive>          symlink to the above, by file name
+//! ```
+//!
+//! `names/` has the layout `linaro_archive::verify::Verifier` expects, so
+//! it can be passed wherever an archives directory is. A cache directory is
+//! also a mirror: other machines can fetch from it as a plain directory (a
+//! network share) or over HTTP with [`serve::Server`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+
+use linaro_manifest::{sha256_reader, Sha256, Toolchain};
+use serde::Serialize;
+
+mod error;
+pub mod mirror;
+pub mod serve;
+
+pub use error::Error;
+pub use mirror::Mirror;
+
+const BLOBS: &str = "sha256";
+const NAMES: &str = "names";
+const TMP: &str = "tmp";
+
+/// An archive held in the cache.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    pub sha256: Sha256,
+    pub size: u64,
+    /// File names under `names/` pointing at this archive.
+    pub names: Vec<String>,
+    pub signed: bool,
+}
+
+/// Whether [`Cache::import`] or [`Cache::fetch`] had to store anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stored {
+    Added,
+    AlreadyPresent,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    root: PathBuf,
+}
+
+impl Cache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The directory of archives by file name.
+    pub fn names_dir(&self) -> PathBuf {
+        self.root.join(NAMES)
+    }
+
+    /// Where the archive with digest `sha256` is stored, relative to the
+    /// cache root; mirrors use the same relative path.
+    pub fn blob_path(sha256: &Sha256) -> String {
+        let hex = sha256.to_string();
+        format!("{BLOBS}/{}/{hex}", &hex[..2])
+    }
+
+    pub fn contains(&self, sha256: &Sha256) -> bool {
+        self.root.join(Self::blob_path(sha256)).is_file()
+    }
+
+    /// Copies the archive at `path` into the cache under its file name,
+    /// along with `<path>.asc` if there is one.
+    pub fn import(&self, path: &Path) -> Result<(Sha256, Stored), Error> {
+        let name = file_name(path)?;
+        let file = fs::File::open(path).map_err(Error::io(path))?;
+        let (sha256, _, stored) = self.store(file, None, &path.display().to_string())?;
+        let signature = path.with_file_name(format!("{name}.asc"));
+        if signature.is_file() {
+            let data = fs::read(&signature).map_err(Error::io(&signature))?;
+            self.store_signature(&sha256, &data)?;
+        }
+        self.link(name, &sha256)?;
+        Ok((sha256, stored))
+    }
+
+    /// Fetches `toolchain`'s archive (and signature, if the mirror has one)
+    /// from `mirror` unless it is already cached, checking its size and
+    /// digest against the manifest.
+    pub fn fetch(&self, mirror: &Mirror, toolchain: &Toolchain) -> Result<Stored, Error> {
+        let stored = if self.contains(&toolchain.sha256) {
+            Stored::AlreadyPresent
+        } else {
+            let path = Self::blob_path(&toolchain.sha256);
+            let reader = mirror.open(&path)?;
+            let expected = (toolchain.sha256, toolchain.size);
+            self.store(reader, Some(expected), &toolchain.archive)?.2
+        };
+        let asc = self
+            .root
+            .join(format!("{}.asc", Self::blob_path(&toolchain.sha256)));
+        if !asc.is_file() {
+            match mirror.open(&format!("{}.asc", Self::blob_path(&toolchain.sha256))) {
+                Ok(mut reader) => {
+                    let mut data = Vec::new();
+                    reader
+                        .read_to_end(&mut data)
+                        .map_err(Error::io(mirror.to_string()))?;
+                    self.store_signature(&toolchain.sha256, &data)?;
+                }
+                Err(Error::NotFound(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        self.link(&toolchain.archive, &toolchain.sha256)?;
+        Ok(stored)
+    }
+
+    /// The cached archives, by digest.
+    pub fn list(&self) -> Result<Vec<Entry>, Error> {
+        let mut names: BTreeMap<Sha256, Vec<String>> = BTreeMap::new();
+        for (name, target) in read_dir(&self.names_dir())? {
+            if name.ends_with(".asc") {
+                continue;
+            }
+            if let Some(sha256) = fs::read_link(&target)
+                .ok()
+                .and_then(|link| link.file_name()?.to_str()?.parse().ok())
+            {
+                names.entry(sha256).or_default().push(name);
+            }
+        }
+
+        let mut entries = Vec::new();
+        for (_, dir) in read_dir(&self.root.join(BLOBS))? {
+            for (name, path) in read_dir(&dir)? {
+                let Ok(sha256) = name.parse::<Sha256>() else {
+                    continue;
+                };
+                let size = fs::metadata(&path).map_err(Error::io(&path))?.len();
+                entries.push(Entry {
+                    sha256,
+                    size,
+                    names: names.remove(&sha256).unwrap_or_default(),
+                    signed: dir.join(format!("{name}.asc")).is_file(),
+                });
+            }
+        }
+        entries.sort_by_key(|e| e.sha256);
+        Ok(entries)
+    }
+
+    /// Removes archives whose digest is not in `keep`, and the names
+    /// pointing at them. With `dry_run`, only reports what would go.
+    pub fn gc(&self, keep: &BTreeSet<Sha256>, dry_run: bool) -> Result<Vec<Entry>, Error> {
+        let unused: Vec<_> = self
+            .list()?
+            .into_iter()
+            .filter(|entry| !keep.contains(&entry.sha256))
+            .collect();
+        if dry_run {
+            return Ok(unused);
+        }
+        for entry in &unused {
+            let blob = self.root.join(Self::blob_path(&entry.sha256));
+            for name in &entry.names {
+                for link in [name.clone(), format!("{name}.asc")] {
+                    remove_if_exists(&self.names_dir().join(link))?;
+                }
+            }
+            remove_if_exists(&blob.with_extension("asc"))?;
+            fs::remove_file(&blob).map_err(Error::io(&blob))?;
+        }
+        Ok(unused)
+    }
+
+    /// Writes `reader` into the cache, checking it against `expected` (digest
+    /// and size) if given. The data goes to a temporary file that is only
+    /// renamed into place once complete and verified.
+    fn store(
+        &self,
+        reader: impl Read,
+        expected: Option<(Sha256, u64)>,
+        what: &str,
+    ) -> Result<(Sha256, u64, Stored), Error> {
+        let tmp_dir = self.root.join(TMP);
+        fs::create_dir_all(&tmp_dir).map_err(Error::io(&tmp_dir))?;
+        let tmp = tempfile::NamedTempFile::new_in(&tmp_dir).map_err(Error::io(&tmp_dir))?;
+        let mut tee = Tee {
+            reader,
+            writer: io::BufWriter::new(tmp.as_file()),
+        };
+        let (sha256, size) = sha256_reader(&mut tee).map_err(Error::io(what))?;
+        tee.writer.flush().map_err(Error::io(tmp.path()))?;
+        drop(tee);
+
+        if let Some((expected_sha256, expected_size)) = expected {
+            if size != expected_size {
+                return Err(Error::SizeMismatch {
+                    what: what.to_owned(),
+                    expected: expected_size,
+                    actual: size,
+                });
+            }
+            if sha256 != expected_sha256 {
+                return Err(Error::DigestMismatch {
+                    what: what.to_owned(),
+                    expected: expected_sha256,
+                    actual: sha256,
+                });
+            }
+        }
+
+        let blob = self.root.join(Self::blob_path(&sha256));
+        if blob.is_file() {
+            return Ok((sha256, size, Stored::AlreadyPresent));
+        }
+        let dir = blob.parent().expect("blob paths have a parent");
+        fs::create_dir_all(dir).map_err(Error::io(dir))?;
+        tmp.persist(&blob)
+            .map_err(|err| Error::io(&blob)(err.error))?;
+        Ok((sha256, size, Stored::Added))
+    }
+
+    fn store_signature(&self, sha256: &Sha256, data: &[u8]) -> Result<(), Error> {
+        let path = self.root.join(format!("{}.asc", Self::blob_path(sha256)));
+        fs::write(&path, data).map_err(Error::io(&path))
+    }
+
+    /// Points `names/<name>` (and `<name>.asc`, when signed) at the archive.
+    fn link(&self, name: &str, sha256: &Sha256) -> Result<(), Error> {
+        let names = self.names_dir();
+        fs::create_dir_all(&names).map_err(Error::io(&names))?;
+        let target = format!("../{}", Self::blob_path(sha256));
+        let mut links = vec![(name.to_owned(), target.clone())];
+        if self
+            .root
+            .join(format!("{}.asc", Self::blob_path(sha256)))
+            .is_file()
+        {
+            links.push((format!("{name}.asc"), format!("{target}.asc")));
+        }
+        for (link, target) in links {
+            let path = names.join(link);
+            if fs::read_link(&path).is_ok_and(|t| t == Path::new(&target)) {
+                continue;
+            }
+            remove_if_exists(&path)?;
+            symlink(&target, &path).map_err(Error::io(&path))?;
+        }
+        Ok(())
+    }
+}
+
+/// Copies everything read from `reader` to `writer`.
+struct Tee<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: Read, W: Write> Read for Tee<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.writer.write_all(&buf[..n])?;
+        Ok(n)
+    }
+}
+
+fn file_name(path: &Path) -> Result<&str, Error> {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| Error::io(path)(io::Error::other("not a file name")))
+}
+
+/// The entries of `dir` as (file name, path), sorted; empty if `dir` does
+/// not exist.
+fn read_dir(dir: &Path) -> Result<Vec<(String, PathBuf)>, Error> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(Error::io(dir)(err)),
+    };
+    let mut result = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(Error::io(dir))?;
+        if let Ok(name) = entry.file_name().into_string() {
+            result.push((name, entry.path()));
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), Error> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(Error::io(path)(err)),
+        _ => Ok(()),
+    }
+}
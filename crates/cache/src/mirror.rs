@@ -0,0 +1,142 @@
+//! Where [`Cache::fetch`](crate::Cache::fetch) gets archives from.
+//!
+//! A mirror is another cache: a directory with the same `sha256/` layout,
+//! typically on a network share, or the same tree served over plain HTTP by
+//! [`Server`](crate::serve::Server) on the LAN. HTTPS is not supported;
+//! integrity comes from the manifest digests, not the transport.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::Error;
+
+/// How long a mirror may stay silent before a fetch gives up.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mirror {
+    Dir(PathBuf),
+    Http {
+        host: String,
+        port: u16,
+        /// Path of the cache root on the server, without a trailing `/`.
+        prefix: String,
+    },
+}
+
+impl FromStr for Mirror {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(rest) = s.strip_prefix("http://") else {
+            if s.contains("://") {
+                return Err(Error::BadMirror(s.to_owned()));
+            }
+            return Ok(Mirror::Dir(s.into()));
+        };
+        let (authority, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse().map_err(|_| Error::BadMirror(s.to_owned()))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(Error::BadMirror(s.to_owned()));
+        }
+        let prefix = prefix.trim_end_matches('/');
+        Ok(Mirror::Http {
+            host: host.to_owned(),
+            port,
+            prefix: if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("/{prefix}")
+            },
+        })
+    }
+}
+
+impl fmt::Display for Mirror {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mirror::Dir(dir) => write!(f, "{}", dir.display()),
+            Mirror::Http { host, port, prefix } => write!(f, "http://{host}:{port}{prefix}"),
+        }
+    }
+}
+
+impl Mirror {
+    /// Opens `path` (relative to the cache root) on the mirror.
+    pub fn open(&self, path: &str) -> Result<Box<dyn Read + Send>, Error> {
+        match self {
+            Mirror::Dir(dir) => {
+                let file = dir.join(path);
+                match fs::File::open(&file) {
+                    Ok(f) => Ok(Box::new(f)),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                        Err(Error::NotFound(file.display().to_string()))
+                    }
+                    Err(err) => Err(Error::io(file)(err)),
+                }
+            }
+            Mirror::Http { host, port, prefix } => {
+                let url = format!("{self}/{path}");
+                let http = |reason: String| Error::Http {
+                    url: url.clone(),
+                    reason,
+                };
+                let mut stream =
+                    TcpStream::connect((host.as_str(), *port)).map_err(|e| http(e.to_string()))?;
+                stream
+                    .set_read_timeout(Some(TIMEOUT))
+                    .map_err(|e| http(e.to_string()))?;
+                write!(
+                    stream,
+                    "GET {prefix}/{path} HTTP/1.0\r\nHost: {host}\r\nConnection: close\r\n\r\n"
+                )
+                .map_err(|e| http(e.to_string()))?;
+
+                let mut reader = BufReader::new(stream);
+                let mut status = String::new();
+                reader
+                    .read_line(&mut status)
+                    .map_err(|e| http(e.to_string()))?;
+                let code = status
+                    .split_whitespace()
+                    .nth(1)
+                    .ok_or_else(|| http(format!("malformed status line `{}`", status.trim())))?;
+                let mut length = None;
+                loop {
+                    let mut line = String::new();
+                    reader
+                        .read_line(&mut line)
+                        .map_err(|e| http(e.to_string()))?;
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse::<u64>().ok();
+                        }
+                    }
+                }
+                match code {
+                    "200" => Ok(match length {
+                        Some(length) => Box::new(reader.take(length)),
+                        None => Box::new(reader),
+                    }),
+                    "404" => Err(Error::NotFound(url)),
+                    _ => Err(http(status.trim().to_owned())),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! A minimal HTTP/1.0 server publishing a cache to the LAN.
+//!
+//! It answers `GET` and `HEAD` for files under `sha256/` and `names/` and
+//! nothing else, which is all [`Mirror`](crate::Mirror) clients ask for.
+//! It is meant for trusted networks: clients check every archive against
+//! the manifest, so the server needs neither TLS nor authentication.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::Error;
+
+pub struct Server {
+    root: PathBuf,
+    listener: TcpListener,
+}
+
+impl Server {
+    /// Listens on `addr` for requests for files in the cache at `root`.
+    pub fn bind(root: impl Into<PathBuf>, addr: impl ToSocketAddrs) -> Result<Self, Error> {
+        let listener = TcpListener::bind(addr).map_err(Error::io("listen address"))?;
+        Ok(Self {
+            root: root.into(),
+            listener,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.listener
+            .local_addr()
+            .map_err(Error::io("listen address"))
+    }
+
+    /// Serves requests until the process exits, one thread per connection.
+    pub fn run(self) -> Result<(), Error> {
+        for stream in self.listener.incoming() {
+            let stream = stream.map_err(Error::io("listen address"))?;
+            let root = self.root.clone();
+            // A failed connection only affects that client.
+            thread::spawn(move || {
+                let _ = handle(&root, stream);
+            });
+        }
+        Ok(())
+    }
+}
+
+fn handle(root: &Path, stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let mut stream = stream;
+    let mut parts = request.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    if method != "GET" && method != "HEAD" {
+        return respond(&mut stream, "405 Method Not Allowed", None);
+    }
+    let Some(path) = resolve(root, target) else {
+        return respond(&mut stream, "404 Not Found", None);
+    };
+    let Ok(mut file) = fs::File::open(&path) else {
+        return respond(&mut stream, "404 Not Found", None);
+    };
+    let length = file.metadata()?.len();
+    respond(&mut stream, "200 OK", Some(length))?;
+    if method == "GET" {
+        io::copy(&mut file, &mut stream)?;
+    }
+    stream.flush()
+}
+
+/// The file `target` names, if it is a plain path into `sha256/` or
+/// `names/`.
+fn resolve(root: &Path, target: &str) -> Option<PathBuf> {
+    let target = target.strip_prefix('/')?;
+    let components: Vec<_> = target.split('/').collect();
+    let safe = components
+        .iter()
+        .all(|c| !c.is_empty() && *c != "." && *c != ".." && !c.contains('\\'));
+    if !safe || !matches!(components[0], "sha256" | "names") || components.len() < 2 {
+        return None;
+    }
+    let path = root.join(target);
+    path.is_file().then_some(path)
+}
+
+fn respond(stream: &mut TcpStream, status: &str, length: Option<u64>) -> io::Result<()> {
+    write!(stream, "HTTP/1.0 {status}\r\nConnection: close\r\n")?;
+    match length {
+        Some(length) => write!(
+            stream,
+            "Content-Type: application/octet-stream\r\nContent-Length: {length}\r\n\r\n"
+        ),
+        None => write!(stream, "Content-Length: 0\r\n\r\n"),
+    }
+}
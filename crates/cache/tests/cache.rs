@@ -0,0 +1,210 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+use linaro_cache::serve::Server;
+use linaro_cache::{Cache, Error, Mirror, Stored};
+use linaro_manifest::{sha256_file, Toolchain};
+
+const ARCHIVE: &str = "gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz";
+const OTHER: &str = "gcc-linaro-7.5.0-2019.12-x86_64_arm-linux-gnueabihf.tar.xz";
+
+fn toolchain_for(path: &Path) -> Toolchain {
+    let (sha256, size) = sha256_file(path).unwrap();
+    Toolchain {
+        gcc: "7.5.0".parse().unwrap(),
+        release: "2019.12".parse().unwrap(),
+        target: "aarch64-linux-gnu".parse().unwrap(),
+        host: "x86_64".into(),
+        archive: path.file_name().unwrap().to_str().unwrap().into(),
+        size,
+        sha256,
+    }
+}
+
+/// A cache at `<dir>/cache` holding two dummy archives, the first signed.
+fn setup(dir: &Path) -> (Cache, Toolchain, Toolchain) {
+    let downloads = dir.join("downloads");
+    fs::create_dir(&downloads).unwrap();
+    fs::write(downloads.join(ARCHIVE), b"aarch64 archive").unwrap();
+    fs::write(downloads.join(format!("{ARCHIVE}.asc")), b"signature").unwrap();
+    fs::write(downloads.join(OTHER), b"arm archive").unwrap();
+    let cache = Cache::new(dir.join("cache"));
+    for name in [ARCHIVE, OTHER] {
+        let (_, stored) = cache.import(&downloads.join(name)).unwrap();
+        assert_eq!(stored, Stored::Added);
+    }
+    (
+        cache,
+        toolchain_for(&downloads.join(ARCHIVE)),
+        toolchain_for(&downloads.join(OTHER)),
+    )
+}
+
+#[test]
+fn imports_and_lists_archives() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cache, aarch64, arm) = setup(dir.path());
+
+    let entries = cache.list().unwrap();
+    let mut expected = [(aarch64.sha256, ARCHIVE, true), (arm.sha256, OTHER, false)];
+    expected.sort();
+    let listed: Vec<_> = entries
+        .iter()
+        .map(|e| (e.sha256, e.names.join(","), e.signed))
+        .collect();
+    assert_eq!(
+        listed,
+        expected.map(|(sha256, name, signed)| (sha256, name.to_owned(), signed))
+    );
+
+    // names/ is laid out like an archives directory.
+    let names = cache.names_dir();
+    assert_eq!(fs::read(names.join(ARCHIVE)).unwrap(), b"aarch64 archive");
+    assert_eq!(
+        fs::read(names.join(format!("{ARCHIVE}.asc"))).unwrap(),
+        b"signature"
+    );
+    assert!(cache
+        .root()
+        .join(Cache::blob_path(&aarch64.sha256))
+        .is_file());
+
+    // Importing the same contents again under another name only adds a name.
+    let copy = dir.path().join("copy.tar.xz");
+    fs::write(&copy, b"arm archive").unwrap();
+    assert_eq!(
+        cache.import(&copy).unwrap(),
+        (arm.sha256, Stored::AlreadyPresent)
+    );
+    let entry = cache
+        .list()
+        .unwrap()
+        .into_iter()
+        .find(|e| e.sha256 == arm.sha256)
+        .unwrap();
+    assert_eq!(entry.names, ["copy.tar.xz", OTHER]);
+    assert!(!cache.root().join("tmp").read_dir().unwrap().any(|_| true));
+}
+
+#[test]
+fn gc_removes_unreferenced_archives() {
+    let dir = tempfile::tempdir().unwrap();
+    let (cache, aarch64, arm) = setup(dir.path());
+    let keep = BTreeSet::from([arm.sha256]);
+
+    let unused = cache.gc(&keep, true).unwrap();
+    assert_eq!(unused.len(), 1);
+    assert_eq!(unused[0].sha256, aarch64.sha256);
+    assert!(cache.contains(&aarch64.sha256));
+
+    cache.gc(&keep, false).unwrap();
+    assert!(!cache.contains(&aarch64.sha256));
+    assert!(cache.contains(&arm.sha256));
+    assert!(fs::symlink_metadata(cache.names_dir().join(ARCHIVE)).is_err());
+    assert!(fs::symlink_metadata(cache.names_dir().join(format!("{ARCHIVE}.asc"))).is_err());
+    assert_eq!(cache.list().unwrap().len(), 1);
+}
+
+#[test]
+fn fetches_from_directory_mirror() {
+    let dir = tempfile::tempdir().unwrap();
+    let (upstream, aarch64, arm) = setup(dir.path());
+    let mirror = Mirror::Dir(upstream.root().to_owned());
+    let cache = Cache::new(dir.path().join("local"));
+
+    assert_eq!(cache.fetch(&mirror, &aarch64).unwrap(), Stored::Added);
+    assert_eq!(
+        cache.fetch(&mirror, &aarch64).unwrap(),
+        Stored::AlreadyPresent
+    );
+    let listed: Vec<_> = upstream
+        .list()
+        .unwrap()
+        .into_iter()
+        .filter(|e| e.sha256 == aarch64.sha256)
+        .collect();
+    assert_eq!(cache.list().unwrap(), listed);
+
+    let mut wrong_size = arm.clone();
+    wrong_size.size += 1;
+    assert!(matches!(
+        cache.fetch(&mirror, &wrong_size),
+        Err(Error::SizeMismatch { .. })
+    ));
+    assert!(!cache.contains(&arm.sha256));
+
+    // A mirror whose blob was corrupted in place.
+    let blob = upstream.root().join(Cache::blob_path(&arm.sha256));
+    fs::write(&blob, b"arm ARCHIVE").unwrap();
+    assert!(matches!(
+        cache.fetch(&mirror, &arm),
+        Err(Error::DigestMismatch { actual, .. }) if actual != arm.sha256
+    ));
+    assert!(!cache.contains(&arm.sha256));
+
+    fs::remove_file(&blob).unwrap();
+    assert!(matches!(
+        cache.fetch(&mirror, &arm),
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[test]
+fn fetches_over_http() {
+    let dir = tempfile::tempdir().unwrap();
+    let (upstream, aarch64, arm) = setup(dir.path());
+    let server = Server::bind(upstream.root(), "127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+
+    let mirror: Mirror = format!("http://{addr}/").parse().unwrap();
+    assert_eq!(mirror.to_string(), format!("http://{addr}"));
+    let cache = Cache::new(dir.path().join("local"));
+    assert_eq!(cache.fetch(&mirror, &aarch64).unwrap(), Stored::Added);
+    assert_eq!(cache.fetch(&mirror, &arm).unwrap(), Stored::Added);
+    assert_eq!(cache.list().unwrap(), upstream.list().unwrap());
+
+    assert!(matches!(
+        mirror.open("sha256/../../downloads"),
+        Err(Error::NotFound(_))
+    ));
+    assert!(matches!(
+        mirror.open("names/missing"),
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[test]
+fn parses_mirrors() {
+    assert_eq!(
+        "/srv/cache".parse::<Mirror>().unwrap(),
+        Mirror::Dir("/srv/cache".into())
+    );
+    assert_eq!(
+        "http://buildcache.lan:8080/linaro/"
+            .parse::<Mirror>()
+            .unwrap(),
+        Mirror::Http {
+            host: "buildcache.lan".into(),
+            port: 8080,
+            prefix: "/linaro".into(),
+        }
+    );
+    assert_eq!(
+        "http://buildcache.lan"
+            .parse::<Mirror>()
+            .unwrap()
+            .to_string(),
+        "http://buildcache.lan:80"
+    );
+    assert!(matches!(
+        "https://buildcache.lan".parse::<Mirror>(),
+        Err(Error::BadMirror(_))
+    ));
+    assert!(matches!(
+        "http://buildcache.lan:http".parse::<Mirror>(),
+        Err(Error::BadMirror(_))
+    ));
+}
@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_abi::{Checker, Rootfs};
+
+use crate::Format;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  every binary would load on the device
+  1  error before checking (unreadable binary, rootfs not found)
+  2  usage error
+  3  a binary has a missing interpreter, library or symbol version";
+
+/// Check binaries against a device root filesystem: interpreter, DT_NEEDED
+/// libraries and GLIBC_x.y symbol versions.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// Binaries or shared libraries to check.
+    #[arg(required = true, value_name = "BINARY")]
+    binaries: Vec<PathBuf>,
+    /// The device's root filesystem, unpacked.
+    #[arg(long)]
+    rootfs: PathBuf,
+    /// Where the binaries will be installed on the device; $ORIGIN in their
+    /// run paths expands to this.
+    #[arg(long, default_value = "/usr/bin")]
+    install_dir: String,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let rootfs = Rootfs::open(&args.rootfs)?;
+    let checker = Checker::new(rootfs).install_dir(args.install_dir);
+    let reports = args
+        .binaries
+        .iter()
+        .map(|binary| {
+            checker
+                .check(binary)
+                .with_context(|| format!("cannot check {}", binary.display()))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    match args.format {
+        Format::Text => {
+            for report in &reports {
+                let verdict = if report.ok() { "ok" } else { "fail" };
+                println!("{verdict}\t{}", report.binary.display());
+                for problem in &report.problems {
+                    println!("  {problem}");
+                }
+            }
+        }
+        Format::Json => {
+            let report = serde_json::json!({ "binaries": reports });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+
+    let failed = reports.iter().any(|r| !r.ok());
+    Ok(ExitCode::from(if failed { 3 } else { 0 }))
+}
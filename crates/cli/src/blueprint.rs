@@ -0,0 +1,58 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_gen::soong;
+use linaro_manifest::Manifest;
+
+use crate::default_host;
+
+/// Exit code of `--check` when a generated file is out of date.
+const EXIT_STALE: u8 = 3;
+
+/// Generate Android.bp and Android.mk for the prebuilts directory.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Manifest listing the vendored toolchains.
+    #[arg(long, default_value = "toolchains.toml")]
+    manifest: PathBuf,
+    /// Directory receiving the files (the root of the installed tree).
+    #[arg(long, default_value = "prebuilts/gcc")]
+    out: PathBuf,
+    /// Only describe toolchains running on this host [default: this machine].
+    #[arg(long)]
+    host: Option<String>,
+    /// Do not write anything; exit with status 3 if a file is out of date.
+    #[arg(long)]
+    check: bool,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let manifest = Manifest::load(&args.manifest)?;
+    let host = args.host.as_deref().unwrap_or(default_host());
+    let files = [
+        ("Android.bp", soong::android_bp(&manifest, host)),
+        ("Android.mk", soong::android_mk(&manifest, host)),
+    ];
+
+    let mut stale = false;
+    for (name, contents) in files {
+        let path = args.out.join(name);
+        if args.check {
+            if fs::read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+                println!("{} is out of date", path.display());
+                stale = true;
+            }
+        } else {
+            fs::create_dir_all(&args.out)
+                .with_context(|| format!("creating {}", args.out.display()))?;
+            fs::write(&path, contents).with_context(|| format!("writing {}", path.display()))?;
+        }
+    }
+    Ok(if stale {
+        ExitCode::from(EXIT_STALE)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
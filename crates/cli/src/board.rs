@@ -0,0 +1,168 @@
+use std::fs;
+use std::path::{self, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::{bail, Context};
+use clap::ValueEnum;
+use linaro_gen::boards::{self, Board, Boards, Resolution};
+use linaro_manifest::{error_chain, Manifest};
+use serde::Serialize;
+
+use crate::default_host;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  every stage resolved to one toolchain
+  1  error (unreadable board table or manifest, unknown board or stage)
+  2  usage error
+  3  a stage matches no vendored toolchain, or several";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum BoardFormat {
+    #[default]
+    Text,
+    Json,
+    /// Make variables LINARO_<STAGE>_CROSS_COMPILE, _GCC, _ARCH and
+    /// _SYSROOT for one board.
+    Make,
+}
+
+/// Resolve the toolchain each firmware stage of a board builds with.
+///
+/// Stages are matched against the manifest by target triple and GCC
+/// version as given in the board table; a stage matching no vendored
+/// toolchain, or several, is an error. Without BOARD every board of the
+/// table is checked. With --stage, only that stage's CROSS_COMPILE prefix
+/// is printed, for use as
+/// `$(shell linaro-prebuilts board pine64-plus --stage u-boot)`.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// Board name, e.g. pine64-plus [default: every board].
+    board: Option<String>,
+    /// Only resolve this stage of the board, e.g. u-boot.
+    #[arg(long, requires = "board")]
+    stage: Option<String>,
+    /// Board table.
+    #[arg(long, default_value = "boards.toml")]
+    boards: PathBuf,
+    /// Manifest listing the vendored toolchains.
+    #[arg(long, default_value = "toolchains.toml")]
+    manifest: PathBuf,
+    /// Root of the installed tree; made absolute in the output.
+    #[arg(long, default_value = "prebuilts/gcc")]
+    prebuilts: PathBuf,
+    /// Host the compilers run on [default: this machine].
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long, value_enum, default_value_t)]
+    format: BoardFormat,
+    /// Write to this file instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct Resolved<'a> {
+    board: &'a str,
+    soc: &'a str,
+    stage: &'a str,
+    target: &'a str,
+    gcc: String,
+    release: &'a str,
+    cross_compile: PathBuf,
+    sysroot: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let table = Boards::load(&args.boards)?;
+    let manifest = Manifest::load(&args.manifest)?;
+    let host = args.host.as_deref().unwrap_or(default_host());
+    let prebuilts = path::absolute(&args.prebuilts)
+        .with_context(|| format!("resolving {}", args.prebuilts.display()))?;
+    let selected: Vec<(&str, &Board)> = match &args.board {
+        Some(name) => vec![(name, table.get(name)?)],
+        None => table.boards.iter().map(|(n, b)| (n.as_str(), b)).collect(),
+    };
+    if args.format == BoardFormat::Make && selected.len() != 1 {
+        bail!("--format make needs a BOARD");
+    }
+
+    let mut failed = false;
+    let mut resolved = Vec::new();
+    for &(name, board) in &selected {
+        let result = match &args.stage {
+            Some(stage) => board
+                .stage(stage)?
+                .resolve(&manifest, host)
+                .map(|toolchain| vec![Resolution { stage, toolchain }]),
+            None => board.resolve(&manifest, host),
+        };
+        match result {
+            Ok(resolutions) => resolved.push((name, board, resolutions)),
+            Err(err) => {
+                eprintln!("linaro-prebuilts: {name}: {}", error_chain(&err));
+                failed = true;
+            }
+        }
+    }
+    if failed && args.board.is_some() {
+        return Ok(ExitCode::from(3));
+    }
+
+    let output = match args.format {
+        BoardFormat::Make => {
+            let (name, board, resolutions) = &resolved[0];
+            boards::board_mk(name, board, resolutions, &prebuilts)
+        }
+        BoardFormat::Text | BoardFormat::Json => {
+            let rows: Vec<_> = resolved
+                .iter()
+                .flat_map(|(name, board, resolutions)| {
+                    resolutions.iter().map(|r| {
+                        let root = prebuilts.join(r.toolchain.install_path());
+                        Resolved {
+                            board: name,
+                            soc: &board.soc,
+                            stage: r.stage,
+                            target: r.toolchain.target.as_str(),
+                            gcc: r.toolchain.gcc.to_string(),
+                            release: r.toolchain.release.as_str(),
+                            cross_compile: root.join("bin").join(r.toolchain.target.tool_prefix()),
+                            sysroot: r.toolchain.sysroot_path().map(|s| prebuilts.join(s)),
+                        }
+                    })
+                })
+                .collect();
+            if args.format == BoardFormat::Json {
+                serde_json::to_string_pretty(&rows)? + "\n"
+            } else if args.stage.is_some() {
+                format!("{}\n", rows[0].cross_compile.display())
+            } else {
+                rows.iter()
+                    .map(|r| {
+                        format!(
+                            "{}\t{}\t{}\t{}\t{}\n",
+                            r.board,
+                            r.stage,
+                            r.target,
+                            r.gcc,
+                            r.cross_compile.display()
+                        )
+                    })
+                    .collect()
+            }
+        }
+    };
+    match &args.out {
+        Some(out) => {
+            fs::write(out, output).with_context(|| format!("writing {}", out.display()))?
+        }
+        None => print!("{output}"),
+    }
+    Ok(if failed {
+        ExitCode::from(3)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_cache::serve::Server;
+use linaro_cache::{Cache, Mirror, Stored};
+use linaro_manifest::{error_chain, Manifest};
+
+use crate::Format;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  success
+  1  error (unreadable manifest, I/O error, mirror unreachable)
+  2  usage error
+  3  fetch: an archive could not be fetched or failed verification";
+
+/// Manage the shared cache of toolchain archives, keyed by SHA-256.
+///
+/// `<cache>/names` holds every cached archive under its file name, so it can
+/// be passed as --archives to verify and install.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// Cache directory [default: $XDG_CACHE_HOME/linaro-prebuilts, or
+    /// ~/.cache/linaro-prebuilts].
+    #[arg(long, global = true)]
+    cache: Option<PathBuf>,
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum CacheCommand {
+    /// Copy downloaded archives, and their `.asc` signatures, into the cache.
+    Import {
+        #[arg(required = true, value_name = "ARCHIVE")]
+        archives: Vec<PathBuf>,
+    },
+    /// List cached archives.
+    List {
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+    },
+    /// Remove cached archives that none of the manifests lists.
+    Gc {
+        /// Manifests whose archives are kept (may be repeated).
+        #[arg(long, default_value = "toolchains.toml")]
+        manifest: Vec<PathBuf>,
+        /// Only report what would be removed.
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+    },
+    /// Fill the cache from a mirror with the archives a manifest lists.
+    Fetch {
+        /// Mirror to fetch from: another cache's directory, or the
+        /// http://host:port URL of `cache serve`.
+        #[arg(long, value_name = "MIRROR")]
+        from: Mirror,
+        #[arg(long, default_value = "toolchains.toml")]
+        manifest: PathBuf,
+        /// Only fetch these archives (may be repeated).
+        #[arg(long = "archive", value_name = "NAME")]
+        only: Vec<String>,
+    },
+    /// Serve the cache over HTTP so other machines can fetch from it.
+    Serve {
+        #[arg(long, default_value = "0.0.0.0:8080", value_name = "ADDR")]
+        listen: String,
+    },
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let cache = Cache::new(match args.cache {
+        Some(dir) => dir,
+        None => default_dir()?,
+    });
+    match args.command {
+        CacheCommand::Import { archives } => {
+            for path in &archives {
+                let (sha256, stored) = cache
+                    .import(path)
+                    .with_context(|| format!("cannot import {}", path.display()))?;
+                println!("{}\t{sha256}\t{}", stored_name(stored), path.display());
+            }
+        }
+        CacheCommand::List { format } => {
+            let entries = cache.list()?;
+            match format {
+                Format::Text => {
+                    for entry in &entries {
+                        let signed = if entry.signed { "signed" } else { "unsigned" };
+                        println!(
+                            "{}\t{}\t{signed}\t{}",
+                            entry.sha256,
+                            entry.size,
+                            entry.names.join(",")
+                        );
+                    }
+                }
+                Format::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+            }
+        }
+        CacheCommand::Gc {
+            manifest,
+            dry_run,
+            format,
+        } => {
+            let mut keep = BTreeSet::new();
+            for path in &manifest {
+                let manifest = Manifest::load(path)?;
+                keep.extend(manifest.toolchains.iter().map(|t| t.sha256));
+            }
+            let removed = cache.gc(&keep, dry_run)?;
+            match format {
+                Format::Text => {
+                    let verb = if dry_run { "would-remove" } else { "removed" };
+                    for entry in &removed {
+                        println!("{verb}\t{}\t{}", entry.sha256, entry.names.join(","));
+                    }
+                }
+                Format::Json => println!("{}", serde_json::to_string_pretty(&removed)?),
+            }
+        }
+        CacheCommand::Fetch {
+            from,
+            manifest: path,
+            only,
+        } => {
+            let manifest = Manifest::load(&path)?;
+            let mut toolchains = Vec::new();
+            for name in &only {
+                toolchains.push(
+                    manifest
+                        .find_archive(name)
+                        .with_context(|| format!("{name} is not listed in {}", path.display()))?,
+                );
+            }
+            if only.is_empty() {
+                toolchains.extend(&manifest.toolchains);
+            }
+            let mut failed = false;
+            for toolchain in toolchains {
+                match cache.fetch(&from, toolchain) {
+                    Ok(stored) => println!("{}\t{}\t", stored_name(stored), toolchain.archive),
+                    Err(err) => {
+                        failed = true;
+                        println!("failed\t{}\t{}", toolchain.archive, error_chain(&err));
+                    }
+                }
+            }
+            if failed {
+                return Ok(ExitCode::from(3));
+            }
+        }
+        CacheCommand::Serve { listen } => {
+            let server = Server::bind(cache.root(), listen.as_str())
+                .with_context(|| format!("cannot listen on {listen}"))?;
+            eprintln!(
+                "serving {} on http://{}",
+                cache.root().display(),
+                server.local_addr()?
+            );
+            server.run()?;
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn stored_name(stored: Stored) -> &'static str {
+    match stored {
+        Stored::Added => "added",
+        Stored::AlreadyPresent => "present",
+    }
+}
+
+fn default_dir() -> anyhow::Result<PathBuf> {
+    let base = match std::env::var_os("XDG_CACHE_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(
+            std::env::var_os("HOME").context("neither --cache, XDG_CACHE_HOME nor HOME is set")?,
+        )
+        .join(".cache"),
+    };
+    Ok(base.join("linaro-prebuilts"))
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::{self, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_gen::cargo;
+use linaro_manifest::{GccVersion, Manifest};
+
+use crate::default_host;
+
+/// Print `.cargo/config.toml` sections using the Linaro compilers as
+/// linkers and `cc` crate compilers for Rust cross targets.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Manifest listing the vendored toolchains.
+    #[arg(long, default_value = "toolchains.toml")]
+    manifest: PathBuf,
+    /// Root of the installed tree; made absolute in the output.
+    #[arg(long, default_value = "prebuilts/gcc")]
+    prebuilts: PathBuf,
+    /// Rust target to configure (may be repeated) [default: every Rust
+    /// target with a matching toolchain].
+    #[arg(long = "target", value_name = "TRIPLE")]
+    targets: Vec<String>,
+    /// Use this GCC version instead of the newest available.
+    #[arg(long)]
+    gcc: Option<GccVersion>,
+    /// Host the compilers run on [default: this machine].
+    #[arg(long)]
+    host: Option<String>,
+    /// Write to this file instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let manifest = Manifest::load(&args.manifest)?;
+    let host = args.host.as_deref().unwrap_or(default_host());
+    let selections = cargo::select(&manifest, host, &args.targets, args.gcc)?;
+    let prebuilts = path::absolute(&args.prebuilts)
+        .with_context(|| format!("resolving {}", args.prebuilts.display()))?;
+    let config = cargo::cargo_config(&selections, &prebuilts);
+
+    match &args.out {
+        Some(out) => {
+            fs::write(out, config).with_context(|| format!("writing {}", out.display()))?
+        }
+        None => print!("{config}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_archive::chunks::{self, Outcome, DEFAULT_CHUNK_SIZE};
+use linaro_archive::Error;
+use linaro_manifest::parse_size;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  success
+  1  error (unreadable archive or index, I/O error)
+  2  usage error
+  3  join: a chunk or a reassembled archive failed verification";
+
+/// Store archives as chunks small enough for a git host, and put them back
+/// together after a checkout.
+///
+/// `split` writes <archive>.part000, <archive>.part001, ... and an index,
+/// <archive>.chunks.toml, with the size and SHA-256 of each chunk and of the
+/// archive. Commit those and ignore the archive; `join` (e.g. from a
+/// post-checkout hook) reassembles it, checking every chunk on the way.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    #[command(subcommand)]
+    command: ChunksCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum ChunksCommand {
+    /// Split archives into chunks and write their indexes.
+    Split {
+        #[arg(required = true, value_name = "ARCHIVE")]
+        archives: Vec<PathBuf>,
+        /// Directory for the chunks and index [default: the archive's].
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Size of each chunk, in bytes or with a K, M or G suffix.
+        #[arg(long, value_parser = chunk_size, default_value_t = DEFAULT_CHUNK_SIZE)]
+        chunk_size: u64,
+    },
+    /// Reassemble and verify archives from their chunks.
+    Join {
+        /// Index files, or directories whose indexes are all joined.
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+    },
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    match args.command {
+        ChunksCommand::Split {
+            archives,
+            out,
+            chunk_size,
+        } => {
+            for archive in &archives {
+                let dir = match &out {
+                    Some(dir) => dir.clone(),
+                    None => archive
+                        .parent()
+                        .map_or_else(|| PathBuf::from("."), PathBuf::from),
+                };
+                let index = chunks::split(archive, &dir, chunk_size)
+                    .with_context(|| format!("cannot split {}", archive.display()))?;
+                println!("split\t{}\t{} chunks", index.archive, index.chunks.len());
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        ChunksCommand::Join { paths } => {
+            let mut indexes = Vec::new();
+            for path in &paths {
+                if path.is_dir() {
+                    indexes.extend(chunks::indexes(path)?);
+                } else {
+                    indexes.push(path.clone());
+                }
+            }
+            let mut failed = false;
+            for index in &indexes {
+                match chunks::join(index) {
+                    Ok(Outcome::Joined(path)) => println!("joined\t{}", path.display()),
+                    Ok(Outcome::AlreadyJoined(path)) => println!("present\t{}", path.display()),
+                    Err(err @ Error::Unverified { .. }) => {
+                        println!("failed\t{}\t{err}", index.display());
+                        failed = true;
+                    }
+                    Err(err) => {
+                        return Err(err).with_context(|| format!("cannot join {}", index.display()))
+                    }
+                }
+            }
+            Ok(if failed {
+                ExitCode::from(3)
+            } else {
+                ExitCode::SUCCESS
+            })
+        }
+    }
+}
+
+/// Parses a chunk size such as `52428800` or `50M`, which must not be zero.
+fn chunk_size(s: &str) -> Result<u64, String> {
+    let size =
+        parse_size(s).ok_or_else(|| format!("invalid size {s:?}: expected bytes or e.g. 50M"))?;
+    if size == 0 {
+        return Err("the chunk size must not be zero".to_owned());
+    }
+    Ok(size)
+}
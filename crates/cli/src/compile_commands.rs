@@ -0,0 +1,41 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_wrap::log;
+
+/// Turn invocation logs written by linaro-wrap wrappers into a
+/// compile_commands.json for clangd and IDEs.
+///
+/// Record a build with `LINARO_WRAP_LOG=/abs/path/build.log` (or `log` in
+/// linaro-wrap.toml) and CROSS_COMPILE pointing at the wrappers. Each source
+/// file gets the command of the last invocation that compiled it.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Invocation logs, in the order they were written.
+    #[arg(required = true, value_name = "LOG")]
+    logs: Vec<PathBuf>,
+    /// Write the database to this file instead of standard output.
+    #[arg(long, short)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let mut invocations = Vec::new();
+    for path in &args.logs {
+        invocations.extend(log::read(path)?);
+    }
+    let commands = log::compile_commands(&invocations);
+    if commands.is_empty() {
+        eprintln!("linaro-prebuilts: warning: no compiler invocations in the logs");
+    }
+    let json = serde_json::to_string_pretty(&commands)? + "\n";
+    match &args.out {
+        Some(path) => {
+            fs::write(path, json).with_context(|| format!("writing {}", path.display()))?
+        }
+        None => print!("{json}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use clap::ValueEnum;
+use linaro_diff::{Diff, Snapshot};
+use linaro_toolchain::Installation;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum DiffFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+/// Compare two installed toolchains: versions, configured defaults, specs,
+/// multilibs, sysroot headers and exported library symbols.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The toolchain being replaced, e.g.
+    /// prebuilts/gcc/x86_64/aarch64-linux-gnu/7.4.1.
+    old: PathBuf,
+    /// The toolchain replacing it.
+    new: PathBuf,
+    #[arg(long, value_enum, default_value_t)]
+    format: DiffFormat,
+    /// Write the report to this file instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let snapshot = |dir: &PathBuf| -> anyhow::Result<Snapshot> {
+        let installation = Installation::open(dir)?;
+        Snapshot::collect(&installation)
+            .with_context(|| format!("cannot inspect {}", dir.display()))
+    };
+    let diff = Diff::between(&snapshot(&args.old)?, &snapshot(&args.new)?);
+    let report = match args.format {
+        DiffFormat::Markdown => diff.to_markdown(),
+        DiffFormat::Json => serde_json::to_string_pretty(&diff)? + "\n",
+    };
+    match &args.out {
+        Some(path) => {
+            fs::write(path, report).with_context(|| format!("writing {}", path.display()))?
+        }
+        None => print!("{report}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
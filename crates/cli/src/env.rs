@@ -0,0 +1,63 @@
+use std::path::{self, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::Context;
+use clap::ValueEnum;
+use linaro_gen::env::{Environment, Profiles};
+use linaro_manifest::Manifest;
+
+use crate::default_host;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum Shell {
+    #[default]
+    Sh,
+    Fish,
+    /// A JSON object of variables, with PATH fully expanded.
+    Json,
+}
+
+/// Print the build environment (CROSS_COMPILE, ARCH, PATH) of a profile.
+///
+/// Use as `eval "$(linaro-prebuilts env kernel-arm64)"`, or
+/// `linaro-prebuilts env --shell fish kernel-arm64 | source`.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Profile name, e.g. kernel-arm64.
+    profile: String,
+    /// File defining the profiles.
+    #[arg(long, default_value = "profiles.toml")]
+    profiles: PathBuf,
+    /// Manifest listing the vendored toolchains.
+    #[arg(long, default_value = "toolchains.toml")]
+    manifest: PathBuf,
+    /// Root of the installed tree; made absolute in the output.
+    #[arg(long, default_value = "prebuilts/gcc")]
+    prebuilts: PathBuf,
+    /// Host the compilers run on [default: this machine].
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long, value_enum, default_value_t)]
+    shell: Shell,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let profiles = Profiles::load(&args.profiles)?;
+    let profile = profiles.get(&args.profile)?;
+    let manifest = Manifest::load(&args.manifest)?;
+    let host = args.host.as_deref().unwrap_or(default_host());
+    let prebuilts = path::absolute(&args.prebuilts)
+        .with_context(|| format!("resolving {}", args.prebuilts.display()))?;
+    let env = Environment::resolve(profile, &manifest, host, &prebuilts)?;
+
+    match args.shell {
+        Shell::Sh => print!("{}", env.to_sh()),
+        Shell::Fish => print!("{}", env.to_fish()),
+        Shell::Json => {
+            let current = std::env::var("PATH").ok();
+            let map = env.to_map(current.as_deref());
+            println!("{}", serde_json::to_string_pretty(&map)?);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,132 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_elf::hardening::{Hardening, Stack};
+use linaro_elf::EM_AARCH64;
+use linaro_hardening::{audit, Policy};
+
+use crate::Format;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  every file meets its component's requirements
+  1  error (unreadable policy or file, overlapping components)
+  2  usage error
+  3  a file is missing a required mitigation";
+
+/// Check the exploit mitigations of ELF files built with the vendored
+/// toolchains, as checksec does, and hold them to a hardening policy.
+///
+/// Every ELF file below the given directories is read for RELRO, PIE,
+/// stack protector and FORTIFY_SOURCE use, stack executability and, on
+/// AArch64, BTI and PAC markings. The policy assigns files to components by
+/// path and lists what each component requires.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// ELF files, or directories to search for them, e.g.
+    /// out/target/product/<device>. Policy paths are relative to them.
+    #[arg(required = true, value_name = "PATH")]
+    paths: Vec<PathBuf>,
+    /// Policy listing the components and their requirements.
+    #[arg(long, default_value = "hardening.toml")]
+    policy: PathBuf,
+    /// Hold every file to this component's requirements, whatever its path.
+    #[arg(long)]
+    component: Option<String>,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+    /// Write the report to this file instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let policy = Policy::load(&args.policy)?;
+    let mut audits = Vec::new();
+    for path in &args.paths {
+        let found = audit(path, &policy, args.component.as_deref())
+            .with_context(|| format!("cannot audit {}", path.display()))?;
+        for mut audit in found {
+            if path.is_dir() {
+                audit.path = path.join(&audit.path);
+            }
+            audits.push(audit);
+        }
+    }
+
+    let report = match args.format {
+        Format::Text => {
+            let mut out = String::new();
+            for audit in &audits {
+                let status = if audit.ok() { "PASS" } else { "FAIL" };
+                writeln!(
+                    out,
+                    "{status}\t{}\t{}\t{}",
+                    audit.path.display(),
+                    audit.component.as_deref().unwrap_or("default"),
+                    summary(&audit.hardening)
+                )
+                .unwrap();
+                for violation in &audit.violations {
+                    writeln!(out, "  {}\t{}", violation.check.name(), violation.reason).unwrap();
+                }
+            }
+            out
+        }
+        Format::Json => serde_json::to_string_pretty(&audits)? + "\n",
+    };
+    match &args.out {
+        Some(path) => {
+            fs::write(path, report).with_context(|| format!("writing {}", path.display()))?
+        }
+        None => print!("{report}"),
+    }
+    Ok(if audits.iter().all(|a| a.ok()) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(3)
+    })
+}
+
+/// One-line `checksec`-style summary, e.g. `relro=full pie=yes ...`; `-`
+/// where a mitigation does not apply.
+fn summary(hardening: &Hardening) -> String {
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    let fortify = if !hardening.fortified.is_empty() {
+        "yes"
+    } else if !hardening.unfortified.is_empty() {
+        "no"
+    } else {
+        "-"
+    };
+    let stack = match hardening.stack {
+        Stack::NonExecutable => "nx",
+        Stack::Executable => "executable",
+        Stack::Unmarked => "unmarked",
+    };
+    let mut summary = format!(
+        "relro={} pie={} stack-protector={} fortify={fortify} stack={stack}",
+        hardening
+            .relro
+            .map_or_else(|| "-".to_owned(), |r| r.to_string()),
+        hardening.pie.map_or("-", yes_no),
+        yes_no(hardening.stack_protector),
+    );
+    if hardening.rwx_segments {
+        summary.push_str(" rwx-segments=yes");
+    }
+    if hardening.machine == EM_AARCH64 {
+        write!(
+            summary,
+            " bti={} pac={}",
+            yes_no(hardening.bti),
+            yes_no(hardening.pac)
+        )
+        .unwrap();
+    }
+    summary
+}
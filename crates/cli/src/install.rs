@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_archive::install::{Installer, Outcome};
+use linaro_manifest::{GccVersion, Triple};
+
+use crate::{default_host, ManifestArgs};
+
+/// Verify a toolchain archive and unpack it into the prebuilts tree.
+///
+/// The toolchain is installed to `<prefix>/<host>/<triple>/<version>/` with
+/// normalized mtimes and permissions. Re-running is a no-op while the tree
+/// matches its stamp file; a tree that has drifted is never overwritten.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Target triple, e.g. aarch64-linux-gnu.
+    triple: Triple,
+    /// GCC version, e.g. 7.5.0.
+    version: GccVersion,
+    /// Host the compiler runs on [default: this machine].
+    #[arg(long)]
+    host: Option<String>,
+    /// Root of the installed tree.
+    #[arg(long, default_value = "prebuilts/gcc")]
+    prefix: PathBuf,
+    #[command(flatten)]
+    manifest: ManifestArgs,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let manifest = args.manifest.load()?;
+    let host = args.host.as_deref().unwrap_or(default_host());
+    let toolchain = manifest
+        .find(args.triple.as_str(), args.version, host)
+        .with_context(|| {
+            format!(
+                "no toolchain for {} gcc {} on host {host} in {}",
+                args.triple,
+                args.version,
+                args.manifest.manifest.display()
+            )
+        })?;
+
+    let installer = Installer::new(args.manifest.verifier(), &args.prefix);
+    match installer.install(toolchain)? {
+        Outcome::Installed(dir) => println!("installed {}", dir.display()),
+        Outcome::AlreadyInstalled(dir) => println!("up to date {}", dir.display()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
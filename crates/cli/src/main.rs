@@ -0,0 +1,152 @@
+//! `linaro-prebuilts`: command-line front end for the vendored Linaro GCC
+//! prebuilts.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use linaro_archive::verify::Verifier;
+use linaro_manifest::Manifest;
+
+mod abi_check;
+mod blueprint;
+mod board;
+mod cache;
+mod cargo_config;
+mod chunks;
+mod compile_commands;
+mod diff;
+mod env;
+mod hardening;
+mod install;
+mod objcache;
+mod preflight;
+mod prune;
+mod releases;
+mod repro;
+mod sbom;
+mod size_diff;
+mod smoke;
+mod stage_runtime;
+mod verify;
+mod wrappers;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    Verify(verify::Args),
+    Install(install::Args),
+    Blueprint(blueprint::Args),
+    CargoConfig(cargo_config::Args),
+    Env(env::Args),
+    Smoke(smoke::Args),
+    Diff(diff::Args),
+    AbiCheck(abi_check::Args),
+    Cache(cache::Args),
+    Releases(releases::Args),
+    Prune(prune::Args),
+    Wrappers(wrappers::Args),
+    CompileCommands(compile_commands::Args),
+    Objcache(objcache::Args),
+    Repro(repro::Args),
+    Sbom(sbom::Args),
+    StageRuntime(stage_runtime::Args),
+    Board(board::Args),
+    Preflight(preflight::Args),
+    SizeDiff(size_diff::Args),
+    Hardening(hardening::Args),
+    Chunks(chunks::Args),
+}
+
+/// Output format shared by subcommands that produce reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Options locating the manifest, the archives it lists and the keyring
+/// their signatures are checked against.
+#[derive(Debug, clap::Args)]
+struct ManifestArgs {
+    /// Manifest listing the vendored toolchains.
+    #[arg(long, default_value = "toolchains.toml")]
+    manifest: PathBuf,
+    /// Directory holding the `.tar.xz` archives and optional `.asc` files.
+    #[arg(long, default_value = ".")]
+    archives: PathBuf,
+    /// Keyring for `.asc` signatures [default: keyring.gpg next to the manifest].
+    #[arg(long)]
+    keyring: Option<PathBuf>,
+}
+
+impl ManifestArgs {
+    fn load(&self) -> anyhow::Result<Manifest> {
+        Ok(Manifest::load(&self.manifest)?)
+    }
+
+    fn verifier(&self) -> Verifier {
+        let keyring = self.keyring.clone().unwrap_or_else(|| {
+            self.manifest
+                .parent()
+                .unwrap_or(".".as_ref())
+                .join("keyring.gpg")
+        });
+        Verifier::new(&self.archives, keyring)
+    }
+}
+
+/// The Linaro host name of the machine we run on.
+fn default_host() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86" => "i686",
+        arch => arch,
+    }
+}
+
+/// Exit code for errors that stop a subcommand before it produces a result
+/// (unreadable manifest, I/O failure, missing tool). Usage errors exit with
+/// clap's code 2; subcommands document their own codes from 3 upwards.
+const EXIT_ERROR: u8 = 1;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Verify(args) => verify::run(args),
+        Command::Install(args) => install::run(args),
+        Command::Blueprint(args) => blueprint::run(args),
+        Command::CargoConfig(args) => cargo_config::run(args),
+        Command::Env(args) => env::run(args),
+        Command::Smoke(args) => smoke::run(args),
+        Command::Diff(args) => diff::run(args),
+        Command::AbiCheck(args) => abi_check::run(args),
+        Command::Cache(args) => cache::run(args),
+        Command::Releases(args) => releases::run(args),
+        Command::Prune(args) => prune::run(args),
+        Command::Wrappers(args) => wrappers::run(args),
+        Command::CompileCommands(args) => compile_commands::run(args),
+        Command::Objcache(args) => objcache::run(args),
+        Command::Repro(args) => repro::run(args),
+        Command::Sbom(args) => sbom::run(args),
+        Command::StageRuntime(args) => stage_runtime::run(args),
+        Command::Board(args) => board::run(args),
+        Command::Preflight(args) => preflight::run(args),
+        Command::SizeDiff(args) => size_diff::run(args),
+        Command::Hardening(args) => hardening::run(args),
+        Command::Chunks(args) => chunks::run(args),
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("linaro-prebuilts: {err:#}");
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
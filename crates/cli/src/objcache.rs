@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_objcache::{default_dir, max_size_from_env, parse_size, Cache, Trimmed};
+
+use crate::Format;
+
+/// Inspect and trim the object cache of the linaro-objcache compiler
+/// launcher.
+///
+/// Builds use it as `make CC="linaro-objcache aarch64-linux-gnu-gcc"`. The
+/// cache lives in $LINARO_OBJCACHE_DIR, or linaro-objcache under
+/// $XDG_CACHE_HOME or ~/.cache, and is limited to $LINARO_OBJCACHE_MAX_SIZE
+/// (5G by default).
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Cache directory [default: as the launcher].
+    #[arg(long, global = true)]
+    dir: Option<PathBuf>,
+    #[command(subcommand)]
+    command: ObjcacheCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum ObjcacheCommand {
+    /// Show hit and miss counts and the cache size.
+    Stats {
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+    },
+    /// Reset the hit and miss counts.
+    Zero,
+    /// Evict the least recently used objects down to a size.
+    Trim {
+        /// Size to trim to, e.g. 2G [default: the cache's limit].
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        max_size: Option<u64>,
+    },
+    /// Remove every cached object.
+    Clear,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let dir = match args.dir {
+        Some(dir) => dir,
+        None => {
+            default_dir().context("neither LINARO_OBJCACHE_DIR, XDG_CACHE_HOME nor HOME is set")?
+        }
+    };
+    let cache = Cache::new(dir).max_size(max_size_from_env()?);
+    match args.command {
+        ObjcacheCommand::Stats { format } => {
+            let stats = cache.stats()?;
+            let (entries, size) = cache.usage()?;
+            let hit_rate = stats.hit_rate().map(|r| (r * 1000.0).round() / 10.0);
+            match format {
+                Format::Text => {
+                    println!("cache\t{}", cache.root().display());
+                    println!("hits\t{}", stats.hits);
+                    println!("misses\t{}", stats.misses);
+                    println!("uncacheable\t{}", stats.uncacheable);
+                    if let Some(rate) = hit_rate {
+                        println!("hit-rate\t{rate}%");
+                    }
+                    println!("entries\t{entries}");
+                    println!("size\t{size}");
+                    println!("limit\t{}", cache.limit());
+                }
+                Format::Json => {
+                    let report = serde_json::json!({
+                        "cache": cache.root(),
+                        "hits": stats.hits,
+                        "misses": stats.misses,
+                        "uncacheable": stats.uncacheable,
+                        "hit_rate": hit_rate,
+                        "entries": entries,
+                        "size": size,
+                        "limit": cache.limit(),
+                    });
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                }
+            }
+        }
+        ObjcacheCommand::Zero => cache.zero_stats()?,
+        ObjcacheCommand::Trim { max_size } => {
+            let trimmed = cache
+                .trim(max_size.unwrap_or(cache.limit()))
+                .context("cannot trim the cache")?;
+            print_trimmed(trimmed);
+        }
+        ObjcacheCommand::Clear => print_trimmed(cache.clear()?),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_trimmed(trimmed: Trimmed) {
+    println!(
+        "removed\t{} entries\t{} bytes",
+        trimmed.entries, trimmed.freed
+    );
+}
@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_abi::preflight::{self, Preflight};
+use linaro_abi::Rootfs;
+use serde::Serialize;
+
+use crate::Format;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  the host can run every toolchain
+  1  error (unreadable toolchain directory, no host programs found)
+  2  usage error
+  3  a toolchain's programs would not load on the host";
+
+/// Check that this host can run the compilers of installed toolchains,
+/// before a long build finds out.
+///
+/// Every ELF program and plugin under bin, <target>/bin and libexec is
+/// checked as the host's dynamic linker would load it: its interpreter,
+/// the libraries it needs and the symbol versions (GLIBC_2.14, ...) they
+/// must define. The newest glibc version required is compared with the
+/// host's.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// Installed toolchain directories, e.g.
+    /// prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0.
+    #[arg(required = true, value_name = "DIR")]
+    toolchains: Vec<PathBuf>,
+    /// Root filesystem of the host to check against, e.g. an unpacked
+    /// build container image.
+    #[arg(long, default_value = "/")]
+    root: PathBuf,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+#[derive(Serialize)]
+struct Checked<'a> {
+    toolchain: &'a Path,
+    verdict: String,
+    #[serde(flatten)]
+    preflight: Preflight,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let host = Rootfs::open(&args.root)?;
+    let mut checked = Vec::new();
+    for dir in &args.toolchains {
+        let preflight = preflight::preflight(dir, &host)
+            .with_context(|| format!("cannot check {}", dir.display()))?;
+        checked.push(Checked {
+            toolchain: dir,
+            verdict: preflight.verdict(),
+            preflight,
+        });
+    }
+
+    match args.format {
+        Format::Text => {
+            for Checked {
+                toolchain,
+                verdict,
+                preflight,
+            } in &checked
+            {
+                let status = if preflight.ok() { "PASS" } else { "FAIL" };
+                println!("{status}\t{}\t{verdict}", toolchain.display());
+                let glibc = |v: &Option<String>| v.clone().unwrap_or_else(|| "none".to_owned());
+                println!("  programs\t{}", preflight.binaries.len());
+                println!("  requires\t{}", glibc(&preflight.required_glibc));
+                println!("  host\t{}", glibc(&preflight.host_glibc));
+                println!("  libraries\t{}", preflight.libraries.join(" "));
+                for issue in &preflight.issues {
+                    print!("  problem\t{}", issue.problem);
+                    match issue.binaries.len() {
+                        1 => println!(),
+                        2 => println!(" (and 1 other program)"),
+                        n => println!(" (and {} other programs)", n - 1),
+                    }
+                }
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&checked)?),
+    }
+    Ok(if checked.iter().all(|c| c.preflight.ok()) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(3)
+    })
+}
@@ -0,0 +1,147 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::{bail, Context};
+use linaro_gen::env::Profiles;
+use linaro_prune::{Error, Outcome, Policies, Pruner, Record, Removed};
+use linaro_toolchain::Installation;
+
+use crate::Format;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  the toolchain was pruned, had already been pruned under the same
+     rules, or (with --dry-run) the plan was printed
+  1  error (unreadable policy, unknown profile, tree modified since it was
+     installed or pruned under other rules)
+  2  usage error
+  3  the pruned toolchain failed to build a test program; nothing was
+     removed";
+
+/// Remove documentation, unused front ends and runtimes from an installed
+/// toolchain according to a keep/drop policy, after checking it still builds
+/// C and C++ programs without them.
+///
+/// The removed paths are recorded in .linaro-prebuilts-pruned.json at the top
+/// of the toolchain.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// Installed toolchain directory, e.g.
+    /// prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0.
+    #[arg(value_name = "DIR")]
+    toolchain: PathBuf,
+    /// File of keep/drop rules by profile.
+    #[arg(long, default_value = "prune.toml")]
+    policy: PathBuf,
+    /// Profile the toolchain must keep serving; repeat for several. Only
+    /// what every profile drops is removed [default: every profile in
+    /// --profiles that targets this toolchain].
+    #[arg(long = "profile", value_name = "NAME")]
+    profiles: Vec<String>,
+    /// File defining the profiles.
+    #[arg(
+        long = "profiles",
+        value_name = "FILE",
+        default_value = "profiles.toml"
+    )]
+    profiles_file: PathBuf,
+    /// Print what would be removed without removing it.
+    #[arg(long)]
+    dry_run: bool,
+    /// Skip building test programs with the pruned toolchain.
+    #[arg(long)]
+    no_verify: bool,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let installation = Installation::open(&args.toolchain)?;
+    let triple = installation.triple();
+    let profiles = Profiles::load(&args.profiles_file)?;
+    let names = if args.profiles.is_empty() {
+        let names: Vec<_> = profiles
+            .profiles
+            .iter()
+            .filter(|(_, p)| &p.target == triple)
+            .map(|(name, _)| name.clone())
+            .collect();
+        if names.is_empty() {
+            bail!(
+                "no profile in {} targets {triple}; pass --profile",
+                args.profiles_file.display()
+            );
+        }
+        names
+    } else {
+        for name in &args.profiles {
+            let target = &profiles.get(name)?.target;
+            if target != triple {
+                bail!(
+                    "profile {name} targets {target}, but {} is {triple}",
+                    args.toolchain.display()
+                );
+            }
+        }
+        args.profiles.clone()
+    };
+    let policy = Policies::load(&args.policy)?.policy(&names, triple)?;
+    let pruner = Pruner::new(policy).verify(!args.no_verify);
+
+    if args.dry_run {
+        let record = pruner.plan(&installation)?;
+        print_record(&record, "planned", args.format)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    let context = || format!("cannot prune {}", args.toolchain.display());
+    let (record, status, verification) = match pruner.prune(&installation) {
+        Ok(Outcome::Pruned {
+            record,
+            verification,
+        }) => (record, "pruned", verification),
+        Ok(Outcome::AlreadyPruned(record)) => (record, "already-pruned", None),
+        Err(err @ Error::Verification { .. }) => {
+            eprintln!("linaro-prebuilts: {err}");
+            return Ok(ExitCode::from(3));
+        }
+        Err(err) => return Err(err).with_context(context),
+    };
+    match args.format {
+        Format::Text => print_record(&record, status, args.format)?,
+        Format::Json => {
+            let report = serde_json::json!({
+                "status": status,
+                "record": record,
+                "verification": verification,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_record(record: &Record, status: &str, format: Format) -> anyhow::Result<()> {
+    match format {
+        Format::Text => {
+            for removed in &record.removed {
+                let kind = match removed {
+                    Removed::File { .. } => "file",
+                    Removed::Symlink { .. } => "symlink",
+                    Removed::Dir { .. } => "dir",
+                };
+                println!("{kind}\t{}", removed.path());
+            }
+            println!(
+                "{status}\t{} paths\t{} bytes",
+                record.removed.len(),
+                record.freed
+            );
+        }
+        Format::Json => {
+            let report = serde_json::json!({ "status": status, "record": record });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use clap::ValueEnum;
+use linaro_manifest::Manifest;
+use linaro_releases::fetch::{Fetcher, LINARO_INDEX};
+use linaro_releases::{scan, Proposer};
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  nothing new was published
+  1  error (unreadable manifest or index, download failure)
+  2  usage error
+  3  propose: new toolchains, incomplete entries or conflicts were found";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum ProposeFormat {
+    /// A unified diff of the manifest, for `git apply`.
+    #[default]
+    Patch,
+    Json,
+}
+
+/// Find toolchains Linaro has published that the manifest does not list.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    #[command(subcommand)]
+    command: ReleasesCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum ReleasesCommand {
+    /// Save the release index listings and checksum/signature files
+    /// (not the archives) for `propose`.
+    Fetch {
+        /// Directory to save the index in.
+        #[arg(long)]
+        out: PathBuf,
+        /// Directory levels to follow below the URL.
+        #[arg(long, default_value_t = 2)]
+        depth: usize,
+        #[arg(default_value = LINARO_INDEX)]
+        url: String,
+    },
+    /// Propose manifest entries for newer GCC versions and new targets found
+    /// in a saved index, without network access.
+    Propose {
+        /// Directory written by `releases fetch`.
+        index: PathBuf,
+        #[arg(long, default_value = "toolchains.toml")]
+        manifest: PathBuf,
+        /// Hosts to propose toolchains for (may be repeated) [default: the
+        /// hosts the manifest already has].
+        #[arg(long)]
+        host: Vec<String>,
+        /// Directory of downloaded archives, used for sizes and digests the
+        /// index does not give.
+        #[arg(long)]
+        archives: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t)]
+        format: ProposeFormat,
+    },
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    match args.command {
+        ReleasesCommand::Fetch { out, depth, url } => {
+            let written = Fetcher::new().depth(depth).fetch(&url, &out)?;
+            eprintln!("saved {} files to {}", written.len(), out.display());
+            Ok(ExitCode::SUCCESS)
+        }
+        ReleasesCommand::Propose {
+            index,
+            manifest: path,
+            host,
+            archives,
+            format,
+        } => {
+            let current =
+                fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+            let manifest: Manifest = current
+                .parse()
+                .with_context(|| format!("loading {}", path.display()))?;
+            let published =
+                scan(&index).with_context(|| format!("reading index {}", index.display()))?;
+            let mut proposer = Proposer::new(&manifest);
+            if !host.is_empty() {
+                proposer = proposer.hosts(host);
+            }
+            if let Some(dir) = archives {
+                proposer = proposer.archives(dir);
+            }
+            let proposal = proposer.propose(&published)?;
+
+            match format {
+                ProposeFormat::Patch => {
+                    let label = path
+                        .strip_prefix(".")
+                        .unwrap_or(&path)
+                        .display()
+                        .to_string();
+                    print!("{}", proposal.patch(&label, &current)?);
+                    for entry in &proposal.incomplete {
+                        eprintln!("incomplete\t{}\t{}", entry.archive, entry.reason);
+                    }
+                    if !proposal.incomplete.is_empty() {
+                        eprintln!(
+                            "download incomplete archives and pass their directory as --archives"
+                        );
+                    }
+                    for conflict in &proposal.conflicts {
+                        eprintln!("conflict\t{}\t{}", conflict.archive, conflict.reason);
+                    }
+                }
+                ProposeFormat::Json => println!("{}", serde_json::to_string_pretty(&proposal)?),
+            }
+            Ok(ExitCode::from(if proposal.is_empty() { 0 } else { 3 }))
+        }
+    }
+}
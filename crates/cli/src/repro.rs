@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::{anyhow, Context};
+use linaro_repro::{Auditor, CauseKind, Report, Status};
+use linaro_toolchain::Installation;
+
+use crate::Format;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  both builds produced identical outputs
+  1  error (unusable toolchain, the input failed to build)
+  2  usage error
+  3  the outputs differ";
+
+/// Build sources twice with a toolchain, in different directories a second
+/// apart, and report what differs between the outputs.
+///
+/// Objects and programs are compared section by section. Differences are
+/// traced to the build directory, __DATE__/__TIME__ or the build ID where
+/// possible, followed by the flags or settings that would remove them.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// Installed toolchain directory, e.g.
+    /// prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0.
+    #[arg(value_name = "TOOLCHAIN")]
+    toolchain: PathBuf,
+    /// Source file, or directory whose C, C++ and assembly files are built.
+    #[arg(value_name = "INPUT")]
+    input: PathBuf,
+    /// Compiler flag; repeat for several. {dir} stands for the build
+    /// directory, e.g. --flag=-ffile-prefix-map={dir}=.
+    #[arg(long = "flag", value_name = "FLAG", allow_hyphen_values = true)]
+    flags: Vec<String>,
+    /// Environment variable for the compiler, e.g. SOURCE_DATE_EPOCH=0;
+    /// repeat for several.
+    #[arg(long = "env", value_name = "NAME=VALUE")]
+    env: Vec<String>,
+    /// Also link the objects into a program.
+    #[arg(long)]
+    link: bool,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let installation = Installation::open(&args.toolchain)?;
+    let mut auditor = Auditor::new(installation).flags(args.flags).link(args.link);
+    for var in &args.env {
+        let (name, value) = var
+            .split_once('=')
+            .ok_or_else(|| anyhow!("--env {var}: expected NAME=VALUE"))?;
+        auditor = auditor.env(name, value);
+    }
+    let report = auditor
+        .audit(&args.input)
+        .with_context(|| format!("cannot audit {}", args.input.display()))?;
+    match args.format {
+        Format::Text => print_text(&report),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    Ok(if report.reproducible() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(3)
+    })
+}
+
+fn print_text(report: &Report) {
+    let size = |size: Option<u64>| size.map_or("-".to_owned(), |s| s.to_string());
+    for output in &report.outputs {
+        let status = match output.status {
+            Status::Identical => "identical",
+            Status::Differs => "differs",
+            Status::Missing => "missing",
+        };
+        println!("{status}\t{}", output.path);
+        for section in &output.sections {
+            let [a, b] = section.size.map(size);
+            let name = &section.name;
+            if section.causes.is_empty() {
+                println!("\t{name}\t{a}\t{b}\t(follows from other differences)");
+            }
+            for cause in &section.causes {
+                let kind = match cause.kind {
+                    CauseKind::Path => "path",
+                    CauseKind::Timestamp => "timestamp",
+                    CauseKind::BuildId => "build-id",
+                    CauseKind::Other => "other",
+                };
+                println!(
+                    "\t{name}\t{a}\t{b}\t{kind}\t{:?}\t{:?}",
+                    cause.first, cause.second
+                );
+            }
+        }
+    }
+    for suggestion in &report.suggestions {
+        println!("fix\t{}\t{}", suggestion.fix, suggestion.reason);
+    }
+}
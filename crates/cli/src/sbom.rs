@@ -0,0 +1,56 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use clap::ValueEnum;
+use linaro_sbom::{created_now, cyclonedx, spdx, Bom};
+use linaro_toolchain::Installation;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum SbomFormat {
+    /// SPDX 2.3 tag-value.
+    #[default]
+    Spdx,
+    /// SPDX 2.3 JSON.
+    SpdxJson,
+    /// CycloneDX 1.5 JSON.
+    Cyclonedx,
+}
+
+/// Write a bill of materials for an installed toolchain: the compiler,
+/// binutils, the GCC runtime libraries it ships and the sysroot's glibc,
+/// with versions, licenses and source locations.
+///
+/// The creation time is taken from SOURCE_DATE_EPOCH when it is set, so
+/// regenerating the document gives identical output.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Installed toolchain directory, e.g.
+    /// prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0.
+    #[arg(value_name = "DIR")]
+    toolchain: PathBuf,
+    #[arg(long, value_enum, default_value_t)]
+    format: SbomFormat,
+    /// Write the document to this file instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let installation = Installation::open(&args.toolchain)?;
+    let bom = Bom::collect(&installation, created_now())
+        .with_context(|| format!("cannot inspect {}", args.toolchain.display()))?;
+    let document = match args.format {
+        SbomFormat::Spdx => spdx::tag_value(&bom),
+        SbomFormat::SpdxJson => spdx::json(&bom),
+        SbomFormat::Cyclonedx => cyclonedx::json(&bom),
+    };
+    match &args.out {
+        Some(path) => {
+            fs::write(path, document).with_context(|| format!("writing {}", path.display()))?
+        }
+        None => print!("{document}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,113 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_size::{Budget, Comparison, Delta, Overrun, Sizes};
+use serde::Serialize;
+
+use crate::Format;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  the new build is within every budget
+  1  error (unreadable or non-ELF file, malformed budget, budget for a
+     section neither build has)
+  2  usage error
+  3  a section of the new build exceeds its budget";
+
+/// Compare the code and data sizes of two builds of the same program, one
+/// per toolchain, section by section and symbol by symbol.
+///
+/// Sections are the allocated ones (.text, .rodata, .data, .bss, ...);
+/// "total" is the loadable image, every allocated section but .bss.
+/// Symbols come from the symbol table, so builds should not be stripped.
+/// Changes are listed largest first.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// The build with the current toolchain, e.g. spl/u-boot-spl.
+    old: PathBuf,
+    /// The build with the new toolchain.
+    new: PathBuf,
+    /// Only list symbols whose size changed by at least this many bytes.
+    #[arg(long, default_value_t = 0)]
+    threshold: u64,
+    /// Fail if a section of the new build is larger than SIZE bytes (K
+    /// and M suffixes allowed), e.g. .text=28K or total=0x8000. May be
+    /// repeated.
+    #[arg(long = "budget", value_name = "SECTION=SIZE")]
+    budgets: Vec<Budget>,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+    /// Write the report to this file instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    #[serde(flatten)]
+    comparison: Comparison,
+    over_budget: Vec<Overrun>,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let comparison = Comparison::between(&Sizes::read(&args.old)?, &Sizes::read(&args.new)?)
+        .threshold(args.threshold);
+    let over_budget = comparison.over_budget(&args.budgets)?;
+
+    let report = match args.format {
+        Format::Text => {
+            let mut out = String::new();
+            let mut line = |kind: &str, name: &str, delta: &Delta| {
+                let size = |s: Option<u64>| s.map_or_else(|| "-".to_owned(), |s| s.to_string());
+                write!(
+                    out,
+                    "{kind}\t{name}\t{}\t{}\t{:+}",
+                    size(delta.old),
+                    size(delta.new),
+                    delta.change()
+                )
+                .unwrap();
+                match &delta.section {
+                    Some(section) => writeln!(out, "\t{section}").unwrap(),
+                    None => writeln!(out).unwrap(),
+                }
+            };
+            for delta in &comparison.sections {
+                line("section", &delta.name, delta);
+            }
+            line("total", "", &comparison.total);
+            for delta in &comparison.symbols {
+                line("symbol", &delta.name, delta);
+            }
+            for overrun in &over_budget {
+                writeln!(out, "over-budget\t{overrun}").unwrap();
+            }
+            out
+        }
+        Format::Json => {
+            let report = Report {
+                comparison,
+                over_budget: over_budget.clone(),
+            };
+            serde_json::to_string_pretty(&report)? + "\n"
+        }
+    };
+    match &args.out {
+        Some(path) => {
+            fs::write(path, report).with_context(|| format!("writing {}", path.display()))?
+        }
+        None => print!("{report}"),
+    }
+    for overrun in &over_budget {
+        eprintln!("linaro-prebuilts: {overrun}");
+    }
+    Ok(if over_budget.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(3)
+    })
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_smoke::exec::{execute, PROGRAMS};
+use linaro_toolchain::Installation;
+
+use crate::Format;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  every case passed for every toolchain
+  1  error before testing (no driver found, unsupported target)
+  2  usage error
+  3  a case failed to build, produced the wrong output or, with --exec,
+     ran incorrectly
+Checks that cannot run on this host are reported as skipped, not failed.";
+
+/// Build bundled sample programs with installed toolchains and inspect the
+/// output; with --exec, also run programs under QEMU user-mode emulation.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// Installed toolchain directories, e.g.
+    /// prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0.
+    #[arg(required = true, value_name = "DIR")]
+    toolchains: Vec<PathBuf>,
+    /// Also run cross-built programs under qemu-<arch>, using the
+    /// toolchain's sysroot; skipped when the emulator is not installed,
+    /// unless the target is the host's architecture.
+    #[arg(long)]
+    exec: bool,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let mut reports = Vec::new();
+    for dir in &args.toolchains {
+        let installation = Installation::open(dir)?;
+        let context = || format!("cannot smoke-test {}", dir.display());
+        let mut report = linaro_smoke::run(&installation).with_context(context)?;
+        if args.exec {
+            let executed = execute(&installation, PROGRAMS).with_context(context)?;
+            report.cases.extend(executed.cases);
+            report.skipped.extend(executed.skipped);
+        }
+        reports.push(report);
+    }
+
+    match args.format {
+        Format::Text => {
+            for report in &reports {
+                let verdict = if report.passed() { "PASS" } else { "FAIL" };
+                println!("{verdict}\t{}\t{}", report.target, report.root.display());
+                for case in &report.cases {
+                    let status = if case.passed { "ok" } else { "fail" };
+                    println!("  {status}\t{}\t{}", case.name, case.failures.join("; "));
+                }
+                for reason in &report.skipped {
+                    println!("  skip\t\t{reason}");
+                }
+            }
+        }
+        Format::Json => {
+            let report = serde_json::json!({ "toolchains": reports });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+
+    let failed = reports.iter().any(|r| !r.passed());
+    Ok(ExitCode::from(if failed { 3 } else { 0 }))
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::{bail, Context};
+use linaro_abi::stage::{self, Entry};
+use linaro_abi::{Checker, Error, Rootfs};
+use linaro_toolchain::Installation;
+
+use crate::Format;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  the libraries were staged
+  1  error (toolchain without a sysroot, unreadable binary)
+  2  usage error
+  3  a binary would not load from the sysroot; nothing was staged";
+
+/// Copy the interpreter and the shared libraries target binaries need
+/// (libstdc++, libgcc_s, glibc, ...) out of a toolchain's sysroot, for a
+/// device image.
+///
+/// Libraries are looked up as the target's dynamic linker would, in the
+/// sysroot and the toolchain's GCC runtime directory, and staged at their
+/// paths on the target together with the symlinks leading to them. The
+/// staged files are listed in .linaro-prebuilts-staged.json in the output
+/// directory.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    /// Installed toolchain directory, e.g.
+    /// prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0.
+    #[arg(value_name = "TOOLCHAIN")]
+    toolchain: PathBuf,
+    /// Target binaries or shared libraries.
+    #[arg(required = true, value_name = "BINARY")]
+    binaries: Vec<PathBuf>,
+    /// Directory to stage into, laid out like the target's root.
+    #[arg(long)]
+    out: PathBuf,
+    /// Where the GCC runtime libraries go on the target.
+    #[arg(long, default_value = "/lib")]
+    runtime_dir: String,
+    /// Where the binaries will be installed on the device; $ORIGIN in their
+    /// run paths expands to this.
+    #[arg(long, default_value = "/usr/bin")]
+    install_dir: String,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let installation = Installation::open(&args.toolchain)?;
+    let Some(sysroot) = installation.sysroot()? else {
+        bail!("{} has no sysroot", args.toolchain.display());
+    };
+    let mut rootfs = Rootfs::open(&sysroot)?;
+    if let Some(runtime) = installation.runtime_dir()? {
+        rootfs = rootfs.overlay(&args.runtime_dir, runtime);
+    }
+    let checker = Checker::new(rootfs).install_dir(args.install_dir);
+    let staged = match stage::stage(&checker, &args.binaries, &args.out) {
+        Ok(staged) => staged,
+        Err(err @ Error::Unresolved { .. }) => {
+            eprintln!("linaro-prebuilts: {err}");
+            return Ok(ExitCode::from(3));
+        }
+        Err(err) => {
+            return Err(err).with_context(|| format!("cannot stage into {}", args.out.display()))
+        }
+    };
+    match args.format {
+        Format::Text => {
+            for entry in &staged.entries {
+                match entry {
+                    Entry::File { path, source, .. } => {
+                        println!("file\t{path}\t{}", source.display())
+                    }
+                    Entry::Symlink { path, target } => println!("symlink\t{path}\t{target}"),
+                }
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&staged)?),
+    }
+    Ok(ExitCode::SUCCESS)
+}
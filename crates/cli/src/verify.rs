@@ -0,0 +1,117 @@
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_archive::verify::{Signature, Status};
+
+use crate::{Format, ManifestArgs};
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  every archive verified
+  1  error before verification (bad manifest, I/O error, gpgv missing)
+  2  usage error
+  3  an archive is missing
+  4  an archive has the wrong size
+  5  an archive has the wrong SHA-256
+  6  an archive has a bad signature
+When several archives fail, the highest code is returned.";
+
+/// Verify local archives against the manifest, fully offline.
+#[derive(Debug, clap::Args)]
+#[command(after_help = EXIT_CODES)]
+pub struct Args {
+    #[command(flatten)]
+    manifest: ManifestArgs,
+    /// Only verify these archives (may be repeated).
+    #[arg(long = "archive", value_name = "NAME")]
+    only: Vec<String>,
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let manifest = args.manifest.load()?;
+    let mut toolchains = Vec::new();
+    for name in &args.only {
+        let toolchain = manifest.find_archive(name).with_context(|| {
+            format!(
+                "{name} is not listed in {}",
+                args.manifest.manifest.display()
+            )
+        })?;
+        toolchains.push(toolchain);
+    }
+    if args.only.is_empty() {
+        toolchains.extend(&manifest.toolchains);
+    }
+
+    let verifier = args.manifest.verifier();
+    let results = verifier.verify_all(toolchains)?;
+    let unlisted = if args.only.is_empty() {
+        verifier.unlisted(&manifest)?
+    } else {
+        Vec::new()
+    };
+
+    match args.format {
+        Format::Text => {
+            for result in &results {
+                let detail = match &result.status {
+                    Status::Ok {
+                        signature: Signature::Verified,
+                    } => "signature verified".to_owned(),
+                    Status::Ok {
+                        signature: Signature::Unsigned,
+                    } => "unsigned".to_owned(),
+                    Status::Missing => String::new(),
+                    Status::SizeMismatch { expected, actual } => {
+                        format!("expected {expected} bytes, found {actual}")
+                    }
+                    Status::DigestMismatch { expected, actual } => {
+                        format!("expected {expected}, found {actual}")
+                    }
+                    Status::BadSignature { detail } => detail.clone(),
+                };
+                println!(
+                    "{}\t{}\t{detail}",
+                    status_name(&result.status),
+                    result.archive
+                );
+            }
+            for name in &unlisted {
+                println!("unlisted\t{name}\t");
+            }
+        }
+        Format::Json => {
+            let report = serde_json::json!({ "archives": results, "unlisted": unlisted });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+
+    let code = results
+        .iter()
+        .map(|r| exit_code(&r.status))
+        .max()
+        .unwrap_or(0);
+    Ok(ExitCode::from(code))
+}
+
+fn status_name(status: &Status) -> &'static str {
+    match status {
+        Status::Ok { .. } => "ok",
+        Status::Missing => "missing",
+        Status::SizeMismatch { .. } => "size-mismatch",
+        Status::DigestMismatch { .. } => "digest-mismatch",
+        Status::BadSignature { .. } => "bad-signature",
+    }
+}
+
+fn exit_code(status: &Status) -> u8 {
+    match status {
+        Status::Ok { .. } => 0,
+        Status::Missing => 3,
+        Status::SizeMismatch { .. } => 4,
+        Status::DigestMismatch { .. } => 5,
+        Status::BadSignature { .. } => 6,
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use linaro_toolchain::Installation;
+use linaro_wrap::CONFIG_FILE;
+
+/// Install relocatable wrapper executables for an installed toolchain.
+///
+/// Every `bin/<triple>-*` tool gets a `linaro-wrap` link of the same name in
+/// the output directory, plus a linaro-wrap.toml locating the toolchain
+/// relative to it. The wrappers pass the toolchain's `--sysroot` and the
+/// configured flags and scrub host variables such as CPATH and
+/// LIBRARY_PATH; point CROSS_COMPILE at `<out>/<triple>-`. Rerunning keeps
+/// hand-edited `flags` and `scrub`.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Installed toolchain directory, e.g.
+    /// prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0.
+    #[arg(value_name = "DIR")]
+    toolchain: PathBuf,
+    /// Directory to install the wrappers in.
+    #[arg(long)]
+    out: PathBuf,
+    /// The linaro-wrap executable [default: next to this program].
+    #[arg(long)]
+    wrapper: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let wrapper = match args.wrapper {
+        Some(path) => path,
+        None => std::env::current_exe()
+            .context("locating linaro-wrap")?
+            .with_file_name("linaro-wrap"),
+    };
+    anyhow::ensure!(
+        wrapper.is_file(),
+        "{} not found; build it or pass --wrapper",
+        wrapper.display()
+    );
+    let installation = Installation::open(&args.toolchain)?;
+    let installed = linaro_wrap::install(&installation, &args.out, &wrapper)
+        .with_context(|| format!("cannot install wrappers in {}", args.out.display()))?;
+    for tool in &installed.tools {
+        println!("{}", installed.dir.join(tool).display());
+    }
+    println!("{}", installed.dir.join(CONFIG_FILE).display());
+    Ok(ExitCode::SUCCESS)
+}
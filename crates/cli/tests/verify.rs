@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use linaro_manifest::sha256_file;
+
+const ARCHIVE: &str = "gcc-linaro-7.5.0-2019.12-x86_64_arm-linux-gnueabihf.tar.xz";
+
+fn write_manifest(dir: &Path, archive: &Path) {
+    let (sha256, size) = sha256_file(archive).unwrap();
+    let manifest = format!(
+        "schema = 1\n\n[[toolchain]]\ngcc = \"7.5.0\"\nrelease = \"2019.12\"\n\
+         target = \"arm-linux-gnueabihf\"\nhost = \"x86_64\"\narchive = \"{ARCHIVE}\"\n\
+         size = {size}\nsha256 = \"{sha256}\"\n"
+    );
+    fs::write(dir.join("toolchains.toml"), manifest).unwrap();
+}
+
+fn verify(dir: &Path, extra: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_linaro-prebuilts"))
+        .arg("verify")
+        .arg("--manifest")
+        .arg(dir.join("toolchains.toml"))
+        .arg("--archives")
+        .arg(dir.join("archives"))
+        .args(extra)
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn exit_code_reflects_the_worst_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    let archives = dir.path().join("archives");
+    fs::create_dir(&archives).unwrap();
+    let archive = archives.join(ARCHIVE);
+    fs::write(&archive, b"archive contents").unwrap();
+    write_manifest(dir.path(), &archive);
+
+    let (code, stdout) = verify(dir.path(), &[]);
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, format!("ok\t{ARCHIVE}\tunsigned\n"));
+
+    fs::write(&archive, b"archive CONTENTS").unwrap();
+    let (code, stdout) = verify(dir.path(), &["--format", "json"]);
+    assert_eq!(code, Some(5));
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(report["archives"][0]["status"], "digest-mismatch");
+
+    fs::remove_file(&archive).unwrap();
+    assert_eq!(verify(dir.path(), &[]).0, Some(3));
+}
+
+#[test]
+fn errors_name_their_cause_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = dir.path().join("nope.toml");
+    let output = Command::new(env!("CARGO_BIN_EXE_linaro-prebuilts"))
+        .arg("verify")
+        .arg("--manifest")
+        .arg(&manifest)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        format!(
+            "linaro-prebuilts: failed to read {}: No such file or directory (os error 2)\n",
+            manifest.display()
+        )
+    );
+}
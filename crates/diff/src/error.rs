@@ -0,0 +1,15 @@
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Toolchain(#[from] linaro_toolchain::Error),
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{}", path.display())]
+    Elf {
+        path: PathBuf,
+        source: linaro_elf::Error,
+    },
+}
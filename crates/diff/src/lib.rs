@@ -0,0 +1,212 @@
+//! Differences between two installed toolchains, for reviewing upgrades.
+//!
+//! A [`Snapshot`] records what a toolchain provides: component versions,
+//! configured defaults, specs, multilibs, sysroot headers and the symbols
+//! its shared libraries export. [`Diff::between`] compares two snapshots
+//! and [`Diff::to_markdown`] renders the result for an upgrade pull request;
+//! the [`Diff`] itself serializes to JSON.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use linaro_toolchain::{Defaults, Info, Multilib};
+use serde::Serialize;
+
+mod error;
+mod markdown;
+mod snapshot;
+
+pub use error::Error;
+pub use snapshot::Snapshot;
+
+/// A value that differs between the two toolchains; `None` where a side
+/// does not have it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    pub name: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Members of a set present on only one side.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SetDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl SetDiff {
+    fn between<'a>(
+        old: impl IntoIterator<Item = &'a String>,
+        new: impl IntoIterator<Item = &'a String>,
+    ) -> Self {
+        let old: BTreeSet<_> = old.into_iter().collect();
+        let new: BTreeSet<_> = new.into_iter().collect();
+        Self {
+            added: new.difference(&old).map(|s| s.to_string()).collect(),
+            removed: old.difference(&new).map(|s| s.to_string()).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A library present on both sides whose exports changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LibraryChange {
+    pub name: String,
+    pub symbols: SetDiff,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LibraryDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<LibraryChange>,
+}
+
+impl LibraryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Everything that differs between two toolchains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diff {
+    /// Label of the old toolchain, e.g. `Linaro GCC 7.4-2019.02`.
+    pub old: String,
+    pub new: String,
+    /// GCC, binutils and glibc versions and other reported facts.
+    pub versions: Vec<Change>,
+    /// Code generation defaults chosen at configure time.
+    pub defaults: Vec<Change>,
+    pub specs: Vec<Change>,
+    /// Multilibs as printed by `gcc -print-multi-lib`.
+    pub multilibs: SetDiff,
+    pub headers: SetDiff,
+    pub libraries: LibraryDiff,
+}
+
+impl Diff {
+    pub fn between(old: &Snapshot, new: &Snapshot) -> Self {
+        let symbols = |s: &BTreeMap<_, _>, name: &String| -> BTreeSet<String> {
+            s.get(name).cloned().unwrap_or_default()
+        };
+        let libraries = SetDiff::between(old.libraries.keys(), new.libraries.keys());
+        let changed = old
+            .libraries
+            .keys()
+            .filter(|name| new.libraries.contains_key(*name))
+            .map(|name| LibraryChange {
+                name: name.clone(),
+                symbols: SetDiff::between(
+                    &symbols(&old.libraries, name),
+                    &symbols(&new.libraries, name),
+                ),
+            })
+            .filter(|change| !change.symbols.is_empty())
+            .collect();
+
+        Self {
+            old: label(&old.info),
+            new: label(&new.info),
+            versions: changes(versions(&old.info), versions(&new.info)),
+            defaults: changes(defaults(&old.info.defaults), defaults(&new.info.defaults)),
+            specs: changes(old.specs.clone(), new.specs.clone()),
+            multilibs: SetDiff::between(
+                &multilibs(&old.info.multilibs),
+                &multilibs(&new.info.multilibs),
+            ),
+            headers: SetDiff::between(&old.headers, &new.headers),
+            libraries: LibraryDiff {
+                added: libraries.added,
+                removed: libraries.removed,
+                changed,
+            },
+        }
+    }
+
+    /// Whether the two toolchains are indistinguishable by what is compared.
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+            && self.defaults.is_empty()
+            && self.specs.is_empty()
+            && self.multilibs.is_empty()
+            && self.headers.is_empty()
+            && self.libraries.is_empty()
+    }
+}
+
+fn label(info: &Info) -> String {
+    info.gcc_package
+        .clone()
+        .unwrap_or_else(|| format!("GCC {}", info.gcc_version))
+}
+
+fn versions(info: &Info) -> BTreeMap<String, String> {
+    [
+        ("GCC", Some(info.gcc_version.to_string())),
+        ("package", info.gcc_package.clone()),
+        ("binutils", Some(info.binutils_version.clone())),
+        ("glibc", info.glibc_version.clone()),
+        ("dynamic linker", info.dynamic_linker.clone()),
+        ("thread model", info.thread_model.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some((name.to_owned(), value?)))
+    .collect()
+}
+
+fn defaults(defaults: &Defaults) -> BTreeMap<String, String> {
+    [
+        ("arch", &defaults.arch),
+        ("cpu", &defaults.cpu),
+        ("tune", &defaults.tune),
+        ("fpu", &defaults.fpu),
+        ("float", &defaults.float_abi),
+        ("mode", &defaults.mode),
+        ("abi", &defaults.abi),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some((name.to_owned(), value.clone()?)))
+    .collect()
+}
+
+/// Multilibs in `gcc -print-multi-lib` form, e.g. `thumb;@mthumb`.
+fn multilibs(multilibs: &[Multilib]) -> Vec<String> {
+    multilibs
+        .iter()
+        .map(|m| {
+            let flags: String = m
+                .flags
+                .iter()
+                .map(|f| format!("@{}", f.trim_start_matches('-')))
+                .collect();
+            format!("{};{flags}", m.dir)
+        })
+        .collect()
+}
+
+/// The entries of `old` and `new` that differ, in key order.
+fn changes(old: BTreeMap<String, String>, mut new: BTreeMap<String, String>) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for (name, old) in old {
+        let new = new.remove(&name);
+        if new.as_ref() != Some(&old) {
+            changes.push(Change {
+                name,
+                old: Some(old),
+                new,
+            });
+        }
+    }
+    changes.extend(new.into_iter().map(|(name, new)| Change {
+        name,
+        old: None,
+        new: Some(new),
+    }));
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    changes
+}
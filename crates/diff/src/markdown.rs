@@ -0,0 +1,148 @@
+use std::fmt::Write;
+
+use crate::{Change, Diff, SetDiff};
+
+/// Lists longer than this are folded into a `<details>` block.
+const FOLD_AFTER: usize = 10;
+
+impl Diff {
+    /// Renders the diff as GitHub-flavoured Markdown. Sections without
+    /// differences are left out.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Toolchain diff: {} → {}\n", self.old, self.new);
+        if self.is_empty() {
+            out.push_str("\nNo differences.\n");
+            return out;
+        }
+
+        table(&mut out, "Versions", &self.versions);
+        table(&mut out, "Configured defaults", &self.defaults);
+
+        if !self.specs.is_empty() {
+            out.push_str("\n## Specs\n");
+            for spec in &self.specs {
+                let _ = writeln!(out, "\n### `{}`\n\n```diff", spec.name);
+                let old = spec.old.as_deref().unwrap_or_default();
+                let new = spec.new.as_deref().unwrap_or_default();
+                for line in line_diff(old, new) {
+                    let _ = writeln!(out, "{line}");
+                }
+                out.push_str("```\n");
+            }
+        }
+
+        set(&mut out, "Multilibs", &self.multilibs);
+        set(&mut out, "Sysroot headers", &self.headers);
+
+        let libraries = &self.libraries;
+        if !libraries.is_empty() {
+            out.push_str("\n## Libraries\n\n");
+            for name in &libraries.added {
+                let _ = writeln!(out, "- added `{name}`");
+            }
+            for name in &libraries.removed {
+                let _ = writeln!(out, "- removed `{name}`");
+            }
+            for change in &libraries.changed {
+                let _ = writeln!(
+                    out,
+                    "- `{}`: {} symbols added, {} removed",
+                    change.name,
+                    change.symbols.added.len(),
+                    change.symbols.removed.len()
+                );
+            }
+            for change in &libraries.changed {
+                let _ = writeln!(out, "\n### `{}`\n", change.name);
+                list(&mut out, &change.symbols);
+            }
+        }
+        out
+    }
+}
+
+fn table(out: &mut String, title: &str, changes: &[Change]) {
+    if changes.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "\n## {title}\n\n| | old | new |\n|---|---|---|");
+    for change in changes {
+        let cell = |v: &Option<String>| v.as_deref().map_or("–".to_owned(), |v| format!("`{v}`"));
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} |",
+            change.name,
+            cell(&change.old),
+            cell(&change.new)
+        );
+    }
+}
+
+fn set(out: &mut String, title: &str, diff: &SetDiff) {
+    if diff.is_empty() {
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "\n## {title}\n\n{} added, {} removed\n",
+        diff.added.len(),
+        diff.removed.len()
+    );
+    list(out, diff);
+}
+
+/// A `+`/`-` list of the members of `diff`, folded when long.
+fn list(out: &mut String, diff: &SetDiff) {
+    let lines: Vec<_> = diff
+        .removed
+        .iter()
+        .map(|s| format!("- {s}"))
+        .chain(diff.added.iter().map(|s| format!("+ {s}")))
+        .collect();
+    let fold = lines.len() > FOLD_AFTER;
+    if fold {
+        let _ = writeln!(out, "<details><summary>{} changes</summary>\n", lines.len());
+    }
+    out.push_str("```diff\n");
+    for line in &lines {
+        let _ = writeln!(out, "{line}");
+    }
+    out.push_str("```\n");
+    if fold {
+        out.push_str("\n</details>\n");
+    }
+}
+
+/// A unified-style line diff of `old` and `new`: unchanged lines prefixed
+/// with a space, removed with `-`, added with `+`.
+fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    // lcs[i][j]: length of the longest common subsequence of old[i..], new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!(" {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(format!("-{}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+{}", new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
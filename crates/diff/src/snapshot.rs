@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use linaro_elf::symbols;
+use linaro_toolchain::{parse, Info, Installation};
+use serde::Serialize;
+
+use crate::Error;
+
+/// Directories of the sysroot holding shared libraries.
+const SYSROOT_LIB_DIRS: &[&str] = &["lib", "lib64", "usr/lib", "usr/lib64"];
+
+/// What is compared between two toolchains, collected from one of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Snapshot {
+    pub info: Info,
+    /// `gcc -dumpspecs`, by spec name.
+    pub specs: BTreeMap<String, String>,
+    /// Headers under the sysroot's `usr/include`, relative to it.
+    pub headers: BTreeSet<String>,
+    /// Exported dynamic symbols of each shared library, formatted as by
+    /// `nm -D`. Sysroot libraries are keyed by their path on the target
+    /// (`/lib/libc.so.6`), the toolchain's own runtime libraries by their
+    /// path in the installation (`aarch64-linux-gnu/lib64/libstdc++.so.6`);
+    /// both use the soname rather than the versioned file name.
+    pub libraries: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Snapshot {
+    /// Runs the drivers of `installation` and scans its sysroot and runtime
+    /// libraries.
+    pub fn collect(installation: &Installation) -> Result<Self, Error> {
+        let outputs = installation.outputs()?;
+        let mut info = Info::from_outputs(&outputs)?;
+        if let Some(sysroot) = &mut info.sysroot {
+            if let Ok(canonical) = fs::canonicalize(&*sysroot) {
+                *sysroot = canonical;
+            }
+        }
+
+        let mut headers = BTreeSet::new();
+        let mut libraries = BTreeMap::new();
+        if let Some(sysroot) = &info.sysroot {
+            let include = sysroot.join("usr/include");
+            if include.is_dir() {
+                collect_headers(&include, &include, &mut headers)?;
+            }
+            for dir in SYSROOT_LIB_DIRS {
+                collect_libraries(&sysroot.join(dir), &format!("/{dir}"), &mut libraries)?;
+            }
+        }
+        let triple = installation.triple().as_str();
+        for dir in ["lib", "lib64"] {
+            let path = installation.root().join(triple).join(dir);
+            collect_libraries(&path, &format!("{triple}/{dir}"), &mut libraries)?;
+        }
+
+        Ok(Self {
+            specs: parse::specs(&outputs.specs),
+            info,
+            headers,
+            libraries,
+        })
+    }
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<(PathBuf, fs::FileType)>, Error> {
+    let io = |source| Error::Io {
+        path: dir.to_owned(),
+        source,
+    };
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir).map_err(io)? {
+        let entry = entry.map_err(io)?;
+        entries.push((entry.path(), entry.file_type().map_err(io)?));
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
+
+fn collect_headers(base: &Path, dir: &Path, headers: &mut BTreeSet<String>) -> Result<(), Error> {
+    for (path, file_type) in read_dir(dir)? {
+        if file_type.is_dir() {
+            collect_headers(base, &path, headers)?;
+        } else {
+            let relative = path.strip_prefix(base).expect("walked from base");
+            headers.insert(relative.to_string_lossy().into_owned());
+        }
+    }
+    Ok(())
+}
+
+/// Adds the shared libraries directly in `dir`, keyed under `prefix`.
+/// Symlinks are skipped, as they point at a library that is scanned itself,
+/// and so are linker scripts such as `libc.so`.
+fn collect_libraries(
+    dir: &Path,
+    prefix: &str,
+    libraries: &mut BTreeMap<String, BTreeSet<String>>,
+) -> Result<(), Error> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for (path, file_type) in read_dir(dir)? {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if !file_type.is_file() || !(name.ends_with(".so") || name.contains(".so.")) {
+            continue;
+        }
+        let bytes = fs::read(&path).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+        if !bytes.starts_with(b"\x7fELF") {
+            continue;
+        }
+        let elf = |source| Error::Elf {
+            path: path.clone(),
+            source,
+        };
+        let info = linaro_elf::ElfInfo::parse(&bytes).map_err(elf)?;
+        let exports = symbols::exports(&bytes).map_err(elf)?;
+        let soname = info.soname.unwrap_or_else(|| name.into_owned());
+        libraries.insert(
+            format!("{prefix}/{soname}"),
+            exports.iter().map(ToString::to_string).collect(),
+        );
+    }
+    Ok(())
+}
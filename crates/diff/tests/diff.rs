@@ -0,0 +1,160 @@
+use std::fs;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::Path;
+use std::process::Command;
+
+use linaro_diff::{Diff, Snapshot};
+use linaro_golden::check_golden;
+use linaro_toolchain::Installation;
+
+const TRIPLE: &str = "aarch64-linux-gnu";
+
+fn write_script(path: &Path, body: &str) {
+    fs::write(path, format!("#!/bin/sh\n{body}")).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Builds a fake installation at `root` whose drivers replay the captured
+/// output in `tests/fixtures/<release>`, with a sysroot holding `headers`
+/// and a `libfoo` built by the host compiler. `None` without a host GCC.
+fn fake_installation(root: &Path, release: &str, headers: &[&str]) -> Option<Installation> {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(release);
+    let sysroot = root.join(TRIPLE).join("libc");
+    let bin = root.join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let f = fixtures.display();
+    write_script(
+        &bin.join(format!("{TRIPLE}-gcc")),
+        &format!(
+            "case \"$1\" in\n\
+             -v) cat {f}/gcc-v.txt >&2 ;;\n\
+             -dumpspecs) cat {f}/dumpspecs.txt ;;\n\
+             -print-multi-lib) cat {f}/multi-lib.txt ;;\n\
+             -print-sysroot) echo {} ;;\n\
+             esac\n",
+            sysroot.display()
+        ),
+    );
+    write_script(
+        &bin.join(format!("{TRIPLE}-ld")),
+        &format!("cat {f}/ld-version.txt\n"),
+    );
+
+    let include = sysroot.join("usr/include");
+    for header in headers {
+        let path = include.join(header);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+    fs::write(
+        include.join("features.h"),
+        "#define __GLIBC__ 2\n#define __GLIBC_MINOR__ 25\n",
+    )
+    .unwrap();
+
+    let lib = sysroot.join("lib");
+    fs::create_dir_all(&lib).unwrap();
+    let built = Command::new("gcc")
+        .args(["-shared", "-fPIC", "-nostdlib", "-Wl,-soname,libfoo.so.1"])
+        .arg(format!("-Wl,--version-script={f}/libfoo.map"))
+        .arg("-o")
+        .arg(lib.join("libfoo-1.0.so"))
+        .arg(fixtures.join("libfoo.c"))
+        .status();
+    if !built.is_ok_and(|s| s.success()) {
+        return None;
+    }
+    symlink("libfoo-1.0.so", lib.join("libfoo.so.1")).unwrap();
+    fs::write(lib.join("libfoo.so"), "GROUP ( libfoo.so.1 )\n").unwrap();
+    Some(Installation::open(root).unwrap())
+}
+
+fn snapshots() -> Option<(Snapshot, Snapshot)> {
+    let dir = tempfile::tempdir().unwrap();
+    let old = fake_installation(
+        &dir.path().join("old"),
+        "old",
+        &["stdio.h", "xlocale.h", "bits/types.h"],
+    )?;
+    let new = fake_installation(
+        &dir.path().join("new"),
+        "new",
+        &["stdio.h", "bits/types.h", "sys/random.h"],
+    )?;
+    let runtime = new.root().join(TRIPLE).join("lib64");
+    fs::create_dir_all(&runtime).unwrap();
+    fs::copy(
+        new.root().join(TRIPLE).join("libc/lib/libfoo-1.0.so"),
+        runtime.join("libatomic.so.1.2.0"),
+    )
+    .unwrap();
+    Some((
+        Snapshot::collect(&old).unwrap(),
+        Snapshot::collect(&new).unwrap(),
+    ))
+}
+
+#[test]
+fn collects_snapshot() {
+    let Some((old, new)) = snapshots() else {
+        eprintln!("skipping: no host gcc");
+        return;
+    };
+    assert_eq!(old.info.gcc_version.to_string(), "7.4.1");
+    assert_eq!(old.info.glibc_version.as_deref(), Some("2.25"));
+    assert_eq!(
+        old.headers.iter().collect::<Vec<_>>(),
+        ["bits/types.h", "features.h", "stdio.h", "xlocale.h"]
+    );
+    assert_eq!(
+        old.libraries.keys().collect::<Vec<_>>(),
+        ["/lib/libfoo.so.1"]
+    );
+    assert_eq!(
+        new.libraries["/lib/libfoo.so.1"].iter().collect::<Vec<_>>(),
+        [
+            "foo_a@@FOO_1",
+            "foo_b@@FOO_2",
+            "foo_b@FOO_1",
+            "foo_c@@FOO_2"
+        ]
+    );
+    assert!(new
+        .libraries
+        .contains_key("aarch64-linux-gnu/lib64/libfoo.so.1"));
+}
+
+#[test]
+fn reports_differences() {
+    let Some((old, new)) = snapshots() else {
+        eprintln!("skipping: no host gcc");
+        return;
+    };
+    let diff = Diff::between(&old, &new);
+    assert_eq!(diff.old, "Linaro GCC 7.4-2019.02");
+    assert_eq!(diff.headers.added, ["sys/random.h"]);
+    assert_eq!(diff.headers.removed, ["xlocale.h"]);
+    assert_eq!(diff.multilibs.added, ["ilp32;@mabi=ilp32"]);
+    let specs: Vec<_> = diff.specs.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(specs, ["link", "link_ssp"]);
+    let libfoo = &diff.libraries.changed[0];
+    assert_eq!(
+        libfoo.symbols.added,
+        ["foo_b@@FOO_2", "foo_b@FOO_1", "foo_c@@FOO_2"]
+    );
+    assert_eq!(libfoo.symbols.removed, ["foo_b@@FOO_1", "foo_count@@FOO_1"]);
+
+    let json = serde_json::to_value(&diff).unwrap();
+    assert_eq!(json["versions"][0]["name"], "GCC");
+    assert_eq!(json["versions"][0]["old"], "7.4.1");
+    assert_eq!(json["versions"][0]["new"], "7.5.0");
+
+    check_golden!("diff.md", &diff.to_markdown());
+    assert!(Diff::between(&new, &new).is_empty());
+    assert_eq!(
+        Diff::between(&new, &new).to_markdown(),
+        "# Toolchain diff: Linaro GCC 7.5-2019.12 → Linaro GCC 7.5-2019.12\n\nNo differences.\n"
+    );
+}
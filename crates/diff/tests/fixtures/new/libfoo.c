@@ -0,0 +1,6 @@
+int foo_a(void) { return 1; }
+int foo_b_v1(void) { return 2; }
+int foo_b_v2(int flags) { return 2 + flags; }
+int foo_c(void) { return 3; }
+__asm__(".symver foo_b_v1,foo_b@FOO_1");
+__asm__(".symver foo_b_v2,foo_b@@FOO_2");
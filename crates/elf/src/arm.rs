@@ -0,0 +1,230 @@
+//! 32-bit Arm specifics: EABI version, float ABI and the `.ARM.attributes`
+//! build attributes (see the Arm "Addenda to the ABI", section 2).
+
+use goblin::elf::Elf;
+use serde::Serialize;
+
+use crate::{section_data, Endian, Error};
+
+/// `e_flags` mask holding the EABI version.
+const EF_ARM_EABIMASK: u32 = 0xff00_0000;
+const EF_ARM_ABI_FLOAT_SOFT: u32 = 0x200;
+const EF_ARM_ABI_FLOAT_HARD: u32 = 0x400;
+
+const TAG_FILE: u8 = 1;
+const TAG_CPU_RAW_NAME: u64 = 4;
+const TAG_CPU_NAME: u64 = 5;
+const TAG_CPU_ARCH: u64 = 6;
+const TAG_FP_ARCH: u64 = 10;
+const TAG_ABI_VFP_ARGS: u64 = 28;
+const TAG_COMPATIBILITY: u64 = 32;
+const TAG_ALSO_COMPATIBLE_WITH: u64 = 65;
+const TAG_CONFORMANCE: u64 = 67;
+
+/// How floating-point values are passed and computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FloatAbi {
+    /// Software floating point, no FPU instructions.
+    Soft,
+    /// FPU instructions, but arguments passed in core registers.
+    Softfp,
+    /// Arguments passed in VFP registers (`gnueabihf`).
+    Hard,
+}
+
+/// Arm-specific facts about an ELF file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArmInfo {
+    /// EABI version from `e_flags`; 5 for current toolchains, 0 for
+    /// legacy (OABI) or unmarked files.
+    pub eabi_version: u8,
+    /// Float ABI recorded in `e_flags`, if any.
+    pub flags_float_abi: Option<FloatAbi>,
+    /// File-scope attributes from `.ARM.attributes`, if present.
+    pub attributes: Option<Attributes>,
+}
+
+impl ArmInfo {
+    pub(crate) fn from_elf(elf: &Elf<'_>, bytes: &[u8], endian: Endian) -> Result<Self, Error> {
+        let flags = elf.header.e_flags;
+        let flags_float_abi = if flags & EF_ARM_ABI_FLOAT_HARD != 0 {
+            Some(FloatAbi::Hard)
+        } else if flags & EF_ARM_ABI_FLOAT_SOFT != 0 {
+            Some(FloatAbi::Soft)
+        } else {
+            None
+        };
+        let attributes = section_data(elf, bytes, ".ARM.attributes")
+            .map(|data| Attributes::parse(data, endian))
+            .transpose()?;
+        Ok(Self {
+            eabi_version: ((flags & EF_ARM_EABIMASK) >> 24) as u8,
+            flags_float_abi,
+            attributes,
+        })
+    }
+
+    /// The float ABI, preferring the build attributes (which tell soft from
+    /// softfp) over `e_flags`.
+    pub fn float_abi(&self) -> Option<FloatAbi> {
+        self.attributes
+            .as_ref()
+            .map(Attributes::float_abi)
+            .or(self.flags_float_abi)
+    }
+}
+
+/// The `aeabi` file-scope build attributes used by the checks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Attributes {
+    /// `Tag_CPU_name`, e.g. `7-A`.
+    pub cpu_name: Option<String>,
+    /// `Tag_CPU_arch`, e.g. 10 for Armv7.
+    pub cpu_arch: Option<u64>,
+    /// `Tag_FP_arch`; 0 or absent when no FPU instructions are allowed.
+    pub fp_arch: Option<u64>,
+    /// `Tag_ABI_VFP_args`; 1 when arguments go in VFP registers.
+    pub vfp_args: Option<u64>,
+}
+
+impl Attributes {
+    /// Parses the contents of an `.ARM.attributes` section.
+    pub fn parse(data: &[u8], endian: Endian) -> Result<Self, Error> {
+        let mut attributes = Self::default();
+        let (&version, mut rest) = data.split_first().ok_or(Error::Attributes("empty"))?;
+        if version != b'A' {
+            return Err(Error::Attributes("unknown format version"));
+        }
+        while !rest.is_empty() {
+            let (section, next) = split_sized(rest, 0, endian)?;
+            rest = next;
+            let (vendor, mut subsections) = split_cstr(&section[4..])?;
+            if vendor != "aeabi" {
+                continue;
+            }
+            while !subsections.is_empty() {
+                let tag = subsections[0];
+                let (subsection, next) = split_sized(subsections, 1, endian)?;
+                subsections = next;
+                if tag == TAG_FILE {
+                    attributes.parse_file_attributes(&subsection[5..])?;
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn parse_file_attributes(&mut self, mut data: &[u8]) -> Result<(), Error> {
+        while !data.is_empty() {
+            let tag = uleb128(&mut data)?;
+            match tag {
+                TAG_CPU_RAW_NAME | TAG_CONFORMANCE | TAG_ALSO_COMPATIBLE_WITH => {
+                    data = split_cstr(data)?.1;
+                }
+                TAG_CPU_NAME => {
+                    let (name, rest) = split_cstr(data)?;
+                    self.cpu_name = Some(name.to_owned());
+                    data = rest;
+                }
+                TAG_COMPATIBILITY => {
+                    uleb128(&mut data)?;
+                    data = split_cstr(data)?.1;
+                }
+                // Above 32, odd tags carry strings and even ones integers.
+                tag if tag > 32 && tag % 2 == 1 => data = split_cstr(data)?.1,
+                tag => {
+                    let value = uleb128(&mut data)?;
+                    match tag {
+                        TAG_CPU_ARCH => self.cpu_arch = Some(value),
+                        TAG_FP_ARCH => self.fp_arch = Some(value),
+                        TAG_ABI_VFP_ARGS => self.vfp_args = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The float ABI implied by `Tag_ABI_VFP_args` and `Tag_FP_arch`; absent
+    /// tags have their default value 0.
+    pub fn float_abi(&self) -> FloatAbi {
+        if self.vfp_args == Some(1) {
+            FloatAbi::Hard
+        } else if self.fp_arch.is_some_and(|fp| fp != 0) {
+            FloatAbi::Softfp
+        } else {
+            FloatAbi::Soft
+        }
+    }
+
+    /// Name of `Tag_CPU_arch`, e.g. `v7`.
+    pub fn cpu_arch_name(&self) -> Option<&'static str> {
+        const NAMES: &[&str] = &[
+            "pre-v4",
+            "v4",
+            "v4T",
+            "v5T",
+            "v5TE",
+            "v5TEJ",
+            "v6",
+            "v6KZ",
+            "v6T2",
+            "v6K",
+            "v7",
+            "v6-M",
+            "v6S-M",
+            "v7E-M",
+            "v8-A",
+            "v8-R",
+            "v8-M.baseline",
+            "v8-M.mainline",
+        ];
+        NAMES.get(usize::try_from(self.cpu_arch?).ok()?).copied()
+    }
+}
+
+/// Splits off a block whose 32-bit length, found after `skip` bytes,
+/// counts the whole block including itself.
+fn split_sized(data: &[u8], skip: usize, endian: Endian) -> Result<(&[u8], &[u8]), Error> {
+    let len_bytes: [u8; 4] = data
+        .get(skip..skip + 4)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(Error::Attributes("truncated length"))?;
+    let len = match endian {
+        Endian::Little => u32::from_le_bytes(len_bytes),
+        Endian::Big => u32::from_be_bytes(len_bytes),
+    } as usize;
+    if len < skip + 4 || len > data.len() {
+        return Err(Error::Attributes("length out of bounds"));
+    }
+    Ok(data.split_at(len))
+}
+
+fn split_cstr(data: &[u8]) -> Result<(&str, &[u8]), Error> {
+    let nul = data
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(Error::Attributes("unterminated string"))?;
+    let s = std::str::from_utf8(&data[..nul]).map_err(|_| Error::Attributes("invalid string"))?;
+    Ok((s, &data[nul + 1..]))
+}
+
+fn uleb128(data: &mut &[u8]) -> Result<u64, Error> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = data
+            .split_first()
+            .ok_or(Error::Attributes("truncated integer"))?;
+        *data = rest;
+        if shift < 64 {
+            value |= u64::from(byte & 0x7f) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("malformed ELF file: {0}")]
+    Malformed(String),
+    #[error("malformed .ARM.attributes section: {0}")]
+    Attributes(&'static str),
+}
+
+impl From<goblin::error::Error> for Error {
+    fn from(err: goblin::error::Error) -> Self {
+        Self::Malformed(err.to_string())
+    }
+}
@@ -0,0 +1,272 @@
+//! The exploit mitigations an ELF file was built and linked with, the facts
+//! `checksec` reports: RELRO, PIE, stack protector, FORTIFY_SOURCE, a
+//! non-executable stack, and AArch64 branch protection.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use goblin::elf::dynamic::{DF_1_NOW, DF_1_PIE, DF_BIND_NOW, DT_BIND_NOW, DT_FLAGS, DT_FLAGS_1};
+use goblin::elf::header::{EM_AARCH64, ET_DYN, ET_EXEC, ET_REL};
+use goblin::elf::note::NT_GNU_PROPERTY_TYPE_0;
+use goblin::elf::program_header::{PF_W, PF_X, PT_GNU_RELRO, PT_GNU_STACK, PT_LOAD};
+use goblin::elf::section_header::{SHF_EXECINSTR, SHN_UNDEF};
+use goblin::elf::Elf;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// `GNU_PROPERTY_AARCH64_FEATURE_1_AND` and its bits.
+const AARCH64_FEATURE_1_AND: u32 = 0xc000_0000;
+const AARCH64_FEATURE_1_BTI: u32 = 1;
+const AARCH64_FEATURE_1_PAC: u32 = 2;
+
+/// Libc functions `_FORTIFY_SOURCE` replaces with a `__*_chk` variant when
+/// the compiler knows the size of the destination.
+const FORTIFIABLE: &[&str] = &[
+    "confstr",
+    "fgets",
+    "fprintf",
+    "fread",
+    "getcwd",
+    "gets",
+    "mbstowcs",
+    "memcpy",
+    "memmove",
+    "mempcpy",
+    "memset",
+    "poll",
+    "pread",
+    "pread64",
+    "printf",
+    "read",
+    "readlink",
+    "realpath",
+    "recv",
+    "recvfrom",
+    "snprintf",
+    "sprintf",
+    "stpcpy",
+    "stpncpy",
+    "strcat",
+    "strcpy",
+    "strncat",
+    "strncpy",
+    "vfprintf",
+    "vprintf",
+    "vsnprintf",
+    "vsprintf",
+    "wcscpy",
+    "wcsncpy",
+    "wcstombs",
+    "wctomb",
+];
+
+/// How much of the relocation data is read-only after start-up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Relro {
+    /// No `PT_GNU_RELRO` segment.
+    No,
+    /// `PT_GNU_RELRO`, but lazy binding leaves the PLT's GOT writable.
+    Partial,
+    /// `PT_GNU_RELRO` and `BIND_NOW` (`-z relro -z now`).
+    Full,
+}
+
+impl fmt::Display for Relro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::No => "no",
+            Self::Partial => "partial",
+            Self::Full => "full",
+        })
+    }
+}
+
+/// What the file says about the executability of the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Stack {
+    NonExecutable,
+    /// Marked executable, e.g. by `-z execstack` or an assembly file
+    /// without a `.note.GNU-stack` section.
+    Executable,
+    /// No `PT_GNU_STACK` segment, or for objects no `.note.GNU-stack`
+    /// section; the kernel or linker decides.
+    Unmarked,
+}
+
+/// The mitigations found in one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hardening {
+    /// `e_machine`, as BTI and PAC only exist on AArch64.
+    pub machine: u16,
+    /// `None` for relocatable objects, which are not linked yet.
+    pub relro: Option<Relro>,
+    /// Whether an executable is position-independent; `None` for shared
+    /// libraries and relocatable objects.
+    pub pie: Option<bool>,
+    /// Whether the file refers to `__stack_chk_fail` or the guard.
+    pub stack_protector: bool,
+    /// The checked functions called, e.g. `__memcpy_chk`.
+    pub fortified: Vec<String>,
+    /// Functions called that have a checked variant, e.g. `memcpy`.
+    pub unfortified: Vec<String>,
+    pub stack: Stack,
+    /// Whether a loadable segment is both writable and executable.
+    pub rwx_segments: bool,
+    /// AArch64 Branch Target Identification (`-mbranch-protection=bti`),
+    /// from the GNU property note.
+    pub bti: bool,
+    /// AArch64 return address signing (`-mbranch-protection=pac-ret`).
+    pub pac: bool,
+}
+
+/// Reads the mitigations of the ELF file in `bytes`.
+pub fn hardening(bytes: &[u8]) -> Result<Hardening, Error> {
+    let elf = Elf::parse(bytes)?;
+    let kind = elf.header.e_type;
+    let dynamic_flag = |tag| {
+        elf.dynamic
+            .iter()
+            .flat_map(|d| &d.dyns)
+            .filter(|d| d.d_tag == tag)
+            .fold(0, |flags, d| flags | d.d_val)
+    };
+    let bind_now = dynamic_flag(DT_FLAGS) & DF_BIND_NOW != 0
+        || dynamic_flag(DT_FLAGS_1) & DF_1_NOW != 0
+        || elf
+            .dynamic
+            .iter()
+            .flat_map(|d| &d.dyns)
+            .any(|d| d.d_tag == DT_BIND_NOW);
+    let has_segment = |kind| elf.program_headers.iter().any(|p| p.p_type == kind);
+    let relro = (kind != ET_REL).then(|| match (has_segment(PT_GNU_RELRO), bind_now) {
+        (false, _) => Relro::No,
+        (true, false) => Relro::Partial,
+        (true, true) => Relro::Full,
+    });
+    let pie = match kind {
+        ET_EXEC => Some(false),
+        ET_DYN if elf.interpreter.is_some() || dynamic_flag(DT_FLAGS_1) & DF_1_PIE != 0 => {
+            Some(true)
+        }
+        _ => None,
+    };
+
+    let stack = if kind == ET_REL {
+        let note = elf
+            .section_headers
+            .iter()
+            .find(|s| elf.shdr_strtab.get_at(s.sh_name) == Some(".note.GNU-stack"));
+        match note {
+            Some(s) if s.sh_flags & u64::from(SHF_EXECINSTR) != 0 => Stack::Executable,
+            Some(_) => Stack::NonExecutable,
+            None => Stack::Unmarked,
+        }
+    } else {
+        match elf
+            .program_headers
+            .iter()
+            .find(|p| p.p_type == PT_GNU_STACK)
+        {
+            Some(p) if p.p_flags & PF_X != 0 => Stack::Executable,
+            Some(_) => Stack::NonExecutable,
+            None => Stack::Unmarked,
+        }
+    };
+    let rwx_segments = elf
+        .program_headers
+        .iter()
+        .any(|p| p.p_type == PT_LOAD && p.p_flags & (PF_W | PF_X) == PF_W | PF_X);
+
+    let mut referenced = BTreeSet::new();
+    let mut called = BTreeSet::new();
+    for (symbols, strings) in [(&elf.syms, &elf.strtab), (&elf.dynsyms, &elf.dynstrtab)] {
+        for sym in symbols.iter() {
+            let Some(name) = strings.get_at(sym.st_name).filter(|n| !n.is_empty()) else {
+                continue;
+            };
+            referenced.insert(name);
+            if sym.st_shndx == SHN_UNDEF as usize {
+                called.insert(name);
+            }
+        }
+    }
+    let stack_protector = [
+        "__stack_chk_fail",
+        "__stack_chk_fail_local",
+        "__stack_chk_guard",
+    ]
+    .iter()
+    .any(|name| referenced.contains(name));
+    let fortified = called
+        .iter()
+        .filter(|name| name.starts_with("__") && name.ends_with("_chk"))
+        .map(|name| name.to_string())
+        .collect();
+    let unfortified = called
+        .iter()
+        .filter(|name| FORTIFIABLE.contains(name))
+        .map(|name| name.to_string())
+        .collect();
+
+    let features = if elf.header.e_machine == EM_AARCH64 {
+        aarch64_features(&elf, bytes)?
+    } else {
+        0
+    };
+    Ok(Hardening {
+        machine: elf.header.e_machine,
+        relro,
+        pie,
+        stack_protector,
+        fortified,
+        unfortified,
+        stack,
+        rwx_segments,
+        bti: features & AARCH64_FEATURE_1_BTI != 0,
+        pac: features & AARCH64_FEATURE_1_PAC != 0,
+    })
+}
+
+/// The `GNU_PROPERTY_AARCH64_FEATURE_1_AND` bits of the GNU property note,
+/// found through the program headers of linked files and the sections of
+/// objects.
+fn aarch64_features(elf: &Elf<'_>, bytes: &[u8]) -> Result<u32, Error> {
+    let notes = elf
+        .iter_note_headers(bytes)
+        .or_else(|| elf.iter_note_sections(bytes, Some(".note.gnu.property")));
+    let Some(notes) = notes else {
+        return Ok(0);
+    };
+    let word = |data: &[u8]| {
+        let data: [u8; 4] = data.try_into().unwrap();
+        if elf.little_endian {
+            u32::from_le_bytes(data)
+        } else {
+            u32::from_be_bytes(data)
+        }
+    };
+    let align = if elf.is_64 { 8 } else { 4 };
+    for note in notes {
+        let note = note?;
+        if note.n_type != NT_GNU_PROPERTY_TYPE_0 || note.name != "GNU" {
+            continue;
+        }
+        // An array of (pr_type, pr_datasz, data padded to the word size).
+        let mut desc = note.desc;
+        while desc.len() >= 8 {
+            let (kind, size) = (word(&desc[..4]), word(&desc[4..8]) as usize);
+            let data = desc
+                .get(8..8 + size)
+                .ok_or_else(|| Error::Malformed("truncated GNU property note".to_owned()))?;
+            if kind == AARCH64_FEATURE_1_AND && size == 4 {
+                return Ok(word(data));
+            }
+            let next = (8 + size).next_multiple_of(align);
+            desc = desc.get(next..).unwrap_or_default();
+        }
+    }
+    Ok(0)
+}
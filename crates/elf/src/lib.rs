@@ -0,0 +1,167 @@
+//! ELF inspection for objects and binaries produced by the Linaro toolchains.
+//!
+//! [`ElfInfo`] collects the header-level facts checks care about (class,
+//! machine, ABI flags, interpreter, needed libraries) into owned values, so
+//! callers never deal with the borrowed parser types. 32-bit Arm files also
+//! get their EABI version and build attributes decoded, see [`arm`].
+//! [`symbols`] lists what shared objects export, [`sections`] gives
+//! section contents for comparing builds, and [`hardening`] the exploit
+//! mitigations a file was built with.
+
+use std::fs;
+use std::path::Path;
+
+use goblin::elf::{header, Elf};
+use serde::Serialize;
+
+pub mod arm;
+mod error;
+pub mod hardening;
+pub mod sections;
+pub mod symbols;
+
+pub use error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Class {
+    Elf32,
+    Elf64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+/// The ELF file type (`e_type`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Relocatable,
+    Executable,
+    /// Shared objects and position-independent executables.
+    Shared,
+    Core,
+    Other(u16),
+}
+
+/// Header-level facts about an ELF file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ElfInfo {
+    pub class: Class,
+    pub endian: Endian,
+    pub kind: Kind,
+    /// `e_machine`, e.g. [`EM_AARCH64`].
+    pub machine: u16,
+    /// `EI_OSABI`.
+    pub os_abi: u8,
+    /// `e_flags`.
+    pub flags: u32,
+    /// `PT_INTERP`, for dynamically linked executables.
+    pub interpreter: Option<String>,
+    /// `DT_NEEDED` entries, in order.
+    pub needed: Vec<String>,
+    pub soname: Option<String>,
+    /// `DT_RPATH` entries, searched before `LD_LIBRARY_PATH` when there is no
+    /// `DT_RUNPATH`.
+    pub rpath: Vec<String>,
+    /// `DT_RUNPATH` entries.
+    pub runpath: Vec<String>,
+    /// 32-bit Arm specifics; `None` for other machines.
+    pub arm: Option<arm::ArmInfo>,
+}
+
+pub use header::{EM_386, EM_AARCH64, EM_ARM, EM_X86_64};
+
+impl ElfInfo {
+    /// Reads and parses the file at `path`.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Self::parse(&bytes)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let elf = Elf::parse(bytes)?;
+        let header = &elf.header;
+        let endian = if elf.little_endian {
+            Endian::Little
+        } else {
+            Endian::Big
+        };
+        let kind = match header.e_type {
+            header::ET_REL => Kind::Relocatable,
+            header::ET_EXEC => Kind::Executable,
+            header::ET_DYN => Kind::Shared,
+            header::ET_CORE => Kind::Core,
+            other => Kind::Other(other),
+        };
+        let arm = (header.e_machine == EM_ARM)
+            .then(|| arm::ArmInfo::from_elf(&elf, bytes, endian))
+            .transpose()?;
+        Ok(Self {
+            class: if elf.is_64 {
+                Class::Elf64
+            } else {
+                Class::Elf32
+            },
+            endian,
+            kind,
+            machine: header.e_machine,
+            os_abi: header.e_ident[header::EI_OSABI],
+            flags: header.e_flags,
+            interpreter: elf.interpreter.map(str::to_owned),
+            needed: elf.libraries.iter().map(|l| l.to_string()).collect(),
+            soname: elf.soname.map(str::to_owned),
+            rpath: split_paths(&elf.rpaths),
+            runpath: split_paths(&elf.runpaths),
+            arm,
+        })
+    }
+
+    /// Human-readable machine name, e.g. `AArch64`.
+    pub fn machine_name(&self) -> String {
+        machine_name(self.machine)
+    }
+}
+
+/// Human-readable name of an `e_machine` value.
+pub fn machine_name(machine: u16) -> String {
+    match machine {
+        EM_ARM => "ARM".to_owned(),
+        EM_AARCH64 => "AArch64".to_owned(),
+        EM_X86_64 => "x86-64".to_owned(),
+        EM_386 => "i386".to_owned(),
+        other => format!("machine {other}"),
+    }
+}
+
+/// Splits colon-separated search path entries.
+fn split_paths(entries: &[&str]) -> Vec<String> {
+    entries
+        .iter()
+        .flat_map(|e| e.split(':'))
+        .filter(|e| !e.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Finds the contents of the section named `name`.
+pub(crate) fn section_data<'a>(elf: &Elf<'_>, bytes: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    let section = elf
+        .section_headers
+        .iter()
+        .find(|s| elf.shdr_strtab.get_at(s.sh_name) == Some(name))?;
+    if section.sh_type == goblin::elf::section_header::SHT_NOBITS {
+        return Some(&[]);
+    }
+    let start = usize::try_from(section.sh_offset).ok()?;
+    let end = start.checked_add(usize::try_from(section.sh_size).ok()?)?;
+    bytes.get(start..end)
+}
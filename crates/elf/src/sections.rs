@@ -0,0 +1,74 @@
+//! Section contents, for comparing two builds of the same file.
+
+use goblin::elf::note::NT_GNU_BUILD_ID;
+use goblin::elf::section_header::{SHF_ALLOC, SHT_NOBITS};
+use goblin::elf::Elf;
+
+use crate::Error;
+
+/// A section and what it holds in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    /// `sh_type`.
+    pub kind: u32,
+    /// `sh_flags`.
+    pub flags: u64,
+    pub size: u64,
+    /// The contents; empty for `SHT_NOBITS` sections such as `.bss`.
+    pub data: Vec<u8>,
+}
+
+impl Section {
+    /// Whether the section takes memory at run time (`SHF_ALLOC`).
+    pub fn is_allocated(&self) -> bool {
+        self.flags & u64::from(SHF_ALLOC) != 0
+    }
+
+    /// Whether the section has contents in the file, unlike `.bss`.
+    pub fn has_contents(&self) -> bool {
+        self.kind != SHT_NOBITS
+    }
+}
+
+/// The sections of the ELF file in `bytes`, in header order, without the
+/// null section.
+pub fn sections(bytes: &[u8]) -> Result<Vec<Section>, Error> {
+    let elf = Elf::parse(bytes)?;
+    let mut sections = Vec::new();
+    for header in elf.section_headers.iter().skip(1) {
+        let name = elf.shdr_strtab.get_at(header.sh_name).unwrap_or_default();
+        let data = if header.sh_type == SHT_NOBITS {
+            Vec::new()
+        } else {
+            let range = header.file_range().unwrap_or_default();
+            bytes
+                .get(range)
+                .ok_or_else(|| Error::Malformed(format!("section {name} lies outside the file")))?
+                .to_vec()
+        };
+        sections.push(Section {
+            name: name.to_owned(),
+            kind: header.sh_type,
+            flags: header.sh_flags,
+            size: header.sh_size,
+            data,
+        });
+    }
+    Ok(sections)
+}
+
+/// The GNU build ID (`NT_GNU_BUILD_ID` note) of the ELF file in `bytes`.
+pub fn build_id(bytes: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    let elf = Elf::parse(bytes)?;
+    let Some(notes) = elf.iter_note_sections(bytes, None) else {
+        return Ok(None);
+    };
+    for note in notes {
+        let note = note?;
+        if note.n_type == NT_GNU_BUILD_ID && note.name == "GNU" {
+            return Ok(Some(note.desc.to_vec()));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,169 @@
+//! Dynamic symbols exported by shared objects, with their symbol versions,
+//! the versions objects require from the libraries they link against, and
+//! the sizes of the functions and data objects in a file.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use goblin::elf::section_header::{SHN_LORESERVE, SHN_UNDEF};
+use goblin::elf::sym::{
+    STB_GLOBAL, STB_GNU_UNIQUE, STB_WEAK, STT_FILE, STT_FUNC, STT_GNU_IFUNC, STT_OBJECT,
+    STT_SECTION, STT_TLS, STV_DEFAULT, STV_PROTECTED,
+};
+use goblin::elf::Elf;
+use serde::Serialize;
+
+use crate::Error;
+
+/// A symbol other objects can link against.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Export {
+    pub name: String,
+    /// The symbol version, e.g. `GLIBC_2.17`; `None` for unversioned symbols.
+    pub version: Option<String>,
+    /// Whether this is a non-default version (`name@VERSION` rather than
+    /// `name@@VERSION`), only used by binaries linked against old releases.
+    pub hidden: bool,
+}
+
+impl fmt::Display for Export {
+    /// Formats as `nm -D` does: `name`, `name@@VERSION` or `name@VERSION`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version {
+            None => f.write_str(&self.name),
+            Some(version) if self.hidden => write!(f, "{}@{version}", self.name),
+            Some(version) => write!(f, "{}@@{version}", self.name),
+        }
+    }
+}
+
+/// The symbols exported through `.dynsym` by the ELF file in `bytes`,
+/// sorted and without duplicates.
+pub fn exports(bytes: &[u8]) -> Result<BTreeSet<Export>, Error> {
+    let elf = Elf::parse(bytes)?;
+    let versions = version_names(&elf);
+    let mut exports = BTreeSet::new();
+    for (index, sym) in elf.dynsyms.iter().enumerate() {
+        let exported = sym.st_shndx != SHN_UNDEF as usize
+            && matches!(sym.st_bind(), STB_GLOBAL | STB_WEAK | STB_GNU_UNIQUE)
+            && matches!(sym.st_visibility(), STV_DEFAULT | STV_PROTECTED)
+            && !matches!(sym.st_type(), STT_SECTION | STT_FILE);
+        let Some(name) = elf.dynstrtab.get_at(sym.st_name).filter(|_| exported) else {
+            continue;
+        };
+        let versym = elf.versym.as_ref().and_then(|v| v.get_at(index));
+        let version = versym
+            .as_ref()
+            .filter(|v| !v.is_local() && !v.is_global())
+            .and_then(|v| versions.iter().find(|(ndx, _)| *ndx == v.version()))
+            .map(|(_, name)| name.clone());
+        // Each version definition comes with an absolute symbol of the same
+        // name; it carries no code or data.
+        if version.as_deref() == Some(name) {
+            continue;
+        }
+        exports.insert(Export {
+            name: name.to_owned(),
+            hidden: version.is_some() && versym.as_ref().is_some_and(|v| v.is_hidden()),
+            version,
+        });
+    }
+    Ok(exports)
+}
+
+/// A function or data object and the space it takes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolSize {
+    pub name: String,
+    /// The section it lives in, e.g. `.text`.
+    pub section: String,
+    pub size: u64,
+}
+
+/// The functions and objects of non-zero size defined in the ELF file in
+/// `bytes`, from `.symtab`, or `.dynsym` for a stripped file, in table
+/// order. Local symbols are included, so a name may repeat.
+pub fn symbol_sizes(bytes: &[u8]) -> Result<Vec<SymbolSize>, Error> {
+    let elf = Elf::parse(bytes)?;
+    let (symbols, strings) = if elf.syms.is_empty() {
+        (&elf.dynsyms, &elf.dynstrtab)
+    } else {
+        (&elf.syms, &elf.strtab)
+    };
+    let mut sizes = Vec::new();
+    for sym in symbols.iter() {
+        let defined = sym.st_shndx != SHN_UNDEF as usize && sym.st_shndx < SHN_LORESERVE as usize;
+        let kind = matches!(
+            sym.st_type(),
+            STT_FUNC | STT_OBJECT | STT_TLS | STT_GNU_IFUNC
+        );
+        if !defined || !kind || sym.st_size == 0 {
+            continue;
+        }
+        let Some(name) = strings.get_at(sym.st_name) else {
+            continue;
+        };
+        let section = elf
+            .section_headers
+            .get(sym.st_shndx)
+            .and_then(|h| elf.shdr_strtab.get_at(h.sh_name))
+            .unwrap_or_default();
+        sizes.push(SymbolSize {
+            name: name.to_owned(),
+            section: section.to_owned(),
+            size: sym.st_size,
+        });
+    }
+    Ok(sizes)
+}
+
+/// The versions an object requires from one of its `DT_NEEDED` libraries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionNeed {
+    /// The library, as named in `DT_NEEDED`, e.g. `libc.so.6`.
+    pub file: String,
+    /// e.g. `["GLIBC_2.17", "GLIBC_2.28"]`, in file order.
+    pub versions: Vec<String>,
+}
+
+/// The `.gnu.version_r` entries of the ELF file in `bytes`: which symbol
+/// versions the dynamic linker must find before it will run the file.
+pub fn version_needs(bytes: &[u8]) -> Result<Vec<VersionNeed>, Error> {
+    let elf = Elf::parse(bytes)?;
+    let Some(verneed) = &elf.verneed else {
+        return Ok(Vec::new());
+    };
+    let name = |offset| elf.dynstrtab.get_at(offset).unwrap_or_default().to_owned();
+    Ok(verneed
+        .iter()
+        .map(|need| VersionNeed {
+            file: name(need.vn_file),
+            versions: need.iter().map(|aux| name(aux.vna_name)).collect(),
+        })
+        .collect())
+}
+
+/// The version names defined in `.gnu.version_d`, e.g. `GLIBC_2.17`; the
+/// first is usually the soname itself.
+pub fn version_definitions(bytes: &[u8]) -> Result<Vec<String>, Error> {
+    let elf = Elf::parse(bytes)?;
+    Ok(version_names(&elf)
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect())
+}
+
+/// The version definitions of `elf`, by version index.
+fn version_names(elf: &Elf<'_>) -> Vec<(u16, String)> {
+    let Some(verdef) = &elf.verdef else {
+        return Vec::new();
+    };
+    verdef
+        .iter()
+        .filter_map(|def| {
+            let aux = def.iter().next()?;
+            let name = elf.dynstrtab.get_at(aux.vda_name)?;
+            Some((def.vd_ndx, name.to_owned()))
+        })
+        .collect()
+}
@@ -0,0 +1,117 @@
+use linaro_elf::arm::{Attributes, FloatAbi};
+use linaro_elf::sections::{build_id, sections};
+use linaro_elf::symbols::symbol_sizes;
+use linaro_elf::{Class, ElfInfo, Endian, Error};
+
+/// Builds an `.ARM.attributes` section holding one `aeabi` file subsection
+/// with `attributes`.
+fn attributes_section(attributes: &[u8]) -> Vec<u8> {
+    let subsection_len = 1 + 4 + attributes.len();
+    let section_len = 4 + b"aeabi\0".len() + subsection_len;
+    let mut data = vec![b'A'];
+    data.extend((section_len as u32).to_le_bytes());
+    data.extend(b"aeabi\0");
+    data.push(1);
+    data.extend((subsection_len as u32).to_le_bytes());
+    data.extend(attributes);
+    data
+}
+
+#[test]
+fn armhf_attributes() {
+    // What GCC emits for -march=armv7-a -mfpu=vfpv3-d16 -mfloat-abi=hard.
+    let data = attributes_section(&[
+        5, b'7', b'-', b'A', 0, // Tag_CPU_name
+        6, 10, // Tag_CPU_arch: v7
+        7, b'A', // Tag_CPU_arch_profile
+        8, 1, // Tag_ARM_ISA_use
+        9, 2, // Tag_THUMB_ISA_use
+        10, 3, // Tag_FP_arch: VFPv3
+        18, 4, // Tag_ABI_PCS_wchar_t
+        26, 2, // Tag_ABI_enum_size
+        28, 1, // Tag_ABI_VFP_args: VFP registers
+        34, 1, // Tag_CPU_unaligned_access
+        67, b'2', b'.', b'0', b'9', 0, // Tag_conformance
+    ]);
+    let attributes = Attributes::parse(&data, Endian::Little).unwrap();
+    assert_eq!(attributes.cpu_name.as_deref(), Some("7-A"));
+    assert_eq!(attributes.cpu_arch_name(), Some("v7"));
+    assert_eq!(attributes.fp_arch, Some(3));
+    assert_eq!(attributes.float_abi(), FloatAbi::Hard);
+}
+
+#[test]
+fn soft_and_softfp_attributes() {
+    let soft = attributes_section(&[6, 10, 8, 1]);
+    let soft = Attributes::parse(&soft, Endian::Little).unwrap();
+    assert_eq!(soft.float_abi(), FloatAbi::Soft);
+
+    let softfp = attributes_section(&[6, 10, 10, 3]);
+    let softfp = Attributes::parse(&softfp, Endian::Little).unwrap();
+    assert_eq!(softfp.float_abi(), FloatAbi::Softfp);
+}
+
+#[test]
+fn rejects_truncated_attributes() {
+    let mut data = attributes_section(&[5, b'7', 0]);
+    data.truncate(data.len() - 2);
+    assert!(matches!(
+        Attributes::parse(&data, Endian::Little),
+        Err(Error::Attributes(_))
+    ));
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu", target_arch = "x86_64"))]
+#[test]
+fn reads_host_executable() {
+    let info = ElfInfo::read(std::env::current_exe().unwrap()).unwrap();
+    assert_eq!(info.class, Class::Elf64);
+    assert_eq!(info.endian, Endian::Little);
+    assert_eq!(info.machine_name(), "x86-64");
+    assert_eq!(
+        info.interpreter.as_deref(),
+        Some("/lib64/ld-linux-x86-64.so.2")
+    );
+    assert!(info.needed.iter().any(|l| l == "libc.so.6"));
+    assert_eq!(info.arm, None);
+}
+
+#[test]
+fn reads_sections() {
+    let bytes = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+    let all = sections(&bytes).unwrap();
+    let text = all.iter().find(|s| s.name == ".text").unwrap();
+    assert_eq!(text.data.len() as u64, text.size);
+    assert!(text.size > 0);
+    assert!(text.is_allocated() && text.has_contents());
+    if let Some(bss) = all.iter().find(|s| s.name == ".bss") {
+        assert!(bss.data.is_empty());
+        assert!(bss.is_allocated() && !bss.has_contents());
+    }
+    if let Some(comment) = all.iter().find(|s| s.name == ".comment") {
+        assert!(!comment.is_allocated());
+    }
+    let note = all.iter().find(|s| s.name == ".note.gnu.build-id");
+    let id = build_id(&bytes).unwrap();
+    assert_eq!(id.is_some(), note.is_some());
+    if let (Some(id), Some(note)) = (id, note) {
+        assert!(note.data.ends_with(&id));
+    }
+    assert!(sections(b"\x7fELF").is_err());
+}
+
+#[test]
+fn reads_symbol_sizes() {
+    let bytes = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+    let symbols = symbol_sizes(&bytes).unwrap();
+    assert!(symbols.iter().all(|s| s.size > 0));
+    // The test harness's entry point is a function of this executable.
+    assert!(symbols
+        .iter()
+        .any(|s| s.name == "main" && s.section == ".text"));
+}
+
+#[test]
+fn rejects_non_elf() {
+    assert!(ElfInfo::parse(b"#!/bin/sh\n").is_err());
+}
@@ -0,0 +1,220 @@
+//! Which vendored toolchain each firmware stage of a board builds with.
+//!
+//! A checked-in board table lists the boards GloDroid supports, their SoC,
+//! and for each stage the target triple and the oldest GCC it builds with:
+//!
+//! ```toml
+//! [board.pine64-plus]
+//! soc = "allwinner-a64"
+//!
+//! [board.pine64-plus.stage.atf]
+//! target = "aarch64-elf"
+//! min-gcc = "7.0.0"
+//!
+//! [board.pine64-plus.stage.u-boot]
+//! target = "aarch64-linux-gnu"
+//! gcc = "7.5.0"
+//! ```
+//!
+//! A stage resolves to exactly one toolchain of the manifest: several
+//! vendored versions satisfying it is an error, so that vendoring a new
+//! release never silently moves a board to another compiler. `gcc` pins
+//! the version outright. [`board_mk`] renders the result as make
+//! variables for device makefiles.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use linaro_manifest::{GccVersion, Manifest, Toolchain, Triple};
+use serde::Deserialize;
+
+use crate::Error;
+
+/// The contents of a board table.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Boards {
+    #[serde(rename = "board", default)]
+    pub boards: BTreeMap<String, Board>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Board {
+    pub soc: String,
+    /// Firmware stages (`atf`, `u-boot`, `kernel`, ...) by name.
+    #[serde(rename = "stage", default)]
+    pub stages: BTreeMap<String, Stage>,
+}
+
+/// What one stage needs of its toolchain.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Stage {
+    pub target: Triple,
+    /// Oldest usable GCC.
+    pub min_gcc: Option<GccVersion>,
+    /// Exact GCC to use.
+    pub gcc: Option<GccVersion>,
+}
+
+impl Boards {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| Error::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        text.parse()
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Board, Error> {
+        self.boards
+            .get(name)
+            .ok_or_else(|| Error::UnknownBoard(name.to_owned()))
+    }
+}
+
+impl FromStr for Boards {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(Error::Boards)
+    }
+}
+
+/// A stage paired with the toolchain resolved for it.
+#[derive(Debug, Clone, Copy)]
+pub struct Resolution<'a> {
+    pub stage: &'a str,
+    pub toolchain: &'a Toolchain,
+}
+
+impl Board {
+    pub fn stage(&self, name: &str) -> Result<&Stage, Error> {
+        self.stages
+            .get(name)
+            .ok_or_else(|| Error::UnknownStage(name.to_owned()))
+    }
+
+    /// Resolves every stage of the board, in name order.
+    pub fn resolve<'a>(
+        &'a self,
+        manifest: &'a Manifest,
+        host: &str,
+    ) -> Result<Vec<Resolution<'a>>, Error> {
+        self.stages
+            .iter()
+            .map(|(name, stage)| {
+                let toolchain = stage
+                    .resolve(manifest, host)
+                    .map_err(|source| Error::Stage {
+                        stage: name.clone(),
+                        source: Box::new(source),
+                    })?;
+                Ok(Resolution {
+                    stage: name,
+                    toolchain,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Stage {
+    /// The one toolchain of `manifest` running on `host` that satisfies
+    /// this stage.
+    pub fn resolve<'a>(&self, manifest: &'a Manifest, host: &str) -> Result<&'a Toolchain, Error> {
+        let vendored: Vec<_> = manifest
+            .toolchains
+            .iter()
+            .filter(|t| t.host == host && t.target == self.target)
+            .collect();
+        let mut candidates: Vec<_> = vendored
+            .iter()
+            .copied()
+            .filter(|t| self.gcc.map_or(true, |v| t.gcc == v))
+            .filter(|t| self.min_gcc.map_or(true, |v| t.gcc >= v))
+            .collect();
+        candidates.sort_by_key(|t| t.gcc);
+        match &candidates[..] {
+            [toolchain] => Ok(toolchain),
+            [] => {
+                let mut available: Vec<_> = vendored.iter().map(|t| t.gcc).collect();
+                available.sort();
+                Err(Error::NoMatch {
+                    target: self.target.to_string(),
+                    host: host.to_owned(),
+                    requirement: self.requirement(),
+                    available,
+                })
+            }
+            _ => Err(Error::Ambiguous {
+                target: self.target.to_string(),
+                host: host.to_owned(),
+                requirement: self.requirement(),
+                candidates: candidates.iter().map(|t| t.gcc).collect(),
+            }),
+        }
+    }
+
+    /// The GCC requirement in words, e.g. `GCC >= 7.0.0`.
+    fn requirement(&self) -> String {
+        match (self.gcc, self.min_gcc) {
+            (Some(gcc), Some(min)) => format!("GCC {gcc} (at least {min})"),
+            (Some(gcc), None) => format!("GCC {gcc}"),
+            (None, Some(min)) => format!("GCC >= {min}"),
+            (None, None) => "any GCC".to_owned(),
+        }
+    }
+}
+
+/// Renders make variables for the stages of board `name`, with toolchains
+/// found below `prebuilts`: `LINARO_<STAGE>_CROSS_COMPILE`, `_GCC`, and
+/// `_ARCH` and `_SYSROOT` where the target has them.
+pub fn board_mk(
+    name: &str,
+    board: &Board,
+    resolutions: &[Resolution<'_>],
+    prebuilts: &Path,
+) -> String {
+    let mut out = format!(
+        "# Generated by `linaro-prebuilts board` from the board table. DO NOT EDIT.\n\
+         # {name} ({})\n",
+        board.soc
+    );
+    for Resolution { stage, toolchain } in resolutions {
+        let var = format!("LINARO_{}", stage.to_uppercase().replace(['-', '.'], "_"));
+        let root = prebuilts.join(toolchain.install_path());
+        writeln!(
+            out,
+            "\n# {stage}: Linaro GCC {} ({}) for {}",
+            toolchain.gcc, toolchain.release, toolchain.target
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "{var}_CROSS_COMPILE := {}",
+            root.join("bin")
+                .join(toolchain.target.tool_prefix())
+                .display()
+        )
+        .unwrap();
+        writeln!(out, "{var}_GCC := {}", toolchain.gcc).unwrap();
+        if let Some(arch) = toolchain.target.kernel_arch() {
+            writeln!(out, "{var}_ARCH := {arch}").unwrap();
+        }
+        if let Some(sysroot) = toolchain.sysroot_path() {
+            writeln!(
+                out,
+                "{var}_SYSROOT := {}",
+                prebuilts.join(sysroot).display()
+            )
+            .unwrap();
+        }
+    }
+    out
+}
@@ -0,0 +1,207 @@
+//! `.cargo/config.toml` sections wiring Rust cross targets to the Linaro
+//! toolchains.
+//!
+//! For every selected Rust target this emits the `linker`, a `--sysroot`
+//! link argument, and `[env]` entries read by the `cc` crate
+//! (`CC_<target>`, `CXX_<target>`, `AR_<target>`, `CFLAGS_<target>`,
+//! `CXXFLAGS_<target>`). Paths are emitted as given, so callers should pass
+//! an absolute prebuilts root: compiler flags are not resolved relative to
+//! the config file.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use linaro_manifest::{GccVersion, Manifest, Toolchain};
+
+use crate::{select_toolchain, Error};
+
+/// A Rust target and the Linaro triple whose compiler serves it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RustTarget {
+    /// Rust target name, e.g. `armv7-unknown-linux-gnueabihf`.
+    pub rust: &'static str,
+    /// Linaro triple, e.g. `arm-linux-gnueabihf`.
+    pub linaro: &'static str,
+    /// C flags selecting the Rust target's baseline when it differs from
+    /// the Linaro compiler's configured default.
+    pub cflags: &'static [&'static str],
+}
+
+/// Rust targets that have a Linaro counterpart.
+pub const RUST_TARGETS: &[RustTarget] = &[
+    RustTarget {
+        rust: "aarch64-unknown-linux-gnu",
+        linaro: "aarch64-linux-gnu",
+        cflags: &[],
+    },
+    RustTarget {
+        rust: "aarch64_be-unknown-linux-gnu",
+        linaro: "aarch64_be-linux-gnu",
+        cflags: &[],
+    },
+    RustTarget {
+        rust: "armv7-unknown-linux-gnueabihf",
+        linaro: "arm-linux-gnueabihf",
+        cflags: &["-march=armv7-a", "-mfpu=vfpv3-d16", "-mfloat-abi=hard"],
+    },
+    RustTarget {
+        rust: "thumbv7neon-unknown-linux-gnueabihf",
+        linaro: "arm-linux-gnueabihf",
+        cflags: &[
+            "-march=armv7-a",
+            "-mthumb",
+            "-mfpu=neon",
+            "-mfloat-abi=hard",
+        ],
+    },
+    RustTarget {
+        rust: "arm-unknown-linux-gnueabihf",
+        linaro: "arm-linux-gnueabihf",
+        cflags: &["-march=armv6", "-marm", "-mfpu=vfp", "-mfloat-abi=hard"],
+    },
+    RustTarget {
+        rust: "armv7-unknown-linux-gnueabi",
+        linaro: "arm-linux-gnueabi",
+        cflags: &["-march=armv7-a", "-mfloat-abi=softfp"],
+    },
+    RustTarget {
+        rust: "arm-unknown-linux-gnueabi",
+        linaro: "arm-linux-gnueabi",
+        cflags: &["-march=armv6", "-marm", "-mfloat-abi=softfp"],
+    },
+    RustTarget {
+        rust: "aarch64-unknown-none",
+        linaro: "aarch64-elf",
+        cflags: &[],
+    },
+    RustTarget {
+        rust: "aarch64-unknown-none-softfloat",
+        linaro: "aarch64-elf",
+        cflags: &["-mgeneral-regs-only"],
+    },
+    RustTarget {
+        rust: "armv7a-none-eabi",
+        linaro: "arm-eabi",
+        cflags: &["-march=armv7-a", "-mfloat-abi=soft"],
+    },
+];
+
+/// Looks up a Rust target by name.
+pub fn rust_target(name: &str) -> Option<&'static RustTarget> {
+    RUST_TARGETS.iter().find(|t| t.rust == name)
+}
+
+/// A Rust target paired with the toolchain chosen for it.
+#[derive(Debug, Clone, Copy)]
+pub struct Selection<'a> {
+    pub rust: &'static RustTarget,
+    pub toolchain: &'a Toolchain,
+}
+
+/// Chooses a toolchain running on `host` for each of `rust_targets`: the
+/// one with GCC `gcc` if given, otherwise the newest. With no targets,
+/// every known Rust target that has a matching toolchain is selected.
+pub fn select<'a>(
+    manifest: &'a Manifest,
+    host: &str,
+    rust_targets: &[String],
+    gcc: Option<GccVersion>,
+) -> Result<Vec<Selection<'a>>, Error> {
+    let pick = |rust: &RustTarget| select_toolchain(manifest, host, rust.linaro, gcc);
+
+    if rust_targets.is_empty() {
+        return Ok(RUST_TARGETS
+            .iter()
+            .filter_map(|rust| {
+                Some(Selection {
+                    rust,
+                    toolchain: pick(rust).ok()?,
+                })
+            })
+            .collect());
+    }
+    rust_targets
+        .iter()
+        .map(|name| {
+            let rust = rust_target(name).ok_or_else(|| Error::UnknownRustTarget(name.clone()))?;
+            let toolchain = pick(rust)?;
+            Ok(Selection { rust, toolchain })
+        })
+        .collect()
+}
+
+/// Renders the config sections for `selections`, with toolchains found
+/// below `prebuilts` (the `prebuilts/gcc` root).
+pub fn cargo_config(selections: &[Selection<'_>], prebuilts: &Path) -> String {
+    let mut targets = String::new();
+    let mut env = String::new();
+    for Selection { rust, toolchain } in selections {
+        let root = prebuilts.join(toolchain.install_path());
+        let tool = |name: &str| {
+            let path = root
+                .join("bin")
+                .join(format!("{}{name}", toolchain.target.tool_prefix()));
+            path.to_string_lossy().into_owned()
+        };
+        let sysroot = toolchain
+            .sysroot_path()
+            .map(|s| prebuilts.join(s).to_string_lossy().into_owned());
+
+        writeln!(
+            targets,
+            "\n# {}: Linaro GCC {} ({})",
+            rust.rust, toolchain.gcc, toolchain.release
+        )
+        .unwrap();
+        writeln!(targets, "[target.{}]", rust.rust).unwrap();
+        writeln!(targets, "linker = {}", quote(&tool("gcc"))).unwrap();
+        if let Some(sysroot) = &sysroot {
+            let flag = format!("link-arg=--sysroot={sysroot}");
+            writeln!(targets, "rustflags = [\"-C\", {}]", quote(&flag)).unwrap();
+        }
+
+        let mut cflags: Vec<String> = rust.cflags.iter().map(|f| f.to_string()).collect();
+        if let Some(sysroot) = &sysroot {
+            cflags.push(format!("--sysroot={sysroot}"));
+        }
+        let cflags = cflags.join(" ");
+        let var = rust.rust.replace('-', "_");
+        for (name, value) in [
+            ("CC", tool("gcc")),
+            ("CXX", tool("g++")),
+            ("AR", tool("ar")),
+            ("CFLAGS", cflags.clone()),
+            ("CXXFLAGS", cflags),
+        ] {
+            if !value.is_empty() {
+                writeln!(env, "{name}_{var} = {}", quote(&value)).unwrap();
+            }
+        }
+    }
+
+    let mut out = String::from(
+        "# Generated by `linaro-prebuilts cargo-config` from the toolchain manifest.\n",
+    );
+    out.push_str(&targets);
+    if !env.is_empty() {
+        out.push_str("\n[env]\n");
+        out.push_str(&env);
+    }
+    out
+}
+
+/// Quotes `s` as a TOML basic string.
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => write!(out, "\\u{:04X}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
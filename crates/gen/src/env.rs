@@ -0,0 +1,152 @@
+//! Shell environments for building firmware and kernels with a toolchain.
+//!
+//! A checked-in profile file names the builds GloDroid performs and the
+//! toolchain each one uses:
+//!
+//! ```toml
+//! [profile.kernel-arm64]
+//! target = "aarch64-linux-gnu"
+//!
+//! [profile.atf-aarch64-elf]
+//! target = "aarch64-elf"
+//! gcc = "7.5.0"
+//! arch = "aarch64"
+//! ```
+//!
+//! A profile resolves to `CROSS_COMPILE`, `ARCH` (the kernel `ARCH` of the
+//! target unless overridden), any extra variables from its `env` table, and
+//! the toolchain's `bin` directory prepended to `PATH`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use linaro_manifest::{GccVersion, Manifest, Triple};
+use serde::Deserialize;
+
+use crate::{select_toolchain, Error};
+
+/// The contents of a profile file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profiles {
+    #[serde(rename = "profile", default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// One named build environment.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub target: Triple,
+    /// GCC version to use; the newest available if absent.
+    pub gcc: Option<GccVersion>,
+    /// `ARCH` to export; defaults to the target's kernel architecture.
+    pub arch: Option<String>,
+    /// Additional variables to export verbatim.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl Profiles {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| Error::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        text.parse()
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Profile, Error> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| Error::UnknownProfile(name.to_owned()))
+    }
+}
+
+impl FromStr for Profiles {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+/// A resolved environment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Environment {
+    /// Variables to set, in output order.
+    pub vars: Vec<(String, String)>,
+    /// Directory to prepend to `PATH`.
+    pub path: PathBuf,
+}
+
+impl Environment {
+    /// Resolves `profile` against the toolchains of `manifest` running on
+    /// `host`, installed below `prebuilts`.
+    pub fn resolve(
+        profile: &Profile,
+        manifest: &Manifest,
+        host: &str,
+        prebuilts: &Path,
+    ) -> Result<Self, Error> {
+        let toolchain = select_toolchain(manifest, host, profile.target.as_str(), profile.gcc)?;
+        let bin = prebuilts.join(toolchain.install_path()).join("bin");
+        let mut vars = vec![(
+            "CROSS_COMPILE".to_owned(),
+            bin.join(toolchain.target.tool_prefix())
+                .to_string_lossy()
+                .into_owned(),
+        )];
+        if let Some(arch) = profile.arch.as_deref().or(toolchain.target.kernel_arch()) {
+            vars.push(("ARCH".to_owned(), arch.to_owned()));
+        }
+        vars.extend(profile.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Ok(Self { vars, path: bin })
+    }
+
+    /// POSIX shell `export` statements.
+    pub fn to_sh(&self) -> String {
+        let mut out = String::new();
+        for (name, value) in &self.vars {
+            writeln!(out, "export {name}={}", sh_quote(value)).unwrap();
+        }
+        let path = sh_quote(&self.path.to_string_lossy());
+        writeln!(out, "export PATH={path}:\"$PATH\"").unwrap();
+        out
+    }
+
+    /// fish `set -gx` statements.
+    pub fn to_fish(&self) -> String {
+        let mut out = String::new();
+        for (name, value) in &self.vars {
+            writeln!(out, "set -gx {name} {}", fish_quote(value)).unwrap();
+        }
+        let path = fish_quote(&self.path.to_string_lossy());
+        writeln!(out, "set -gx PATH {path} $PATH").unwrap();
+        out
+    }
+
+    /// The variables as a map, with `PATH` expanded against `current_path`.
+    pub fn to_map(&self, current_path: Option<&str>) -> BTreeMap<String, String> {
+        let mut map: BTreeMap<_, _> = self.vars.iter().cloned().collect();
+        let mut path = self.path.to_string_lossy().into_owned();
+        if let Some(current) = current_path.filter(|p| !p.is_empty()) {
+            path.push(':');
+            path.push_str(current);
+        }
+        map.insert("PATH".to_owned(), path);
+        map
+    }
+}
+
+fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r#"'\''"#))
+}
+
+fn fish_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', r"\\").replace('\'', r"\'"))
+}
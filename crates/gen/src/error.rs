@@ -0,0 +1,55 @@
+use std::io;
+use std::path::PathBuf;
+
+use linaro_manifest::GccVersion;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("no Linaro toolchain is known for Rust target {0}")]
+    UnknownRustTarget(String),
+    #[error("the manifest has no {target} toolchain{} for host {host}", gcc.map(|v| format!(" with GCC {v}")).unwrap_or_default())]
+    NoToolchain {
+        target: String,
+        host: String,
+        gcc: Option<GccVersion>,
+    },
+    #[error("failed to read {}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("malformed profile file")]
+    Profiles(#[from] toml::de::Error),
+    #[error("no profile named {0}")]
+    UnknownProfile(String),
+    #[error("malformed board table")]
+    Boards(#[source] toml::de::Error),
+    #[error("no board named {0}")]
+    UnknownBoard(String),
+    #[error("the board has no stage named {0}")]
+    UnknownStage(String),
+    #[error(
+        "no vendored {target} toolchain for host {host} has {requirement} (vendored: {})",
+        versions(available)
+    )]
+    NoMatch {
+        target: String,
+        host: String,
+        requirement: String,
+        available: Vec<GccVersion>,
+    },
+    #[error("several vendored {target} toolchains for host {host} have {requirement}: {}; pin one with `gcc = \"<version>\"`", versions(candidates))]
+    Ambiguous {
+        target: String,
+        host: String,
+        requirement: String,
+        candidates: Vec<GccVersion>,
+    },
+    #[error("stage {stage}")]
+    Stage { stage: String, source: Box<Error> },
+}
+
+fn versions(versions: &[GccVersion]) -> String {
+    if versions.is_empty() {
+        return "none".to_owned();
+    }
+    let versions: Vec<_> = versions.iter().map(ToString::to_string).collect();
+    versions.join(", ")
+}
@@ -0,0 +1,47 @@
+//! Generators for the build-system glue that points other projects at the
+//! vendored toolchains.
+//!
+//! Every generator is a pure function of the manifest, so its output can be
+//! checked in and compared against golden files.
+
+use linaro_manifest::{GccVersion, Manifest, Toolchain};
+
+pub mod boards;
+pub mod cargo;
+pub mod env;
+mod error;
+pub mod soong;
+
+pub use error::Error;
+
+/// The toolchains of `manifest` that run on `host`, in a stable order.
+fn toolchains_for_host<'a>(manifest: &'a Manifest, host: &str) -> Vec<&'a Toolchain> {
+    let mut toolchains: Vec<_> = manifest
+        .toolchains
+        .iter()
+        .filter(|t| t.host == host)
+        .collect();
+    toolchains.sort_by(|a, b| (&a.target, a.gcc).cmp(&(&b.target, b.gcc)));
+    toolchains
+}
+
+/// The toolchain for `target` running on `host`: the one with GCC `gcc` if
+/// given, otherwise the newest.
+pub fn select_toolchain<'a>(
+    manifest: &'a Manifest,
+    host: &str,
+    target: &str,
+    gcc: Option<GccVersion>,
+) -> Result<&'a Toolchain, Error> {
+    manifest
+        .toolchains
+        .iter()
+        .filter(|t| t.host == host && t.target.as_str() == target)
+        .filter(|t| gcc.map_or(true, |v| t.gcc == v))
+        .max_by_key(|t| t.gcc)
+        .ok_or_else(|| Error::NoToolchain {
+            target: target.to_owned(),
+            host: host.to_owned(),
+            gcc,
+        })
+}
@@ -0,0 +1,130 @@
+//! `Android.bp` and `Android.mk` for the `prebuilts/gcc` directory of an
+//! AOSP tree.
+//!
+//! For each toolchain the Blueprint file declares a filegroup covering the
+//! whole tree (for `tool_files` of genrules), one filegroup per driver
+//! binary (for `$(location)`), and one for the glibc sysroot. Soong has no
+//! notion of exported variables, so the make fallback carries the
+//! `CROSS_COMPILE`, `ARCH` and sysroot values for kernel and U-Boot builds.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use linaro_manifest::{Manifest, Toolchain};
+
+use crate::toolchains_for_host;
+
+/// Driver binaries that get their own filegroup.
+pub const TOOLS: &[&str] = &[
+    "gcc", "g++", "cpp", "as", "ld", "ar", "nm", "objcopy", "objdump", "readelf", "strip",
+];
+
+const HEADER: &str =
+    "Generated by `linaro-prebuilts blueprint` from the toolchain manifest. DO NOT EDIT.";
+
+/// Soong module name of a toolchain, e.g. `linaro-gcc-aarch64-linux-gnu-7.5.0`.
+pub fn module_name(toolchain: &Toolchain) -> String {
+    format!("linaro-gcc-{}-{}", toolchain.target, toolchain.gcc)
+}
+
+/// Prefix of the make variables describing a toolchain, e.g.
+/// `LINARO_GCC_AARCH64_LINUX_GNU_7_5_0`.
+pub fn make_prefix(toolchain: &Toolchain) -> String {
+    module_name(toolchain)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn description(toolchain: &Toolchain) -> String {
+    format!(
+        "{}: Linaro GCC {} ({})",
+        toolchain.target, toolchain.gcc, toolchain.release
+    )
+}
+
+fn path(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Renders `Android.bp` for the toolchains running on `host`.
+pub fn android_bp(manifest: &Manifest, host: &str) -> String {
+    let mut out = format!("// {HEADER}\n");
+    for toolchain in toolchains_for_host(manifest, host) {
+        let name = module_name(toolchain);
+        let root = path(&toolchain.install_path());
+        let prefix = toolchain.target.tool_prefix();
+
+        writeln!(out, "\n// {}", description(toolchain)).unwrap();
+        writeln!(out, "// CROSS_COMPILE: {root}/bin/{prefix}").unwrap();
+        if let Some(arch) = toolchain.target.kernel_arch() {
+            writeln!(out, "// ARCH: {arch}").unwrap();
+        }
+        filegroup(&mut out, &name, &root, &format!("{root}/**/*"));
+        for tool in TOOLS {
+            filegroup(
+                &mut out,
+                &format!("{name}-{tool}"),
+                &format!("{root}/bin"),
+                &format!("{root}/bin/{prefix}{tool}"),
+            );
+        }
+        if let Some(sysroot) = toolchain.sysroot_path() {
+            let sysroot = path(&sysroot);
+            filegroup(
+                &mut out,
+                &format!("{name}-sysroot"),
+                &sysroot,
+                &format!("{sysroot}/**/*"),
+            );
+        }
+    }
+    out
+}
+
+fn filegroup(out: &mut String, name: &str, path: &str, src: &str) {
+    writeln!(
+        out,
+        "\nfilegroup {{\n    name: \"{name}\",\n    path: \"{path}\",\n    srcs: [\"{src}\"],\n}}"
+    )
+    .unwrap();
+}
+
+/// Renders the `Android.mk` fallback for the toolchains running on `host`.
+pub fn android_mk(manifest: &Manifest, host: &str) -> String {
+    let mut out = format!("# {HEADER}\n\nLOCAL_PATH := $(call my-dir)\n");
+    for toolchain in toolchains_for_host(manifest, host) {
+        let var = make_prefix(toolchain);
+        writeln!(out, "\n# {}", description(toolchain)).unwrap();
+        writeln!(
+            out,
+            "{var}_ROOT := $(LOCAL_PATH)/{}",
+            path(&toolchain.install_path())
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "{var}_CROSS_COMPILE := $({var}_ROOT)/bin/{}",
+            toolchain.target.tool_prefix()
+        )
+        .unwrap();
+        if let Some(arch) = toolchain.target.kernel_arch() {
+            writeln!(out, "{var}_ARCH := {arch}").unwrap();
+        }
+        if toolchain.sysroot_path().is_some() {
+            writeln!(
+                out,
+                "{var}_SYSROOT := $({var}_ROOT)/{}/libc",
+                toolchain.target
+            )
+            .unwrap();
+        }
+    }
+    out
+}
@@ -0,0 +1,247 @@
+use std::path::Path;
+
+use linaro_gen::boards::{self, Boards};
+use linaro_gen::env::{Environment, Profiles};
+use linaro_gen::{cargo, soong, Error};
+use linaro_golden::check_golden;
+use linaro_manifest::{error_chain, GccVersion, Manifest};
+
+fn manifest() -> Manifest {
+    Manifest::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/toolchains.toml"))
+        .unwrap()
+}
+
+#[test]
+fn android_bp() {
+    check_golden!("Android.bp", &soong::android_bp(&manifest(), "x86_64"));
+}
+
+#[test]
+fn android_mk() {
+    check_golden!("Android.mk", &soong::android_mk(&manifest(), "x86_64"));
+}
+
+#[test]
+fn only_includes_toolchains_for_the_host() {
+    let bp = soong::android_bp(&manifest(), "aarch64");
+    assert!(bp.contains("name: \"linaro-gcc-aarch64-linux-gnu-7.5.0\","));
+    assert!(!bp.contains("arm-linux-gnueabihf"));
+    assert!(!bp.contains("x86_64/"));
+}
+
+#[test]
+fn cargo_config() {
+    let manifest = manifest();
+    let selections = cargo::select(&manifest, "x86_64", &[], None).unwrap();
+    let config = cargo::cargo_config(&selections, Path::new("/opt/prebuilts/gcc"));
+    toml::from_str::<toml::Table>(&config).unwrap();
+    check_golden!("config.toml", &config);
+}
+
+#[test]
+fn cargo_selection() {
+    let manifest = manifest();
+    let targets = ["aarch64-unknown-linux-gnu".to_owned()];
+    let newest = cargo::select(&manifest, "x86_64", &targets, None).unwrap();
+    assert_eq!(newest[0].toolchain.gcc, GccVersion::new(7, 5, 0));
+    let pinned = cargo::select(
+        &manifest,
+        "x86_64",
+        &targets,
+        Some(GccVersion::new(6, 5, 0)),
+    )
+    .unwrap();
+    assert_eq!(pinned[0].toolchain.gcc, GccVersion::new(6, 5, 0));
+
+    let unknown = ["riscv64gc-unknown-linux-gnu".to_owned()];
+    assert!(matches!(
+        cargo::select(&manifest, "x86_64", &unknown, None),
+        Err(Error::UnknownRustTarget(_))
+    ));
+    let armel = ["armv7-unknown-linux-gnueabi".to_owned()];
+    assert!(matches!(
+        cargo::select(&manifest, "x86_64", &armel, None),
+        Err(Error::NoToolchain { .. })
+    ));
+}
+
+#[test]
+fn checked_in_files_resolve() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let manifest = Manifest::load(root.join("toolchains.toml")).unwrap();
+    let profiles = Profiles::load(root.join("profiles.toml")).unwrap();
+    for (name, profile) in &profiles.profiles {
+        if let Err(err) = Environment::resolve(profile, &manifest, "x86_64", Path::new("/p")) {
+            panic!("profile {name}: {}", error_chain(&err));
+        }
+    }
+    for (name, board) in &Boards::load(root.join("boards.toml")).unwrap().boards {
+        if let Err(err) = board.resolve(&manifest, "x86_64") {
+            panic!("board {name}: {}", error_chain(&err));
+        }
+    }
+}
+
+fn environment(profile: &str) -> Environment {
+    let profiles =
+        Profiles::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("../../profiles.toml")).unwrap();
+    let profile = profiles.get(profile).unwrap();
+    Environment::resolve(
+        profile,
+        &manifest(),
+        "x86_64",
+        Path::new("/opt/prebuilts/gcc"),
+    )
+    .unwrap()
+}
+
+#[test]
+fn env_shells() {
+    check_golden!("env-kernel-arm64.sh", &environment("kernel-arm64").to_sh());
+    check_golden!(
+        "env-atf-aarch64-elf.fish",
+        &environment("atf-aarch64-elf").to_fish(),
+    );
+}
+
+#[test]
+fn env_map() {
+    let map = environment("uboot-armhf").to_map(Some("/usr/bin:/bin"));
+    assert_eq!(map["ARCH"], "arm");
+    assert_eq!(
+        map["PATH"],
+        "/opt/prebuilts/gcc/x86_64/arm-linux-gnueabihf/7.5.0/bin:/usr/bin:/bin"
+    );
+}
+
+#[test]
+fn env_profiles() {
+    let profiles: Profiles = r#"
+        [profile.quoted]
+        target = "aarch64-linux-gnu"
+        gcc = "6.5.0"
+        env = { KCFLAGS = "-Werror 'x'" }
+    "#
+    .parse()
+    .unwrap();
+    let profile = profiles.get("quoted").unwrap();
+    let env = Environment::resolve(profile, &manifest(), "x86_64", Path::new("/p")).unwrap();
+    assert_eq!(
+        env.to_sh(),
+        r#"export CROSS_COMPILE='/p/x86_64/aarch64-linux-gnu/6.5.0/bin/aarch64-linux-gnu-'
+export ARCH='arm64'
+export KCFLAGS='-Werror '\''x'\'''
+export PATH='/p/x86_64/aarch64-linux-gnu/6.5.0/bin':"$PATH"
+"#
+    );
+    assert!(matches!(
+        profiles.get("kernel"),
+        Err(Error::UnknownProfile(_))
+    ));
+    assert!("[profile.x]\ntarget = \"aarch64\"\n"
+        .parse::<Profiles>()
+        .is_err());
+}
+
+fn boards() -> Boards {
+    Boards::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("../../boards.toml")).unwrap()
+}
+
+#[test]
+fn board_mk() {
+    let manifest = manifest();
+    let boards = boards();
+    for (name, board) in &boards.boards {
+        if let Err(err) = board.resolve(&manifest, "x86_64") {
+            panic!("{name}: {err}");
+        }
+    }
+    let board = boards.get("pine64-plus").unwrap();
+    let resolutions = board.resolve(&manifest, "x86_64").unwrap();
+    check_golden!(
+        "board-pine64-plus.mk",
+        &boards::board_mk(
+            "pine64-plus",
+            board,
+            &resolutions,
+            Path::new("/opt/prebuilts/gcc"),
+        ),
+    );
+}
+
+#[test]
+fn board_stages() {
+    let manifest = manifest();
+    let boards: Boards = r#"
+        [board.test]
+        soc = "allwinner-a64"
+
+        [board.test.stage.any]
+        target = "aarch64-linux-gnu"
+
+        [board.test.stage.old]
+        target = "aarch64-linux-gnu"
+        min-gcc = "6.0.0"
+
+        [board.test.stage.pinned]
+        target = "aarch64-linux-gnu"
+        gcc = "6.5.0"
+
+        [board.test.stage.new]
+        target = "aarch64-linux-gnu"
+        min-gcc = "7.0.0"
+
+        [board.test.stage.future]
+        target = "aarch64-linux-gnu"
+        min-gcc = "8.0.0"
+
+        [board.test.stage.absent]
+        target = "arm-eabi"
+    "#
+    .parse()
+    .unwrap();
+    let board = boards.get("test").unwrap();
+    let resolve = |stage: &str| board.stage(stage).unwrap().resolve(&manifest, "x86_64");
+
+    assert_eq!(resolve("new").unwrap().gcc, GccVersion::new(7, 5, 0));
+    assert_eq!(resolve("pinned").unwrap().gcc, GccVersion::new(6, 5, 0));
+    match resolve("old") {
+        Err(Error::Ambiguous { candidates, .. }) => assert_eq!(
+            candidates,
+            [GccVersion::new(6, 5, 0), GccVersion::new(7, 5, 0)]
+        ),
+        other => panic!("expected an ambiguous match, got {other:?}"),
+    }
+    assert!(matches!(resolve("any"), Err(Error::Ambiguous { .. })));
+    match resolve("future") {
+        Err(err @ Error::NoMatch { .. }) => assert_eq!(
+            err.to_string(),
+            "no vendored aarch64-linux-gnu toolchain for host x86_64 has GCC >= 8.0.0 \
+             (vendored: 6.5.0, 7.5.0)"
+        ),
+        other => panic!("expected no match, got {other:?}"),
+    }
+    assert!(matches!(resolve("absent"), Err(Error::NoMatch { .. })));
+    assert_eq!(
+        board
+            .stage("new")
+            .unwrap()
+            .resolve(&manifest, "aarch64")
+            .unwrap()
+            .host,
+        "aarch64"
+    );
+
+    let err = board.resolve(&manifest, "x86_64").unwrap_err();
+    assert_eq!(err.to_string(), "stage absent");
+    assert!(
+        error_chain(&err).starts_with("stage absent: no vendored arm-eabi toolchain"),
+        "{}",
+        error_chain(&err)
+    );
+    assert!(matches!(board.stage("atf"), Err(Error::UnknownStage(_))));
+    assert!(matches!(boards.get("rpi4"), Err(Error::UnknownBoard(_))));
+    assert!("[board.x]\nsoc = \"h3\"\ncpu = \"a7\"\n"
+        .parse::<Boards>()
+        .is_err());
+}
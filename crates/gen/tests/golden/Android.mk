@@ -0,0 +1,26 @@
+# Generated by `linaro-prebuilts blueprint` from the toolchain manifest. DO NOT EDIT.
+
+LOCAL_PATH := $(call my-dir)
+
+# aarch64-elf: Linaro GCC 7.5.0 (2019.12)
+LINARO_GCC_AARCH64_ELF_7_5_0_ROOT := $(LOCAL_PATH)/x86_64/aarch64-elf/7.5.0
+LINARO_GCC_AARCH64_ELF_7_5_0_CROSS_COMPILE := $(LINARO_GCC_AARCH64_ELF_7_5_0_ROOT)/bin/aarch64-elf-
+LINARO_GCC_AARCH64_ELF_7_5_0_ARCH := arm64
+
+# aarch64-linux-gnu: Linaro GCC 6.5.0 (2018.12)
+LINARO_GCC_AARCH64_LINUX_GNU_6_5_0_ROOT := $(LOCAL_PATH)/x86_64/aarch64-linux-gnu/6.5.0
+LINARO_GCC_AARCH64_LINUX_GNU_6_5_0_CROSS_COMPILE := $(LINARO_GCC_AARCH64_LINUX_GNU_6_5_0_ROOT)/bin/aarch64-linux-gnu-
+LINARO_GCC_AARCH64_LINUX_GNU_6_5_0_ARCH := arm64
+LINARO_GCC_AARCH64_LINUX_GNU_6_5_0_SYSROOT := $(LINARO_GCC_AARCH64_LINUX_GNU_6_5_0_ROOT)/aarch64-linux-gnu/libc
+
+# aarch64-linux-gnu: Linaro GCC 7.5.0 (2019.12)
+LINARO_GCC_AARCH64_LINUX_GNU_7_5_0_ROOT := $(LOCAL_PATH)/x86_64/aarch64-linux-gnu/7.5.0
+LINARO_GCC_AARCH64_LINUX_GNU_7_5_0_CROSS_COMPILE := $(LINARO_GCC_AARCH64_LINUX_GNU_7_5_0_ROOT)/bin/aarch64-linux-gnu-
+LINARO_GCC_AARCH64_LINUX_GNU_7_5_0_ARCH := arm64
+LINARO_GCC_AARCH64_LINUX_GNU_7_5_0_SYSROOT := $(LINARO_GCC_AARCH64_LINUX_GNU_7_5_0_ROOT)/aarch64-linux-gnu/libc
+
+# arm-linux-gnueabihf: Linaro GCC 7.5.0 (2019.12)
+LINARO_GCC_ARM_LINUX_GNUEABIHF_7_5_0_ROOT := $(LOCAL_PATH)/x86_64/arm-linux-gnueabihf/7.5.0
+LINARO_GCC_ARM_LINUX_GNUEABIHF_7_5_0_CROSS_COMPILE := $(LINARO_GCC_ARM_LINUX_GNUEABIHF_7_5_0_ROOT)/bin/arm-linux-gnueabihf-
+LINARO_GCC_ARM_LINUX_GNUEABIHF_7_5_0_ARCH := arm
+LINARO_GCC_ARM_LINUX_GNUEABIHF_7_5_0_SYSROOT := $(LINARO_GCC_ARM_LINUX_GNUEABIHF_7_5_0_ROOT)/arm-linux-gnueabihf/libc
@@ -0,0 +1,19 @@
+# Generated by `linaro-prebuilts board` from the board table. DO NOT EDIT.
+# pine64-plus (allwinner-a64)
+
+# atf: Linaro GCC 7.5.0 (2019.12) for aarch64-elf
+LINARO_ATF_CROSS_COMPILE := /opt/prebuilts/gcc/x86_64/aarch64-elf/7.5.0/bin/aarch64-elf-
+LINARO_ATF_GCC := 7.5.0
+LINARO_ATF_ARCH := arm64
+
+# kernel: Linaro GCC 7.5.0 (2019.12) for aarch64-linux-gnu
+LINARO_KERNEL_CROSS_COMPILE := /opt/prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0/bin/aarch64-linux-gnu-
+LINARO_KERNEL_GCC := 7.5.0
+LINARO_KERNEL_ARCH := arm64
+LINARO_KERNEL_SYSROOT := /opt/prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0/aarch64-linux-gnu/libc
+
+# u-boot: Linaro GCC 7.5.0 (2019.12) for aarch64-linux-gnu
+LINARO_U_BOOT_CROSS_COMPILE := /opt/prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0/bin/aarch64-linux-gnu-
+LINARO_U_BOOT_GCC := 7.5.0
+LINARO_U_BOOT_ARCH := arm64
+LINARO_U_BOOT_SYSROOT := /opt/prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0/aarch64-linux-gnu/libc
@@ -0,0 +1,3 @@
+set -gx CROSS_COMPILE '/opt/prebuilts/gcc/x86_64/aarch64-elf/7.5.0/bin/aarch64-elf-'
+set -gx ARCH 'aarch64'
+set -gx PATH '/opt/prebuilts/gcc/x86_64/aarch64-elf/7.5.0/bin' $PATH
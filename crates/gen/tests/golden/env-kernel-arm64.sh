@@ -0,0 +1,3 @@
+export CROSS_COMPILE='/opt/prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0/bin/aarch64-linux-gnu-'
+export ARCH='arm64'
+export PATH='/opt/prebuilts/gcc/x86_64/aarch64-linux-gnu/7.5.0/bin':"$PATH"
@@ -0,0 +1,88 @@
+//! Path patterns, as used by the prune and hardening policies.
+//!
+//! A [`Pattern`] is a `/`-separated path relative to some root, in which
+//! `*` matches any run of characters and `?` any single one within a
+//! component, and a `**` component matches any number of components. A
+//! pattern matching a directory covers everything below it.
+
+/// An invalid pattern.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid pattern `{pattern}`: {reason}")]
+pub struct Error {
+    pub pattern: String,
+    pub reason: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern(Vec<String>);
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        let invalid = |reason| Error {
+            pattern: pattern.to_owned(),
+            reason,
+        };
+        if pattern.starts_with('/') {
+            return Err(invalid("patterns are relative paths"));
+        }
+        let components: Vec<_> = pattern
+            .trim_end_matches('/')
+            .split('/')
+            .map(str::to_owned)
+            .collect();
+        if components
+            .iter()
+            .any(|c| c.is_empty() || c == "." || c == "..")
+        {
+            return Err(invalid("empty, `.` and `..` components are not allowed"));
+        }
+        if components.iter().any(|c| c.contains("**") && c != "**") {
+            return Err(invalid("`**` must be a whole component"));
+        }
+        Ok(Self(components))
+    }
+
+    /// The number of path components in the pattern.
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the pattern matches `path`, split into its components, or
+    /// one of its ancestors.
+    pub fn covers(&self, path: &[&str]) -> bool {
+        (1..=path.len()).any(|len| matches(&self.0, &path[..len]))
+    }
+}
+
+fn matches(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| matches(rest, &path[skip..]))
+        }
+        Some((first, rest)) => path
+            .split_first()
+            .is_some_and(|(name, tail)| wildcard_match(first, name) && matches(rest, tail)),
+    }
+}
+
+/// Matches the file name `name` against `pattern`, where `*` stands for
+/// any run of characters and `?` for any single one.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let mut chars = pattern.chars();
+    match chars.next() {
+        None => name.is_empty(),
+        Some('*') => {
+            let rest = chars.as_str();
+            (0..=name.len())
+                .filter(|&i| name.is_char_boundary(i))
+                .any(|i| wildcard_match(rest, &name[i..]))
+        }
+        Some(c) => {
+            let mut names = name.chars();
+            names.next().is_some_and(|n| {
+                (c == '?' || c == n) && wildcard_match(chars.as_str(), names.as_str())
+            })
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use linaro_glob::{wildcard_match, Pattern};
+
+fn covers(pattern: &str, path: &str) -> bool {
+    let components: Vec<_> = path.split('/').collect();
+    Pattern::new(pattern).unwrap().covers(&components)
+}
+
+#[test]
+fn matches_paths() {
+    assert!(covers("share/doc", "share/doc"));
+    assert!(covers("share/doc", "share/doc/gcc/README"));
+    assert!(!covers("share/doc", "share"));
+    assert!(covers(
+        "libexec/gcc/*/*/f951",
+        "libexec/gcc/x86_64-linux-gnu/7.5.0/f951"
+    ));
+    assert!(!covers(
+        "libexec/gcc/*/f951",
+        "libexec/gcc/x86_64-linux-gnu/7.5.0/f951"
+    ));
+    assert!(covers("**/cc1plus", "cc1plus"));
+    assert!(covers("**/cc1plus", "libexec/gcc/x/7.5.0/cc1plus"));
+    assert!(covers("vendor/**/bin", "vendor/firmware/a/b/bin/flash"));
+    assert!(covers("vendor/bin/", "vendor/bin/tool"));
+    assert_eq!(Pattern::new("vendor/bin/hw/*").unwrap().depth(), 4);
+
+    assert!(wildcard_match("libc.so.?", "libc.so.6"));
+    assert!(wildcard_match("*.conf", "x86_64-linux-gnu.conf"));
+    assert!(!wildcard_match("*.conf", "README"));
+    assert!(wildcard_match("ü*", "über"));
+}
+
+#[test]
+fn rejects_invalid_patterns() {
+    for (pattern, reason) in [
+        ("/usr/share", "patterns are relative paths"),
+        (
+            "share/../bin",
+            "empty, `.` and `..` components are not allowed",
+        ),
+        (
+            "share//doc",
+            "empty, `.` and `..` components are not allowed",
+        ),
+        ("share/a**", "`**` must be a whole component"),
+    ] {
+        assert_eq!(
+            Pattern::new(pattern).unwrap_err().reason,
+            reason,
+            "{pattern}"
+        );
+    }
+}
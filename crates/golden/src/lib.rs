@@ -0,0 +1,39 @@
+//! Golden-file comparison shared by the tests of the crates whose output is
+//! checked against files under their `tests/golden` directory.
+//!
+//! Setting `UPDATE_GOLDEN` rewrites the golden files with the actual output
+//! instead of comparing against them.
+
+use std::fs;
+use std::path::Path;
+
+/// Compares `actual` with the golden file `name` in the calling crate's
+/// `tests/golden` directory, rewriting it instead when `UPDATE_GOLDEN` is
+/// set.
+#[macro_export]
+macro_rules! check_golden {
+    ($name:expr, $actual:expr $(,)?) => {
+        $crate::check(
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/golden")
+                .join($name),
+            $actual,
+        )
+    };
+}
+
+/// Compares `actual` with the golden file at `path`, rewriting it instead
+/// when `UPDATE_GOLDEN` is set.
+pub fn check(path: &Path, actual: &str) {
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(path).unwrap();
+    assert_eq!(
+        actual,
+        expected,
+        "{} differs; rerun with UPDATE_GOLDEN=1 to update",
+        path.display()
+    );
+}
@@ -0,0 +1,35 @@
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{}", path.display())]
+    Elf {
+        path: PathBuf,
+        source: linaro_elf::Error,
+    },
+    #[error("malformed policy")]
+    Policy(#[from] toml::de::Error),
+    #[error("policy has no component `{0}`")]
+    UnknownComponent(String),
+    #[error(transparent)]
+    Pattern(#[from] linaro_glob::Error),
+    #[error(
+        "the [default] requirements cannot have `paths`; they apply to files no component claims"
+    )]
+    DefaultPaths,
+    #[error("{} is claimed equally by components {}", path.display(), components.join(", "))]
+    Overlap {
+        path: PathBuf,
+        components: Vec<String>,
+    },
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
@@ -0,0 +1,143 @@
+//! Hardening audits of the ELF files the Linaro toolchains produce, such as
+//! GloDroid's userspace helpers.
+//!
+//! [`audit`] reads the exploit mitigations of every ELF file below a
+//! directory (see [`linaro_elf::hardening`]) and holds each file to the
+//! requirements of the component it belongs to, as set out in a
+//! [`Policy`] file.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use linaro_elf::hardening::{hardening, Hardening};
+use serde::Serialize;
+
+mod error;
+pub mod policy;
+
+pub use error::Error;
+pub use policy::{Policy, Rules};
+
+/// A mitigation a policy can require.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Check {
+    Relro,
+    Pie,
+    StackProtector,
+    Fortify,
+    NxStack,
+    Bti,
+    Pac,
+}
+
+impl Check {
+    /// The name used in policy files and reports, e.g. `stack-protector`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Relro => "relro",
+            Self::Pie => "pie",
+            Self::StackProtector => "stack-protector",
+            Self::Fortify => "fortify",
+            Self::NxStack => "nx-stack",
+            Self::Bti => "bti",
+            Self::Pac => "pac",
+        }
+    }
+}
+
+/// A requirement a file does not meet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    pub check: Check,
+    pub reason: String,
+}
+
+/// The audit of one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Audit {
+    /// Relative to the scanned directory, or as given for a single file.
+    pub path: PathBuf,
+    /// The component whose requirements applied; `None` for the defaults.
+    pub component: Option<String>,
+    #[serde(flatten)]
+    pub hardening: Hardening,
+    pub violations: Vec<Violation>,
+}
+
+impl Audit {
+    pub fn ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Audits `path`, an ELF file or a directory searched for them, against
+/// `policy`. Files are assigned to components by their paths, unless
+/// `component` names the one they all belong to. Symlinks and files that
+/// are not ELF are skipped; audits are sorted by path.
+pub fn audit(path: &Path, policy: &Policy, component: Option<&str>) -> Result<Vec<Audit>, Error> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        collect_elf_files(path, path, &mut files)?;
+        files.sort();
+    } else {
+        files.push(path.to_owned());
+    }
+    let mut audits = Vec::new();
+    for shown in files {
+        let full = if path.is_dir() {
+            path.join(&shown)
+        } else {
+            shown.clone()
+        };
+        let bytes = fs::read(&full).map_err(Error::io(&full))?;
+        let hardening = hardening(&bytes).map_err(|source| Error::Elf {
+            path: full.clone(),
+            source,
+        })?;
+        let component = match component {
+            Some(name) => Some(name),
+            None => policy.component_of(&slash_path(&shown))?,
+        };
+        let violations = policy.rules(component)?.check(&hardening);
+        audits.push(Audit {
+            path: shown,
+            component: component.map(str::to_owned),
+            hardening,
+            violations,
+        });
+    }
+    Ok(audits)
+}
+
+/// Appends the ELF files below `dir`, relative to `root`, to `out`.
+fn collect_elf_files(root: &Path, dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(dir).map_err(Error::io(dir))? {
+        let entry = entry.map_err(Error::io(dir))?;
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(Error::io(&path))?;
+        if file_type.is_dir() {
+            collect_elf_files(root, &path, out)?;
+        } else if file_type.is_file() && is_elf(&path)? {
+            out.push(path.strip_prefix(root).unwrap_or(&path).to_owned());
+        }
+    }
+    Ok(())
+}
+
+fn is_elf(path: &Path) -> Result<bool, Error> {
+    let mut magic = [0; 4];
+    let mut file = fs::File::open(path).map_err(Error::io(path))?;
+    Ok(file.read_exact(&mut magic).is_ok() && magic == *b"\x7fELF")
+}
+
+/// `path` with `/` separators and without a leading `./`, for matching
+/// against component patterns.
+fn slash_path(path: &Path) -> String {
+    let path = path.strip_prefix(".").unwrap_or(path);
+    path.iter()
+        .map(|c| c.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,213 @@
+//! Policy files: the mitigations each component must be built with.
+//!
+//! ```toml
+//! [default]
+//! relro = "full"
+//! nx-stack = true
+//!
+//! [component.camera-helpers]
+//! paths = ["vendor/bin/hw/android.hardware.camera*"]
+//! pie = true
+//! stack-protector = true
+//! bti = true
+//! ```
+//!
+//! A component's `paths` are [`linaro_glob`] patterns relative to the
+//! scanned directory. A file claimed by several components belongs to the
+//! one with the longest matching pattern. Files are held to their
+//! component's requirements on top of the default ones; a component may
+//! waive a default one by setting it to `false`. Files no component claims
+//! get the default requirements.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use linaro_elf::hardening::{Hardening, Relro, Stack};
+use linaro_elf::EM_AARCH64;
+use linaro_glob::Pattern;
+use serde::{Deserialize, Serialize};
+
+use crate::{Check, Error, Violation};
+
+/// The contents of a policy file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// Requirements for every file.
+    #[serde(default)]
+    pub default: Rules,
+    #[serde(rename = "component", default)]
+    pub components: BTreeMap<String, Rules>,
+}
+
+/// The files of a component and the mitigations they need. Unset
+/// requirements are not checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Rules {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// The least RELRO linked files must have.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relro: Option<Relro>,
+    /// Executables must be position-independent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pie: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack_protector: Option<bool>,
+    /// Files calling functions `_FORTIFY_SOURCE` checks must call at least
+    /// one checked variant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fortify: Option<bool>,
+    /// The stack must be marked non-executable and no segment may be both
+    /// writable and executable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nx_stack: Option<bool>,
+    /// AArch64 files must be marked BTI-compatible.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bti: Option<bool>,
+    /// AArch64 files must be marked as signing return addresses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pac: Option<bool>,
+}
+
+impl Policy {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        fs::read_to_string(path).map_err(Error::io(path))?.parse()
+    }
+
+    /// The component claiming `path` (relative to the scanned directory,
+    /// `/`-separated), if any. When several do, the one with the longest
+    /// matching pattern, in path components, wins.
+    pub fn component_of(&self, path: &str) -> Result<Option<&str>, Error> {
+        let components: Vec<_> = path.split('/').collect();
+        let mut claimed = Vec::new();
+        for (name, rules) in &self.components {
+            let mut longest = None;
+            for pattern in &rules.paths {
+                let pattern = Pattern::new(pattern)?;
+                if pattern.covers(&components) {
+                    longest = longest.max(Some(pattern.depth()));
+                }
+            }
+            if let Some(len) = longest {
+                claimed.push((len, name.as_str()));
+            }
+        }
+        let Some(&(best, name)) = claimed.iter().max_by_key(|(len, _)| *len) else {
+            return Ok(None);
+        };
+        let tied: Vec<_> = claimed.iter().filter(|(len, _)| *len == best).collect();
+        if tied.len() > 1 {
+            return Err(Error::Overlap {
+                path: path.into(),
+                components: tied.iter().map(|(_, name)| name.to_string()).collect(),
+            });
+        }
+        Ok(Some(name))
+    }
+
+    /// The requirements for files of `component`, or for unclaimed files.
+    pub fn rules(&self, component: Option<&str>) -> Result<Rules, Error> {
+        let Some(name) = component else {
+            return Ok(self.default.clone());
+        };
+        let own = self
+            .components
+            .get(name)
+            .ok_or_else(|| Error::UnknownComponent(name.to_owned()))?;
+        let base = &self.default;
+        Ok(Rules {
+            paths: own.paths.clone(),
+            relro: own.relro.or(base.relro),
+            pie: own.pie.or(base.pie),
+            stack_protector: own.stack_protector.or(base.stack_protector),
+            fortify: own.fortify.or(base.fortify),
+            nx_stack: own.nx_stack.or(base.nx_stack),
+            bti: own.bti.or(base.bti),
+            pac: own.pac.or(base.pac),
+        })
+    }
+}
+
+impl FromStr for Policy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let policy: Self = toml::from_str(s)?;
+        if !policy.default.paths.is_empty() {
+            return Err(Error::DefaultPaths);
+        }
+        for pattern in policy.components.values().flat_map(|r| &r.paths) {
+            Pattern::new(pattern)?;
+        }
+        Ok(policy)
+    }
+}
+
+impl Rules {
+    /// The requirements `hardening` falls short of. Requirements that do
+    /// not apply to the file, such as PIE for a shared library or BTI off
+    /// AArch64, are skipped.
+    pub fn check(&self, hardening: &Hardening) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut fail = |check, reason: String| violations.push(Violation { check, reason });
+        if let (Some(required), Some(relro)) = (self.relro, hardening.relro) {
+            if relro < required {
+                fail(Check::Relro, format!("{relro} RELRO, {required} required"));
+            }
+        }
+        if self.pie == Some(true) && hardening.pie == Some(false) {
+            fail(
+                Check::Pie,
+                "not a position-independent executable".to_owned(),
+            );
+        }
+        if self.stack_protector == Some(true) && !hardening.stack_protector {
+            fail(Check::StackProtector, "no stack protector".to_owned());
+        }
+        if self.fortify == Some(true)
+            && hardening.fortified.is_empty()
+            && !hardening.unfortified.is_empty()
+        {
+            fail(
+                Check::Fortify,
+                format!(
+                    "calls {} without FORTIFY_SOURCE checks",
+                    hardening.unfortified.join(", ")
+                ),
+            );
+        }
+        if self.nx_stack == Some(true) {
+            match hardening.stack {
+                Stack::NonExecutable => {}
+                Stack::Executable => fail(Check::NxStack, "executable stack".to_owned()),
+                Stack::Unmarked => fail(
+                    Check::NxStack,
+                    "no GNU-stack marking, the stack may be executable".to_owned(),
+                ),
+            }
+            if hardening.rwx_segments {
+                fail(
+                    Check::NxStack,
+                    "a segment is both writable and executable".to_owned(),
+                );
+            }
+        }
+        if hardening.machine == EM_AARCH64 {
+            if self.bti == Some(true) && !hardening.bti {
+                fail(Check::Bti, "not marked BTI-compatible".to_owned());
+            }
+            if self.pac == Some(true) && !hardening.pac {
+                fail(
+                    Check::Pac,
+                    "not marked as signing return addresses".to_owned(),
+                );
+            }
+        }
+        violations
+    }
+}
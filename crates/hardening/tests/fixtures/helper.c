@@ -0,0 +1,13 @@
+/* Stand-in for a GloDroid userspace helper: reads into a stack buffer
+ * and formats into another, both fortifiable. */
+#include <stdio.h>
+#include <unistd.h>
+
+int main(int argc, char **argv)
+{
+	char in[16], out[16];
+	ssize_t n = read(0, in, (size_t)argc * 4);
+
+	snprintf(out, (size_t)argc * 8, "%s", argv[0]);
+	return n > 0 && in[0] == out[0];
+}
@@ -0,0 +1,13 @@
+/* A GNU property note marking an object BTI- and PAC-compatible, as GCC
+ * emits for -mbranch-protection=standard on AArch64. */
+	.section .note.gnu.property,"a"
+	.p2align 3
+	.long 4			/* n_namesz */
+	.long 16		/* n_descsz */
+	.long 5			/* NT_GNU_PROPERTY_TYPE_0 */
+	.asciz "GNU"
+	.long 0xc0000000	/* GNU_PROPERTY_AARCH64_FEATURE_1_AND */
+	.long 4
+	.long 3			/* BTI | PAC */
+	.long 0
+	.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,261 @@
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use linaro_elf::hardening::{hardening, Relro, Stack};
+use linaro_hardening::{audit, Check, Error, Policy};
+
+/// Builds the fixture `source` into `out` with the host GCC; `false` if
+/// there is none.
+fn gcc(args: &[&str], out: &Path, source: &str) -> bool {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(source);
+    fs::create_dir_all(out.parent().unwrap()).unwrap();
+    Command::new("gcc")
+        .args(args)
+        .arg("-o")
+        .arg(out)
+        .arg(source)
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+const HARDENED: &[&str] = &[
+    "-O2",
+    "-fPIE",
+    "-pie",
+    "-fstack-protector-all",
+    "-D_FORTIFY_SOURCE=2",
+    "-Wl,-z,relro,-z,now",
+];
+
+const WEAK: &[&str] = &[
+    "-O0",
+    "-no-pie",
+    "-fno-stack-protector",
+    "-U_FORTIFY_SOURCE",
+    "-Wl,-z,norelro,-z,execstack",
+];
+
+/// A product output tree with a hardened HAL helper, a vendor tool built
+/// without any mitigations, a shared library, and a script and symlink
+/// that are not audited. `None` without a host GCC.
+fn product(dir: &Path) -> Option<PathBuf> {
+    let root = dir.join("product");
+    let library = [
+        "-shared",
+        "-fPIC",
+        "-fstack-protector-all",
+        "-Wl,-z,relro,-z,now",
+    ];
+    let built = gcc(
+        HARDENED,
+        &root.join("vendor/bin/hw/android.hardware.lights-service.glodroid"),
+        "helper.c",
+    ) && gcc(WEAK, &root.join("vendor/bin/gd_legacy_tool"), "helper.c")
+        && gcc(
+            &library,
+            &root.join("vendor/lib64/libgd_helper.so"),
+            "helper.c",
+        );
+    if !built {
+        return None;
+    }
+    fs::write(root.join("vendor/bin/init.gd.sh"), "#!/bin/sh\n").unwrap();
+    symlink("gd_legacy_tool", root.join("vendor/bin/gd_tool")).unwrap();
+    Some(root)
+}
+
+fn policy() -> Policy {
+    Policy::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("../../hardening.toml")).unwrap()
+}
+
+#[test]
+fn audits_a_product_tree() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(root) = product(dir.path()) else {
+        eprintln!("skipping: needs gcc");
+        return;
+    };
+    let audits = audit(&root, &policy(), None).unwrap();
+    let paths: Vec<_> = audits.iter().map(|a| a.path.as_path()).collect();
+    assert_eq!(
+        paths,
+        [
+            Path::new("vendor/bin/gd_legacy_tool"),
+            Path::new("vendor/bin/hw/android.hardware.lights-service.glodroid"),
+            Path::new("vendor/lib64/libgd_helper.so"),
+        ]
+    );
+
+    let weak = &audits[0];
+    assert_eq!(weak.component.as_deref(), Some("vendor-tools"));
+    assert_eq!(weak.hardening.relro, Some(Relro::No));
+    assert_eq!(weak.hardening.pie, Some(false));
+    assert!(!weak.hardening.stack_protector);
+    assert_eq!(weak.hardening.unfortified, ["read", "snprintf"]);
+    assert_eq!(weak.hardening.stack, Stack::Executable);
+    let checks: Vec<_> = weak.violations.iter().map(|v| v.check).collect();
+    assert_eq!(
+        checks,
+        [
+            Check::Relro,
+            Check::Pie,
+            Check::StackProtector,
+            Check::Fortify,
+            Check::NxStack,
+        ]
+    );
+    assert_eq!(weak.violations[0].reason, "no RELRO, full required");
+    assert_eq!(
+        weak.violations[3].reason,
+        "calls read, snprintf without FORTIFY_SOURCE checks"
+    );
+
+    let helper = &audits[1];
+    assert_eq!(helper.component.as_deref(), Some("hal-helpers"));
+    assert_eq!(helper.hardening.relro, Some(Relro::Full));
+    assert_eq!(helper.hardening.pie, Some(true));
+    assert!(helper.hardening.stack_protector);
+    assert_eq!(helper.hardening.fortified, ["__read_chk", "__snprintf_chk"]);
+    assert_eq!(helper.hardening.stack, Stack::NonExecutable);
+    // BTI and PAC are only required of AArch64 files.
+    assert!(helper.ok(), "{:?}", helper.violations);
+
+    let library = &audits[2];
+    assert_eq!(library.component.as_deref(), Some("vendor-libraries"));
+    assert_eq!(library.hardening.pie, None);
+    assert!(library.ok(), "{:?}", library.violations);
+}
+
+#[test]
+fn holds_files_to_a_named_component() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(root) = product(dir.path()) else {
+        eprintln!("skipping: needs gcc");
+        return;
+    };
+    let file = root.join("vendor/lib64/libgd_helper.so");
+    let audits = audit(&file, &policy(), Some("hal-helpers")).unwrap();
+    assert_eq!(audits.len(), 1);
+    assert_eq!(audits[0].path, file);
+    // The library was built without _FORTIFY_SOURCE; `pie` does not apply
+    // to it.
+    let checks: Vec<_> = audits[0].violations.iter().map(|v| v.check).collect();
+    assert_eq!(checks, [Check::Fortify]);
+
+    let relocatable = dir.path().join("helper.o");
+    assert!(gcc(
+        &["-c", "-fno-stack-protector"],
+        &relocatable,
+        "helper.c"
+    ));
+    let audits = audit(&relocatable, &policy(), None).unwrap();
+    assert_eq!(audits[0].hardening.relro, None);
+    assert_eq!(audits[0].hardening.stack, Stack::NonExecutable);
+
+    assert!(matches!(
+        audit(&file, &policy(), Some("bootloader")),
+        Err(Error::UnknownComponent(name)) if name == "bootloader"
+    ));
+}
+
+#[test]
+fn reads_aarch64_branch_protection() {
+    let dir = tempfile::tempdir().unwrap();
+    let object = dir.path().join("property.o");
+    if !gcc(&["-c"], &object, "property.s") {
+        eprintln!("skipping: needs gcc");
+        return;
+    }
+    // Assembled for the host; the note reads the same once the header
+    // claims AArch64 (little-endian e_machine at offset 18).
+    let mut bytes = fs::read(&object).unwrap();
+    let host = hardening(&bytes).unwrap();
+    assert!(!host.bti && !host.pac);
+    bytes[18..20].copy_from_slice(&linaro_elf::EM_AARCH64.to_le_bytes());
+    let aarch64 = hardening(&bytes).unwrap();
+    assert!(aarch64.bti && aarch64.pac);
+
+    let strict: Policy = "[default]\nbti = true\npac = true\n".parse().unwrap();
+    assert!(strict.default.check(&aarch64).is_empty());
+    let checks: Vec<_> = strict
+        .default
+        .check(&host)
+        .into_iter()
+        .map(|v| v.check)
+        .collect();
+    assert!(checks.is_empty(), "{checks:?}");
+    let mut unprotected = aarch64.clone();
+    unprotected.pac = false;
+    let violations = strict.default.check(&unprotected);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].check, Check::Pac);
+}
+
+#[test]
+fn parses_policies() {
+    let policy = policy();
+    assert_eq!(
+        policy.component_of("vendor/bin/hw/foo").unwrap(),
+        Some("hal-helpers")
+    );
+    assert_eq!(
+        policy.component_of("vendor/bin/foo").unwrap(),
+        Some("vendor-tools")
+    );
+    assert_eq!(policy.component_of("system/bin/foo").unwrap(), None);
+    let library = policy.rules(Some("vendor-libraries")).unwrap();
+    assert_eq!(library.relro, Some(Relro::Full));
+    assert_eq!(library.pie, None);
+
+    let waived: Policy = r#"
+        [default]
+        stack-protector = true
+
+        [component.firmware-tools]
+        paths = ["vendor/firmware/**/bin"]
+        stack-protector = false
+    "#
+    .parse()
+    .unwrap();
+    assert_eq!(
+        waived
+            .component_of("vendor/firmware/a/b/bin/flash")
+            .unwrap(),
+        Some("firmware-tools")
+    );
+    let rules = waived.rules(Some("firmware-tools")).unwrap();
+    assert_eq!(rules.stack_protector, Some(false));
+
+    let overlapping: Policy = r#"
+        [component.a]
+        paths = ["vendor/bin"]
+        [component.b]
+        paths = ["vendor/*"]
+    "#
+    .parse()
+    .unwrap();
+    assert!(matches!(
+        overlapping.component_of("vendor/bin/tool"),
+        Err(Error::Overlap { components, .. }) if components == ["a", "b"]
+    ));
+
+    for (invalid, expected) in [
+        ("[default]\npaths = [\"vendor\"]\n", "DefaultPaths"),
+        ("[default]\nrelro = \"some\"\n", "Policy"),
+        ("[default]\nstack-protektor = true\n", "Policy"),
+        ("[component.x]\npaths = [\"/vendor\"]\n", "Pattern"),
+    ] {
+        let err = invalid.parse::<Policy>().unwrap_err();
+        let kind = match err {
+            Error::DefaultPaths => "DefaultPaths",
+            Error::Policy(_) => "Policy",
+            Error::Pattern { .. } => "Pattern",
+            other => panic!("{invalid}: {other}"),
+        };
+        assert_eq!(kind, expected, "{invalid}");
+    }
+}
@@ -0,0 +1,37 @@
+use std::io;
+use std::path::PathBuf;
+
+/// Errors produced while loading, validating or writing a manifest.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to read {}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("failed to write {}", path.display())]
+    Write { path: PathBuf, source: io::Error },
+    #[error("malformed manifest")]
+    Parse(#[from] toml::de::Error),
+    #[error("failed to serialize manifest")]
+    Serialize(#[from] toml::ser::Error),
+    #[error("unsupported manifest schema {found} (expected {expected})")]
+    Schema { found: u32, expected: u32 },
+    #[error("toolchain {archive}: {reason}")]
+    Invalid { archive: String, reason: String },
+    #[error("archive {0} is listed more than once")]
+    DuplicateArchive(String),
+    #[error("toolchain {target} gcc {gcc} for host {host} is listed more than once")]
+    DuplicateToolchain {
+        target: String,
+        gcc: String,
+        host: String,
+    },
+}
+
+/// `err` followed by each of its sources, separated by `: `, as `anyhow`
+/// prints an error with `{:#}`. The crates' error messages leave their
+/// sources out, so this is how to show one in full.
+pub fn error_chain(err: &dyn std::error::Error) -> String {
+    std::iter::successors(Some(err), |err| err.source())
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(": ")
+}
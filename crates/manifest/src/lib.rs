@@ -0,0 +1,35 @@
+//! Manifest format for the Linaro GCC prebuilts vendored in this repository.
+//!
+//! The manifest is a TOML file listing every toolchain archive the repository
+//! carries, together with the Linaro release it was taken from and the
+//! size and SHA-256 of the archive:
+//!
+//! ```toml
+//! schema = 1
+//!
+//! [[toolchain]]
+//! gcc = "7.5.0"
+//! release = "2019.12"
+//! target = "aarch64-linux-gnu"
+//! host = "x86_64"
+//! archive = "gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz"
+//! size = 123456789
+//! sha256 = "<64 hex digits>"
+//! ```
+//!
+//! Parsing always validates, so a [`Manifest`] value obtained from
+//! [`Manifest::load`] or [`str::parse`] is known to be consistent.
+
+mod digest;
+mod error;
+mod manifest;
+mod size;
+mod triple;
+mod version;
+
+pub use digest::{sha256_file, sha256_reader, Sha256};
+pub use error::{error_chain, Error};
+pub use manifest::{Manifest, Toolchain, SCHEMA_VERSION};
+pub use size::parse_size;
+pub use triple::Triple;
+pub use version::{GccVersion, Release};
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, GccVersion, Release, Sha256, Triple};
+
+/// The manifest schema understood by this crate.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The full list of vendored toolchains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub schema: u32,
+    #[serde(rename = "toolchain", default)]
+    pub toolchains: Vec<Toolchain>,
+}
+
+/// One prebuilt toolchain archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Toolchain {
+    /// GCC version, e.g. `7.5.0`.
+    pub gcc: GccVersion,
+    /// Linaro release tag, e.g. `2019.12`.
+    pub release: Release,
+    /// Target triple, e.g. `aarch64-linux-gnu`.
+    pub target: Triple,
+    /// Host the compiler binaries run on, e.g. `x86_64`.
+    pub host: String,
+    /// File name of the archive as published by Linaro.
+    pub archive: String,
+    /// Size of the archive in bytes.
+    pub size: u64,
+    /// SHA-256 of the archive.
+    pub sha256: Sha256,
+}
+
+impl Manifest {
+    /// Creates an empty manifest of the current schema.
+    pub fn new() -> Self {
+        Self {
+            schema: SCHEMA_VERSION,
+            toolchains: Vec::new(),
+        }
+    }
+
+    /// Reads and validates the manifest at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| Error::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        text.parse()
+    }
+
+    /// Validates the manifest and writes it to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let text = self.to_toml_string()?;
+        fs::write(path, text).map_err(|source| Error::Write {
+            path: path.to_owned(),
+            source,
+        })
+    }
+
+    /// Validates the manifest and serializes it to TOML.
+    pub fn to_toml_string(&self) -> Result<String, Error> {
+        self.validate()?;
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Checks the invariants that cannot be expressed by the field types:
+    /// the schema version, archive naming and uniqueness of entries.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.schema != SCHEMA_VERSION {
+            return Err(Error::Schema {
+                found: self.schema,
+                expected: SCHEMA_VERSION,
+            });
+        }
+        let mut archives = HashSet::new();
+        let mut keys = HashSet::new();
+        for toolchain in &self.toolchains {
+            toolchain.validate()?;
+            if !archives.insert(toolchain.archive.as_str()) {
+                return Err(Error::DuplicateArchive(toolchain.archive.clone()));
+            }
+            if !keys.insert((&toolchain.target, toolchain.gcc, toolchain.host.as_str())) {
+                return Err(Error::DuplicateToolchain {
+                    target: toolchain.target.to_string(),
+                    gcc: toolchain.gcc.to_string(),
+                    host: toolchain.host.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the toolchain for `target` and `gcc` built for `host`.
+    pub fn find(&self, target: &str, gcc: GccVersion, host: &str) -> Option<&Toolchain> {
+        self.toolchains
+            .iter()
+            .find(|t| t.target.as_str() == target && t.gcc == gcc && t.host == host)
+    }
+
+    /// Finds the toolchain whose archive is named `archive`.
+    pub fn find_archive(&self, archive: &str) -> Option<&Toolchain> {
+        self.toolchains.iter().find(|t| t.archive == archive)
+    }
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let manifest: Self = toml::from_str(s)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+}
+
+impl Toolchain {
+    /// The archive name Linaro uses for this toolchain, e.g.
+    /// `gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz`.
+    pub fn conventional_archive_name(&self) -> String {
+        format!(
+            "gcc-linaro-{}-{}-{}_{}.tar.xz",
+            self.gcc, self.release, self.host, self.target
+        )
+    }
+
+    /// The directory name the archive unpacks to (the archive name without
+    /// its `.tar.xz` extension).
+    pub fn archive_stem(&self) -> &str {
+        self.archive
+            .strip_suffix(".tar.xz")
+            .unwrap_or(&self.archive)
+    }
+
+    /// Where the toolchain lives relative to the `prebuilts/gcc` root:
+    /// `<host>/<triple>/<gcc version>`.
+    pub fn install_path(&self) -> PathBuf {
+        [
+            self.host.as_str(),
+            self.target.as_str(),
+            &self.gcc.to_string(),
+        ]
+        .iter()
+        .collect()
+    }
+
+    /// The glibc sysroot shipped with Linux toolchains, relative to the
+    /// `prebuilts/gcc` root. Bare-metal toolchains have none.
+    pub fn sysroot_path(&self) -> Option<PathBuf> {
+        (!self.target.is_bare_metal())
+            .then(|| self.install_path().join(self.target.as_str()).join("libc"))
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = |reason: String| Error::Invalid {
+            archive: self.archive.clone(),
+            reason,
+        };
+        let host_ok = !self.host.is_empty()
+            && self
+                .host
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-');
+        if !host_ok {
+            return Err(invalid(format!("`{}` is not a host name", self.host)));
+        }
+        let expected = self.conventional_archive_name();
+        if self.archive != expected {
+            return Err(invalid(format!(
+                "archive name does not match its fields (expected {expected})"
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+/// Parses a byte count written in decimal or `0x` hex, optionally followed
+/// by a `K`, `M`, `G` or `T` suffix for powers of 1024: `4096`, `0x8000`,
+/// `28K`, `500M`. `None` if it is malformed or overflows.
+pub fn parse_size(text: &str) -> Option<u64> {
+    let (number, shift) = match text.char_indices().last()? {
+        (i, 'K' | 'k') => (&text[..i], 10),
+        (i, 'M' | 'm') => (&text[..i], 20),
+        (i, 'G' | 'g') => (&text[..i], 30),
+        (i, 'T' | 't') => (&text[..i], 40),
+        _ => (text, 0),
+    };
+    let number = match number.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => number.parse(),
+    }
+    .ok()?;
+    number.checked_mul(1 << shift)
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// A GNU target triple such as `aarch64-linux-gnu` or `arm-eabi`.
+///
+/// Linaro triples omit the vendor field, so only the architecture is
+/// interpreted; the remainder is kept verbatim.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Triple(String);
+
+impl Triple {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The architecture component, e.g. `aarch64` or `arm`.
+    pub fn arch(&self) -> &str {
+        self.0.split('-').next().unwrap_or_default()
+    }
+
+    /// Everything after the architecture, e.g. `linux-gnueabihf`.
+    pub fn system(&self) -> &str {
+        self.0.split_once('-').map_or("", |(_, rest)| rest)
+    }
+
+    /// Whether this triple targets bare metal (`aarch64-elf`, `arm-eabi`).
+    pub fn is_bare_metal(&self) -> bool {
+        !self.system().split('-').any(|c| c == "linux")
+    }
+
+    /// The `CROSS_COMPILE` prefix for this triple, e.g. `aarch64-linux-gnu-`.
+    pub fn tool_prefix(&self) -> String {
+        format!("{}-", self.0)
+    }
+
+    /// The Linux kernel `ARCH` for this triple, e.g. `arm64` for `aarch64`.
+    pub fn kernel_arch(&self) -> Option<&'static str> {
+        match self.arch() {
+            "aarch64" | "aarch64_be" => Some("arm64"),
+            arch if arch.starts_with("arm") => Some("arm"),
+            "x86_64" | "i386" | "i486" | "i586" | "i686" => Some("x86"),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Triple {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let components: Vec<&str> = s.split('-').collect();
+        let well_formed = (2..=4).contains(&components.len())
+            && components.iter().all(|c| {
+                !c.is_empty()
+                    && c.bytes().all(|b| {
+                        b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'.'
+                    })
+            });
+        if !well_formed {
+            return Err(format!("`{s}` is not a target triple"));
+        }
+        Ok(Self(s.to_owned()))
+    }
+}
+
+impl TryFrom<String> for Triple {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Triple> for String {
+    fn from(triple: Triple) -> Self {
+        triple.0
+    }
+}
+
+impl fmt::Display for Triple {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for Triple {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Triple({})", self.0)
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use linaro_manifest::{parse_size, Error, GccVersion, Manifest};
+
+const FIXTURE: &str = include_str!("fixtures/toolchains.toml");
+
+#[test]
+fn parses_fixture() {
+    let manifest: Manifest = FIXTURE.parse().unwrap();
+    assert_eq!(manifest.toolchains.len(), 3);
+
+    let aarch64 = manifest
+        .find("aarch64-linux-gnu", GccVersion::new(7, 5, 0), "x86_64")
+        .unwrap();
+    assert_eq!(aarch64.release.as_str(), "2019.12");
+    assert_eq!(aarch64.target.arch(), "aarch64");
+    assert_eq!(aarch64.target.tool_prefix(), "aarch64-linux-gnu-");
+    assert!(!aarch64.target.is_bare_metal());
+    assert_eq!(
+        aarch64.archive_stem(),
+        "gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu"
+    );
+    assert_eq!(aarch64.target.kernel_arch(), Some("arm64"));
+    assert_eq!(
+        aarch64.install_path(),
+        Path::new("x86_64/aarch64-linux-gnu/7.5.0")
+    );
+    assert_eq!(
+        aarch64.sysroot_path().unwrap(),
+        Path::new("x86_64/aarch64-linux-gnu/7.5.0/aarch64-linux-gnu/libc")
+    );
+
+    let elf = manifest
+        .find_archive("gcc-linaro-7.5.0-2019.12-x86_64_aarch64-elf.tar.xz")
+        .unwrap();
+    assert!(elf.target.is_bare_metal());
+    assert_eq!(elf.sysroot_path(), None);
+}
+
+#[test]
+fn round_trips_through_toml() {
+    let manifest = Manifest::load(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/toolchains.toml"),
+    )
+    .unwrap();
+    let text = manifest.to_toml_string().unwrap();
+    assert_eq!(text.parse::<Manifest>().unwrap(), manifest);
+}
+
+#[test]
+fn rejects_malformed_fields() {
+    for (from, to) in [
+        ("gcc = \"7.5.0\"", "gcc = \"7.5\""),
+        ("release = \"2019.12\"", "release = \"12.2019\""),
+        ("target = \"aarch64-linux-gnu\"", "target = \"aarch64\""),
+        ("sha256 = \"1111", "sha256 = \"zz11"),
+    ] {
+        let text = FIXTURE.replacen(from, to, 1);
+        assert!(
+            matches!(text.parse::<Manifest>(), Err(Error::Parse(_))),
+            "{to} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn rejects_inconsistent_archive_name() {
+    let text = FIXTURE.replacen(
+        "2019.12-x86_64_aarch64-linux-gnu",
+        "2019.02-x86_64_aarch64-linux-gnu",
+        1,
+    );
+    assert!(matches!(
+        text.parse::<Manifest>(),
+        Err(Error::Invalid { .. })
+    ));
+}
+
+#[test]
+fn rejects_duplicates_and_unknown_schema() {
+    let first = FIXTURE
+        .split("\n\n")
+        .take(2)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let duplicated = format!("{first}\n\n{}", first.split("\n\n").nth(1).unwrap());
+    assert!(matches!(
+        duplicated.parse::<Manifest>(),
+        Err(Error::DuplicateArchive(_))
+    ));
+
+    let text = FIXTURE.replacen("schema = 1", "schema = 2", 1);
+    assert!(matches!(
+        text.parse::<Manifest>(),
+        Err(Error::Schema {
+            found: 2,
+            expected: 1
+        })
+    ));
+}
+
+#[test]
+fn parses_sizes() {
+    assert_eq!(parse_size("4096"), Some(4096));
+    assert_eq!(parse_size("0x8000"), Some(0x8000));
+    assert_eq!(parse_size("28K"), Some(28 << 10));
+    assert_eq!(parse_size("50m"), Some(50 << 20));
+    assert_eq!(parse_size("0"), Some(0));
+    for bad in ["", "K", " 1K", "1.5G", "-1", "12X", "99999999999T"] {
+        assert_eq!(parse_size(bad), None, "{bad}");
+    }
+}
@@ -0,0 +1,136 @@
+//! Which compiler command lines can be cached, and what they read and write.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use linaro_wrap::argv::{is_source, WITH_VALUE};
+
+/// Prefixes of options that read or write files the cache does not track.
+const UNTRACKED: &[&str] = &[
+    "--coverage",
+    "-ftest-coverage",
+    "-fprofile-",
+    "-fauto-profile",
+    "-fbranch-probabilities",
+    "-save-temps",
+    "-gsplit-dwarf",
+    "-fstack-usage",
+    "-fcallgraph-info",
+    "-fdump-",
+    "-aux-info",
+    "-frecord-gcc-switches",
+];
+
+/// A cacheable `-c` compilation of one source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compilation {
+    pub source: PathBuf,
+    pub output: PathBuf,
+    /// The dependency file written by `-MD`/`-MMD`, if any.
+    pub dep_file: Option<PathBuf>,
+    /// The arguments that preprocess the source to standard output.
+    pub preprocess: Vec<OsString>,
+    /// Whether debug information, which records the working directory, is
+    /// generated.
+    pub debug: bool,
+}
+
+impl Compilation {
+    /// Classifies the compiler arguments `args`; `Err` says why they cannot
+    /// be cached.
+    pub fn parse(args: &[OsString]) -> Result<Self, String> {
+        let mut compile = false;
+        let mut output = None;
+        let mut deps = false;
+        let mut dep_file = None;
+        let mut language = false;
+        let mut sources = Vec::new();
+        let mut debug = false;
+        let mut preprocess = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let arg = arg
+                .to_str()
+                .ok_or_else(|| format!("non-UTF-8 argument {arg:?}"))?;
+            let mut value = || {
+                iter.next()
+                    .and_then(|v| v.to_str())
+                    .map(str::to_owned)
+                    .ok_or_else(|| format!("{arg} needs a value"))
+            };
+            match arg {
+                "-c" => {
+                    compile = true;
+                    preprocess.push("-E".into());
+                }
+                "-E" | "-S" | "-M" | "-MM" => {
+                    return Err(format!("{arg} does not produce an object"))
+                }
+                "-" => return Err("source read from standard input".to_owned()),
+                "-o" => output = Some(value()?),
+                "-MD" | "-MMD" => deps = true,
+                "-MF" => dep_file = Some(value()?),
+                "-MT" | "-MQ" => {
+                    value()?;
+                }
+                "-MP" => {}
+                _ if arg.starts_with("-Wp,-MD,") || arg.starts_with("-Wp,-MMD,") => {
+                    deps = true;
+                    dep_file = arg.splitn(3, ',').nth(2).map(str::to_owned);
+                }
+                _ if arg.starts_with('@') => return Err(format!("response file {arg}")),
+                _ if UNTRACKED.iter().any(|p| arg.starts_with(p)) => {
+                    return Err(format!("{arg} uses files the cache does not track"))
+                }
+                _ if WITH_VALUE.contains(&arg) => {
+                    let value = value()?;
+                    if arg == "-x" {
+                        language = value != "none";
+                    }
+                    preprocess.extend([arg.into(), value.into()]);
+                }
+                _ if arg.starts_with("-o") => output = Some(arg[2..].to_owned()),
+                _ if arg.starts_with('-') => {
+                    if let Some(value) = arg.strip_prefix("-x") {
+                        language = value != "none";
+                    }
+                    if arg.starts_with("-g") {
+                        debug = arg != "-g0";
+                    }
+                    preprocess.push(arg.into());
+                }
+                _ if language || is_source(arg) => {
+                    sources.push(PathBuf::from(arg));
+                    preprocess.push(arg.into());
+                }
+                _ => return Err(format!("{arg} is not a source file (linking?)")),
+            }
+        }
+
+        if !compile {
+            return Err("no -c".to_owned());
+        }
+        let source = match <[PathBuf; 1]>::try_from(sources) {
+            Ok([source]) => source,
+            Err(sources) => return Err(format!("{} source files", sources.len())),
+        };
+        let output = match output {
+            Some(output) if output == "-" => return Err("object written to standard output".into()),
+            Some(output) => PathBuf::from(output),
+            None => Path::new(source.file_stem().unwrap_or_default()).with_extension("o"),
+        };
+        let dep_file = match (deps, dep_file) {
+            (false, _) => None,
+            (true, Some(file)) => Some(PathBuf::from(file)),
+            (true, None) => Some(output.with_extension("d")),
+        };
+        Ok(Self {
+            source,
+            output,
+            dep_file,
+            preprocess,
+            debug,
+        })
+    }
+}
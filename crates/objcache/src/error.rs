@@ -0,0 +1,27 @@
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error(
+        "invalid size `{0}` (expected a number of bytes with an optional K, M, G or T suffix)"
+    )]
+    BadSize(String),
+    #[error("{0} not found on PATH")]
+    NoCompiler(String),
+    #[error("`{program} {args}` failed: {reason}", args = args.join(" "))]
+    Probe {
+        program: String,
+        args: Vec<String>,
+        reason: String,
+    },
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
@@ -0,0 +1,295 @@
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+use filetime::FileTime;
+use sha2::{Digest, Sha256};
+
+use crate::{Cache, Compilation, Error, COMPILERS, TMP};
+
+/// Bumped when the key or entry layout changes.
+const KEY_VERSION: &str = "linaro-objcache 1";
+
+/// Variables that change what the compiler does but not its arguments.
+const KEY_ENV: &[&str] = &["GCC_EXEC_PREFIX", "COMPILER_PATH", "SOURCE_DATE_EPOCH"];
+
+/// How a compilation was served.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Hit,
+    Miss,
+    /// Run without the cache, for the given reason.
+    Uncacheable(String),
+}
+
+/// The result of [`Launcher::run`].
+#[derive(Debug)]
+pub struct Run {
+    /// The exit status to report: the compiler's, or 128 plus the signal
+    /// that killed it.
+    pub code: i32,
+    pub outcome: Outcome,
+    /// A cache failure that did not stop the compilation.
+    pub warning: Option<Error>,
+}
+
+/// Runs compilations through a [`Cache`].
+#[derive(Debug, Clone)]
+pub struct Launcher {
+    cache: Cache,
+}
+
+impl Launcher {
+    pub fn new(cache: Cache) -> Self {
+        Self { cache }
+    }
+
+    /// Runs `compiler` with `args`, from the cache when possible. Errors
+    /// are returned only before the compiler has run, so the caller can
+    /// still run it directly.
+    pub fn run(&self, compiler: &OsStr, args: &[OsString]) -> Result<Run, Error> {
+        let compilation = match Compilation::parse(args) {
+            Ok(compilation) => compilation,
+            Err(reason) => return self.uncached(compiler, args, reason),
+        };
+        let identity = self.identity(compiler)?;
+        let preprocessed = Command::new(compiler)
+            .args(&compilation.preprocess)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(Error::io(compiler))?;
+        if !preprocessed.status.success() {
+            // The real compilation reports the error.
+            return self.uncached(compiler, args, "preprocessing failed".to_owned());
+        }
+        let key = key(&identity, args, &compilation, &preprocessed.stdout);
+        let dir = self.cache.entry_dir(&key);
+
+        let mut warning = None;
+        if dir.is_dir() {
+            match restore(&dir, &compilation) {
+                Ok(()) => {
+                    let now = FileTime::now();
+                    let _ = filetime::set_file_mtime(&dir, now);
+                    return Ok(Run {
+                        code: 0,
+                        outcome: Outcome::Hit,
+                        warning: self.cache.record("hit").err(),
+                    });
+                }
+                Err(err) => warning = Some(err),
+            }
+        }
+
+        let output = Command::new(compiler)
+            .args(args)
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(Error::io(compiler))?;
+        let _ = io::stderr().write_all(&output.stderr);
+        let code = exit_code(output.status);
+        // Failed compilations are not stored.
+        let stored = if code == 0 {
+            self.store(&dir, &compilation, &output.stderr)
+        } else {
+            Ok(0)
+        };
+        let recorded = stored
+            .and_then(|size| self.cache.record(&format!("miss {size}")))
+            .and_then(|()| self.cache.maintain());
+        warning = recorded.err().or(warning);
+        Ok(Run {
+            code,
+            outcome: Outcome::Miss,
+            warning,
+        })
+    }
+
+    fn uncached(&self, compiler: &OsStr, args: &[OsString], reason: String) -> Result<Run, Error> {
+        let status = Command::new(compiler)
+            .args(args)
+            .status()
+            .map_err(Error::io(compiler))?;
+        Ok(Run {
+            code: exit_code(status),
+            outcome: Outcome::Uncacheable(reason),
+            warning: self.cache.record("uncacheable").err(),
+        })
+    }
+
+    /// A digest identifying `compiler`, remembered per driver path, size
+    /// and modification time.
+    fn identity(&self, compiler: &OsStr) -> Result<String, Error> {
+        let path = find_program(compiler)?;
+        let metadata = fs::metadata(&path).map_err(Error::io(&path))?;
+        let stamp = format!(
+            "{} {} {}",
+            metadata.len(),
+            metadata.mtime(),
+            metadata.mtime_nsec()
+        );
+        let memo = self
+            .cache
+            .root
+            .join(COMPILERS)
+            .join(hex(Sha256::digest(path.as_os_str().as_bytes())));
+        if let Some(identity) = fs::read_to_string(&memo)
+            .ok()
+            .and_then(|text| Some(text.strip_prefix(&stamp)?.trim().to_owned()))
+        {
+            return Ok(identity);
+        }
+
+        let mut hasher = Sha256::new();
+        let mut driver = fs::File::open(&path).map_err(Error::io(&path))?;
+        io::copy(&mut driver, &mut hasher).map_err(Error::io(&path))?;
+        for probe in ["-dumpmachine", "--version"] {
+            hasher.update(probe_output(compiler, probe)?);
+        }
+        if let Some(config) = path.parent().map(|dir| dir.join(linaro_wrap::CONFIG_FILE)) {
+            if let Ok(text) = fs::read(config) {
+                hasher.update(text);
+            }
+        }
+        let identity = hex(hasher.finalize());
+
+        let dir = memo.parent().expect("memo files have a parent");
+        let _ = fs::create_dir_all(dir)
+            .and_then(|()| fs::write(&memo, format!("{stamp} {identity}\n")));
+        Ok(identity)
+    }
+
+    /// Copies the compilation's outputs into the entry at `dir`, returning
+    /// their size.
+    fn store(&self, dir: &Path, compilation: &Compilation, stderr: &[u8]) -> Result<u64, Error> {
+        let tmp_root = self.cache.root.join(TMP);
+        fs::create_dir_all(&tmp_root).map_err(Error::io(&tmp_root))?;
+        let tmp = tempfile::TempDir::new_in(&tmp_root).map_err(Error::io(&tmp_root))?;
+        let mut size = fs::copy(&compilation.output, tmp.path().join("object"))
+            .map_err(Error::io(&compilation.output))?;
+        if let Some(deps) = &compilation.dep_file {
+            size += fs::copy(deps, tmp.path().join("deps")).map_err(Error::io(deps))?;
+        }
+        let path = tmp.path().join("stderr");
+        fs::write(&path, stderr).map_err(Error::io(&path))?;
+        size += stderr.len() as u64;
+
+        let shard = dir.parent().expect("entries have a parent");
+        fs::create_dir_all(shard).map_err(Error::io(shard))?;
+        match fs::rename(tmp.path(), dir) {
+            // Another compiler stored the same entry first.
+            Err(_) if dir.is_dir() => Ok(0),
+            Err(err) => Err(Error::io(dir)(err)),
+            Ok(()) => Ok(size),
+        }
+    }
+}
+
+/// Writes the entry at `dir` back to the compilation's outputs and replays
+/// the compiler's messages.
+fn restore(dir: &Path, compilation: &Compilation) -> Result<(), Error> {
+    copy_atomically(&dir.join("object"), &compilation.output)?;
+    if let Some(deps) = &compilation.dep_file {
+        copy_atomically(&dir.join("deps"), deps)?;
+    }
+    let path = dir.join("stderr");
+    let stderr = fs::read(&path).map_err(Error::io(&path))?;
+    let _ = io::stderr().write_all(&stderr);
+    Ok(())
+}
+
+/// Copies `from` to `to` through a temporary file, so `to` is never seen
+/// half written.
+fn copy_atomically(from: &Path, to: &Path) -> Result<(), Error> {
+    let dir = match to.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut tmp = tempfile::NamedTempFile::new_in(dir).map_err(Error::io(dir))?;
+    let mut source = fs::File::open(from).map_err(Error::io(from))?;
+    io::copy(&mut source, &mut tmp).map_err(Error::io(to))?;
+    tmp.persist(to).map_err(|err| Error::io(to)(err.error))?;
+    Ok(())
+}
+
+fn key(
+    identity: &str,
+    args: &[OsString],
+    compilation: &Compilation,
+    preprocessed: &[u8],
+) -> String {
+    let mut hasher = Sha256::new();
+    let mut field = |bytes: &[u8]| {
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(bytes);
+    };
+    field(KEY_VERSION.as_bytes());
+    field(identity.as_bytes());
+    for arg in args {
+        field(arg.as_bytes());
+    }
+    for name in KEY_ENV {
+        field(env::var_os(name).unwrap_or_default().as_bytes());
+    }
+    if compilation.debug {
+        field(
+            env::current_dir()
+                .unwrap_or_default()
+                .as_os_str()
+                .as_bytes(),
+        );
+    }
+    field(preprocessed);
+    hex(hasher.finalize())
+}
+
+/// `name` as given if it contains a slash, else its first match on `PATH`.
+fn find_program(name: &OsStr) -> Result<PathBuf, Error> {
+    let path = Path::new(name);
+    if name.as_bytes().contains(&b'/') {
+        return Ok(path.to_owned());
+    }
+    env::var_os("PATH")
+        .iter()
+        .flat_map(env::split_paths)
+        .map(|dir| dir.join(path))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| Error::NoCompiler(name.to_string_lossy().into_owned()))
+}
+
+fn probe_output(compiler: &OsStr, arg: &str) -> Result<Vec<u8>, Error> {
+    let program = compiler.to_string_lossy().into_owned();
+    let failed = |reason: String| Error::Probe {
+        program: program.clone(),
+        args: vec![arg.to_owned()],
+        reason,
+    };
+    let output = Command::new(compiler)
+        .arg(arg)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| failed(err.to_string()))?;
+    if !output.status.success() {
+        return Err(failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+fn exit_code(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .or(status.signal().map(|s| 128 + s))
+        .unwrap_or(1)
+}
+
+fn hex(digest: sha2::digest::Output<Sha256>) -> String {
+    linaro_manifest::Sha256::from_bytes(digest.into()).to_string()
+}
@@ -0,0 +1,251 @@
+//! A compiler launcher caching the objects built with the vendored
+//! toolchains.
+//!
+//! `linaro-objcache aarch64-linux-gnu-gcc -c foo.c -o foo.o` looks the
+//! compilation up by a key hashing
+//!
+//! - the compiler's identity: its driver binary, `-dumpmachine` and
+//!   `--version` output (and, for a `linaro-wrap` wrapper, the
+//!   `linaro-wrap.toml` beside it), so a different Linaro release never
+//!   shares entries;
+//! - the full argument list;
+//! - the preprocessed source, as printed by the same command with `-E`;
+//! - the working directory, when debug information records it.
+//!
+//! On a hit the object, the `-MD` dependency file and the compiler's
+//! warnings are restored without compiling. Anything that is not a plain
+//! `-c` compilation of one source runs uncached. Entries live under
+//!
+//! ```text
+//! <cache>/objects/ab/ab12…ef/{object,deps,stderr}
+//! ```
+//!
+//! and the least recently used are evicted once the cache grows past its
+//! size limit.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use filetime::FileTime;
+
+mod args;
+mod error;
+mod launch;
+mod stats;
+
+pub use args::Compilation;
+pub use error::Error;
+pub use launch::{Launcher, Outcome, Run};
+pub use stats::Stats;
+
+/// Overrides the cache directory.
+pub const DIR_VAR: &str = "LINARO_OBJCACHE_DIR";
+/// Overrides the size limit, e.g. `20G`.
+pub const MAX_SIZE_VAR: &str = "LINARO_OBJCACHE_MAX_SIZE";
+/// When set to anything but `0` or empty, compilers run uncached.
+pub const DISABLE_VAR: &str = "LINARO_OBJCACHE_DISABLE";
+
+pub const DEFAULT_MAX_SIZE: u64 = 5 << 30;
+
+const OBJECTS: &str = "objects";
+const COMPILERS: &str = "compilers";
+const TMP: &str = "tmp";
+const STATS: &str = "stats";
+
+/// Stats files larger than this are folded on the next store.
+const STATS_COMPACT_AT: u64 = 64 << 10;
+
+/// What [`Cache::trim`] removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Trimmed {
+    pub entries: u64,
+    pub freed: u64,
+}
+
+/// A cached compilation on disk.
+struct Entry {
+    dir: PathBuf,
+    size: u64,
+    used: FileTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    root: PathBuf,
+    max_size: u64,
+}
+
+impl Cache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+
+    /// The cache size past which old entries are evicted. Defaults to
+    /// [`DEFAULT_MAX_SIZE`].
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = bytes;
+        self
+    }
+
+    /// The cache configured by [`DIR_VAR`] and [`MAX_SIZE_VAR`], in
+    /// [`default_dir`] if the former is unset.
+    pub fn from_env() -> Result<Self, Error> {
+        let root = default_dir().ok_or_else(|| {
+            Error::io(DIR_VAR)(io::Error::other(
+                "neither it, XDG_CACHE_HOME nor HOME is set",
+            ))
+        })?;
+        Ok(Self::new(root).max_size(max_size_from_env()?))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.max_size
+    }
+
+    pub fn stats(&self) -> Result<Stats, Error> {
+        Ok(Stats::read(&self.root.join(STATS))?.0)
+    }
+
+    /// Resets the counters, keeping the size.
+    pub fn zero_stats(&self) -> Result<(), Error> {
+        let size = self.stats()?.size;
+        self.write_stats(&Stats {
+            size,
+            ..Stats::default()
+        })
+    }
+
+    /// The number of entries and their total size, counted on disk.
+    pub fn usage(&self) -> Result<(u64, u64), Error> {
+        let entries = self.entries()?;
+        Ok((entries.len() as u64, entries.iter().map(|e| e.size).sum()))
+    }
+
+    /// Evicts the least recently used entries until the cache holds at most
+    /// `limit` bytes.
+    pub fn trim(&self, limit: u64) -> Result<Trimmed, Error> {
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|e| e.used);
+        let mut size: u64 = entries.iter().map(|e| e.size).sum();
+        let mut trimmed = Trimmed::default();
+        for entry in entries {
+            if size <= limit {
+                break;
+            }
+            match fs::remove_dir_all(&entry.dir) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                    return Err(Error::io(&entry.dir)(err))
+                }
+                _ => {}
+            }
+            size -= entry.size;
+            trimmed.entries += 1;
+            trimmed.freed += entry.size;
+        }
+        let stats = self.stats()?;
+        self.write_stats(&Stats { size, ..stats })?;
+        Ok(trimmed)
+    }
+
+    /// Removes every entry, keeping the counters.
+    pub fn clear(&self) -> Result<Trimmed, Error> {
+        self.trim(0)
+    }
+
+    fn entry_dir(&self, key: &str) -> PathBuf {
+        self.root.join(OBJECTS).join(&key[..2]).join(key)
+    }
+
+    fn record(&self, event: &str) -> Result<(), Error> {
+        fs::create_dir_all(&self.root).map_err(Error::io(&self.root))?;
+        stats::record(&self.root.join(STATS), event)
+    }
+
+    fn write_stats(&self, stats: &Stats) -> Result<(), Error> {
+        fs::create_dir_all(&self.root).map_err(Error::io(&self.root))?;
+        stats.write(&self.root.join(STATS))
+    }
+
+    /// Trims to 90% of the limit once the recorded size exceeds it, and
+    /// folds the stats file when it has grown long.
+    fn maintain(&self) -> Result<(), Error> {
+        let path = self.root.join(STATS);
+        let (stats, length) = Stats::read(&path)?;
+        if stats.size > self.max_size {
+            self.trim(self.max_size / 10 * 9)?;
+        } else if length > STATS_COMPACT_AT {
+            stats.write(&path)?;
+        }
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<Entry>, Error> {
+        let mut entries = Vec::new();
+        for shard in read_dir(&self.root.join(OBJECTS))? {
+            for dir in read_dir(&shard)? {
+                let mut size = 0;
+                for file in read_dir(&dir)? {
+                    size += fs::metadata(&file).map_err(Error::io(&file))?.len();
+                }
+                let metadata = fs::metadata(&dir).map_err(Error::io(&dir))?;
+                entries.push(Entry {
+                    dir,
+                    size,
+                    used: FileTime::from_last_modification_time(&metadata),
+                });
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// `$LINARO_OBJCACHE_DIR`, else `linaro-objcache` under `$XDG_CACHE_HOME`
+/// or `~/.cache`.
+pub fn default_dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+    if let Some(dir) = var(DIR_VAR) {
+        return Some(dir.into());
+    }
+    let base = match var("XDG_CACHE_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(var("HOME")?).join(".cache"),
+    };
+    Some(base.join("linaro-objcache"))
+}
+
+/// `$LINARO_OBJCACHE_MAX_SIZE`, else [`DEFAULT_MAX_SIZE`].
+pub fn max_size_from_env() -> Result<u64, Error> {
+    match std::env::var_os(MAX_SIZE_VAR).filter(|s| !s.is_empty()) {
+        Some(size) => parse_size(&size.to_string_lossy()),
+        None => Ok(DEFAULT_MAX_SIZE),
+    }
+}
+
+/// Parses a size such as `500M` or `20G` (powers of 1024) or plain bytes;
+/// see [`linaro_manifest::parse_size`].
+pub fn parse_size(text: &str) -> Result<u64, Error> {
+    linaro_manifest::parse_size(text.trim()).ok_or_else(|| Error::BadSize(text.to_owned()))
+}
+
+/// The entries of `dir`, sorted; empty if `dir` does not exist.
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(Error::io(dir)(err)),
+    };
+    let mut paths = entries
+        .map(|e| e.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::io(dir))?;
+    paths.sort();
+    Ok(paths)
+}
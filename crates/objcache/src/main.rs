@@ -0,0 +1,47 @@
+//! `linaro-objcache`: runs a compiler through the object cache, as in
+//! `make CC="linaro-objcache aarch64-linux-gnu-gcc"` (see the
+//! `linaro_objcache` library).
+//!
+//! The exit status is the compiler's. If the cache itself fails before the
+//! compiler has run, the compiler runs uncached; with
+//! `LINARO_OBJCACHE_DISABLE` set it always does.
+
+use std::env;
+use std::ffi::OsString;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, ExitCode};
+
+use linaro_manifest::error_chain;
+use linaro_objcache::{Cache, Launcher, DISABLE_VAR};
+
+/// Usage errors, as for clap-based commands.
+const EXIT_USAGE: u8 = 2;
+
+fn main() -> ExitCode {
+    let mut args = env::args_os().skip(1);
+    let Some(compiler) = args.next() else {
+        eprintln!("usage: linaro-objcache COMPILER [ARGS...]");
+        return ExitCode::from(EXIT_USAGE);
+    };
+    let args: Vec<OsString> = args.collect();
+
+    let disabled = env::var_os(DISABLE_VAR).is_some_and(|v| !v.is_empty() && v != "0");
+    if !disabled {
+        let run = Cache::from_env().and_then(|cache| Launcher::new(cache).run(&compiler, &args));
+        match run {
+            Ok(run) => {
+                if let Some(err) = run.warning {
+                    eprintln!("linaro-objcache: warning: {}", error_chain(&err));
+                }
+                std::process::exit(run.code);
+            }
+            Err(err) => eprintln!(
+                "linaro-objcache: warning: running uncached: {}",
+                error_chain(&err)
+            ),
+        }
+    }
+    let err = Command::new(&compiler).args(&args).exec();
+    eprintln!("linaro-objcache: {}: {err}", compiler.to_string_lossy());
+    ExitCode::from(127)
+}
@@ -0,0 +1,92 @@
+//! Hit and miss counters.
+//!
+//! Concurrent compilers record events by appending a short line each to
+//! one file; [`Cache::trim`](crate::Cache::trim) folds them into a single
+//! `total` line. Events recorded while that happens may be lost, so the
+//! counters are approximate; the size is recomputed on every trim.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::Error;
+
+/// Counters since the statistics were last zeroed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Compilations run without the cache, e.g. links or `-E`.
+    pub uncacheable: u64,
+    /// Bytes held in the cache.
+    pub size: u64,
+}
+
+impl Stats {
+    /// Hits as a fraction of cacheable compilations.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+
+    pub(crate) fn read(path: &Path) -> Result<(Self, u64), Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(Error::io(path)(err)),
+        };
+        let mut stats = Stats::default();
+        for line in text.lines() {
+            let fields: Vec<u64> = line
+                .split_whitespace()
+                .skip(1)
+                .map_while(|f| f.parse().ok())
+                .collect();
+            match (line.split_whitespace().next(), &fields[..]) {
+                (Some("hit"), []) => stats.hits += 1,
+                (Some("miss"), [size]) => {
+                    stats.misses += 1;
+                    stats.size += size;
+                }
+                (Some("uncacheable"), []) => stats.uncacheable += 1,
+                (Some("evict"), [size]) => stats.size = stats.size.saturating_sub(*size),
+                (Some("total"), [hits, misses, uncacheable, size]) => {
+                    stats = Stats {
+                        hits: *hits,
+                        misses: *misses,
+                        uncacheable: *uncacheable,
+                        size: *size,
+                    }
+                }
+                // Torn or unknown lines are skipped.
+                _ => {}
+            }
+        }
+        Ok((stats, text.len() as u64))
+    }
+
+    /// Replaces the file at `path` with a single `total` line.
+    pub(crate) fn write(&self, path: &Path) -> Result<(), Error> {
+        let dir = path.parent().expect("stats files have a parent");
+        let mut tmp = tempfile::NamedTempFile::new_in(dir).map_err(Error::io(dir))?;
+        writeln!(
+            tmp,
+            "total {} {} {} {}",
+            self.hits, self.misses, self.uncacheable, self.size
+        )
+        .map_err(Error::io(tmp.path()))?;
+        tmp.persist(path)
+            .map_err(|err| Error::io(path)(err.error))?;
+        Ok(())
+    }
+}
+
+/// Appends `event` (`hit`, `miss <bytes>`, ...) to the file at `path`.
+pub(crate) fn record(path: &Path, event: &str) -> Result<(), Error> {
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(format!("{event}\n").as_bytes()))
+        .map_err(Error::io(path))
+}
@@ -0,0 +1,262 @@
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use linaro_objcache::{parse_size, Cache, Compilation, Stats, DIR_VAR, MAX_SIZE_VAR};
+
+const LAUNCHER: &str = env!("CARGO_BIN_EXE_linaro-objcache");
+
+fn args(args: &[&str]) -> Vec<OsString> {
+    args.iter().map(OsString::from).collect()
+}
+
+#[test]
+fn classifies_command_lines() {
+    let kernel = Compilation::parse(&args(&[
+        "-Wp,-MMD,init/.main.o.d",
+        "-nostdinc",
+        "-isystem",
+        "/tc/include",
+        "-O2",
+        "-c",
+        "-o",
+        "init/main.o",
+        "init/main.c",
+    ]))
+    .unwrap();
+    assert_eq!(kernel.source, Path::new("init/main.c"));
+    assert_eq!(kernel.output, Path::new("init/main.o"));
+    assert_eq!(
+        kernel.dep_file.as_deref(),
+        Some(Path::new("init/.main.o.d"))
+    );
+    assert_eq!(
+        kernel.preprocess,
+        args(&[
+            "-nostdinc",
+            "-isystem",
+            "/tc/include",
+            "-O2",
+            "-E",
+            "init/main.c"
+        ])
+    );
+    assert!(!kernel.debug);
+
+    let plain = Compilation::parse(&args(&["-g", "-MD", "-MT", "x", "-c", "src/a.cpp"])).unwrap();
+    assert_eq!(plain.output, Path::new("a.o"));
+    assert_eq!(plain.dep_file.as_deref(), Some(Path::new("a.d")));
+    assert!(plain.debug);
+    let plain = Compilation::parse(&args(&["-g", "-g0", "-c", "-xc", "a.inc", "-oa.o"])).unwrap();
+    assert_eq!(plain.source, Path::new("a.inc"));
+    assert_eq!(plain.output, Path::new("a.o"));
+    assert!(!plain.debug);
+
+    for (command, reason) in [
+        (&["-o", "prog", "a.c"][..], "no -c"),
+        (&["-c", "a.c", "b.c"], "2 source files"),
+        (&["-c", "a.o"], "a.o is not a source file (linking?)"),
+        (&["-E", "a.c"], "-E does not produce an object"),
+        (&["-c", "-x", "c", "-"], "source read from standard input"),
+        (
+            &["-c", "--coverage", "a.c"],
+            "--coverage uses files the cache does not track",
+        ),
+        (&["@args", "-c", "a.c"], "response file @args"),
+        (&["-c", "a.c", "-o"], "-o needs a value"),
+    ] {
+        assert_eq!(Compilation::parse(&args(command)), Err(reason.to_owned()));
+    }
+}
+
+#[test]
+fn parses_sizes() {
+    assert_eq!(parse_size("1024").unwrap(), 1024);
+    assert_eq!(parse_size("500M").unwrap(), 500 << 20);
+    assert_eq!(parse_size(" 20g ").unwrap(), 20 << 30);
+    for bad in ["", "G", "1.5G", "-1", "99999999999T"] {
+        assert!(parse_size(bad).is_err(), "{bad}");
+    }
+}
+
+/// A project with two sources sharing a header, and compiler stand-ins
+/// reporting different Linaro releases. `None` without a host GCC.
+fn project(dir: &Path) -> Option<PathBuf> {
+    if !Path::new("/usr/bin/gcc").exists() {
+        return None;
+    }
+    let src = dir.join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(src.join("config.h"), "#define ANSWER 42\n").unwrap();
+    fs::write(
+        src.join("main.c"),
+        "#include \"config.h\"\n#warning cached warnings are replayed\nint main(void) { return ANSWER; }\n",
+    )
+    .unwrap();
+    fs::write(src.join("broken.c"), "int main(void) { return }\n").unwrap();
+    for release in ["7.4-2019.02", "7.5-2019.12"] {
+        let path = dir.join(format!("gcc-{release}"));
+        fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\n\
+                 [ \"$1\" = --version ] && {{ echo 'gcc (Linaro GCC {release})'; exit; }}\n\
+                 exec /usr/bin/gcc \"$@\"\n"
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    Some(src)
+}
+
+/// Runs the launcher in `cwd` with the cache at `cache`.
+fn launch(cache: &Path, cwd: &Path, command: &[&str]) -> (i32, String) {
+    fs::create_dir_all(cwd).unwrap();
+    let output = Command::new(LAUNCHER)
+        .args(command)
+        .current_dir(cwd)
+        .env(DIR_VAR, cache)
+        .env_remove(MAX_SIZE_VAR)
+        .output()
+        .unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+fn stats(cache: &Path) -> (u64, u64, u64) {
+    let Stats {
+        hits,
+        misses,
+        uncacheable,
+        ..
+    } = Cache::new(cache).stats().unwrap();
+    (hits, misses, uncacheable)
+}
+
+#[test]
+fn serves_repeated_compilations() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(src) = project(dir.path()) else {
+        eprintln!("skipping: needs a host gcc");
+        return;
+    };
+    let cache = dir.path().join("cache");
+    let gcc = dir.path().join("gcc-7.5-2019.12");
+    let gcc = gcc.to_str().unwrap();
+    let main_c = src.join("main.c");
+    let main_c = main_c.to_str().unwrap();
+    let board = |name: &str| dir.path().join(name);
+    let compile = ["-O2", "-Wp,-MMD,.main.o.d", "-c", "-o", "main.o", main_c];
+    let command: Vec<_> = [gcc].iter().chain(&compile).copied().collect();
+
+    let (code, stderr) = launch(&cache, &board("a"), &command);
+    assert_eq!(code, 0, "{stderr}");
+    assert!(stderr.contains("cached warnings are replayed"), "{stderr}");
+    assert_eq!(stats(&cache), (0, 1, 0));
+    let object = fs::read(board("a/main.o")).unwrap();
+    let deps = fs::read_to_string(board("a/.main.o.d")).unwrap();
+
+    // Another board's build directory shares the entry.
+    let (code, stderr) = launch(&cache, &board("b"), &command);
+    assert_eq!(code, 0);
+    assert!(stderr.contains("cached warnings are replayed"), "{stderr}");
+    assert_eq!(stats(&cache), (1, 1, 0));
+    assert_eq!(fs::read(board("b/main.o")).unwrap(), object);
+    assert_eq!(fs::read_to_string(board("b/.main.o.d")).unwrap(), deps);
+
+    // A header change, another Linaro release, or debug information in a
+    // different directory miss.
+    fs::write(src.join("config.h"), "#define ANSWER 43\n").unwrap();
+    launch(&cache, &board("b"), &command);
+    assert_eq!(stats(&cache), (1, 2, 0));
+    let other = dir.path().join("gcc-7.4-2019.02");
+    let mut command_74 = command.clone();
+    command_74[0] = other.to_str().unwrap();
+    launch(&cache, &board("b"), &command_74);
+    assert_eq!(stats(&cache), (1, 3, 0));
+    let mut debug = command.clone();
+    debug.insert(1, "-g");
+    launch(&cache, &board("a"), &debug);
+    launch(&cache, &board("b"), &debug);
+    launch(&cache, &board("b"), &debug);
+    assert_eq!(stats(&cache), (2, 5, 0));
+    assert_eq!(Cache::new(&cache).usage().unwrap().0, 5);
+
+    // Links run uncached; failures are not stored.
+    let (code, _) = launch(&cache, &board("a"), &[gcc, "-o", "prog", main_c]);
+    assert_eq!(code, 0);
+    assert!(board("a/prog").is_file());
+    let broken = src.join("broken.c");
+    for _ in 0..2 {
+        let (code, stderr) = launch(&cache, &board("a"), &[gcc, "-c", broken.to_str().unwrap()]);
+        assert_eq!(code, 1, "{stderr}");
+        assert!(stderr.contains("error"), "{stderr}");
+    }
+    assert_eq!(stats(&cache), (2, 7, 1));
+    assert_eq!(Cache::new(&cache).usage().unwrap().0, 5);
+}
+
+#[test]
+fn evicts_least_recently_used() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(src) = project(dir.path()) else {
+        eprintln!("skipping: needs a host gcc");
+        return;
+    };
+    let cache_dir = dir.path().join("cache");
+    let cache = Cache::new(&cache_dir);
+    let main_c = src.join("main.c");
+    for level in ["-O0", "-O1", "-O2"] {
+        let (code, _) = launch(
+            &cache_dir,
+            dir.path(),
+            &["gcc", level, "-c", main_c.to_str().unwrap()],
+        );
+        assert_eq!(code, 0);
+        // Modification times order the entries.
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    let (entries, size) = cache.usage().unwrap();
+    assert_eq!(entries, 3);
+    assert_eq!(cache.stats().unwrap().size, size);
+
+    // Using the oldest entry makes it the newest.
+    launch(
+        &cache_dir,
+        dir.path(),
+        &["gcc", "-O0", "-c", main_c.to_str().unwrap()],
+    );
+    let trimmed = cache.trim(size - 1).unwrap();
+    assert_eq!(trimmed.entries, 1);
+    assert_eq!(cache.usage().unwrap(), (2, size - trimmed.freed));
+    launch(
+        &cache_dir,
+        dir.path(),
+        &["gcc", "-O0", "-c", main_c.to_str().unwrap()],
+    );
+    assert_eq!(cache.stats().unwrap().hits, 2);
+
+    cache.zero_stats().unwrap();
+    let stats = cache.stats().unwrap();
+    assert_eq!(
+        (stats.hits, stats.misses, stats.size),
+        (0, 0, size - trimmed.freed)
+    );
+
+    // Exceeding the limit trims the cache after storing.
+    let output = Command::new(LAUNCHER)
+        .args(["gcc", "-O3", "-c", main_c.to_str().unwrap()])
+        .current_dir(dir.path())
+        .env(DIR_VAR, &cache_dir)
+        .env(MAX_SIZE_VAR, "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(cache.usage().unwrap(), (0, 0));
+    assert_eq!(cache.clear().unwrap().entries, 0);
+}
@@ -0,0 +1,42 @@
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("malformed policy")]
+    Policy(#[from] toml::de::Error),
+    #[error("policy has no rules for profile `{0}`")]
+    UnknownProfile(String),
+    #[error(transparent)]
+    Pattern(#[from] linaro_glob::Error),
+    #[error("{} was already pruned for profiles {}; reinstall it to apply a different policy", path.display(), profiles.join(", "))]
+    AlreadyPruned {
+        path: PathBuf,
+        profiles: Vec<String>,
+    },
+    #[error("malformed prune record {}: {reason}", path.display())]
+    Record { path: PathBuf, reason: String },
+    #[error("{} no longer builds after pruning, so nothing was removed: {failures}", path.display())]
+    Verification { path: PathBuf, failures: String },
+    #[error("cannot put {} back after a failed prune; the removed entries are kept in {}", path.display(), aside.display())]
+    Restore {
+        path: PathBuf,
+        aside: PathBuf,
+        source: io::Error,
+    },
+    #[error(transparent)]
+    Archive(#[from] linaro_archive::Error),
+    #[error(transparent)]
+    Smoke(#[from] linaro_smoke::Error),
+    #[error(transparent)]
+    Toolchain(#[from] linaro_toolchain::Error),
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
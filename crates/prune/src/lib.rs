@@ -0,0 +1,359 @@
+//! Removes what a vendored toolchain's users do not need (documentation,
+//! unused language front ends and runtimes, multilibs) according to a
+//! [`Policy`], and keeps a record of exactly what went.
+//!
+//! Pruning is transactional: matching files are first moved aside, the
+//! pruned toolchain must still compile and link a C (and, while `g++` is
+//! left, a C++) program, and only then are they deleted. Otherwise they are
+//! put back. An installed tree's stamp is updated so `install` keeps
+//! recognising it, and [`RECORD_FILE`] at its top lists every removed path
+//! with its digest.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use linaro_archive::install::{read_stamp, restamp, tree_digest, STAMP_FILE};
+use linaro_manifest::{sha256_file, Sha256};
+use linaro_smoke::{CaseReport, ToolchainReport, CASES};
+use linaro_toolchain::Installation;
+use serde::{Deserialize, Serialize};
+
+mod error;
+pub mod policy;
+
+pub use error::Error;
+pub use policy::{Policies, Policy, Rules};
+
+/// Name of the record written at the top of a pruned tree.
+pub const RECORD_FILE: &str = ".linaro-prebuilts-pruned.json";
+
+/// Smoke cases a pruned toolchain must still pass.
+const VERIFY_CASES: &[&str] = &["hello-c", "hello-cxx", "freestanding"];
+
+/// A path removed from the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Removed {
+    File {
+        path: String,
+        mode: String,
+        size: u64,
+        sha256: Sha256,
+    },
+    Symlink {
+        path: String,
+        target: String,
+    },
+    Dir {
+        path: String,
+    },
+}
+
+impl Removed {
+    pub fn path(&self) -> &str {
+        match self {
+            Removed::File { path, .. } | Removed::Symlink { path, .. } | Removed::Dir { path } => {
+                path
+            }
+        }
+    }
+}
+
+/// What was removed from a tree, and under which rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Record {
+    /// The expanded rules, by profile name.
+    pub rules: BTreeMap<String, Rules>,
+    /// Bytes of file content removed.
+    pub freed: u64,
+    /// Removed paths, children before their directories.
+    pub removed: Vec<Removed>,
+}
+
+impl Record {
+    /// Reads the record of the tree at `dir`, if it has been pruned.
+    pub fn read(dir: &Path) -> Result<Option<Self>, Error> {
+        let path = dir.join(RECORD_FILE);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::io(&path)(err)),
+        };
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|err| Error::Record {
+                path,
+                reason: err.to_string(),
+            })
+    }
+}
+
+/// What [`Pruner::prune`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The tree was pruned; `verification` is `None` when it was skipped.
+    Pruned {
+        record: Record,
+        verification: Option<ToolchainReport>,
+    },
+    /// The tree had already been pruned under the same rules.
+    AlreadyPruned(Record),
+}
+
+/// Applies a [`Policy`] to installed toolchains.
+#[derive(Debug, Clone)]
+pub struct Pruner {
+    policy: Policy,
+    verify: bool,
+}
+
+impl Pruner {
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            verify: true,
+        }
+    }
+
+    /// Whether to check that the pruned toolchain still builds programs
+    /// before committing to the removal. On by default.
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// What [`prune`](Self::prune) would remove, without touching the tree.
+    pub fn plan(&self, installation: &Installation) -> Result<Record, Error> {
+        let mut record = Record {
+            rules: self.policy.rules().clone(),
+            freed: 0,
+            removed: Vec::new(),
+        };
+        self.walk(installation.root(), "", &mut record)?;
+        Ok(record)
+    }
+
+    pub fn prune(&self, installation: &Installation) -> Result<Outcome, Error> {
+        let root = installation.root();
+        if let Some(previous) = Record::read(root)? {
+            if previous.rules == *self.policy.rules() {
+                return Ok(Outcome::AlreadyPruned(previous));
+            }
+            return Err(Error::AlreadyPruned {
+                path: root.to_owned(),
+                profiles: previous.rules.into_keys().collect(),
+            });
+        }
+        let stamped = root.join(STAMP_FILE).exists();
+        if stamped && tree_digest(root)? != read_stamp(root)?.tree {
+            return Err(linaro_archive::Error::Drifted(root.to_owned()).into());
+        }
+
+        let record = self.plan(installation)?;
+        let mut aside = Aside::create(root)?;
+        for removed in &record.removed {
+            if !matches!(removed, Removed::Dir { .. }) {
+                aside.take(removed.path())?;
+            }
+        }
+
+        let verification = if self.verify {
+            let report = verify(installation)?;
+            if !report.passed() {
+                aside.restore()?;
+                let failures = report
+                    .cases
+                    .iter()
+                    .filter(|c| !c.passed)
+                    .map(|c| format!("{}: {}", c.name, c.failures.join("; ")))
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                return Err(Error::Verification {
+                    path: root.to_owned(),
+                    failures,
+                });
+            }
+            Some(report)
+        } else {
+            None
+        };
+
+        // Directories go only once nothing can be put back into them.
+        aside.commit();
+        for removed in &record.removed {
+            if let Removed::Dir { path } = removed {
+                let dir = root.join(path);
+                fs::remove_dir(&dir).map_err(Error::io(&dir))?;
+            }
+        }
+        let path = root.join(RECORD_FILE);
+        let text = serde_json::to_string_pretty(&record).expect("record serializes") + "\n";
+        fs::write(&path, text).map_err(Error::io(&path))?;
+        if stamped {
+            restamp(root)?;
+        }
+        Ok(Outcome::Pruned {
+            record,
+            verification,
+        })
+    }
+
+    /// Adds what the policy drops below `dir` (at `prefix` in the tree) to
+    /// `record`, returning whether everything in `dir` is dropped.
+    fn walk(&self, dir: &Path, prefix: &str, record: &mut Record) -> Result<bool, Error> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(dir).map_err(Error::io(dir))? {
+            let entry = entry.map_err(Error::io(dir))?;
+            entries.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        entries.sort();
+
+        let mut emptied = true;
+        for name in entries {
+            if prefix.is_empty() && (name == STAMP_FILE || name == RECORD_FILE) {
+                emptied = false;
+                continue;
+            }
+            let path = dir.join(&name);
+            let relative = format!("{prefix}{name}");
+            let metadata = path.symlink_metadata().map_err(Error::io(&path))?;
+            let removed = if metadata.is_dir() {
+                let children = self.walk(&path, &format!("{relative}/"), record)?;
+                (children && self.policy.drops(&relative)).then(|| Removed::Dir {
+                    path: relative.clone(),
+                })
+            } else if !self.policy.drops(&relative) {
+                None
+            } else if metadata.is_symlink() {
+                let target = fs::read_link(&path).map_err(Error::io(&path))?;
+                Some(Removed::Symlink {
+                    path: relative.clone(),
+                    target: target.to_string_lossy().into_owned(),
+                })
+            } else {
+                let (sha256, size) = sha256_file(&path).map_err(Error::io(&path))?;
+                record.freed += size;
+                Some(Removed::File {
+                    path: relative.clone(),
+                    mode: format!("{:o}", metadata.permissions().mode() & 0o7777),
+                    size,
+                    sha256,
+                })
+            };
+            match removed {
+                Some(removed) => record.removed.push(removed),
+                None => emptied = false,
+            }
+        }
+        Ok(emptied)
+    }
+}
+
+/// Builds the verification cases with the pruned toolchain, leaving out C++
+/// once its driver is gone.
+fn verify(installation: &Installation) -> Result<ToolchainReport, Error> {
+    if !installation.tool("gcc").exists() {
+        return Ok(ToolchainReport {
+            root: installation.root().to_owned(),
+            target: installation.triple().to_string(),
+            cases: vec![CaseReport {
+                name: "hello-c",
+                passed: false,
+                failures: vec!["the gcc driver was removed".to_owned()],
+            }],
+            skipped: Vec::new(),
+        });
+    }
+    let has_cxx = installation.tool("g++").exists();
+    let cases: Vec<_> = CASES
+        .iter()
+        .filter(|c| VERIFY_CASES.contains(&c.name))
+        .filter(|c| has_cxx || !c.file.ends_with(".cpp"))
+        .copied()
+        .collect();
+    Ok(linaro_smoke::run_cases(installation, &cases)?)
+}
+
+/// A directory next to the tree holding entries taken out of it until the
+/// pruning is committed or rolled back.
+struct Aside {
+    root: PathBuf,
+    dir: PathBuf,
+    taken: Vec<String>,
+    /// A restore failed, so the directory holds entries that are nowhere
+    /// else.
+    stranded: bool,
+}
+
+impl Aside {
+    fn create(root: &Path) -> Result<Self, Error> {
+        let name = root.file_name().unwrap_or_default().to_string_lossy();
+        let parent = root.parent().unwrap_or(Path::new("."));
+        let dir = parent.join(format!(".{name}.pruning-{}", std::process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).map_err(Error::io(&dir))?;
+        }
+        fs::create_dir(&dir).map_err(Error::io(&dir))?;
+        Ok(Self {
+            root: root.to_owned(),
+            dir,
+            taken: Vec::new(),
+            stranded: false,
+        })
+    }
+
+    fn take(&mut self, path: &str) -> Result<(), Error> {
+        let to = self.dir.join(path);
+        let parent = to.parent().expect("entries have a parent");
+        fs::create_dir_all(parent).map_err(Error::io(parent))?;
+        let from = self.root.join(path);
+        fs::rename(&from, &to).map_err(Error::io(&from))?;
+        self.taken.push(path.to_owned());
+        Ok(())
+    }
+
+    /// Puts everything taken back where it was. On failure the entries not
+    /// yet put back stay in the aside directory, which the error names.
+    fn restore(&mut self) -> Result<(), Error> {
+        while let Some(path) = self.taken.last() {
+            let to = self.root.join(path);
+            let parent = to.parent().expect("entries have a parent");
+            let restored =
+                fs::create_dir_all(parent).and_then(|()| fs::rename(self.dir.join(path), &to));
+            if let Err(source) = restored {
+                self.stranded = true;
+                return Err(Error::Restore {
+                    path: to,
+                    aside: self.dir.clone(),
+                    source,
+                });
+            }
+            self.taken.pop();
+        }
+        Ok(())
+    }
+
+    /// Keeps the removal; what was taken is deleted on drop.
+    fn commit(mut self) {
+        self.taken.clear();
+    }
+}
+
+impl Drop for Aside {
+    fn drop(&mut self) {
+        // A failed restore was already reported; keep what it left.
+        if self.stranded {
+            return;
+        }
+        // An error between taking entries and committing: put them back.
+        match self.restore() {
+            Ok(()) => {
+                let _ = fs::remove_dir_all(&self.dir);
+            }
+            Err(err) => eprintln!("warning: {err}"),
+        }
+    }
+}
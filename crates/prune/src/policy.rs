@@ -0,0 +1,124 @@
+//! Policy files: which parts of a toolchain each profile can do without.
+//!
+//! ```toml
+//! [default]
+//! drop = ["share/doc", "share/info", "share/man", "bin/{triple}-gfortran"]
+//!
+//! [profile.kernel-arm64]
+//! drop = ["bin/{triple}-g++", "{triple}/include/c++"]
+//! keep = ["share/man/man1/{triple}-gcc.1"]
+//! ```
+//!
+//! Patterns are [`linaro_glob`] patterns relative to the toolchain root, in
+//! which `{triple}` stands for the target triple. Under a profile, a path is
+//! dropped when it matches a `drop` pattern (its own or the default's) and
+//! no `keep` pattern.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use linaro_glob::Pattern;
+use linaro_manifest::Triple;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// The contents of a policy file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policies {
+    /// Rules shared by every profile.
+    #[serde(default)]
+    pub default: Rules,
+    #[serde(rename = "profile", default)]
+    pub profiles: BTreeMap<String, Rules>,
+}
+
+/// Patterns to drop, and exceptions to keep.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    #[serde(default)]
+    pub drop: Vec<String>,
+    #[serde(default)]
+    pub keep: Vec<String>,
+}
+
+impl Policies {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        fs::read_to_string(path).map_err(Error::io(path))?.parse()
+    }
+
+    /// The policy for a toolchain targeting `triple` shared by `profiles`:
+    /// it removes only what every one of them drops.
+    pub fn policy(&self, profiles: &[String], triple: &Triple) -> Result<Policy, Error> {
+        let mut resolved = BTreeMap::new();
+        for name in profiles {
+            let own = self
+                .profiles
+                .get(name)
+                .ok_or_else(|| Error::UnknownProfile(name.clone()))?;
+            let expand = |patterns: &[String], extra: &[String]| -> Vec<String> {
+                patterns
+                    .iter()
+                    .chain(extra)
+                    .map(|p| p.replace("{triple}", triple.as_str()))
+                    .collect()
+            };
+            let rules = Rules {
+                drop: expand(&self.default.drop, &own.drop),
+                keep: expand(&self.default.keep, &own.keep),
+            };
+            resolved.insert(name.clone(), rules);
+        }
+        Policy::new(resolved)
+    }
+}
+
+impl FromStr for Policies {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+/// Resolved rules for a set of profiles, ready for matching.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    rules: BTreeMap<String, Rules>,
+    compiled: Vec<(Vec<Pattern>, Vec<Pattern>)>,
+}
+
+impl Policy {
+    /// Builds a policy from already expanded rules, by profile name.
+    pub fn new(rules: BTreeMap<String, Rules>) -> Result<Self, Error> {
+        let compile = |patterns: &[String]| -> Result<Vec<Pattern>, Error> {
+            patterns.iter().map(|p| Ok(Pattern::new(p)?)).collect()
+        };
+        let compiled = rules
+            .values()
+            .map(|r| Ok((compile(&r.drop)?, compile(&r.keep)?)))
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { rules, compiled })
+    }
+
+    /// The expanded rules, by profile name.
+    pub fn rules(&self) -> &BTreeMap<String, Rules> {
+        &self.rules
+    }
+
+    /// Whether every profile drops `path` (relative to the toolchain root,
+    /// `/`-separated). A policy without profiles drops nothing.
+    pub fn drops(&self, path: &str) -> bool {
+        let components: Vec<_> = path.split('/').collect();
+        !self.compiled.is_empty()
+            && self.compiled.iter().all(|(drop, keep)| {
+                drop.iter().any(|p| p.covers(&components))
+                    && !keep.iter().any(|p| p.covers(&components))
+            })
+    }
+}
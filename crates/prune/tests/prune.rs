@@ -0,0 +1,329 @@
+use std::fs;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use linaro_archive::install::{read_stamp, tree_digest};
+use linaro_manifest::Triple;
+use linaro_prune::{Error, Outcome, Policies, Pruner, Record, Removed, RECORD_FILE};
+use linaro_toolchain::Installation;
+
+const TRIPLE: &str = "x86_64-linux-gnu";
+
+const POLICY: &str = r#"
+[default]
+drop = ["share/doc", "share/man", "bin/{triple}-gfortran", "libexec/gcc/*/*/f951"]
+keep = ["share/man/man1/{triple}-gcc.1"]
+
+[profile.kernel]
+drop = ["bin/{triple}-g++", "**/cc1plus"]
+
+[profile.userspace]
+drop = ["share/locale/**"]
+"#;
+
+fn policies() -> Policies {
+    POLICY.parse().unwrap()
+}
+
+fn triple() -> Triple {
+    TRIPLE.parse().unwrap()
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+/// A stamped fake toolchain at `<dir>/toolchain` whose drivers run the host
+/// compiler, but only while `libexec/.../cc1` exists. `None` without a host
+/// GCC.
+fn installation(dir: &Path) -> Option<Installation> {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return None;
+    }
+    let root = dir.join("toolchain");
+    let libexec = root.join(format!("libexec/gcc/{TRIPLE}/7.5.0"));
+    for tool in ["gcc", "g++"] {
+        let host = Path::new("/usr/bin").join(tool);
+        if !host.exists() {
+            return None;
+        }
+        let driver = root.join(format!("bin/{TRIPLE}-{tool}"));
+        write(
+            &driver,
+            &format!(
+                "#!/bin/sh\n\
+                 [ -f {}/cc1 ] || {{ echo \"{tool}: fatal error: cannot execute 'cc1'\" >&2; exit 1; }}\n\
+                 exec {} \"$@\"\n",
+                libexec.display(),
+                host.display()
+            ),
+        );
+        fs::set_permissions(&driver, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    write(&root.join(format!("bin/{TRIPLE}-gfortran")), "fortran");
+    for name in ["cc1", "cc1plus", "f951"] {
+        write(&libexec.join(name), name);
+    }
+    write(&root.join("share/doc/gcc/README"), "docs");
+    write(
+        &root.join(format!("share/man/man1/{TRIPLE}-gcc.1")),
+        "gcc(1)",
+    );
+    write(
+        &root.join(format!("share/man/man1/{TRIPLE}-gfortran.1")),
+        "gfortran(1)",
+    );
+    write(&root.join("share/locale/de/LC_MESSAGES/gcc.mo"), "de");
+    symlink(
+        format!("{TRIPLE}-gcc"),
+        root.join(format!("bin/{TRIPLE}-cc")),
+    )
+    .unwrap();
+    stamp(&root);
+    Some(Installation::open(&root).unwrap())
+}
+
+/// Records the tree at `root` as installed, as `install` would.
+fn stamp(root: &Path) {
+    let tree = tree_digest(root).unwrap();
+    fs::write(
+        root.join(".linaro-prebuilts-stamp"),
+        format!(
+            "archive = \"fake.tar.xz\"\nsha256 = \"{}\"\ntree = \"{tree}\"\n",
+            "0".repeat(64)
+        ),
+    )
+    .unwrap();
+}
+
+fn exists(root: &Path, path: &str) -> bool {
+    root.join(path).symlink_metadata().is_ok()
+}
+
+#[test]
+fn matches_patterns() {
+    let policy = policies().policy(&["kernel".into()], &triple()).unwrap();
+    for path in [
+        "share/doc",
+        "share/doc/gcc/README",
+        "share/man/man1/x86_64-linux-gnu-gfortran.1",
+        "bin/x86_64-linux-gnu-g++",
+        "libexec/gcc/x86_64-linux-gnu/7.5.0/cc1plus",
+        "libexec/gcc/x86_64-linux-gnu/7.5.0/f951",
+    ] {
+        assert!(policy.drops(path), "{path}");
+    }
+    for path in [
+        "share",
+        "share/man/man1/x86_64-linux-gnu-gcc.1",
+        "share/locale/de/LC_MESSAGES/gcc.mo",
+        "bin/x86_64-linux-gnu-gcc",
+        "bin/aarch64-linux-gnu-gfortran",
+        "libexec/gcc/x86_64-linux-gnu/7.5.0/cc1",
+    ] {
+        assert!(!policy.drops(path), "{path}");
+    }
+    assert_eq!(
+        policy.rules()["kernel"].drop[2],
+        "bin/x86_64-linux-gnu-gfortran"
+    );
+
+    // With several profiles, only what all of them drop goes.
+    let shared = policies()
+        .policy(&["kernel".into(), "userspace".into()], &triple())
+        .unwrap();
+    assert!(shared.drops("share/doc/gcc/README"));
+    assert!(!shared.drops("bin/x86_64-linux-gnu-g++"));
+    assert!(!shared.drops("share/locale/de/LC_MESSAGES/gcc.mo"));
+
+    assert!(matches!(
+        policies().policy(&["android".into()], &triple()),
+        Err(Error::UnknownProfile(name)) if name == "android"
+    ));
+    for pattern in ["/usr/share", "share/../bin", "share/a**"] {
+        let text = format!("[profile.p]\ndrop = [\"{pattern}\"]\n");
+        let policies: Policies = text.parse().unwrap();
+        assert!(
+            matches!(
+                policies.policy(&["p".into()], &triple()),
+                Err(Error::Pattern { .. })
+            ),
+            "{pattern}"
+        );
+    }
+}
+
+#[test]
+fn checked_in_policy_is_valid() {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let policies = Policies::load(workspace.join("prune.toml")).unwrap();
+    let profiles = fs::read_to_string(workspace.join("profiles.toml")).unwrap();
+    for name in policies.profiles.keys() {
+        assert!(
+            profiles.contains(&format!("[profile.{name}]")),
+            "{name} is not in profiles.toml"
+        );
+        policies
+            .policy(std::slice::from_ref(name), &triple())
+            .unwrap();
+    }
+}
+
+#[test]
+fn prunes_and_records_removals() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = installation(dir.path()) else {
+        eprintln!("skipping: needs an x86-64 host gcc");
+        return;
+    };
+    let root = installation.root().to_owned();
+    let policy = policies().policy(&["kernel".into()], &triple()).unwrap();
+    let pruner = Pruner::new(policy);
+
+    let plan = pruner.plan(&installation).unwrap();
+    let planned: Vec<_> = plan.removed.iter().map(Removed::path).collect();
+    assert_eq!(
+        planned,
+        [
+            "bin/x86_64-linux-gnu-g++",
+            "bin/x86_64-linux-gnu-gfortran",
+            "libexec/gcc/x86_64-linux-gnu/7.5.0/cc1plus",
+            "libexec/gcc/x86_64-linux-gnu/7.5.0/f951",
+            "share/doc/gcc/README",
+            "share/doc/gcc",
+            "share/doc",
+            "share/man/man1/x86_64-linux-gnu-gfortran.1",
+        ]
+    );
+    assert!(exists(&root, "share/doc"), "planning changes nothing");
+
+    let Outcome::Pruned {
+        record,
+        verification,
+    } = pruner.prune(&installation).unwrap()
+    else {
+        panic!("expected the tree to be pruned");
+    };
+    assert_eq!(record, plan);
+    let g_plus_plus = fs::metadata(root.join("bin/x86_64-linux-gnu-gcc"))
+        .unwrap()
+        .len();
+    // The g++ driver is the same size as gcc; gfortran, cc1plus, f951,
+    // README and the gfortran man page follow.
+    assert_eq!(record.freed, g_plus_plus + 7 + 7 + 4 + 4 + 11);
+    let verification = verification.unwrap();
+    let cases: Vec<_> = verification.cases.iter().map(|c| c.name).collect();
+    assert_eq!(cases, ["hello-c"], "C++ is not checked once g++ is gone");
+    for path in &planned {
+        assert!(!exists(&root, path), "{path}");
+    }
+    for path in [
+        "bin/x86_64-linux-gnu-cc",
+        "share/man/man1/x86_64-linux-gnu-gcc.1",
+        "share/locale/de/LC_MESSAGES/gcc.mo",
+    ] {
+        assert!(exists(&root, path), "{path}");
+    }
+    assert!(!dir.path().read_dir().unwrap().any(|e| e
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .contains("pruning")));
+
+    assert_eq!(Record::read(&root).unwrap(), Some(record.clone()));
+    let json = fs::read_to_string(root.join(RECORD_FILE)).unwrap();
+    assert!(json.contains("\"type\": \"file\""), "{json}");
+    // The stamp follows the pruned tree, so `install` still accepts it.
+    assert_eq!(read_stamp(&root).unwrap().tree, tree_digest(&root).unwrap());
+
+    assert_eq!(
+        pruner.prune(&installation).unwrap(),
+        Outcome::AlreadyPruned(record)
+    );
+    let other = policies().policy(&["userspace".into()], &triple()).unwrap();
+    assert!(matches!(
+        Pruner::new(other).prune(&installation),
+        Err(Error::AlreadyPruned { profiles, .. }) if profiles == ["kernel"]
+    ));
+}
+
+#[test]
+fn restores_tree_when_toolchain_breaks() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = installation(dir.path()) else {
+        eprintln!("skipping: needs an x86-64 host gcc");
+        return;
+    };
+    let root: PathBuf = installation.root().to_owned();
+    let before = tree_digest(&root).unwrap();
+    let policies: Policies = "[profile.p]\ndrop = [\"libexec\", \"share\"]\n"
+        .parse()
+        .unwrap();
+    let pruner = Pruner::new(policies.policy(&["p".into()], &triple()).unwrap());
+
+    let err = pruner.prune(&installation).unwrap_err();
+    assert!(
+        matches!(&err, Error::Verification { failures, .. } if failures.contains("cc1")),
+        "{err}"
+    );
+    assert_eq!(tree_digest(&root).unwrap(), before);
+    assert!(!exists(&root, RECORD_FILE));
+
+    // Without verification the same policy goes through.
+    assert!(matches!(
+        pruner.verify(false).prune(&installation).unwrap(),
+        Outcome::Pruned {
+            verification: None,
+            ..
+        }
+    ));
+    assert!(!exists(&root, "libexec"));
+
+    // A tree modified since it was installed is not pruned.
+    let dir = tempfile::tempdir().unwrap();
+    let installation = self::installation(dir.path()).unwrap();
+    fs::write(installation.root().join("share/doc/gcc/README"), "edited").unwrap();
+    assert!(matches!(
+        Pruner::new(policies.policy(&["p".into()], &triple()).unwrap()).prune(&installation),
+        Err(Error::Archive(linaro_archive::Error::Drifted(_)))
+    ));
+}
+
+#[test]
+fn reports_directories_it_cannot_remove() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = installation(dir.path()) else {
+        eprintln!("skipping: needs an x86-64 host gcc");
+        return;
+    };
+    let root = installation.root().to_owned();
+    // Verification leaves a file in a directory the policy drops, so it is
+    // no longer empty when its turn comes.
+    let driver = root.join(format!("bin/{TRIPLE}-gcc"));
+    let script = fs::read_to_string(&driver).unwrap().replacen(
+        "#!/bin/sh\n",
+        &format!("#!/bin/sh\ntouch {}/share/doc/gcc/late\n", root.display()),
+        1,
+    );
+    fs::write(&driver, script).unwrap();
+    stamp(&root);
+    let policy = policies().policy(&["kernel".into()], &triple()).unwrap();
+
+    let err = Pruner::new(policy).prune(&installation).unwrap_err();
+    assert!(
+        matches!(&err, Error::Io { path, .. } if *path == root.join("share/doc/gcc")),
+        "{err}"
+    );
+    // The removal was committed before any directory went: the files are
+    // gone rather than stranded next to the tree.
+    assert!(exists(&root, "share/doc/gcc/late"));
+    assert!(!exists(&root, "share/doc/gcc/README"));
+    assert!(!exists(&root, "bin/x86_64-linux-gnu-gfortran"));
+    assert!(!exists(&root, RECORD_FILE));
+    assert!(!dir.path().read_dir().unwrap().any(|e| e
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .contains("pruning")));
+}
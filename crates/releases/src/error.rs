@@ -0,0 +1,25 @@
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("malformed {}: {reason}", path.display())]
+    Sidecar { path: PathBuf, reason: String },
+    #[error("failed to run curl: {0}")]
+    Curl(io::Error),
+    #[error("{url}: {reason}")]
+    Fetch { url: String, reason: String },
+    #[error("{0} is not an http(s) URL")]
+    BadUrl(String),
+    #[error(transparent)]
+    Manifest(#[from] linaro_manifest::Error),
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
@@ -0,0 +1,129 @@
+//! Mirroring the parts of the release site that [`scan`](crate::scan)
+//! reads: directory listings and the sidecar files next to the archives,
+//! but not the archives themselves.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::listing::{self, Link};
+use crate::{parse_archive_name, Error};
+
+/// The published release index.
+pub const LINARO_INDEX: &str = "https://releases.linaro.org/components/toolchain/binaries/";
+
+/// Downloads listings and sidecars with `curl`.
+#[derive(Debug, Clone)]
+pub struct Fetcher {
+    curl: OsString,
+    depth: usize,
+}
+
+impl Default for Fetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fetcher {
+    pub fn new() -> Self {
+        Self {
+            curl: "curl".into(),
+            depth: 2,
+        }
+    }
+
+    /// Overrides the `curl` program used for downloads.
+    pub fn curl(mut self, program: impl Into<OsString>) -> Self {
+        self.curl = program.into();
+        self
+    }
+
+    /// How many directory levels below the starting URL to follow. The
+    /// default of 2 covers `<release>/<target>/` under the index root.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Mirrors the listing at `url` into `out`, returning the files written.
+    pub fn fetch(&self, url: &str, out: &Path) -> Result<Vec<PathBuf>, Error> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(Error::BadUrl(url.to_owned()));
+        }
+        let base = format!("{}/", url.trim_end_matches('/'));
+        let mut written = Vec::new();
+        let mut pending = vec![(base, out.to_owned(), self.depth)];
+        while let Some((url, dir, depth)) = pending.pop() {
+            fs::create_dir_all(&dir).map_err(Error::io(&dir))?;
+            let index = dir.join("index.html");
+            self.download(&url, &index)?;
+            let html = fs::read_to_string(&index).map_err(Error::io(&index))?;
+            written.push(index);
+            for link in listing::parse(&html) {
+                let Some(child) = child_url(&url, &link) else {
+                    continue;
+                };
+                if link.dir {
+                    if depth > 0 {
+                        pending.push((child, dir.join(&link.name), depth - 1));
+                    }
+                } else if is_sidecar(&link.name) {
+                    let path = dir.join(&link.name);
+                    self.download(&child, &path)?;
+                    written.push(path);
+                }
+            }
+        }
+        written.sort();
+        Ok(written)
+    }
+
+    fn download(&self, url: &str, path: &Path) -> Result<(), Error> {
+        let output = Command::new(&self.curl)
+            .args(["--fail", "--silent", "--show-error", "--location"])
+            .args(["--retry", "2", "--output"])
+            .arg(path)
+            .arg(url)
+            .output()
+            .map_err(Error::Curl)?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(Error::Fetch {
+            url: url.to_owned(),
+            reason: stderr.lines().last().map_or_else(
+                || format!("curl exited with {}", output.status),
+                str::to_owned,
+            ),
+        })
+    }
+}
+
+/// The URL `link` on the listing of `base` points to, if it is a direct
+/// child of `base`.
+fn child_url(base: &str, link: &Link) -> Option<String> {
+    let href = link.href.split(['?', '#']).next()?;
+    let url = if href.contains("://") {
+        href.to_owned()
+    } else if href.starts_with('/') {
+        let scheme_end = base.find("://")? + 3;
+        let origin_end = base[scheme_end..]
+            .find('/')
+            .map_or(base.len(), |i| scheme_end + i);
+        format!("{}{href}", &base[..origin_end])
+    } else {
+        format!("{base}{href}")
+    };
+    let rest = url.strip_prefix(base)?;
+    (rest.trim_end_matches('/') == link.name).then_some(url)
+}
+
+fn is_sidecar(name: &str) -> bool {
+    [".sha256", ".asc"].iter().any(|ext| {
+        name.strip_suffix(ext)
+            .is_some_and(|archive| parse_archive_name(archive).is_some())
+    })
+}
@@ -0,0 +1,338 @@
+//! Finds toolchains Linaro has published that the manifest does not list
+//! yet, and proposes manifest entries for them.
+//!
+//! Everything works on a saved copy of the release site: a directory tree
+//! with the `index.html` listing of each directory, plus the `.sha256` and
+//! `.asc` files published next to the archives. [`fetch::Fetcher`] can
+//! mirror that much of the site with `curl`; [`scan`] and [`Proposer`] never
+//! touch the network.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use linaro_manifest::{sha256_file, GccVersion, Manifest, Release, Sha256, Toolchain, Triple};
+use serde::Serialize;
+
+mod error;
+pub mod fetch;
+pub mod listing;
+mod patch;
+
+pub use error::Error;
+
+/// Hosts Linaro builds toolchains for, as they appear in archive names.
+pub const HOSTS: &[&str] = &["x86_64", "i686", "aarch64", "armv8l", "i686-mingw32"];
+
+/// A toolchain archive found in the release index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Published {
+    pub gcc: GccVersion,
+    pub release: Release,
+    pub target: Triple,
+    pub host: String,
+    pub archive: String,
+    /// The directory listing it, relative to the scanned root.
+    pub location: String,
+    /// Exact size from the listing, if it gives one.
+    pub size: Option<u64>,
+    /// Digest from `<archive>.sha256`.
+    pub sha256: Option<Sha256>,
+    /// Whether `<archive>.asc` is an OpenPGP signature. Older releases
+    /// publish an MD5 checksum under that name instead.
+    pub signed: bool,
+}
+
+/// Splits a Linaro archive name such as
+/// `gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz` into GCC
+/// version, release, host and target.
+pub fn parse_archive_name(name: &str) -> Option<(GccVersion, Release, String, Triple)> {
+    let rest = name.strip_prefix("gcc-linaro-")?.strip_suffix(".tar.xz")?;
+    let (gcc, rest) = rest.split_once('-')?;
+    let gcc: GccVersion = gcc.parse().ok()?;
+    HOSTS.iter().find_map(|host| {
+        let (release, target) = rest.split_once(&format!("-{host}_"))?;
+        Some((
+            gcc,
+            release.parse().ok()?,
+            (*host).to_owned(),
+            target.parse().ok()?,
+        ))
+    })
+}
+
+/// Reads every `index.html` under `root` and returns the toolchain archives
+/// they list, with whatever sidecar files were saved next to them.
+pub fn scan(root: &Path) -> Result<Vec<Published>, Error> {
+    let mut found = BTreeMap::new();
+    let mut dirs = vec![root.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = fs::read_dir(&dir)
+            .map_err(Error::io(&dir))?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::io(&dir))?;
+        entries.sort();
+        dirs.extend(entries.into_iter().rev().filter(|p| p.is_dir()));
+
+        let index = dir.join("index.html");
+        if !index.is_file() {
+            continue;
+        }
+        let html = fs::read_to_string(&index).map_err(Error::io(&index))?;
+        let location = dir
+            .strip_prefix(root)
+            .unwrap_or(&dir)
+            .to_string_lossy()
+            .into_owned();
+        for link in listing::parse(&html) {
+            let Some((gcc, release, host, target)) = parse_archive_name(&link.name) else {
+                continue;
+            };
+            if link.dir || found.contains_key(&link.name) {
+                continue;
+            }
+            let published = Published {
+                gcc,
+                release,
+                target,
+                host,
+                sha256: read_sha256(&dir, &link.name)?,
+                signed: read_signed(&dir, &link.name)?,
+                archive: link.name.clone(),
+                location: location.clone(),
+                size: link.size,
+            };
+            found.insert(link.name, published);
+        }
+    }
+    Ok(found.into_values().collect())
+}
+
+/// The digest in `<dir>/<archive>.sha256`, in `sha256sum` format or bare.
+fn read_sha256(dir: &Path, archive: &str) -> Result<Option<Sha256>, Error> {
+    let path = dir.join(format!("{archive}.sha256"));
+    let Some(text) = read_optional(&path)? else {
+        return Ok(None);
+    };
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(digest), name) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if name.is_some_and(|n| n.trim_start_matches('*') != archive) {
+            continue;
+        }
+        return digest.parse().map(Some).map_err(|reason| Error::Sidecar {
+            path: path.clone(),
+            reason,
+        });
+    }
+    Err(Error::Sidecar {
+        path,
+        reason: format!("no digest for {archive}"),
+    })
+}
+
+fn read_signed(dir: &Path, archive: &str) -> Result<bool, Error> {
+    let path = dir.join(format!("{archive}.asc"));
+    Ok(read_optional(&path)?.is_some_and(|text| text.contains("-----BEGIN PGP SIGNATURE-----")))
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>, Error> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(Error::io(path)(err)),
+    }
+}
+
+/// A published archive that should be added but cannot be described fully.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Incomplete {
+    pub archive: String,
+    pub reason: String,
+}
+
+/// A published archive that disagrees with what the manifest records.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Conflict {
+    pub archive: String,
+    pub reason: String,
+}
+
+/// What [`Proposer::propose`] suggests changing in the manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Proposal {
+    /// New entries, ordered by target, GCC version and host.
+    pub added: Vec<Toolchain>,
+    pub incomplete: Vec<Incomplete>,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Proposal {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.incomplete.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+/// Decides which published toolchains belong in a manifest.
+///
+/// For a target and host the manifest already tracks, every newer GCC
+/// version is proposed; for one it does not, only the newest.
+#[derive(Debug, Clone)]
+pub struct Proposer<'a> {
+    manifest: &'a Manifest,
+    hosts: BTreeSet<String>,
+    archives: Option<PathBuf>,
+}
+
+impl<'a> Proposer<'a> {
+    /// Proposes toolchains for the hosts `manifest` already has.
+    pub fn new(manifest: &'a Manifest) -> Self {
+        Self {
+            manifest,
+            hosts: manifest.toolchains.iter().map(|t| t.host.clone()).collect(),
+            archives: None,
+        }
+    }
+
+    /// Only proposes toolchains for these hosts.
+    pub fn hosts(mut self, hosts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.hosts = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Takes sizes and digests from archives already downloaded to `dir`
+    /// when the index does not give them.
+    pub fn archives(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.archives = Some(dir.into());
+        self
+    }
+
+    pub fn propose(&self, published: &[Published]) -> Result<Proposal, Error> {
+        let mut proposal = Proposal::default();
+        let mut newest: BTreeMap<(&Triple, &str), GccVersion> = BTreeMap::new();
+        for toolchain in &self.manifest.toolchains {
+            let key = (&toolchain.target, toolchain.host.as_str());
+            let gcc = newest.entry(key).or_insert(toolchain.gcc);
+            *gcc = (*gcc).max(toolchain.gcc);
+        }
+
+        let mut candidates: BTreeMap<(&Triple, &str), Vec<&Published>> = BTreeMap::new();
+        for p in published {
+            if !self.hosts.contains(&p.host) {
+                continue;
+            }
+            if let Some(conflict) = self.conflict(p) {
+                proposal.conflicts.push(conflict);
+                continue;
+            }
+            let tracked = self
+                .manifest
+                .find(p.target.as_str(), p.gcc, &p.host)
+                .is_some();
+            let newer = newest
+                .get(&(&p.target, p.host.as_str()))
+                .map_or(true, |gcc| p.gcc > *gcc);
+            if !tracked && newer {
+                candidates
+                    .entry((&p.target, p.host.as_str()))
+                    .or_default()
+                    .push(p);
+            }
+        }
+
+        for ((target, host), mut found) in candidates {
+            found.sort_by_key(|p| (p.gcc, p.release.clone()));
+            // Several releases of one GCC version: only the latest counts.
+            found.dedup_by(|later, earlier| {
+                let same = later.gcc == earlier.gcc;
+                if same {
+                    *earlier = *later;
+                }
+                same
+            });
+            if !newest.contains_key(&(target, host)) {
+                found.drain(..found.len() - 1);
+            }
+            for p in found {
+                match self.describe(p)? {
+                    Ok(toolchain) => proposal.added.push(toolchain),
+                    Err(reason) => proposal.incomplete.push(Incomplete {
+                        archive: p.archive.clone(),
+                        reason,
+                    }),
+                }
+            }
+        }
+        proposal
+            .added
+            .sort_by(|a, b| (&a.target, a.gcc, &a.host).cmp(&(&b.target, b.gcc, &b.host)));
+        Ok(proposal)
+    }
+
+    fn conflict(&self, p: &Published) -> Option<Conflict> {
+        let listed = self.manifest.find_archive(&p.archive)?;
+        let reason = match (p.sha256, p.size) {
+            (Some(sha256), _) if sha256 != listed.sha256 => format!(
+                "published SHA-256 {sha256} differs from the manifest's {}",
+                listed.sha256
+            ),
+            (_, Some(size)) if size != listed.size => format!(
+                "published size {size} differs from the manifest's {}",
+                listed.size
+            ),
+            _ => return None,
+        };
+        Some(Conflict {
+            archive: p.archive.clone(),
+            reason,
+        })
+    }
+
+    /// The manifest entry for `p`, or why one cannot be written.
+    fn describe(&self, p: &Published) -> Result<Result<Toolchain, String>, Error> {
+        let mut size = p.size;
+        let mut sha256 = p.sha256;
+        let local = self
+            .archives
+            .as_ref()
+            .map(|dir| dir.join(&p.archive))
+            .filter(|path| path.is_file());
+        if let Some(path) = local {
+            let (actual, actual_size) = sha256_file(&path).map_err(Error::io(&path))?;
+            if sha256.is_some_and(|published| published != actual) {
+                return Ok(Err(format!(
+                    "{} does not match the published SHA-256",
+                    path.display()
+                )));
+            }
+            if size.is_some_and(|published| published != actual_size) {
+                return Ok(Err(format!(
+                    "{} does not match the published size",
+                    path.display()
+                )));
+            }
+            size = Some(actual_size);
+            sha256 = Some(actual);
+        }
+        let (Some(size), Some(sha256)) = (size, sha256) else {
+            let missing = match (size, sha256) {
+                (None, None) => "size and SHA-256",
+                (None, _) => "size",
+                _ => "SHA-256",
+            };
+            return Ok(Err(format!("{missing} not given by the index")));
+        };
+        Ok(Ok(Toolchain {
+            gcc: p.gcc,
+            release: p.release.clone(),
+            target: p.target.clone(),
+            host: p.host.clone(),
+            archive: p.archive.clone(),
+            size,
+            sha256,
+        }))
+    }
+}
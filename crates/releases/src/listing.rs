@@ -0,0 +1,111 @@
+//! Parsing of HTML directory listings.
+//!
+//! Only what the release site's pages have in common is relied on: one
+//! `<a href>` per entry, followed on the same row by a date and a size.
+//! Both nginx-style `<pre>` listings and table-based ones are understood.
+
+/// One entry of a directory listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The `href` as written in the page.
+    pub href: String,
+    /// The last path component of `href`, without a trailing `/`.
+    pub name: String,
+    pub dir: bool,
+    /// The size in bytes, when the listing gives it exactly. Sizes rounded
+    /// for display (`112.3M`) are not used.
+    pub size: Option<u64>,
+}
+
+/// The entries of the listing in `html`, in page order. Parent-directory,
+/// sorting and fragment links are left out.
+pub fn parse(html: &str) -> Vec<Link> {
+    // ASCII lowercasing keeps byte offsets, so `lower` indexes `html`.
+    let lower = html.to_ascii_lowercase();
+    let mut links = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = lower[pos..].find("<a ").map(|i| pos + i) {
+        let Some(tag_end) = lower[start..].find('>').map(|i| start + i) else {
+            break;
+        };
+        let href = attribute(&html[start..tag_end], &lower[start..tag_end], "href");
+        let body = tag_end + 1;
+        let after = lower[body..].find("</a>").map_or(body, |i| body + i + 4);
+        let row_end = ["<a ", "</tr>"]
+            .iter()
+            .filter_map(|end| lower[after..].find(end))
+            .min()
+            .map_or(html.len(), |i| after + i);
+        pos = after;
+
+        let Some(href) = href else { continue };
+        if href.is_empty()
+            || href.starts_with(['?', '#'])
+            || (href.contains(':') && !href.contains("://"))
+        {
+            continue;
+        }
+        let path = href.split(['?', '#']).next().unwrap_or_default();
+        let dir = path.ends_with('/');
+        let name = path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        if name.is_empty() || name == "." || name == ".." || path.ends_with("../") {
+            continue;
+        }
+        let size = strip_tags(&html[after..row_end])
+            .split_whitespace()
+            .last()
+            .filter(|token| token.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|token| token.parse().ok());
+        links.push(Link {
+            href: href.to_owned(),
+            name: name.to_owned(),
+            dir,
+            size: if dir { None } else { size },
+        });
+    }
+    links
+}
+
+/// The value of attribute `name` in the start tag `tag`.
+fn attribute<'a>(tag: &'a str, lower: &str, name: &str) -> Option<&'a str> {
+    let mut pos = 0;
+    while let Some(i) = lower[pos..].find(name).map(|i| pos + i) {
+        pos = i + name.len();
+        let preceded = lower[..i].ends_with(|c: char| c.is_ascii_whitespace());
+        let rest = lower[pos..].trim_start();
+        if !preceded || !rest.starts_with('=') {
+            continue;
+        }
+        let value_start = lower.len() - rest[1..].trim_start().len();
+        let value = &tag[value_start..];
+        return Some(match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value
+                .split(|c: char| c.is_ascii_whitespace())
+                .next()
+                .unwrap_or_default(),
+        });
+    }
+    None
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+}
@@ -0,0 +1,82 @@
+use std::fmt::Write;
+
+use linaro_manifest::Manifest;
+
+use crate::{Error, Proposal};
+
+/// Lines of unchanged context shown before the added entries.
+const CONTEXT: usize = 3;
+
+impl Proposal {
+    /// The manifest text `current` with the proposed entries appended.
+    /// The result is validated, so duplicates are caught here.
+    pub fn apply(&self, current: &str) -> Result<String, Error> {
+        let mut text = current.to_owned();
+        let added = self.added_toml()?;
+        if !added.is_empty() {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push('\n');
+            text.push_str(&added);
+        }
+        text.parse::<Manifest>()?;
+        Ok(text)
+    }
+
+    /// A unified diff adding the proposed entries to the manifest at `path`
+    /// (relative to the repository root), whose contents are `current`.
+    /// Empty when nothing is added; otherwise ready for `git apply`.
+    pub fn patch(&self, path: &str, current: &str) -> Result<String, Error> {
+        if self.added.is_empty() {
+            return Ok(String::new());
+        }
+        let updated = self.apply(current)?;
+        let old: Vec<_> = current.lines().collect();
+        let new: Vec<_> = updated.lines().collect();
+        // Only the last line can change: it gains a newline if it had none.
+        let unchanged = if current.ends_with('\n') || old.is_empty() {
+            old.len()
+        } else {
+            old.len() - 1
+        };
+        let start = unchanged.saturating_sub(CONTEXT);
+
+        let mut out = format!("--- a/{path}\n+++ b/{path}\n");
+        let range = |count: usize| {
+            let first = if count == 0 { start } else { start + 1 };
+            format!("{first},{count}")
+        };
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            range(old.len() - start),
+            range(new.len() - start)
+        );
+        for line in &old[start..unchanged] {
+            let _ = writeln!(out, " {line}");
+        }
+        if unchanged < old.len() {
+            let _ = writeln!(out, "-{}\n\\ No newline at end of file", old[unchanged]);
+        }
+        for line in &new[unchanged..] {
+            let _ = writeln!(out, "+{line}");
+        }
+        Ok(out)
+    }
+
+    /// The `[[toolchain]]` tables for the added entries, formatted as
+    /// [`Manifest::save`] would.
+    fn added_toml(&self) -> Result<String, Error> {
+        if self.added.is_empty() {
+            return Ok(String::new());
+        }
+        let manifest = Manifest {
+            toolchains: self.added.clone(),
+            ..Manifest::new()
+        };
+        let text = toml::to_string(&manifest).map_err(linaro_manifest::Error::from)?;
+        let tables = text.find("[[toolchain]]").unwrap_or(0);
+        Ok(text[tables..].to_owned())
+    }
+}
@@ -0,0 +1,295 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use linaro_golden::check_golden;
+use linaro_manifest::Manifest;
+use linaro_releases::fetch::Fetcher;
+use linaro_releases::{listing, parse_archive_name, scan, Error, Proposer, Published};
+
+const BASE: &str = "http://releases.test/components/toolchain/binaries/";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn published() -> Vec<Published> {
+    scan(&fixture("index")).unwrap()
+}
+
+#[test]
+fn parses_listings() {
+    let nginx =
+        fs::read_to_string(fixture("index/7.5-2019.12/aarch64-linux-gnu/index.html")).unwrap();
+    let links = listing::parse(&nginx);
+    assert_eq!(links.len(), 8);
+    assert_eq!(
+        links[0].name,
+        "gcc-linaro-7.5.0-2019.12-i686_aarch64-linux-gnu.tar.xz"
+    );
+    assert_eq!(links[0].size, Some(102222222));
+    assert_eq!(links[2].size, Some(131));
+
+    let table = fs::read_to_string(fixture("index/7.5-2019.12/index.html")).unwrap();
+    let links = listing::parse(&table);
+    let names: Vec<_> = links.iter().map(|l| (l.name.as_str(), l.dir)).collect();
+    assert_eq!(
+        names,
+        [
+            ("aarch64-linux-gnu", true),
+            ("aarch64_be-linux-gnu", true),
+            ("arm-linux-gnueabihf", true)
+        ]
+    );
+
+    // Rounded sizes are not sizes.
+    let table =
+        fs::read_to_string(fixture("index/7.5-2019.12/arm-linux-gnueabihf/index.html")).unwrap();
+    let links = listing::parse(&table);
+    assert_eq!(links[0].size, None);
+    assert_eq!(links[3].size, Some(5111111));
+
+    let links = listing::parse(
+        "<A HREF='/abs/dir/file.tar.xz?x=1'>f</A> 3\n<a href=\"mailto:x@y\">m</a>\n",
+    );
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].name, "file.tar.xz");
+    assert_eq!(links[0].size, Some(3));
+}
+
+#[test]
+fn parses_archive_names() {
+    let (gcc, release, host, target) =
+        parse_archive_name("gcc-linaro-7.5.0-2019.12-x86_64_aarch64_be-linux-gnu.tar.xz").unwrap();
+    assert_eq!(
+        (
+            gcc.to_string().as_str(),
+            release.as_str(),
+            host.as_str(),
+            target.as_str()
+        ),
+        ("7.5.0", "2019.12", "x86_64", "aarch64_be-linux-gnu")
+    );
+    let (_, release, host, target) =
+        parse_archive_name("gcc-linaro-7.4.1-2019.02-rc1-i686-mingw32_arm-eabi.tar.xz").unwrap();
+    assert_eq!(
+        (release.as_str(), host.as_str(), target.as_str()),
+        ("2019.02-rc1", "i686-mingw32", "arm-eabi")
+    );
+    for name in [
+        "runtime-gcc-linaro-7.5.0-2019.12-aarch64-linux-gnu.tar.xz",
+        "gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz.asc",
+        "gcc-linaro-7.5-2019.12-x86_64_aarch64-linux-gnu.tar.xz",
+        "gcc-linaro-7.5.0-2019.12-sparc_aarch64-linux-gnu.tar.xz",
+    ] {
+        assert_eq!(parse_archive_name(name), None, "{name}");
+    }
+}
+
+#[test]
+fn scans_saved_index() {
+    let published = published();
+    let archives: Vec<_> = published
+        .iter()
+        .map(|p| (p.location.as_str(), p.host.as_str(), p.target.as_str()))
+        .collect();
+    assert_eq!(
+        archives,
+        [
+            ("7.4-2019.02/aarch64-linux-gnu", "i686", "aarch64-linux-gnu"),
+            (
+                "7.4-2019.02/aarch64-linux-gnu",
+                "x86_64",
+                "aarch64-linux-gnu"
+            ),
+            (
+                "7.4-2019.02/aarch64_be-linux-gnu",
+                "x86_64",
+                "aarch64_be-linux-gnu"
+            ),
+            ("7.5-2019.12/aarch64-linux-gnu", "i686", "aarch64-linux-gnu"),
+            (
+                "7.5-2019.12/aarch64-linux-gnu",
+                "x86_64",
+                "aarch64-linux-gnu"
+            ),
+            (
+                "7.5-2019.12/aarch64_be-linux-gnu",
+                "x86_64",
+                "aarch64_be-linux-gnu"
+            ),
+            (
+                "7.5-2019.12/arm-linux-gnueabihf",
+                "x86_64",
+                "arm-linux-gnueabihf"
+            ),
+        ]
+    );
+    let aarch64 = &published[4];
+    assert_eq!(aarch64.size, Some(113333333));
+    assert!(aarch64.sha256.is_some());
+    assert!(aarch64.signed);
+    // An MD5 checksum published as `.asc` is not a signature.
+    assert!(!published[5].signed);
+    assert!(published[5].sha256.is_some());
+    assert_eq!(published[6].size, None);
+}
+
+#[test]
+fn proposes_newer_versions_and_new_targets() {
+    let manifest = Manifest::load(fixture("toolchains.toml")).unwrap();
+    let proposal = Proposer::new(&manifest).propose(&published()).unwrap();
+
+    let added: Vec<_> = proposal.added.iter().map(|t| t.archive.as_str()).collect();
+    assert_eq!(
+        added,
+        [
+            "gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz",
+            // New target: only the newest version.
+            "gcc-linaro-7.5.0-2019.12-x86_64_aarch64_be-linux-gnu.tar.xz",
+        ]
+    );
+    assert_eq!(
+        proposal.incomplete[0].archive,
+        "gcc-linaro-7.5.0-2019.12-x86_64_arm-linux-gnueabihf.tar.xz"
+    );
+    assert_eq!(proposal.incomplete[0].reason, "size not given by the index");
+    assert_eq!(proposal.conflicts.len(), 1);
+    assert_eq!(
+        proposal.conflicts[0].archive,
+        "gcc-linaro-7.4.1-2019.02-x86_64_aarch64-linux-gnu.tar.xz"
+    );
+
+    let i686 = Proposer::new(&manifest)
+        .hosts(["i686"])
+        .propose(&published())
+        .unwrap();
+    assert_eq!(
+        i686.added[0].archive,
+        "gcc-linaro-7.5.0-2019.12-i686_aarch64-linux-gnu.tar.xz"
+    );
+    assert_eq!(i686.added.len(), 1);
+}
+
+#[test]
+fn completes_entries_from_downloaded_archives() {
+    let manifest = Manifest::load(fixture("toolchains.toml")).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let archive = dir
+        .path()
+        .join("gcc-linaro-7.5.0-2019.12-x86_64_arm-linux-gnueabihf.tar.xz");
+    fs::write(&archive, "arm-linux-gnueabihf 7.5.0\n").unwrap();
+
+    let proposal = Proposer::new(&manifest)
+        .archives(dir.path())
+        .propose(&published())
+        .unwrap();
+    assert!(proposal.incomplete.is_empty());
+    let arm = &proposal.added[2];
+    assert_eq!(arm.target.as_str(), "arm-linux-gnueabihf");
+    assert_eq!(arm.size, 26);
+
+    let current = fs::read_to_string(fixture("toolchains.toml")).unwrap();
+    let patch = proposal.patch("toolchains.toml", &current).unwrap();
+    check_golden!("propose.patch", &patch);
+    let updated: Manifest = proposal.apply(&current).unwrap().parse().unwrap();
+    assert_eq!(updated.toolchains.len(), 5);
+
+    // A download that does not match the published digest is not used.
+    fs::write(&archive, "corrupt").unwrap();
+    let proposal = Proposer::new(&manifest)
+        .archives(dir.path())
+        .propose(&published())
+        .unwrap();
+    assert!(proposal.incomplete[0]
+        .reason
+        .ends_with("does not match the published SHA-256"));
+}
+
+#[test]
+fn patches_manifest_without_trailing_newline() {
+    let manifest = Manifest::load(fixture("toolchains.toml")).unwrap();
+    let proposal = Proposer::new(&manifest).propose(&published()).unwrap();
+    let current = fs::read_to_string(fixture("toolchains.toml")).unwrap();
+    let patch = proposal
+        .patch("toolchains.toml", current.trim_end())
+        .unwrap();
+    let lines: Vec<_> = patch.lines().collect();
+    assert_eq!(lines[2], "@@ -16,4 +16,22 @@");
+    assert_eq!(
+        lines[6],
+        "-sha256 = \"5414d90f525226804253ae4b175f319440e98f22097d52931736a4a596e7bf3e\""
+    );
+    assert_eq!(lines[7], "\\ No newline at end of file");
+
+    let empty = Proposer::new(&manifest).hosts(["armv8l"]);
+    assert_eq!(
+        empty
+            .propose(&published())
+            .unwrap()
+            .patch("toolchains.toml", &current)
+            .unwrap(),
+        ""
+    );
+}
+
+/// A `curl` that serves the fixture index for URLs under [`BASE`].
+fn stub_curl(dir: &Path) -> PathBuf {
+    let path = dir.join("curl");
+    fs::write(
+        &path,
+        format!(
+            "#!/bin/sh\n\
+             while [ $# -gt 1 ]; do\n\
+             \x20 case \"$1\" in --output) out=\"$2\"; shift ;; esac\n\
+             \x20 shift\n\
+             done\n\
+             path=\"${{1#{BASE}}}\"\n\
+             case \"$path\" in ''|*/) path=\"${{path}}index.html\" ;; esac\n\
+             cp \"{}/$path\" \"$out\" 2>/dev/null || {{\n\
+             \x20 echo 'curl: (22) The requested URL returned error: 404' >&2\n\
+             \x20 exit 22\n\
+             }}\n",
+            fixture("index").display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn fetches_listings_and_sidecars() {
+    let dir = tempfile::tempdir().unwrap();
+    let curl = stub_curl(dir.path());
+    let out = dir.path().join("index");
+    let written = Fetcher::new().curl(&curl).fetch(BASE, &out).unwrap();
+    assert_eq!(written.len(), 22);
+    assert!(!written
+        .iter()
+        .any(|p| p.extension().is_some_and(|e| e == "xz")));
+    assert_eq!(scan(&out).unwrap(), published());
+
+    let shallow = dir.path().join("shallow");
+    let written = Fetcher::new()
+        .curl(&curl)
+        .depth(0)
+        .fetch(BASE, &shallow)
+        .unwrap();
+    assert_eq!(written, [shallow.join("index.html")]);
+
+    let err = Fetcher::new()
+        .curl(&curl)
+        .fetch(&format!("{BASE}8.3-2019.03"), &dir.path().join("missing"))
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Fetch { reason, .. } if reason.ends_with("404")),
+        "{err}"
+    );
+    assert!(matches!(
+        Fetcher::new().fetch("ftp://releases.test/", &out),
+        Err(Error::BadUrl(_))
+    ));
+}
@@ -0,0 +1,332 @@
+//! Section-by-section comparison of two build output trees, and what the
+//! differences point to.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use linaro_elf::sections::{build_id, sections, Section};
+use serde::Serialize;
+
+use crate::Error;
+
+/// Printable runs shorter than this are not compared as strings.
+const MIN_STRING: usize = 4;
+
+const MONTHS: &[&str] = &[
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// How one output file compares between the builds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutputDiff {
+    /// Relative to the output directory.
+    pub path: String,
+    pub status: Status,
+    /// For ELF files, the differing sections; otherwise a single
+    /// `(contents)` entry.
+    pub sections: Vec<SectionDiff>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    Identical,
+    Differs,
+    /// Produced by only one of the builds.
+    Missing,
+}
+
+/// A section whose contents differ.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SectionDiff {
+    pub name: String,
+    /// Sizes in the first and second build; `None` where absent.
+    pub size: [Option<u64>; 2],
+    /// What was found to differ. Empty when no embedded value explains the
+    /// difference, typically offsets shifted by differences elsewhere.
+    pub causes: Vec<Cause>,
+}
+
+/// A value embedded differently by the two builds, with a sample of each.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Cause {
+    pub kind: CauseKind,
+    pub first: String,
+    pub second: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CauseKind {
+    /// The build directory.
+    Path,
+    /// A `__DATE__` or `__TIME__` expansion.
+    Timestamp,
+    /// The GNU build ID, which hashes the rest of the file.
+    BuildId,
+    /// Some other string.
+    Other,
+}
+
+/// Compares every file under `first` with its counterpart under `second`.
+/// `dirs` are the two builds' directories, looked for in differing strings.
+pub fn compare(first: &Path, second: &Path, dirs: [&Path; 2]) -> Result<Vec<OutputDiff>, Error> {
+    let mut paths = BTreeSet::new();
+    for root in [first, second] {
+        files(root, root, &mut paths)?;
+    }
+    let dirs = dirs.map(|d| d.to_string_lossy().into_owned());
+    let mut outputs = Vec::new();
+    for path in paths {
+        let read = |root: &Path| {
+            let file = root.join(&path);
+            match fs::read(&file) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(Error::io(file)(err)),
+            }
+        };
+        let (status, sections) = match (read(first)?, read(second)?) {
+            (Some(a), Some(b)) if a == b => (Status::Identical, Vec::new()),
+            (Some(a), Some(b)) => (Status::Differs, diff_files(&a, &b, &dirs)),
+            _ => (Status::Missing, Vec::new()),
+        };
+        outputs.push(OutputDiff {
+            path: path.to_string_lossy().into_owned(),
+            status,
+            sections,
+        });
+    }
+    Ok(outputs)
+}
+
+fn diff_files(a: &[u8], b: &[u8], dirs: &[String; 2]) -> Vec<SectionDiff> {
+    let (Ok(sections_a), Ok(sections_b)) = (sections(a), sections(b)) else {
+        return vec![SectionDiff {
+            name: "(contents)".to_owned(),
+            size: [Some(a.len() as u64), Some(b.len() as u64)],
+            causes: causes(a, b, dirs),
+        }];
+    };
+    let mut rest = keyed(sections_b);
+    let mut diffs = Vec::new();
+    for (key, first) in keyed(sections_a) {
+        let second = rest.remove(&key);
+        if second.as_ref() != Some(&first) {
+            diffs.push(diff_section(
+                key.0,
+                Some(&first),
+                second.as_ref(),
+                [a, b],
+                dirs,
+            ));
+        }
+    }
+    for ((name, _), second) in rest {
+        diffs.push(diff_section(name, None, Some(&second), [a, b], dirs));
+    }
+    if diffs.is_empty() {
+        diffs.push(SectionDiff {
+            name: "(headers)".to_owned(),
+            size: [Some(a.len() as u64), Some(b.len() as u64)],
+            causes: Vec::new(),
+        });
+    }
+    diffs
+}
+
+/// Sections keyed by name and occurrence, since objects can repeat names
+/// (`.group`, `.text.*` in COMDAT groups).
+fn keyed(sections: Vec<Section>) -> BTreeMap<(String, usize), Section> {
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    let mut keyed = BTreeMap::new();
+    for section in sections {
+        let n = seen.entry(section.name.clone()).or_default();
+        keyed.insert((section.name.clone(), *n), section);
+        *n += 1;
+    }
+    keyed
+}
+
+fn diff_section(
+    name: String,
+    a: Option<&Section>,
+    b: Option<&Section>,
+    files: [&[u8]; 2],
+    dirs: &[String; 2],
+) -> SectionDiff {
+    let causes = if name == ".note.gnu.build-id" {
+        let id = |file| {
+            build_id(file)
+                .ok()
+                .flatten()
+                .map(|id| hex(&id))
+                .unwrap_or_default()
+        };
+        vec![Cause {
+            kind: CauseKind::BuildId,
+            first: id(files[0]),
+            second: id(files[1]),
+        }]
+    } else if name == ".shstrtab" {
+        // Section names; any difference shows as sections present in only
+        // one build.
+        Vec::new()
+    } else {
+        causes(data(a), data(b), dirs)
+    };
+    SectionDiff {
+        name,
+        size: [a.map(|s| s.size), b.map(|s| s.size)],
+        causes,
+    }
+}
+
+fn data(section: Option<&Section>) -> &[u8] {
+    section.map_or(&[], |s| &s.data)
+}
+
+/// Classifies the strings found in only one of `a` and `b`, keeping one
+/// sample pair per kind.
+fn causes(a: &[u8], b: &[u8], dirs: &[String; 2]) -> Vec<Cause> {
+    let (strings_a, strings_b) = (strings(a), strings(b));
+    let mut samples: BTreeMap<CauseKind, [Option<String>; 2]> = BTreeMap::new();
+    for (side, (own, other)) in [(&strings_a, &strings_b), (&strings_b, &strings_a)]
+        .into_iter()
+        .enumerate()
+    {
+        for s in own.difference(other) {
+            let kind = if s.contains(&dirs[side]) {
+                CauseKind::Path
+            } else if has_timestamp(s) {
+                CauseKind::Timestamp
+            } else {
+                CauseKind::Other
+            };
+            samples.entry(kind).or_default()[side].get_or_insert_with(|| s.clone());
+        }
+    }
+    samples
+        .into_iter()
+        .map(|(kind, [first, second])| Cause {
+            kind,
+            first: first.unwrap_or_default(),
+            second: second.unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Runs of at least [`MIN_STRING`] printable ASCII characters, as `strings`
+/// would find them.
+fn strings(bytes: &[u8]) -> BTreeSet<String> {
+    bytes
+        .split(|b| !(b.is_ascii_graphic() || *b == b' '))
+        .filter(|run| run.len() >= MIN_STRING)
+        .map(|run| String::from_utf8_lossy(run).into_owned())
+        .collect()
+}
+
+/// Whether `s` holds a `__TIME__` (`hh:mm:ss`) or `__DATE__`
+/// (`Mmm dd yyyy`) expansion.
+fn has_timestamp(s: &str) -> bool {
+    let b = s.as_bytes();
+    let digits = |w: &[u8]| w.iter().all(u8::is_ascii_digit);
+    let time = b.windows(8).any(|w| {
+        digits(&w[..2]) && w[2] == b':' && digits(&w[3..5]) && w[5] == b':' && digits(&w[6..])
+    });
+    let date = b.windows(11).any(|w| {
+        MONTHS.iter().any(|m| w.starts_with(m.as_bytes()))
+            && w[3] == b' '
+            && (w[4] == b' ' || w[4].is_ascii_digit())
+            && w[5].is_ascii_digit()
+            && w[6] == b' '
+            && digits(&w[7..])
+    });
+    time || date
+}
+
+/// Build IDs are not `linaro_manifest::Sha256`s: `ld --build-id` picks the
+/// hash, so they are 20 bytes (sha1, the default), 16 (md5) or whatever
+/// length `--build-id=0x…` gave.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn files(root: &Path, dir: &Path, out: &mut BTreeSet<PathBuf>) -> Result<(), Error> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(Error::io(dir)(err)),
+    };
+    for entry in entries {
+        let path = entry.map_err(Error::io(dir))?.path();
+        if path.is_dir() {
+            files(root, &path, out)?;
+        } else {
+            out.insert(path.strip_prefix(root).unwrap_or(&path).to_owned());
+        }
+    }
+    Ok(())
+}
+
+/// A flag or setting that would remove a kind of difference.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Suggestion {
+    pub fix: String,
+    pub reason: String,
+}
+
+/// Suggests fixes for the differences in `outputs`, worded for a GCC
+/// release `gcc_major`.
+pub fn suggest(outputs: &[OutputDiff], gcc_major: u32) -> Vec<Suggestion> {
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    let mut add = |fix: &str, reason: &str| {
+        if !suggestions.iter().any(|s| s.fix == fix) {
+            suggestions.push(Suggestion {
+                fix: fix.to_owned(),
+                reason: reason.to_owned(),
+            });
+        }
+    };
+    for section in outputs.iter().flat_map(|o| &o.sections) {
+        let debug = section.name.starts_with(".debug") || section.name.starts_with(".zdebug");
+        for cause in &section.causes {
+            match cause.kind {
+                CauseKind::Path if debug && gcc_major >= 8 => add(
+                    "-ffile-prefix-map=<build dir>=.",
+                    "debug information records the build directory and source paths",
+                ),
+                CauseKind::Path if debug => add(
+                    "-fdebug-prefix-map=<build dir>=.",
+                    "debug information records the build directory and source paths",
+                ),
+                CauseKind::Path if gcc_major >= 8 => add(
+                    "-ffile-prefix-map=<build dir>=.",
+                    "__FILE__ and assertion messages embed source paths",
+                ),
+                CauseKind::Path => add(
+                    "pass source files by relative path",
+                    "__FILE__ and assertion messages embed source paths, \
+                     and GCC 7 has no -fmacro-prefix-map",
+                ),
+                CauseKind::Timestamp => add(
+                    "SOURCE_DATE_EPOCH=<commit time>",
+                    "__DATE__ and __TIME__ embed the build time; GCC expands them \
+                     from SOURCE_DATE_EPOCH when set, and -Wdate-time flags each use",
+                ),
+                CauseKind::Other
+                    if matches!(&*section.name, ".symtab" | ".strtab")
+                        || section.name.starts_with(".gnu.lto") =>
+                {
+                    add(
+                        "-frandom-seed=<output file>",
+                        "symbol names generated from a random seed differ",
+                    )
+                }
+                CauseKind::BuildId | CauseKind::Other => {}
+            }
+        }
+    }
+    suggestions
+}
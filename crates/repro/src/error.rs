@@ -0,0 +1,25 @@
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("no C, C++ or assembly sources in {}", .0.display())]
+    NoSources(PathBuf),
+    #[error("cannot run {}", program.display())]
+    Run { program: PathBuf, source: io::Error },
+    #[error("`{command}` failed:\n{output}")]
+    Build { command: String, output: String },
+    #[error("cannot read the GCC version from `{0}`")]
+    Version(String),
+    #[error(transparent)]
+    Toolchain(#[from] linaro_toolchain::Error),
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
@@ -0,0 +1,261 @@
+//! Checks whether a Linaro toolchain builds the same input reproducibly.
+//!
+//! An [`Auditor`] copies the input into two build directories at different
+//! depths, builds it in each a wall-clock second apart, and compares the
+//! outputs section by section ([`compare`]). Differences are traced back to
+//! the build directory, `__DATE__`/`__TIME__` expansions or the build ID
+//! where possible, and [`suggest`] names the flags or settings that would
+//! remove them.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use linaro_toolchain::Installation;
+use serde::Serialize;
+
+pub mod compare;
+mod error;
+
+pub use compare::{
+    compare, suggest, Cause, CauseKind, OutputDiff, SectionDiff, Status, Suggestion,
+};
+pub use error::Error;
+
+/// Stands for the build directory in [`Auditor::flags`].
+pub const DIR_PLACEHOLDER: &str = "{dir}";
+
+/// Extensions of the files compiled; `g++` is used for the C++ ones.
+const C_SOURCES: &[&str] = &["c", "s", "S"];
+const CXX_SOURCES: &[&str] = &["cc", "cpp", "cxx", "C"];
+
+/// Builds an input twice with one toolchain and compares the results.
+#[derive(Debug, Clone)]
+pub struct Auditor {
+    installation: Installation,
+    flags: Vec<String>,
+    env: Vec<(String, String)>,
+    link: bool,
+}
+
+/// The outcome of an audit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    /// The two build directories, which no longer exist.
+    pub dirs: [PathBuf; 2],
+    /// Every output, relative to the build's `out` directory.
+    pub outputs: Vec<OutputDiff>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Report {
+    pub fn reproducible(&self) -> bool {
+        self.outputs.iter().all(|o| o.status == Status::Identical)
+    }
+}
+
+impl Auditor {
+    pub fn new(installation: Installation) -> Self {
+        Self {
+            installation,
+            flags: Vec::new(),
+            env: Vec::new(),
+            link: false,
+        }
+    }
+
+    /// Adds compiler flags; [`DIR_PLACEHOLDER`] in them is replaced by the
+    /// build directory, e.g. `-ffile-prefix-map={dir}=.`.
+    pub fn flags<I, S>(mut self, flags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.flags.extend(flags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable for the compiler, e.g.
+    /// `SOURCE_DATE_EPOCH`.
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((name.into(), value.into()));
+        self
+    }
+
+    /// Also links the objects into `out/program`.
+    pub fn link(mut self, link: bool) -> Self {
+        self.link = link;
+        self
+    }
+
+    /// Builds `input`, a source file or a directory of sources, twice and
+    /// compares the outputs.
+    pub fn audit(&self, input: &Path) -> Result<Report, Error> {
+        let gcc_major = self.gcc_major()?;
+        let scratch = tempfile::tempdir().map_err(Error::io(std::env::temp_dir()))?;
+        let root = scratch
+            .path()
+            .canonicalize()
+            .map_err(Error::io(scratch.path()))?;
+        // The second build sits deeper so that paths differ in length too,
+        // as they would between two developers' checkouts.
+        let dirs = [root.join("a"), root.join("second-build/b")];
+        self.build(input, &dirs[0])?;
+        wait_for_next_second();
+        self.build(input, &dirs[1])?;
+        let outputs = compare(
+            &dirs[0].join("out"),
+            &dirs[1].join("out"),
+            [&dirs[0], &dirs[1]],
+        )?;
+        let suggestions = suggest(&outputs, gcc_major);
+        Ok(Report {
+            dirs,
+            outputs,
+            suggestions,
+        })
+    }
+
+    fn gcc_major(&self) -> Result<u32, Error> {
+        let gcc = self.installation.tool("gcc");
+        let output = Command::new(&gcc)
+            .arg("-dumpversion")
+            .output()
+            .map_err(|source| Error::Run {
+                program: gcc.clone(),
+                source,
+            })?;
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+        version
+            .split('.')
+            .next()
+            .and_then(|major| major.parse().ok())
+            .ok_or(Error::Version(version))
+    }
+
+    /// Copies `input` to `<dir>/src` and compiles each source, by absolute
+    /// path as build systems do, to `<dir>/out/<relative path>.o`.
+    fn build(&self, input: &Path, dir: &Path) -> Result<(), Error> {
+        let src = dir.join("src");
+        let out = dir.join("out");
+        if input.is_dir() {
+            copy_dir(input, &src)?;
+        } else {
+            fs::create_dir_all(&src).map_err(Error::io(&src))?;
+            let name = input.file_name().unwrap_or_default();
+            fs::copy(input, src.join(name)).map_err(Error::io(input))?;
+        }
+        let mut sources = Vec::new();
+        collect_sources(&src, &mut sources)?;
+        sources.sort();
+        if sources.is_empty() {
+            return Err(Error::NoSources(input.to_owned()));
+        }
+
+        let mut objects = Vec::new();
+        let mut cxx = false;
+        for source in &sources {
+            let relative = source.strip_prefix(&src).unwrap_or(source);
+            let object = out.join(format!("{}.o", relative.display()));
+            let parent = object.parent().expect("objects have a parent");
+            fs::create_dir_all(parent).map_err(Error::io(parent))?;
+            let is_cxx = extension_in(source, CXX_SOURCES);
+            cxx |= is_cxx;
+            let mut command = self.command(if is_cxx { "g++" } else { "gcc" }, dir);
+            command.arg("-c").arg(source).arg("-o").arg(&object);
+            run(command)?;
+            objects.push(object);
+        }
+        if self.link {
+            let mut command = self.command(if cxx { "g++" } else { "gcc" }, dir);
+            command.args(&objects).arg("-o").arg(out.join("program"));
+            run(command)?;
+        }
+        Ok(())
+    }
+
+    fn command(&self, tool: &str, dir: &Path) -> Command {
+        let dir_text = dir.to_string_lossy();
+        let mut command = Command::new(self.installation.tool(tool));
+        command
+            .current_dir(dir)
+            .args(
+                self.flags
+                    .iter()
+                    .map(|f| f.replace(DIR_PLACEHOLDER, &dir_text)),
+            )
+            .envs(self.env.iter().map(|(k, v)| (k, v)))
+            .env("LC_ALL", "C");
+        command
+    }
+}
+
+fn run(mut command: Command) -> Result<(), Error> {
+    let program = PathBuf::from(command.get_program());
+    let output = command
+        .output()
+        .map_err(|source| Error::Run { program, source })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let words: Vec<_> = std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(OsStr::to_string_lossy)
+        .collect();
+    Err(Error::Build {
+        command: words.join(" "),
+        output: String::from_utf8_lossy(&output.stderr)
+            .trim_end()
+            .to_owned(),
+    })
+}
+
+/// Sleeps until the wall clock enters a new second, so `__TIME__` differs
+/// between the builds.
+fn wait_for_next_second() {
+    let now = || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    };
+    let start = now();
+    while now() == start {
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn extension_in(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|e| extensions.contains(&e))
+}
+
+fn collect_sources(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(dir).map_err(Error::io(dir))? {
+        let path = entry.map_err(Error::io(dir))?.path();
+        if path.is_dir() {
+            collect_sources(&path, out)?;
+        } else if extension_in(&path, C_SOURCES) || extension_in(&path, CXX_SOURCES) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), Error> {
+    fs::create_dir_all(to).map_err(Error::io(to))?;
+    for entry in fs::read_dir(from).map_err(Error::io(from))? {
+        let path = entry.map_err(Error::io(from))?.path();
+        let target = to.join(path.file_name().unwrap_or_default());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target).map_err(Error::io(&path))?;
+        }
+    }
+    Ok(())
+}
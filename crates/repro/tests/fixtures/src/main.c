@@ -0,0 +1,9 @@
+#include <stdio.h>
+
+#include "util.h"
+
+int main(void)
+{
+	printf("%s built %s %s\n", __FILE__, __DATE__, __TIME__);
+	return square(3) == 9 ? 0 : 1;
+}
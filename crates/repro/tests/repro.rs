@@ -0,0 +1,243 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use linaro_repro::{
+    compare, suggest, Auditor, Cause, CauseKind, Error, OutputDiff, SectionDiff, Status,
+};
+use linaro_toolchain::Installation;
+
+const TRIPLE: &str = "x86_64-linux-gnu";
+
+fn fixtures() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/src"))
+}
+
+/// A fake toolchain at `<dir>/toolchain` whose drivers run the host
+/// compiler. `None` without a host GCC.
+fn installation(dir: &Path) -> Option<Installation> {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return None;
+    }
+    let bin = dir.join("toolchain/bin");
+    fs::create_dir_all(&bin).unwrap();
+    for tool in ["gcc", "g++"] {
+        let host = Path::new("/usr/bin").join(tool);
+        if !host.exists() {
+            return None;
+        }
+        let driver = bin.join(format!("{TRIPLE}-{tool}"));
+        fs::write(
+            &driver,
+            format!("#!/bin/sh\nexec {} \"$@\"\n", host.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&driver, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    Some(Installation::open(dir.join("toolchain")).unwrap())
+}
+
+fn output<'a>(outputs: &'a [OutputDiff], path: &str) -> &'a OutputDiff {
+    outputs.iter().find(|o| o.path == path).unwrap()
+}
+
+fn kinds(output: &OutputDiff, debug: bool) -> Vec<CauseKind> {
+    let mut kinds: Vec<_> = output
+        .sections
+        .iter()
+        .filter(|s| s.name.starts_with(".debug") == debug)
+        .flat_map(|s| s.causes.iter().map(|c| c.kind))
+        .collect();
+    kinds.sort();
+    kinds.dedup();
+    kinds
+}
+
+#[test]
+fn finds_paths_and_timestamps() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = installation(dir.path()) else {
+        eprintln!("skipping: needs a host gcc");
+        return;
+    };
+    let report = Auditor::new(installation)
+        .flags(["-g", "-O1"])
+        .audit(fixtures())
+        .unwrap();
+    assert!(!report.reproducible());
+
+    let main = output(&report.outputs, "main.c.o");
+    assert_eq!(main.status, Status::Differs);
+    assert_eq!(kinds(main, true), [CauseKind::Path]);
+    assert_eq!(kinds(main, false), [CauseKind::Path, CauseKind::Timestamp]);
+    let path = main
+        .sections
+        .iter()
+        .flat_map(|s| &s.causes)
+        .find(|c| c.kind == CauseKind::Path)
+        .unwrap();
+    assert!(path.first.contains(&*report.dirs[0].to_string_lossy()));
+    assert!(path.second.contains(&*report.dirs[1].to_string_lossy()));
+
+    // Without __FILE__ or __DATE__, only the debug information differs.
+    let util = output(&report.outputs, "util.c.o");
+    assert_eq!(util.status, Status::Differs);
+    assert_eq!(kinds(util, true), [CauseKind::Path]);
+    assert_eq!(kinds(util, false), []);
+
+    let fixes: Vec<_> = report.suggestions.iter().map(|s| &*s.fix).collect();
+    assert_eq!(
+        fixes,
+        [
+            "-ffile-prefix-map=<build dir>=.",
+            "SOURCE_DATE_EPOCH=<commit time>"
+        ]
+    );
+}
+
+#[test]
+fn neutralized_builds_are_reproducible() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = installation(dir.path()) else {
+        eprintln!("skipping: needs a host gcc");
+        return;
+    };
+    let report = Auditor::new(installation)
+        .flags(["-g", "-O1", "-ffile-prefix-map={dir}=."])
+        .env("SOURCE_DATE_EPOCH", "1500000000")
+        .link(true)
+        .audit(fixtures())
+        .unwrap();
+    assert!(report.reproducible(), "{:#?}", report.outputs);
+    let paths: Vec<_> = report.outputs.iter().map(|o| &*o.path).collect();
+    assert_eq!(paths, ["main.c.o", "program", "util.c.o"]);
+    assert!(report.suggestions.is_empty());
+}
+
+#[test]
+fn linked_programs_differ_in_build_id() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = installation(dir.path()) else {
+        eprintln!("skipping: needs a host gcc");
+        return;
+    };
+    let report = Auditor::new(installation)
+        .flags(["-Wl,--build-id", "-ffile-prefix-map={dir}=."])
+        .link(true)
+        .audit(fixtures())
+        .unwrap();
+    let program = output(&report.outputs, "program");
+    assert_eq!(program.status, Status::Differs);
+    let build_id = program
+        .sections
+        .iter()
+        .find(|s| s.name == ".note.gnu.build-id")
+        .unwrap();
+    assert_eq!(build_id.causes[0].kind, CauseKind::BuildId);
+    assert_eq!(build_id.causes[0].first.len(), 40);
+    assert_ne!(build_id.causes[0].first, build_id.causes[0].second);
+    // Only __DATE__ and __TIME__ are left to fix.
+    let fixes: Vec<_> = report.suggestions.iter().map(|s| &*s.fix).collect();
+    assert_eq!(fixes, ["SOURCE_DATE_EPOCH=<commit time>"]);
+}
+
+#[test]
+fn reports_failed_builds() {
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = installation(dir.path()) else {
+        eprintln!("skipping: needs a host gcc");
+        return;
+    };
+    // main.c alone lacks the header it includes.
+    let err = Auditor::new(installation)
+        .link(true)
+        .audit(&fixtures().join("main.c"))
+        .unwrap_err();
+    assert!(matches!(err, Error::Build { .. }), "{err}");
+    assert!(err.to_string().contains("util.h"), "{err}");
+}
+
+#[test]
+fn compares_other_files_as_strings() {
+    let dir = tempfile::tempdir().unwrap();
+    let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+    for (root, stamp) in [(&a, "Jan  5 2024 10:00:00"), (&b, "Jan  5 2024 10:00:01")] {
+        fs::create_dir_all(root.join("out/gen")).unwrap();
+        let text = format!("source {}/src/x.c\nbuilt {stamp}\n", root.display());
+        fs::write(root.join("out/gen/info.txt"), text).unwrap();
+        fs::write(root.join("out/same.txt"), "same").unwrap();
+    }
+    fs::write(a.join("out/only-a.txt"), "a").unwrap();
+
+    let outputs = compare(&a.join("out"), &b.join("out"), [&a, &b]).unwrap();
+    let statuses: Vec<_> = outputs.iter().map(|o| (&*o.path, o.status)).collect();
+    assert_eq!(
+        statuses,
+        [
+            ("gen/info.txt", Status::Differs),
+            ("only-a.txt", Status::Missing),
+            ("same.txt", Status::Identical),
+        ]
+    );
+    let info = &outputs[0].sections[0];
+    assert_eq!(info.name, "(contents)");
+    assert_eq!(
+        info.causes,
+        [
+            Cause {
+                kind: CauseKind::Path,
+                first: format!("source {}/src/x.c", a.display()),
+                second: format!("source {}/src/x.c", b.display()),
+            },
+            Cause {
+                kind: CauseKind::Timestamp,
+                first: "built Jan  5 2024 10:00:00".to_owned(),
+                second: "built Jan  5 2024 10:00:01".to_owned(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn suggestions_follow_the_gcc_release() {
+    let section = |name: &str, kind| SectionDiff {
+        name: name.to_owned(),
+        size: [Some(1), Some(1)],
+        causes: vec![Cause {
+            kind,
+            first: String::new(),
+            second: String::new(),
+        }],
+    };
+    let outputs = [OutputDiff {
+        path: "main.c.o".to_owned(),
+        status: Status::Differs,
+        sections: vec![
+            section(".debug_str", CauseKind::Path),
+            section(".rodata.str1.1", CauseKind::Path),
+            section(".symtab", CauseKind::Other),
+            section(".text", CauseKind::Other),
+        ],
+    }];
+    let fixes = |major| {
+        suggest(&outputs, major)
+            .into_iter()
+            .map(|s| s.fix)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        fixes(7),
+        [
+            "-fdebug-prefix-map=<build dir>=.",
+            "pass source files by relative path",
+            "-frandom-seed=<output file>",
+        ]
+    );
+    assert_eq!(
+        fixes(12),
+        [
+            "-ffile-prefix-map=<build dir>=.",
+            "-frandom-seed=<output file>"
+        ]
+    );
+}
@@ -0,0 +1,188 @@
+//! CycloneDX 1.5 JSON documents.
+
+use serde::Serialize;
+
+use crate::{Bom, Component, Kind};
+
+pub const SPEC_VERSION: &str = "1.5";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Document<'a> {
+    bom_format: &'a str,
+    spec_version: &'a str,
+    version: u32,
+    metadata: Metadata<'a>,
+    components: Vec<Entry<'a>>,
+    dependencies: Vec<Dependency<'a>>,
+}
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    timestamp: &'a str,
+    tools: Tools,
+    component: Entry<'a>,
+}
+
+#[derive(Serialize)]
+struct Tools {
+    components: Vec<Tool>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supplier: Option<Supplier>,
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hashes: Vec<Hash>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<License<'a>>,
+    #[serde(rename = "externalReferences", skip_serializing_if = "Vec::is_empty")]
+    external_references: Vec<Reference<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    properties: Vec<Property<'a>>,
+}
+
+#[derive(Serialize)]
+struct Supplier {
+    name: &'static str,
+}
+
+#[derive(Serialize)]
+struct Hash {
+    alg: &'static str,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct License<'a> {
+    expression: &'a str,
+}
+
+#[derive(Serialize)]
+struct Reference<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    url: &'a str,
+}
+
+#[derive(Serialize)]
+struct Property<'a> {
+    name: &'static str,
+    value: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Dependency<'a> {
+    #[serde(rename = "ref")]
+    reference: &'a str,
+    depends_on: Vec<&'a str>,
+}
+
+fn entry(component: &Component) -> Entry<'_> {
+    Entry {
+        kind: match component.kind {
+            Kind::Toolchain | Kind::Tool => "application",
+            Kind::Library => "library",
+        },
+        bom_ref: &component.id,
+        supplier: (component.kind == Kind::Toolchain).then_some(Supplier { name: "Linaro" }),
+        name: &component.name,
+        version: component.version.as_deref(),
+        hashes: component
+            .sha256
+            .iter()
+            .map(|sha256| Hash {
+                alg: "SHA-256",
+                content: sha256.to_string(),
+            })
+            .collect(),
+        licenses: component
+            .license
+            .iter()
+            .map(|expression| License { expression })
+            .collect(),
+        external_references: component
+            .source
+            .iter()
+            .map(|url| Reference {
+                kind: "source-distribution",
+                url,
+            })
+            .collect(),
+        properties: component
+            .files
+            .iter()
+            .map(|value| Property {
+                name: "linaro:file",
+                value,
+            })
+            .collect(),
+    }
+}
+
+/// Writes `bom` as a CycloneDX JSON document. The toolchain is the
+/// document's subject and depends on each shipped component; the compiler
+/// depends on the libraries linked into it.
+pub fn json(bom: &Bom) -> String {
+    let mut dependencies = vec![Dependency {
+        reference: &bom.toolchain.id,
+        depends_on: bom
+            .components
+            .iter()
+            .filter(|c| c.linked_into.is_none())
+            .map(|c| &*c.id)
+            .collect(),
+    }];
+    for component in &bom.components {
+        let linked: Vec<_> = bom
+            .components
+            .iter()
+            .filter(|c| c.linked_into.as_ref() == Some(&component.id))
+            .map(|c| &*c.id)
+            .collect();
+        if !linked.is_empty() {
+            dependencies.push(Dependency {
+                reference: &component.id,
+                depends_on: linked,
+            });
+        }
+    }
+    let document = Document {
+        bom_format: "CycloneDX",
+        spec_version: SPEC_VERSION,
+        version: 1,
+        metadata: Metadata {
+            timestamp: &bom.created,
+            tools: Tools {
+                components: vec![Tool {
+                    kind: "application",
+                    name: "linaro-prebuilts",
+                    version: env!("CARGO_PKG_VERSION"),
+                }],
+            },
+            component: entry(&bom.toolchain),
+        },
+        components: bom.components.iter().map(entry).collect(),
+        dependencies,
+    };
+    let mut text = serde_json::to_string_pretty(&document).expect("CycloneDX documents serialize");
+    text.push('\n');
+    text
+}
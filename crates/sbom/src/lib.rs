@@ -0,0 +1,320 @@
+//! Bills of materials for the vendored toolchains, for the license and
+//! provenance records of devices shipping code built with them.
+//!
+//! A [`Bom`] lists what an installed toolchain is made of: the compiler and
+//! binutils named by `gcc -v`, the libraries statically linked into the
+//! compiler, the GCC runtime libraries (libgcc, libstdc++, libatomic, ...)
+//! found in the tree, which end up in the programs it builds, and the
+//! sysroot's glibc. Each component carries its version, SPDX license
+//! expression and source location. [`spdx`] and [`cyclonedx`] write it out.
+
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use linaro_archive::install::read_stamp;
+use linaro_manifest::Sha256;
+use linaro_toolchain::{Info, Installation};
+use serde::Serialize;
+
+pub mod cyclonedx;
+mod error;
+pub mod spdx;
+
+pub use error::Error;
+
+/// License of the GCC runtime libraries, which the exception allows to be
+/// combined with programs under any license.
+pub const RUNTIME_LICENSE: &str = "GPL-3.0-or-later WITH GCC-exception-3.1";
+
+/// GCC runtime libraries: component id, name and the file name prefixes of
+/// their static and shared libraries.
+const RUNTIMES: &[(&str, &str, &[&str])] = &[
+    (
+        "libgcc",
+        "libgcc",
+        &["libgcc.", "libgcc_eh.", "libgcc_s.", "libgcov."],
+    ),
+    ("libstdcxx", "libstdc++", &["libstdc++.", "libsupc++."]),
+    ("libatomic", "libatomic", &["libatomic."]),
+    ("libgomp", "libgomp", &["libgomp."]),
+    ("libitm", "libitm", &["libitm."]),
+    ("libquadmath", "libquadmath", &["libquadmath."]),
+    ("libssp", "libssp", &["libssp.", "libssp_nonshared."]),
+    ("libasan", "libasan", &["libasan."]),
+    ("liblsan", "liblsan", &["liblsan."]),
+    ("libtsan", "libtsan", &["libtsan."]),
+    ("libubsan", "libubsan", &["libubsan."]),
+    ("libgfortran", "libgfortran", &["libgfortran."]),
+];
+
+/// Libraries GCC's `--with-<name>` configure options link into the
+/// compiler: option name, component name and license.
+const HOST_LIBRARIES: &[(&str, &str, &str)] = &[
+    ("gmp", "GMP", "LGPL-3.0-or-later OR GPL-2.0-or-later"),
+    ("mpfr", "MPFR", "LGPL-3.0-or-later"),
+    ("mpc", "MPC", "LGPL-3.0-or-later"),
+    ("isl", "isl", "MIT"),
+];
+
+const LINARO_RELEASES: &str = "https://releases.linaro.org/components/toolchain";
+const GNU_MIRROR: &str = "https://ftp.gnu.org/gnu";
+
+/// What a toolchain is made of.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Bom {
+    /// Document name: the archive's name without `.tar.xz`, or a name made
+    /// from the target and GCC version for an unstamped tree.
+    pub name: String,
+    /// Creation time, RFC 3339 in UTC.
+    pub created: String,
+    /// The toolchain as a whole.
+    pub toolchain: Component,
+    pub components: Vec<Component>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Component {
+    /// Identifier within the document, e.g. `libstdcxx`.
+    pub id: String,
+    pub name: String,
+    pub kind: Kind,
+    pub version: Option<String>,
+    /// SPDX license expression; `None` when not known.
+    pub license: Option<String>,
+    /// Where the sources can be downloaded; `None` when not known.
+    pub source: Option<String>,
+    pub sha256: Option<Sha256>,
+    /// Files of the component in the tree, relative to its top.
+    pub files: Vec<String>,
+    /// Id of the component this one is statically linked into; otherwise
+    /// it is shipped in the toolchain directly.
+    pub linked_into: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    /// A toolchain archive.
+    Toolchain,
+    /// Programs run on the build host.
+    Tool,
+    /// Libraries, linked into the compiler or into what it builds.
+    Library,
+}
+
+/// The archive an installed tree came from, as recorded in its stamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Archive {
+    pub name: String,
+    pub sha256: Sha256,
+}
+
+impl Bom {
+    /// Collects the bill of materials of the toolchain installed at
+    /// `installation`, running its drivers for the versions.
+    pub fn collect(installation: &Installation, created: impl Into<String>) -> Result<Self, Error> {
+        let root = installation.root();
+        let info = installation.introspect()?;
+        let archive = match read_stamp(root) {
+            Ok(stamp) => Some(Archive {
+                name: stamp.archive,
+                sha256: stamp.sha256,
+            }),
+            Err(linaro_archive::Error::Unstamped(_)) => None,
+            Err(err) => return Err(err.into()),
+        };
+        let mut files = Vec::new();
+        list_files(root, root, &mut files)?;
+        files.sort();
+        Ok(Self::new(&info, archive.as_ref(), &files, created))
+    }
+
+    /// Builds the bill of materials from what is known about a toolchain:
+    /// its driver's report, its archive and the files of the tree.
+    pub fn new(
+        info: &Info,
+        archive: Option<&Archive>,
+        files: &[String],
+        created: impl Into<String>,
+    ) -> Self {
+        let gcc = info.gcc_version.to_string();
+        // `Linaro GCC 7.5-2019.12` names the Linaro release.
+        let linaro = info
+            .gcc_package
+            .as_deref()
+            .and_then(|p| p.strip_prefix("Linaro GCC "));
+        let gcc_source = match linaro {
+            Some(release) => {
+                format!("{LINARO_RELEASES}/gcc-linaro/{release}/gcc-linaro-{release}.tar.xz")
+            }
+            None => format!("{GNU_MIRROR}/gcc/gcc-{gcc}/gcc-{gcc}.tar.xz"),
+        };
+        let component = |id: &str, name: &str, kind, version: Option<&str>| Component {
+            id: id.to_owned(),
+            name: name.to_owned(),
+            kind,
+            version: version.map(str::to_owned),
+            license: None,
+            source: None,
+            sha256: None,
+            files: Vec::new(),
+            linked_into: None,
+        };
+
+        let name = match archive {
+            Some(archive) => archive
+                .name
+                .strip_suffix(".tar.xz")
+                .unwrap_or(&archive.name)
+                .to_owned(),
+            None => format!("gcc-linaro-{gcc}-{}", info.target),
+        };
+        let toolchain = Component {
+            source: archive.zip(linaro).map(|(archive, release)| {
+                format!(
+                    "{LINARO_RELEASES}/binaries/{release}/{}/{}",
+                    info.target, archive.name
+                )
+            }),
+            sha256: archive.map(|a| a.sha256),
+            ..component(
+                "toolchain",
+                &name,
+                Kind::Toolchain,
+                Some(linaro.unwrap_or(&gcc)),
+            )
+        };
+
+        let mut components = vec![Component {
+            license: Some("GPL-3.0-or-later".to_owned()),
+            source: Some(gcc_source.clone()),
+            ..component("gcc", "GCC", Kind::Tool, Some(&gcc))
+        }];
+        for &(option, name, license) in HOST_LIBRARIES {
+            let flag = format!("--with-{option}");
+            let configured = info.configure_args.iter().any(|arg| {
+                arg == &flag
+                    || arg
+                        .strip_prefix(&flag)
+                        .and_then(|rest| rest.strip_prefix('='))
+                        .is_some_and(|value| value != "no")
+            });
+            if configured {
+                components.push(Component {
+                    license: Some(license.to_owned()),
+                    linked_into: Some("gcc".to_owned()),
+                    ..component(option, name, Kind::Library, None)
+                });
+            }
+        }
+        let binutils = &info.binutils_version;
+        // Linaro ships binutils snapshots, e.g. 2.28.2.20170706, which
+        // have no release tarball.
+        let released = binutils.split('.').count() <= 3
+            && binutils.split('.').all(|p| p.parse::<u32>().is_ok());
+        components.push(Component {
+            license: Some("GPL-3.0-or-later".to_owned()),
+            source: released.then(|| format!("{GNU_MIRROR}/binutils/binutils-{binutils}.tar.xz")),
+            ..component("binutils", "GNU Binutils", Kind::Tool, Some(binutils))
+        });
+        for &(id, runtime, prefixes) in RUNTIMES {
+            let files: Vec<_> = files
+                .iter()
+                .filter(|path| is_library_of(path, prefixes))
+                .cloned()
+                .collect();
+            if !files.is_empty() {
+                components.push(Component {
+                    license: Some(RUNTIME_LICENSE.to_owned()),
+                    source: Some(gcc_source.clone()),
+                    files,
+                    ..component(id, runtime, Kind::Library, Some(&gcc))
+                });
+            }
+        }
+        if let Some(glibc) = &info.glibc_version {
+            components.push(Component {
+                license: Some("LGPL-2.1-or-later".to_owned()),
+                source: Some(format!("{GNU_MIRROR}/glibc/glibc-{glibc}.tar.xz")),
+                ..component("glibc", "GNU C Library", Kind::Library, Some(glibc))
+            });
+        }
+
+        Self {
+            name,
+            created: created.into(),
+            toolchain,
+            components,
+        }
+    }
+
+    /// A URI unique to this document, derived from the archive digest
+    /// where there is one so that regenerating it gives the same URI.
+    pub fn namespace(&self) -> String {
+        let unique = match &self.toolchain.sha256 {
+            Some(sha256) => sha256.to_string(),
+            None => self.created.replace([':', '-'], ""),
+        };
+        format!("https://spdx.org/spdxdocs/{}-{unique}", self.name)
+    }
+}
+
+/// Whether `path` is a static or shared library whose file name starts
+/// with one of `prefixes`.
+fn is_library_of(path: &str, prefixes: &[&str]) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let library = name.ends_with(".a") || (name.contains(".so") && !name.ends_with(".py"));
+    library && prefixes.iter().any(|p| name.starts_with(p))
+}
+
+fn list_files(root: &Path, dir: &Path, out: &mut Vec<String>) -> Result<(), Error> {
+    for entry in fs::read_dir(dir).map_err(Error::io(dir))? {
+        let entry = entry.map_err(Error::io(dir))?;
+        let path = entry.path();
+        if entry.file_type().map_err(Error::io(&path))?.is_dir() {
+            list_files(root, &path, out)?;
+        } else {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            out.push(relative.to_string_lossy().into_owned());
+        }
+    }
+    Ok(())
+}
+
+/// `secs` since the Unix epoch as an RFC 3339 UTC timestamp.
+pub fn timestamp(secs: u64) -> String {
+    let days = secs / 86_400;
+    let rest = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rest / 3_600,
+        rest % 3_600 / 60,
+        rest % 60
+    )
+}
+
+/// The creation time to record: `SOURCE_DATE_EPOCH` when set, so that
+/// regenerated documents are identical, otherwise now.
+pub fn created_now() -> String {
+    let secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        });
+    timestamp(secs)
+}
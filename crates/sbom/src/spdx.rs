@@ -0,0 +1,218 @@
+//! SPDX 2.3 documents, in tag-value and JSON form.
+
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::{Bom, Component, Kind};
+
+pub const VERSION: &str = "SPDX-2.3";
+
+const NOASSERTION: &str = "NOASSERTION";
+
+/// The tool named as the document's creator.
+fn creator() -> String {
+    format!("Tool: linaro-prebuilts-{}", env!("CARGO_PKG_VERSION"))
+}
+
+fn spdx_id(component: &Component) -> String {
+    format!("SPDXRef-Package-{}", component.id)
+}
+
+/// `(element, relationship, related element)` triples: the document
+/// describes the toolchain, which contains each component not linked into
+/// another one.
+fn relationships(bom: &Bom) -> Vec<(String, &'static str, String)> {
+    let mut relationships = vec![(
+        "SPDXRef-DOCUMENT".to_owned(),
+        "DESCRIBES",
+        spdx_id(&bom.toolchain),
+    )];
+    for component in &bom.components {
+        relationships.push(match &component.linked_into {
+            Some(into) => (
+                format!("SPDXRef-Package-{into}"),
+                "STATIC_LINK",
+                spdx_id(component),
+            ),
+            None => (spdx_id(&bom.toolchain), "CONTAINS", spdx_id(component)),
+        });
+    }
+    relationships
+}
+
+/// Linaro is named as the supplier of the archive only; the components
+/// come from their upstream projects.
+fn supplier(component: &Component) -> Option<&'static str> {
+    (component.kind == Kind::Toolchain).then_some("Organization: Linaro")
+}
+
+fn comment(component: &Component) -> Option<String> {
+    (!component.files.is_empty()).then(|| format!("Files: {}", component.files.join(", ")))
+}
+
+/// Writes `bom` as an SPDX tag-value document.
+pub fn tag_value(bom: &Bom) -> String {
+    let mut out = String::new();
+    field(&mut out, "SPDXVersion", VERSION);
+    field(&mut out, "DataLicense", "CC0-1.0");
+    field(&mut out, "SPDXID", "SPDXRef-DOCUMENT");
+    field(&mut out, "DocumentName", &bom.name);
+    field(&mut out, "DocumentNamespace", &bom.namespace());
+    field(&mut out, "Creator", &creator());
+    field(&mut out, "Created", &bom.created);
+    for component in std::iter::once(&bom.toolchain).chain(&bom.components) {
+        out.push('\n');
+        field(&mut out, "PackageName", &component.name);
+        field(&mut out, "SPDXID", &spdx_id(component));
+        field(
+            &mut out,
+            "PackageVersion",
+            component.version.as_deref().unwrap_or(NOASSERTION),
+        );
+        if let Some(supplier) = supplier(component) {
+            field(&mut out, "PackageSupplier", supplier);
+        }
+        field(
+            &mut out,
+            "PackageDownloadLocation",
+            component.source.as_deref().unwrap_or(NOASSERTION),
+        );
+        field(&mut out, "FilesAnalyzed", "false");
+        if let Some(sha256) = &component.sha256 {
+            field(&mut out, "PackageChecksum", &format!("SHA256: {sha256}"));
+        }
+        field(&mut out, "PackageLicenseConcluded", NOASSERTION);
+        field(
+            &mut out,
+            "PackageLicenseDeclared",
+            component.license.as_deref().unwrap_or(NOASSERTION),
+        );
+        field(&mut out, "PackageCopyrightText", NOASSERTION);
+        if let Some(comment) = comment(component) {
+            field(
+                &mut out,
+                "PackageComment",
+                &format!("<text>{comment}</text>"),
+            );
+        }
+    }
+    out.push('\n');
+    for (element, relationship, related) in relationships(bom) {
+        field(
+            &mut out,
+            "Relationship",
+            &format!("{element} {relationship} {related}"),
+        );
+    }
+    out
+}
+
+fn field(out: &mut String, tag: &str, value: &str) {
+    writeln!(out, "{tag}: {value}").expect("writing to a string");
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Document<'a> {
+    spdx_version: &'a str,
+    data_license: &'a str,
+    #[serde(rename = "SPDXID")]
+    spdx_id: &'a str,
+    name: &'a str,
+    document_namespace: String,
+    creation_info: CreationInfo<'a>,
+    packages: Vec<Package<'a>>,
+    relationships: Vec<Relationship>,
+}
+
+#[derive(Serialize)]
+struct CreationInfo<'a> {
+    created: &'a str,
+    creators: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Package<'a> {
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    name: &'a str,
+    version_info: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supplier: Option<&'a str>,
+    download_location: &'a str,
+    files_analyzed: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    checksums: Vec<Checksum>,
+    license_concluded: &'a str,
+    license_declared: &'a str,
+    copyright_text: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Checksum {
+    algorithm: &'static str,
+    checksum_value: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Relationship {
+    spdx_element_id: String,
+    relationship_type: &'static str,
+    related_spdx_element: String,
+}
+
+/// Writes `bom` as an SPDX JSON document.
+pub fn json(bom: &Bom) -> String {
+    let packages = std::iter::once(&bom.toolchain)
+        .chain(&bom.components)
+        .map(|component| Package {
+            spdx_id: spdx_id(component),
+            name: &component.name,
+            version_info: component.version.as_deref().unwrap_or(NOASSERTION),
+            supplier: supplier(component),
+            download_location: component.source.as_deref().unwrap_or(NOASSERTION),
+            files_analyzed: false,
+            checksums: component
+                .sha256
+                .iter()
+                .map(|sha256| Checksum {
+                    algorithm: "SHA256",
+                    checksum_value: sha256.to_string(),
+                })
+                .collect(),
+            license_concluded: NOASSERTION,
+            license_declared: component.license.as_deref().unwrap_or(NOASSERTION),
+            copyright_text: NOASSERTION,
+            comment: comment(component),
+        })
+        .collect();
+    let document = Document {
+        spdx_version: VERSION,
+        data_license: "CC0-1.0",
+        spdx_id: "SPDXRef-DOCUMENT",
+        name: &bom.name,
+        document_namespace: bom.namespace(),
+        creation_info: CreationInfo {
+            created: &bom.created,
+            creators: vec![creator()],
+        },
+        packages,
+        relationships: relationships(bom)
+            .into_iter()
+            .map(|(element, relationship, related)| Relationship {
+                spdx_element_id: element,
+                relationship_type: relationship,
+                related_spdx_element: related,
+            })
+            .collect(),
+    };
+    let mut text = serde_json::to_string_pretty(&document).expect("SPDX documents serialize");
+    text.push('\n');
+    text
+}
@@ -0,0 +1,157 @@
+use std::fs;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use linaro_archive::install::STAMP_FILE;
+use linaro_golden::check_golden;
+use linaro_sbom::{cyclonedx, spdx, timestamp, Archive, Bom, Kind, RUNTIME_LICENSE};
+use linaro_toolchain::{Info, Installation, Outputs};
+
+const SHA256: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+/// `linaro-toolchain`'s captured driver output for `target`.
+fn info(target: &str) -> Info {
+    let dir: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../toolchain/tests/fixtures")
+        .join(target);
+    let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+    Info::from_outputs(&Outputs {
+        verbose: read("gcc-v.txt"),
+        specs: read("dumpspecs.txt"),
+        multi_lib: read("print-multi-lib.txt"),
+        sysroot: read("print-sysroot.txt"),
+        ld_version: read("ld-version.txt"),
+        features_h: fs::read_to_string(dir.join("features.h")).ok(),
+    })
+    .unwrap()
+}
+
+fn files(paths: &[&str]) -> Vec<String> {
+    paths.iter().map(|p| p.to_string()).collect()
+}
+
+fn aarch64_bom() -> Bom {
+    let archive = Archive {
+        name: "gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu.tar.xz".to_owned(),
+        sha256: SHA256.parse().unwrap(),
+    };
+    let files = files(&[
+        "aarch64-linux-gnu/lib64/libasan.so.4.0.0",
+        "aarch64-linux-gnu/lib64/libatomic.a",
+        "aarch64-linux-gnu/lib64/libatomic.so.1.2.0",
+        "aarch64-linux-gnu/lib64/libgcc_s.so.1",
+        "aarch64-linux-gnu/lib64/libstdc++.a",
+        "aarch64-linux-gnu/lib64/libstdc++.la",
+        "aarch64-linux-gnu/lib64/libstdc++.so.6.0.24",
+        "aarch64-linux-gnu/lib64/libstdc++.so.6.0.24-gdb.py",
+        "aarch64-linux-gnu/lib64/libsupc++.a",
+        "bin/aarch64-linux-gnu-gcc",
+        "lib/gcc/aarch64-linux-gnu/7.5.0/libgcc.a",
+        "lib/gcc/aarch64-linux-gnu/7.5.0/libgcc_eh.a",
+        "lib/gcc/aarch64-linux-gnu/7.5.0/libgcov.a",
+    ]);
+    Bom::new(
+        &info("aarch64-linux-gnu"),
+        Some(&archive),
+        &files,
+        timestamp(1_576_000_000),
+    )
+}
+
+#[test]
+fn spdx_tag_value() {
+    check_golden!("aarch64-linux-gnu.spdx", &spdx::tag_value(&aarch64_bom()));
+}
+
+#[test]
+fn spdx_json() {
+    check_golden!("aarch64-linux-gnu.spdx.json", &spdx::json(&aarch64_bom()));
+}
+
+#[test]
+fn cyclonedx_json() {
+    check_golden!(
+        "aarch64-linux-gnu.cdx.json",
+        &cyclonedx::json(&aarch64_bom()),
+    );
+}
+
+#[test]
+fn bare_metal_unstamped() {
+    let bom = Bom::new(
+        &info("arm-eabi"),
+        None,
+        &files(&[
+            "arm-eabi/lib/libstdc++.a",
+            "lib/gcc/arm-eabi/7.5.0/libgcc.a",
+        ]),
+        timestamp(1_576_000_000),
+    );
+    assert_eq!(bom.name, "gcc-linaro-7.5.0-arm-eabi");
+    assert_eq!(bom.toolchain.source, None);
+    assert_eq!(bom.toolchain.sha256, None);
+    assert!(bom.components.iter().all(|c| c.id != "glibc"));
+    check_golden!("arm-eabi.spdx", &spdx::tag_value(&bom));
+}
+
+#[test]
+fn timestamps() {
+    assert_eq!(timestamp(0), "1970-01-01T00:00:00Z");
+    assert_eq!(timestamp(951_782_400), "2000-02-29T00:00:00Z");
+    assert_eq!(timestamp(1_576_000_000), "2019-12-10T17:46:40Z");
+}
+
+#[test]
+fn collects_installed_tree() {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux"))
+        || !Path::new("/usr/bin/gcc").exists()
+        || !Path::new("/usr/bin/ld").exists()
+    {
+        eprintln!("skipping: needs a host gcc and ld");
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("toolchain");
+    let bin = root.join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let gcc = bin.join("x86_64-linux-gnu-gcc");
+    fs::write(&gcc, "#!/bin/sh\nexec /usr/bin/gcc \"$@\"\n").unwrap();
+    fs::set_permissions(&gcc, fs::Permissions::from_mode(0o755)).unwrap();
+    symlink("/usr/bin/ld", bin.join("x86_64-linux-gnu-ld")).unwrap();
+    let lib = root.join("x86_64-linux-gnu/lib64");
+    fs::create_dir_all(&lib).unwrap();
+    for name in ["libstdc++.so.6.0.30", "libatomic.a", "libfoo.so.1"] {
+        fs::write(lib.join(name), name).unwrap();
+    }
+    fs::write(
+        root.join(STAMP_FILE),
+        format!("archive = \"toolchain.tar.xz\"\nsha256 = \"{SHA256}\"\ntree = \"{SHA256}\"\n"),
+    )
+    .unwrap();
+
+    let installation = Installation::open(&root).unwrap();
+    let bom = Bom::collect(&installation, "2024-01-01T00:00:00Z").unwrap();
+    assert_eq!(bom.name, "toolchain");
+    assert_eq!(bom.toolchain.kind, Kind::Toolchain);
+    assert_eq!(bom.toolchain.sha256.unwrap().to_string(), SHA256);
+    let runtimes: Vec<_> = bom
+        .components
+        .iter()
+        .filter(|c| c.license.as_deref() == Some(RUNTIME_LICENSE))
+        .map(|c| (&*c.name, c.files.clone()))
+        .collect();
+    assert_eq!(
+        runtimes,
+        [
+            (
+                "libstdc++",
+                vec!["x86_64-linux-gnu/lib64/libstdc++.so.6.0.30".to_owned()]
+            ),
+            (
+                "libatomic",
+                vec!["x86_64-linux-gnu/lib64/libatomic.a".to_owned()]
+            ),
+        ]
+    );
+    assert!(bom.components.iter().any(|c| c.id == "binutils"));
+}
@@ -0,0 +1,17 @@
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{}", path.display())]
+    Elf {
+        path: PathBuf,
+        source: linaro_elf::Error,
+    },
+    #[error("invalid budget {0:?}: expected SECTION=SIZE, e.g. .text=28K")]
+    Budget(String),
+    #[error("budget for section {0}, which neither build has")]
+    UnknownSection(String),
+}
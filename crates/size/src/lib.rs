@@ -0,0 +1,243 @@
+//! Code-size comparison between two builds of the same program, such as a
+//! U-Boot SPL built with two GCC releases.
+//!
+//! [`Sizes::read`] measures an ELF file: its allocated sections and the
+//! functions and data objects in them. [`Comparison::between`] pairs two
+//! measurements into per-section and per-symbol deltas, largest change
+//! first, and [`Comparison::over_budget`] holds the new build to
+//! [`Budget`]s, for memories the program has to fit into.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use linaro_elf::sections::sections;
+use linaro_elf::symbols::symbol_sizes;
+use serde::Serialize;
+
+mod error;
+
+pub use error::Error;
+
+/// Budget name for the whole loadable image: every allocated section with
+/// contents in the file, so not `.bss`.
+pub const TOTAL: &str = "total";
+
+/// The sizes in one build.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Sizes {
+    /// Allocated sections by name.
+    pub sections: BTreeMap<String, u64>,
+    /// The loadable image, see [`TOTAL`].
+    pub total: u64,
+    /// Functions and objects by name. Local symbols sharing a name (static
+    /// functions of different files) are added up.
+    pub symbols: BTreeMap<String, Symbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Symbol {
+    pub section: String,
+    pub size: u64,
+}
+
+impl Sizes {
+    /// Measures the ELF file at `path`.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        let elf = |source| Error::Elf {
+            path: path.to_owned(),
+            source,
+        };
+        let mut sizes = Self::default();
+        for section in sections(&bytes).map_err(elf)? {
+            if !section.is_allocated() {
+                continue;
+            }
+            if section.has_contents() {
+                sizes.total += section.size;
+            }
+            *sizes.sections.entry(section.name).or_default() += section.size;
+        }
+        for symbol in symbol_sizes(&bytes).map_err(elf)? {
+            sizes
+                .symbols
+                .entry(symbol.name)
+                .and_modify(|s| s.size += symbol.size)
+                .or_insert(Symbol {
+                    section: symbol.section,
+                    size: symbol.size,
+                });
+        }
+        Ok(sizes)
+    }
+}
+
+/// The size of one section or symbol in both builds; `None` where a build
+/// does not have it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Delta {
+    pub name: String,
+    /// The section a symbol lives in, in the new build if it has it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+impl Delta {
+    /// New size minus old size, in bytes.
+    pub fn change(&self) -> i64 {
+        self.new.unwrap_or(0) as i64 - self.old.unwrap_or(0) as i64
+    }
+}
+
+/// How the sizes changed from one build to the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Comparison {
+    pub total: Delta,
+    /// Every allocated section of either build.
+    pub sections: Vec<Delta>,
+    /// Symbols whose size changed, or that exist in one build only.
+    pub symbols: Vec<Delta>,
+}
+
+impl Comparison {
+    /// Compares the `old` build with the `new` one. Deltas are sorted by
+    /// the size of the change, largest first, then by name.
+    pub fn between(old: &Sizes, new: &Sizes) -> Self {
+        let names: BTreeSet<_> = old.sections.keys().chain(new.sections.keys()).collect();
+        let mut sections: Vec<_> = names
+            .into_iter()
+            .map(|name| Delta {
+                name: name.clone(),
+                section: None,
+                old: old.sections.get(name).copied(),
+                new: new.sections.get(name).copied(),
+            })
+            .collect();
+        sort(&mut sections);
+
+        let names: BTreeSet<_> = old.symbols.keys().chain(new.symbols.keys()).collect();
+        let mut symbols: Vec<_> = names
+            .into_iter()
+            .filter_map(|name| {
+                let (before, after) = (old.symbols.get(name), new.symbols.get(name));
+                let delta = Delta {
+                    name: name.clone(),
+                    section: after.or(before).map(|s| s.section.clone()),
+                    old: before.map(|s| s.size),
+                    new: after.map(|s| s.size),
+                };
+                (delta.old != delta.new).then_some(delta)
+            })
+            .collect();
+        sort(&mut symbols);
+
+        Self {
+            total: Delta {
+                name: TOTAL.to_owned(),
+                section: None,
+                old: Some(old.total),
+                new: Some(new.total),
+            },
+            sections,
+            symbols,
+        }
+    }
+
+    /// Drops symbols that changed by less than `bytes` either way.
+    pub fn threshold(mut self, bytes: u64) -> Self {
+        self.symbols.retain(|d| d.change().unsigned_abs() >= bytes);
+        self
+    }
+
+    /// The budgets the new build exceeds. A budget for a section neither
+    /// build has is an error rather than a pass, so a misspelt name cannot
+    /// switch the check off.
+    pub fn over_budget(&self, budgets: &[Budget]) -> Result<Vec<Overrun>, Error> {
+        let mut overruns = Vec::new();
+        for budget in budgets {
+            let size = if budget.section == TOTAL {
+                self.total.new
+            } else {
+                self.sections
+                    .iter()
+                    .find(|d| d.name == budget.section)
+                    .ok_or_else(|| Error::UnknownSection(budget.section.clone()))?
+                    .new
+            }
+            .unwrap_or(0);
+            if size > budget.limit {
+                overruns.push(Overrun {
+                    section: budget.section.clone(),
+                    size,
+                    limit: budget.limit,
+                });
+            }
+        }
+        Ok(overruns)
+    }
+}
+
+fn sort(deltas: &mut [Delta]) {
+    deltas.sort_by(|a, b| {
+        (Reverse(a.change().unsigned_abs()), &a.name)
+            .cmp(&(Reverse(b.change().unsigned_abs()), &b.name))
+    });
+}
+
+/// The most a section, or the image as a whole ([`TOTAL`]), may take.
+///
+/// Parses from `SECTION=SIZE`, where the size is read by
+/// [`linaro_manifest::parse_size`]: `.text=28K`, `total=0x8000`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Budget {
+    pub section: String,
+    pub limit: u64,
+}
+
+impl FromStr for Budget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::Budget(s.to_owned());
+        let (section, size) = s.split_once('=').ok_or_else(invalid)?;
+        let limit = linaro_manifest::parse_size(size).ok_or_else(invalid)?;
+        if section.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            section: section.to_owned(),
+            limit,
+        })
+    }
+}
+
+/// A budget the new build exceeds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Overrun {
+    pub section: String,
+    pub size: u64,
+    pub limit: u64,
+}
+
+impl fmt::Display for Overrun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is {} bytes, {} over its budget of {}",
+            self.section,
+            self.size,
+            self.size - self.limit,
+            self.limit
+        )
+    }
+}
@@ -0,0 +1,23 @@
+/* A stand-in for a boot loader stage; -DGROWN builds the next release. */
+
+#ifdef GROWN
+#define TABLE_SIZE 512
+#else
+#define TABLE_SIZE 256
+#endif
+
+const unsigned char crc_table[TABLE_SIZE] = { 1 };
+static char buffer[1024];
+int counter = 1;
+
+#ifdef GROWN
+int new_feature(int x) { return x * 3 + counter; }
+#else
+int legacy(int x) { return x - counter; }
+#endif
+
+int board_init(int x)
+{
+    buffer[x] = crc_table[x];
+    return buffer[x + 1];
+}
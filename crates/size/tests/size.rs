@@ -0,0 +1,177 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use linaro_size::{Budget, Comparison, Delta, Error, Sizes, Symbol, TOTAL};
+
+/// Compiles the SPL stand-in into `dir`, as the old release or, with
+/// `grown`, the new one. `None` without a host GCC.
+fn build(dir: &Path, grown: bool) -> Option<PathBuf> {
+    let out = dir.join(if grown { "new.o" } else { "old.o" });
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/spl.c");
+    let mut gcc = Command::new("gcc");
+    gcc.args(["-c", "-O2", "-fno-common", "-o"])
+        .arg(&out)
+        .arg(source);
+    if grown {
+        gcc.arg("-DGROWN");
+    }
+    gcc.status().is_ok_and(|s| s.success()).then_some(out)
+}
+
+fn symbol(section: &str, size: u64) -> Symbol {
+    Symbol {
+        section: section.to_owned(),
+        size,
+    }
+}
+
+fn delta(name: &str, section: Option<&str>, old: Option<u64>, new: Option<u64>) -> Delta {
+    Delta {
+        name: name.to_owned(),
+        section: section.map(str::to_owned),
+        old,
+        new,
+    }
+}
+
+#[test]
+fn compares_two_builds() {
+    let dir = tempfile::tempdir().unwrap();
+    let (Some(old), Some(new)) = (build(dir.path(), false), build(dir.path(), true)) else {
+        eprintln!("skipping: needs gcc");
+        return;
+    };
+    let (old, new) = (Sizes::read(old).unwrap(), Sizes::read(new).unwrap());
+    assert_eq!(old.symbols["crc_table"].size, 256);
+    assert_eq!(old.symbols["buffer"], symbol(".bss", 1024));
+    assert!(!old.sections.contains_key(".comment"));
+
+    let comparison = Comparison::between(&old, &new);
+    let rodata = comparison
+        .sections
+        .iter()
+        .find(|d| d.name == ".rodata")
+        .unwrap();
+    assert_eq!(rodata.change(), 256);
+    let bss = comparison
+        .sections
+        .iter()
+        .find(|d| d.name == ".bss")
+        .unwrap();
+    assert_eq!(bss.change(), 0);
+    assert_eq!(
+        comparison.symbols[0],
+        delta("crc_table", Some(".rodata"), Some(256), Some(512))
+    );
+    let names: Vec<_> = comparison.symbols.iter().map(|d| d.name.as_str()).collect();
+    assert!(names.contains(&"new_feature") && names.contains(&"legacy"));
+    assert!(!names.contains(&"board_init"));
+    // .bss is not part of the image.
+    let image: u64 = new
+        .sections
+        .iter()
+        .filter(|(name, _)| *name != ".bss")
+        .map(|(_, size)| size)
+        .sum();
+    assert_eq!(comparison.total.new, Some(image));
+
+    let over = comparison
+        .over_budget(&[
+            ".rodata=300".parse().unwrap(),
+            ".bss=1K".parse().unwrap(),
+            ".data=4K".parse().unwrap(),
+        ])
+        .unwrap();
+    assert_eq!(over.len(), 1);
+    assert_eq!(
+        over[0].to_string(),
+        ".rodata is 512 bytes, 212 over its budget of 300"
+    );
+}
+
+#[test]
+fn sorts_and_thresholds_deltas() {
+    let old = Sizes {
+        sections: [(".text".to_owned(), 1000), (".data".to_owned(), 64)].into(),
+        total: 1064,
+        symbols: [
+            ("main".to_owned(), symbol(".text", 100)),
+            ("memcpy".to_owned(), symbol(".text", 40)),
+            ("old".to_owned(), symbol(".text", 8)),
+            ("same".to_owned(), symbol(".data", 64)),
+        ]
+        .into(),
+    };
+    let new = Sizes {
+        sections: [(".text".to_owned(), 1100), (".data".to_owned(), 64)].into(),
+        total: 1164,
+        symbols: [
+            ("main".to_owned(), symbol(".text", 90)),
+            ("memcpy".to_owned(), symbol(".text", 152)),
+            ("same".to_owned(), symbol(".data", 64)),
+        ]
+        .into(),
+    };
+    let comparison = Comparison::between(&old, &new);
+    assert_eq!(
+        comparison.sections,
+        [
+            delta(".text", None, Some(1000), Some(1100)),
+            delta(".data", None, Some(64), Some(64)),
+        ]
+    );
+    assert_eq!(
+        comparison.symbols,
+        [
+            delta("memcpy", Some(".text"), Some(40), Some(152)),
+            delta("main", Some(".text"), Some(100), Some(90)),
+            delta("old", Some(".text"), Some(8), None),
+        ]
+    );
+    assert_eq!(comparison.symbols[1].change(), -10);
+    let names: Vec<_> = comparison
+        .clone()
+        .threshold(10)
+        .symbols
+        .into_iter()
+        .map(|d| d.name)
+        .collect();
+    assert_eq!(names, ["memcpy", "main"]);
+
+    let total: Budget = format!("{TOTAL}=0x480").parse().unwrap();
+    assert_eq!(total.limit, 1152);
+    let over = comparison
+        .over_budget(&[total, ".text=1100".parse().unwrap()])
+        .unwrap();
+    assert_eq!(over.len(), 1);
+    assert_eq!(over[0].section, TOTAL);
+
+    // A misspelt section must not pass unchecked.
+    assert!(matches!(
+        comparison.over_budget(&[".txt=28K".parse().unwrap()]),
+        Err(Error::UnknownSection(section)) if section == ".txt"
+    ));
+    // A section the new build dropped is within any budget.
+    let mut dropped = new.clone();
+    dropped.sections.remove(".data");
+    let comparison = Comparison::between(&old, &dropped);
+    assert!(comparison
+        .over_budget(&[".data=0".parse().unwrap()])
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn parses_budgets() {
+    let budget: Budget = ".text=28K".parse().unwrap();
+    assert_eq!(budget.section, ".text");
+    assert_eq!(budget.limit, 28 * 1024);
+    assert_eq!("total=1M".parse::<Budget>().unwrap().limit, 1 << 20);
+    assert_eq!("total=1G".parse::<Budget>().unwrap().limit, 1 << 30);
+    for invalid in [".text", "=12", ".text=", ".text=12X", ".text=-1"] {
+        assert!(
+            matches!(invalid.parse::<Budget>(), Err(Error::Budget(_))),
+            "{invalid}"
+        );
+    }
+}
@@ -0,0 +1,24 @@
+#include <iostream>
+#include <memory>
+#include <stdexcept>
+
+struct Guard {
+	~Guard() { std::cout << "unwound" << std::endl; }
+};
+
+static void fail()
+{
+	auto guard = std::make_unique<Guard>();
+	throw std::runtime_error("boom");
+}
+
+int main()
+{
+	try {
+		fail();
+	} catch (const std::exception &e) {
+		std::cout << "caught: " << e.what() << std::endl;
+		return 0;
+	}
+	return 1;
+}
@@ -0,0 +1,14 @@
+#include <stdio.h>
+
+/* volatile keeps the compiler from folding the arithmetic away. */
+static volatile double base = 1.5;
+static volatile float ratio = 0.25f;
+
+int main(void)
+{
+	double sum = 0;
+	for (int i = 1; i <= 10; i++)
+		sum += base / i;
+	printf("%.6f %.4f\n", sum, (double)(ratio * 3.0f));
+	return 0;
+}
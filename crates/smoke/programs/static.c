@@ -0,0 +1,10 @@
+#include <stdio.h>
+#include <string.h>
+
+int main(void)
+{
+	char buf[32];
+	snprintf(buf, sizeof(buf), "%s-%zu", "static", strlen("linked"));
+	puts(buf);
+	return 7;
+}
@@ -0,0 +1,12 @@
+/* Passes and returns floating-point values, so the object records the
+ * float ABI in use. */
+
+double scale(double value, float factor, int shift)
+{
+	return value * factor + (double)(1 << shift);
+}
+
+float mix(float a, float b, double t)
+{
+	return (float)(a + (b - a) * t);
+}
@@ -0,0 +1,9 @@
+/* Linked without any C library, as firmware is. */
+
+static volatile unsigned int counter;
+
+void entry(void)
+{
+	for (;;)
+		counter++;
+}
@@ -0,0 +1,26 @@
+#include <iostream>
+#include <stdexcept>
+#include <string>
+#include <vector>
+
+static int parse(const std::string &s)
+{
+	if (s.empty())
+		throw std::invalid_argument("empty");
+	return std::stoi(s);
+}
+
+int main(int argc, char **argv)
+{
+	std::vector<std::string> args(argv + 1, argv + argc);
+	int sum = 0;
+	for (const auto &arg : args) {
+		try {
+			sum += parse(arg);
+		} catch (const std::exception &e) {
+			std::cerr << "skipping '" << arg << "': " << e.what() << '\n';
+		}
+	}
+	std::cout << "sum " << sum << std::endl;
+	return 0;
+}
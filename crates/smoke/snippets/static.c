@@ -0,0 +1,10 @@
+#include <stdlib.h>
+#include <string.h>
+
+int main(int argc, char **argv)
+{
+	size_t total = 0;
+	for (int i = 1; i < argc; i++)
+		total += strlen(argv[i]);
+	return total > 255 ? EXIT_FAILURE : (int)total;
+}
@@ -0,0 +1,16 @@
+use std::io;
+use std::path::PathBuf;
+
+use linaro_manifest::Triple;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("no smoke-test expectations for target {0}")]
+    UnsupportedTarget(Triple),
+    #[error("failed to run {}", program.display())]
+    Run { program: PathBuf, source: io::Error },
+    #[error(transparent)]
+    Toolchain(#[from] linaro_toolchain::Error),
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
@@ -0,0 +1,336 @@
+//! Running cross-built programs under QEMU user-mode emulation.
+//!
+//! Programs are linked against the toolchain's own sysroot and run with
+//! `qemu-<arch> -L <sysroot>`, so the dynamic linker, libc and libstdc++ that
+//! would ship with a product are the ones exercised. When the target is the
+//! host's architecture and there is no emulator, they are started through
+//! the sysroot's dynamic linker instead, never the host's. Other hosts
+//! without the matching `qemu-<arch>` skip these checks rather than fail
+//! them.
+
+use std::env;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use linaro_elf::ElfInfo;
+use linaro_manifest::Triple;
+use linaro_toolchain::Installation;
+
+use crate::{compile, work_dir, CaseReport, Error, ToolchainReport};
+
+/// How long a program may run before it is killed; emulation is slow, but
+/// none of the bundled programs does real work.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A bundled program and what running it must produce.
+#[derive(Debug, Clone, Copy)]
+pub struct Program {
+    pub name: &'static str,
+    /// File name the source is written to; `.cpp` files are built with g++.
+    pub file: &'static str,
+    pub source: &'static str,
+    /// Extra compiler flags, e.g. `-static`.
+    pub flags: &'static [&'static str],
+    pub args: &'static [&'static str],
+    pub stdout: &'static str,
+    pub exit_code: i32,
+}
+
+pub const PROGRAMS: &[Program] = &[
+    Program {
+        name: "run-args",
+        file: "args.c",
+        source: include_str!("../programs/args.c"),
+        flags: &[],
+        args: &["one", "two words"],
+        stdout: "argv[1]=one\nargv[2]=two words\n",
+        exit_code: 43,
+    },
+    Program {
+        name: "run-exceptions",
+        file: "exceptions.cpp",
+        source: include_str!("../programs/exceptions.cpp"),
+        flags: &[],
+        args: &[],
+        stdout: "unwound\ncaught: boom\n",
+        exit_code: 0,
+    },
+    Program {
+        name: "run-float",
+        file: "float.c",
+        source: include_str!("../programs/float.c"),
+        flags: &[],
+        args: &[],
+        stdout: "4.393452 0.7500\n",
+        exit_code: 0,
+    },
+    Program {
+        name: "run-static",
+        file: "static.c",
+        source: include_str!("../programs/static.c"),
+        flags: &["-static"],
+        args: &[],
+        stdout: "static-6\n",
+        exit_code: 7,
+    },
+];
+
+/// How target programs are run on this host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Runner {
+    /// User-mode emulation, resolving absolute library paths in `sysroot`.
+    Qemu {
+        program: PathBuf,
+        sysroot: Option<PathBuf>,
+    },
+    /// The target is the host architecture. Dynamic programs are started
+    /// by the dynamic linker in `sysroot`, with its libraries; without a
+    /// sysroot they run directly.
+    Native { sysroot: Option<PathBuf> },
+}
+
+impl Runner {
+    /// Finds a way to run programs built by `installation`: `qemu-<arch>` on
+    /// `PATH`, or the host itself when the architectures match. `None` when
+    /// neither is available or the target is bare metal.
+    pub fn find(installation: &Installation) -> Result<Option<Self>, Error> {
+        let triple = installation.triple();
+        if triple.is_bare_metal() {
+            return Ok(None);
+        }
+        if let Some(program) = qemu_name(triple).and_then(|name| find_in_path(&name)) {
+            return Ok(Some(Runner::Qemu {
+                program,
+                sysroot: installation.sysroot()?,
+            }));
+        }
+        if triple.arch() != host_arch() {
+            return Ok(None);
+        }
+        Ok(Some(Runner::Native {
+            sysroot: installation.sysroot()?,
+        }))
+    }
+}
+
+/// The QEMU user-mode emulator for `triple`, e.g. `qemu-aarch64`.
+pub fn qemu_name(triple: &Triple) -> Option<String> {
+    let name = match triple.arch() {
+        arch @ ("aarch64" | "aarch64_be" | "x86_64") => arch,
+        arch if arch.starts_with("armeb") => "armeb",
+        arch if arch.starts_with("arm") => "arm",
+        "i386" | "i586" | "i686" => "i386",
+        _ => return None,
+    };
+    Some(format!("qemu-{name}"))
+}
+
+/// Builds and runs `programs` with `installation`. If nothing can run them
+/// on this host, the report has no cases and records why in `skipped`.
+pub fn execute(
+    installation: &Installation,
+    programs: &[Program],
+) -> Result<ToolchainReport, Error> {
+    let triple = installation.triple();
+    let mut report = ToolchainReport {
+        root: installation.root().to_owned(),
+        target: triple.to_string(),
+        cases: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let Some(runner) = Runner::find(installation)? else {
+        report.skipped.push(match qemu_name(triple) {
+            _ if triple.is_bare_metal() => "bare-metal target, nothing to run".to_owned(),
+            Some(qemu) => format!("{qemu} not found in PATH, programs not run"),
+            None => format!("no QEMU emulator known for {}", triple.arch()),
+        });
+        return Ok(report);
+    };
+
+    let work = work_dir()?;
+    let library_path = library_path(installation);
+    for program in programs {
+        let failures = match compile(
+            installation,
+            work.path(),
+            program.file,
+            program.source,
+            program.flags,
+        )? {
+            Ok(exe) => run(&runner, &library_path, &exe, program)?,
+            Err(stderr) => vec![format!("build failed: {stderr}")],
+        };
+        report.cases.push(CaseReport {
+            name: program.name,
+            passed: failures.is_empty(),
+            failures,
+        });
+    }
+    Ok(report)
+}
+
+/// Runs `exe` with `runner` and compares what it did with `program`'s
+/// expectations.
+fn run(
+    runner: &Runner,
+    library_path: &str,
+    exe: &Path,
+    program: &Program,
+) -> Result<Vec<String>, Error> {
+    let command = match runner {
+        Runner::Qemu { program, sysroot } => {
+            let mut command = Command::new(program);
+            if let Some(sysroot) = sysroot {
+                command.arg("-L").arg(sysroot);
+            }
+            if !library_path.is_empty() {
+                command
+                    .arg("-E")
+                    .arg(format!("LD_LIBRARY_PATH={library_path}"));
+            }
+            command.arg(exe);
+            command
+        }
+        Runner::Native { sysroot: None } => {
+            let mut command = Command::new(exe);
+            if !library_path.is_empty() {
+                command.env("LD_LIBRARY_PATH", library_path);
+            }
+            command
+        }
+        Runner::Native {
+            sysroot: Some(sysroot),
+        } => {
+            let interpreter = ElfInfo::read(exe).ok().and_then(|info| info.interpreter);
+            let Some(interpreter) = interpreter else {
+                // Static: nothing is loaded from the host.
+                return check_run(Command::new(exe), exe, program);
+            };
+            let loader = sysroot.join(interpreter.trim_start_matches('/'));
+            if !loader.is_file() {
+                return Ok(vec![format!(
+                    "dynamic linker {interpreter} not found in {}",
+                    sysroot.display()
+                )]);
+            }
+            let mut dirs = sysroot_lib_dirs(sysroot);
+            dirs.extend(
+                library_path
+                    .split(':')
+                    .filter(|d| !d.is_empty())
+                    .map(str::to_owned),
+            );
+            let mut command = Command::new(loader);
+            command
+                .arg("--inhibit-cache")
+                .arg("--library-path")
+                .arg(dirs.join(":"))
+                .arg(exe);
+            command
+        }
+    };
+    check_run(command, exe, program)
+}
+
+/// Runs `command`, which starts `exe`, and compares what it did with
+/// `program`'s expectations.
+fn check_run(mut command: Command, exe: &Path, program: &Program) -> Result<Vec<String>, Error> {
+    let launcher = command.get_program().into();
+    let mut child = command
+        .args(program.args)
+        .env("LC_ALL", "C")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|source| Error::Run {
+            program: launcher,
+            source,
+        })?;
+
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    });
+    let started = Instant::now();
+    let status = loop {
+        let waited = child.try_wait().map_err(|source| Error::Io {
+            path: exe.to_owned(),
+            source,
+        })?;
+        if let Some(status) = waited {
+            break Some(status);
+        }
+        if started.elapsed() > TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    let output = reader
+        .join()
+        .expect("stdout reader panicked")
+        .map_err(|source| Error::Io {
+            path: exe.to_owned(),
+            source,
+        })?;
+
+    let mut failures = Vec::new();
+    match status.map(|s| s.code()) {
+        None => failures.push(format!("timed out after {}s", TIMEOUT.as_secs())),
+        Some(None) => failures.push("killed by a signal".to_owned()),
+        Some(Some(code)) if code != program.exit_code => {
+            failures.push(format!("exit code {code}, expected {}", program.exit_code));
+        }
+        Some(Some(_)) => {}
+    }
+    let stdout = String::from_utf8_lossy(&output);
+    if stdout != program.stdout {
+        failures.push(format!("stdout {stdout:?}, expected {:?}", program.stdout));
+    }
+    Ok(failures)
+}
+
+/// The library directories of `sysroot`, in the order the target's dynamic
+/// linker searches them.
+fn sysroot_lib_dirs(sysroot: &Path) -> Vec<String> {
+    ["lib", "usr/lib", "lib64", "usr/lib64"]
+        .iter()
+        .map(|lib| sysroot.join(lib))
+        .filter(|dir| dir.is_dir())
+        .map(|dir| dir.display().to_string())
+        .collect()
+}
+
+/// The directories holding the toolchain's target runtime libraries
+/// (libstdc++, libgcc_s), which Linaro installs beside rather than inside
+/// the sysroot.
+fn library_path(installation: &Installation) -> String {
+    let target_dir = installation.root().join(installation.triple().as_str());
+    ["lib", "lib64"]
+        .iter()
+        .map(|lib| target_dir.join(lib))
+        .filter(|dir| dir.is_dir())
+        .map(|dir| dir.display().to_string())
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// The host architecture, spelled as in target triples.
+fn host_arch() -> &'static str {
+    match env::consts::ARCH {
+        "x86" => "i686",
+        arch => arch,
+    }
+}
@@ -0,0 +1,88 @@
+//! What correct output looks like for each supported target.
+
+use linaro_elf::arm::FloatAbi;
+use linaro_elf::{Class, Endian, EM_386, EM_AARCH64, EM_ARM, EM_X86_64};
+use linaro_manifest::Triple;
+
+/// Properties every ELF file built for a target must have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expectation {
+    pub machine: u16,
+    pub class: Class,
+    pub endian: Endian,
+    /// Required EABI version, for 32-bit Arm.
+    pub eabi_version: Option<u8>,
+    /// Whether the hard-float calling convention is required (`Some(true)`)
+    /// or forbidden (`Some(false)`); `None` for non-Arm targets.
+    pub hard_float: Option<bool>,
+    /// The dynamic linker of dynamically linked executables; `None` for
+    /// bare-metal targets.
+    pub interpreter: Option<&'static str>,
+}
+
+impl Expectation {
+    /// The expectation for `triple`, if it is one we know.
+    pub fn for_triple(triple: &Triple) -> Option<Self> {
+        let arch = triple.arch();
+        let system = triple.system();
+        let linux = !triple.is_bare_metal();
+        let big = arch.ends_with("_be") || arch.starts_with("armeb");
+        let endian = if big { Endian::Big } else { Endian::Little };
+
+        let expectation = match arch {
+            "aarch64" | "aarch64_be" => Self {
+                machine: EM_AARCH64,
+                class: Class::Elf64,
+                endian,
+                eabi_version: None,
+                hard_float: None,
+                interpreter: linux.then_some(if big {
+                    "/lib/ld-linux-aarch64_be.so.1"
+                } else {
+                    "/lib/ld-linux-aarch64.so.1"
+                }),
+            },
+            arch if arch.starts_with("arm") => {
+                let hard = system.ends_with("eabihf");
+                Self {
+                    machine: EM_ARM,
+                    class: Class::Elf32,
+                    endian,
+                    eabi_version: system.contains("eabi").then_some(5),
+                    hard_float: Some(hard),
+                    interpreter: linux.then_some(if hard {
+                        "/lib/ld-linux-armhf.so.3"
+                    } else {
+                        "/lib/ld-linux.so.3"
+                    }),
+                }
+            }
+            "x86_64" => Self {
+                machine: EM_X86_64,
+                class: Class::Elf64,
+                endian,
+                eabi_version: None,
+                hard_float: None,
+                interpreter: linux.then_some("/lib64/ld-linux-x86-64.so.2"),
+            },
+            "i686" | "i586" | "i386" => Self {
+                machine: EM_386,
+                class: Class::Elf32,
+                endian,
+                eabi_version: None,
+                hard_float: None,
+                interpreter: linux.then_some("/lib/ld-linux.so.2"),
+            },
+            _ => return None,
+        };
+        Some(expectation)
+    }
+}
+
+pub(crate) fn float_abi_name(abi: FloatAbi) -> &'static str {
+    match abi {
+        FloatAbi::Soft => "soft",
+        FloatAbi::Softfp => "softfp",
+        FloatAbi::Hard => "hard",
+    }
+}
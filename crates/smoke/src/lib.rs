@@ -0,0 +1,316 @@
+//! Smoke tests for installed toolchains.
+//!
+//! Each toolchain builds a handful of bundled programs and the results are
+//! inspected with [`linaro_elf`]: machine, class, endianness, Arm EABI
+//! version and float ABI, dynamic linker and needed libraries. These checks
+//! run nothing, so they work on any build host and make a cheap gate before
+//! a toolchain is promoted.
+//!
+//! [`exec`] optionally goes further and runs a second set of programs,
+//! under QEMU user-mode emulation or, when the target is the host
+//! architecture, through the sysroot's dynamic linker, checking their output
+//! and exit status. It is skipped, and
+//! the report says why, for bare-metal targets and when no `qemu-<arch>`
+//! for the target is on `PATH` and the host cannot run the programs itself.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use linaro_elf::{machine_name, ElfInfo, Kind};
+use linaro_manifest::error_chain;
+use linaro_toolchain::Installation;
+use serde::Serialize;
+
+mod error;
+pub mod exec;
+pub mod expect;
+
+pub use error::Error;
+pub use expect::Expectation;
+
+/// How a case is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Build {
+    /// Compiled to a relocatable object only.
+    Object,
+    /// Linked against the shared C (and C++) runtime.
+    Dynamic,
+    /// Linked with `-static`.
+    Static,
+    /// Linked with `-nostdlib` for targets without a C library.
+    Freestanding,
+}
+
+impl Build {
+    fn applies(self, bare_metal: bool) -> bool {
+        match self {
+            Build::Object => true,
+            Build::Dynamic | Build::Static => !bare_metal,
+            Build::Freestanding => bare_metal,
+        }
+    }
+
+    fn flags(self) -> &'static [&'static str] {
+        match self {
+            Build::Object => &["-c"],
+            Build::Dynamic => &[],
+            Build::Static => &["-static"],
+            Build::Freestanding => &["-ffreestanding", "-nostdlib", "-static", "-Wl,-e,entry"],
+        }
+    }
+}
+
+/// A bundled program and how to build it.
+#[derive(Debug, Clone, Copy)]
+pub struct Case {
+    pub name: &'static str,
+    /// File name the source is written to; `.cpp` files are built with g++.
+    pub file: &'static str,
+    pub source: &'static str,
+    pub build: Build,
+    /// Libraries the output must list in `DT_NEEDED`.
+    pub needed: &'static [&'static str],
+}
+
+pub const CASES: &[Case] = &[
+    Case {
+        name: "hello-c",
+        file: "hello.c",
+        source: include_str!("../snippets/hello.c"),
+        build: Build::Dynamic,
+        needed: &["libc.so.6"],
+    },
+    Case {
+        name: "hello-cxx",
+        file: "hello.cpp",
+        source: include_str!("../snippets/hello.cpp"),
+        build: Build::Dynamic,
+        needed: &["libstdc++.so.6", "libc.so.6"],
+    },
+    Case {
+        name: "float-abi",
+        file: "float.c",
+        source: include_str!("../snippets/float.c"),
+        build: Build::Object,
+        needed: &[],
+    },
+    Case {
+        name: "static",
+        file: "static.c",
+        source: include_str!("../snippets/static.c"),
+        build: Build::Static,
+        needed: &[],
+    },
+    Case {
+        name: "freestanding",
+        file: "freestanding.c",
+        source: include_str!("../snippets/freestanding.c"),
+        build: Build::Freestanding,
+        needed: &[],
+    },
+];
+
+/// The outcome of one case.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CaseReport {
+    pub name: &'static str,
+    pub passed: bool,
+    /// Why the case failed: a compiler error or the checks that did not hold.
+    pub failures: Vec<String>,
+}
+
+/// The outcome of every applicable case for one toolchain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolchainReport {
+    pub root: PathBuf,
+    pub target: String,
+    pub cases: Vec<CaseReport>,
+    /// Checks that could not be run on this host, with the reason.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+}
+
+impl ToolchainReport {
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|c| c.passed)
+    }
+}
+
+/// Builds every case that applies to `installation` and checks the output.
+pub fn run(installation: &Installation) -> Result<ToolchainReport, Error> {
+    run_cases(installation, CASES)
+}
+
+/// Like [`run`], for a subset of [`CASES`]; cases that do not apply to the
+/// target are skipped.
+pub fn run_cases(installation: &Installation, cases: &[Case]) -> Result<ToolchainReport, Error> {
+    let triple = installation.triple();
+    let expectation =
+        Expectation::for_triple(triple).ok_or_else(|| Error::UnsupportedTarget(triple.clone()))?;
+    let work = work_dir()?;
+
+    let mut reports = Vec::new();
+    for case in cases
+        .iter()
+        .filter(|c| c.build.applies(triple.is_bare_metal()))
+    {
+        let failures = match compile(
+            installation,
+            work.path(),
+            case.file,
+            case.source,
+            case.build.flags(),
+        )? {
+            Ok(output) => match ElfInfo::read(&output) {
+                Ok(info) => check(&info, &expectation, case),
+                Err(err) => vec![format!("unreadable output: {}", error_chain(&err))],
+            },
+            Err(stderr) => vec![format!("build failed: {stderr}")],
+        };
+        reports.push(CaseReport {
+            name: case.name,
+            passed: failures.is_empty(),
+            failures,
+        });
+    }
+    Ok(ToolchainReport {
+        root: installation.root().to_owned(),
+        target: triple.to_string(),
+        cases: reports,
+        skipped: Vec::new(),
+    })
+}
+
+/// A scratch directory for build outputs, removed when dropped.
+pub(crate) fn work_dir() -> Result<tempfile::TempDir, Error> {
+    tempfile::Builder::new()
+        .prefix("linaro-smoke-")
+        .tempdir()
+        .map_err(|source| Error::Io {
+            path: std::env::temp_dir(),
+            source,
+        })
+}
+
+/// Compiles `file` (containing `source`) in `dir` with the extra `flags`,
+/// returning the output path or the last line of the compiler's diagnostics.
+pub(crate) fn compile(
+    installation: &Installation,
+    dir: &Path,
+    file: &str,
+    source: &str,
+    flags: &[&str],
+) -> Result<Result<PathBuf, String>, Error> {
+    let path = dir.join(file);
+    fs::write(&path, source).map_err(|source| Error::Io {
+        path: path.clone(),
+        source,
+    })?;
+    let output = dir.join(format!("{file}.out"));
+    let driver = installation.tool(if file.ends_with(".cpp") { "g++" } else { "gcc" });
+    let result = Command::new(&driver)
+        .args(["-O2", "-Wall"])
+        .args(flags)
+        .arg("-o")
+        .arg(&output)
+        .arg(&path)
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|source| Error::Run {
+            program: driver.clone(),
+            source,
+        })?;
+    if result.status.success() {
+        Ok(Ok(output))
+    } else {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        Ok(Err(stderr.trim().lines().last().unwrap_or("").to_owned()))
+    }
+}
+
+/// The ways `info`, built from `case`, falls short of `expectation`.
+pub fn check(info: &ElfInfo, expectation: &Expectation, case: &Case) -> Vec<String> {
+    let mut failures = Vec::new();
+    if info.machine != expectation.machine {
+        failures.push(format!(
+            "machine is {}, expected {}",
+            info.machine_name(),
+            machine_name(expectation.machine)
+        ));
+    }
+    if info.class != expectation.class {
+        failures.push(format!(
+            "class is {:?}, expected {:?}",
+            info.class, expectation.class
+        ));
+    }
+    if info.endian != expectation.endian {
+        failures.push(format!(
+            "{:?} endian, expected {:?}",
+            info.endian, expectation.endian
+        ));
+    }
+
+    let expected_kind = match case.build {
+        Build::Object => Some(Kind::Relocatable),
+        Build::Static | Build::Freestanding => Some(Kind::Executable),
+        // Position-independent executables are `ET_DYN`.
+        Build::Dynamic => None,
+    };
+    match expected_kind {
+        Some(kind) if info.kind != kind => {
+            failures.push(format!("file type is {:?}, expected {kind:?}", info.kind))
+        }
+        None if !matches!(info.kind, Kind::Executable | Kind::Shared) => failures.push(format!(
+            "file type is {:?}, expected an executable",
+            info.kind
+        )),
+        _ => {}
+    }
+
+    if let Some(version) = expectation.eabi_version {
+        match &info.arm {
+            Some(arm) if arm.eabi_version != version => failures.push(format!(
+                "EABI version is {}, expected {version}",
+                arm.eabi_version
+            )),
+            Some(_) => {}
+            None => failures.push("no Arm EABI information".to_owned()),
+        }
+    }
+    if let (Some(hard), Some(arm)) = (expectation.hard_float, &info.arm) {
+        match arm.float_abi() {
+            Some(abi) if (abi == linaro_elf::arm::FloatAbi::Hard) != hard => {
+                failures.push(format!(
+                    "float ABI is {}, expected {}",
+                    expect::float_abi_name(abi),
+                    if hard { "hard" } else { "soft or softfp" }
+                ))
+            }
+            Some(_) => {}
+            None => failures.push("float ABI is not recorded".to_owned()),
+        }
+    }
+
+    let expected_interpreter = match case.build {
+        Build::Dynamic => expectation.interpreter,
+        _ => None,
+    };
+    if info.interpreter.as_deref() != expected_interpreter {
+        failures.push(format!(
+            "interpreter is {}, expected {}",
+            info.interpreter.as_deref().unwrap_or("none"),
+            expected_interpreter.unwrap_or("none")
+        ));
+    }
+    for lib in case.needed {
+        if !info.needed.iter().any(|n| n == lib) {
+            failures.push(format!("{lib} is not in DT_NEEDED"));
+        }
+    }
+    if case.build != Build::Dynamic && !info.needed.is_empty() {
+        failures.push(format!("unexpected DT_NEEDED: {}", info.needed.join(", ")));
+    }
+    failures
+}
@@ -0,0 +1,253 @@
+use std::fs;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::Path;
+
+use linaro_elf::{Class, Endian, EM_AARCH64, EM_ARM};
+use linaro_manifest::Triple;
+use linaro_smoke::exec::{execute, qemu_name, Program, PROGRAMS};
+use linaro_smoke::{check, Build, Case, Expectation, CASES};
+use linaro_toolchain::Installation;
+
+fn expectation(triple: &str) -> Expectation {
+    Expectation::for_triple(&triple.parse::<Triple>().unwrap()).unwrap()
+}
+
+#[test]
+fn derives_expectations_from_triples() {
+    let aarch64 = expectation("aarch64-linux-gnu");
+    assert_eq!(aarch64.machine, EM_AARCH64);
+    assert_eq!(aarch64.class, Class::Elf64);
+    assert_eq!(aarch64.interpreter, Some("/lib/ld-linux-aarch64.so.1"));
+    assert_eq!(aarch64.hard_float, None);
+
+    let armhf = expectation("arm-linux-gnueabihf");
+    assert_eq!(armhf.machine, EM_ARM);
+    assert_eq!(armhf.eabi_version, Some(5));
+    assert_eq!(armhf.hard_float, Some(true));
+    assert_eq!(armhf.interpreter, Some("/lib/ld-linux-armhf.so.3"));
+
+    let armeb = expectation("armeb-linux-gnueabi");
+    assert_eq!(armeb.endian, Endian::Big);
+    assert_eq!(armeb.hard_float, Some(false));
+    assert_eq!(armeb.interpreter, Some("/lib/ld-linux.so.3"));
+
+    let bare = expectation("aarch64_be-elf");
+    assert_eq!(bare.endian, Endian::Big);
+    assert_eq!(bare.interpreter, None);
+
+    assert!(Expectation::for_triple(&"riscv64-linux-gnu".parse().unwrap()).is_none());
+}
+
+/// A fake installation whose `x86_64-linux-gnu-*` drivers are the host
+/// compiler, or `None` if the host has no GCC.
+fn host_installation(dir: &Path) -> Option<Installation> {
+    let bin = dir.join("bin");
+    fs::create_dir(&bin).unwrap();
+    for tool in ["gcc", "g++"] {
+        let host = Path::new("/usr/bin").join(tool);
+        if !host.exists() {
+            return None;
+        }
+        symlink(host, bin.join(format!("x86_64-linux-gnu-{tool}"))).unwrap();
+    }
+    Some(Installation::open(dir).unwrap())
+}
+
+#[test]
+fn host_toolchain_passes() {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = host_installation(dir.path()) else {
+        eprintln!("skipping: no host gcc");
+        return;
+    };
+    let report = linaro_smoke::run(&installation).unwrap();
+    assert_eq!(report.target, "x86_64-linux-gnu");
+    let names: Vec<_> = report.cases.iter().map(|c| c.name).collect();
+    assert_eq!(names, ["hello-c", "hello-cxx", "float-abi", "static"]);
+    assert!(report.passed(), "{report:#?}");
+}
+
+#[test]
+fn reports_mismatches_against_another_target() {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = host_installation(dir.path()) else {
+        eprintln!("skipping: no host gcc");
+        return;
+    };
+    let report = linaro_smoke::run(&installation).unwrap();
+    assert!(report.passed());
+
+    // Hold a host binary to the armhf expectations instead.
+    let hello = CASES.iter().find(|c| c.name == "hello-c").unwrap();
+    let exe = dir.path().join("hello");
+    let status = std::process::Command::new(installation.tool("gcc"))
+        .arg("-o")
+        .arg(&exe)
+        .arg("-xc")
+        .arg("-")
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            use std::io::Write;
+            child
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(hello.source.as_bytes())?;
+            child.wait()
+        })
+        .unwrap();
+    assert!(status.success());
+    let info = linaro_elf::ElfInfo::read(&exe).unwrap();
+    let failures = check(&info, &expectation("arm-linux-gnueabihf"), hello);
+    assert!(
+        failures.iter().any(|f| f.contains("machine is x86-64")),
+        "{failures:?}"
+    );
+    assert!(
+        failures.iter().any(|f| f.contains("no Arm EABI")),
+        "{failures:?}"
+    );
+    assert!(
+        failures.iter().any(|f| f.contains("ld-linux-armhf.so.3")),
+        "{failures:?}"
+    );
+
+    let as_static = Case {
+        build: Build::Static,
+        ..*hello
+    };
+    let failures = check(&info, &expectation("x86_64-linux-gnu"), &as_static);
+    assert!(
+        failures.iter().any(|f| f.starts_with("interpreter is")),
+        "{failures:?}"
+    );
+    assert!(
+        failures
+            .iter()
+            .any(|f| f.starts_with("unexpected DT_NEEDED")),
+        "{failures:?}"
+    );
+}
+
+#[test]
+fn names_qemu_emulators() {
+    for (triple, qemu) in [
+        ("aarch64-linux-gnu", Some("qemu-aarch64")),
+        ("aarch64_be-linux-gnu", Some("qemu-aarch64_be")),
+        ("arm-linux-gnueabihf", Some("qemu-arm")),
+        ("armv8l-linux-gnueabihf", Some("qemu-arm")),
+        ("armeb-linux-gnueabi", Some("qemu-armeb")),
+        ("riscv64-linux-gnu", None),
+    ] {
+        let triple: Triple = triple.parse().unwrap();
+        assert_eq!(qemu_name(&triple).as_deref(), qemu, "{triple}");
+    }
+}
+
+#[test]
+fn runs_host_programs() {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let Some(installation) = host_installation(dir.path()) else {
+        eprintln!("skipping: no host gcc");
+        return;
+    };
+    let report = execute(&installation, PROGRAMS).unwrap();
+    assert!(report.skipped.is_empty());
+    assert_eq!(report.cases.len(), PROGRAMS.len());
+    assert!(report.passed(), "{report:#?}");
+
+    let wrong = Program {
+        stdout: "argv[1]=uno\n",
+        exit_code: 0,
+        ..PROGRAMS[0]
+    };
+    let report = execute(&installation, &[wrong]).unwrap();
+    assert!(!report.passed());
+    let failures = &report.cases[0].failures;
+    assert_eq!(failures[0], "exit code 43, expected 0");
+    assert!(
+        failures[1].starts_with("stdout \"argv[1]=one\\n"),
+        "{failures:?}"
+    );
+}
+
+#[test]
+fn runs_host_programs_with_the_sysroot_loader() {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let bin = dir.path().join("bin");
+    let sysroot = dir.path().join("sysroot");
+    fs::create_dir_all(sysroot.join("lib")).unwrap();
+    // The host compiler, claiming a sysroot without a dynamic linker.
+    for tool in ["gcc", "g++"] {
+        let host = Path::new("/usr/bin").join(tool);
+        if !host.exists() {
+            eprintln!("skipping: no host gcc");
+            return;
+        }
+        let driver = bin.join(format!("x86_64-linux-gnu-{tool}"));
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(
+            &driver,
+            format!(
+                "#!/bin/sh
+                 [ \"$1\" = -print-sysroot ] && {{ echo {}; exit; }}
+                 exec {} \"$@\"
+",
+                sysroot.display(),
+                host.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&driver, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let installation = Installation::open(dir.path()).unwrap();
+    let report = execute(&installation, PROGRAMS).unwrap();
+    if !report.skipped.is_empty() || qemu_name(installation.triple()).is_some_and(on_path) {
+        eprintln!("skipping: runs under QEMU on this host");
+        return;
+    }
+    // Dynamic programs are never handed to the host's loader.
+    for case in &report.cases {
+        if case.name == "run-static" {
+            assert!(case.passed, "{case:?}");
+        } else {
+            assert_eq!(
+                case.failures,
+                [format!(
+                    "dynamic linker /lib64/ld-linux-x86-64.so.2 not found in {}",
+                    sysroot.display()
+                )]
+            );
+        }
+    }
+}
+
+fn on_path(name: String) -> bool {
+    std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default())
+        .any(|dir| dir.join(&name).is_file())
+}
+
+#[test]
+fn skips_targets_it_cannot_run() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("bin")).unwrap();
+    fs::write(dir.path().join("bin/aarch64-elf-gcc"), "").unwrap();
+    let installation = Installation::open(dir.path()).unwrap();
+    let report = execute(&installation, PROGRAMS).unwrap();
+    assert!(report.cases.is_empty());
+    assert!(report.passed());
+    assert_eq!(report.skipped, ["bare-metal target, nothing to run"]);
+}
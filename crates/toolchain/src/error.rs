@@ -0,0 +1,23 @@
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("no *-gcc driver found in {}", .0.display())]
+    NoDriver(PathBuf),
+    #[error("several gcc drivers in {}: {}", dir.display(), drivers.join(", "))]
+    AmbiguousDriver { dir: PathBuf, drivers: Vec<String> },
+    #[error("failed to run {}", program.display())]
+    Run { program: PathBuf, source: io::Error },
+    #[error("{} exited with {status}: {stderr}", program.display())]
+    Failed {
+        program: PathBuf,
+        status: ExitStatus,
+        stderr: String,
+    },
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("cannot parse `{what}` output: {reason}")]
+    Parse { what: &'static str, reason: String },
+}
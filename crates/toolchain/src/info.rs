@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use linaro_manifest::GccVersion;
+use serde::Serialize;
+
+use crate::parse::{self, Multilib};
+use crate::Error;
+
+/// Captured output of the commands [`Info`] is built from.
+#[derive(Debug, Clone, Default)]
+pub struct Outputs {
+    /// stderr of `gcc -v`.
+    pub verbose: String,
+    /// stdout of `gcc -dumpspecs`.
+    pub specs: String,
+    /// stdout of `gcc -print-multi-lib`.
+    pub multi_lib: String,
+    /// stdout of `gcc -print-sysroot`.
+    pub sysroot: String,
+    /// stdout of `ld --version`.
+    pub ld_version: String,
+    /// Contents of `<sysroot>/usr/include/features.h`, if there is one.
+    pub features_h: Option<String>,
+}
+
+/// Code generation defaults baked in at configure time.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Defaults {
+    /// Default `-march`.
+    pub arch: Option<String>,
+    /// Default `-mcpu`.
+    pub cpu: Option<String>,
+    /// Default `-mtune`.
+    pub tune: Option<String>,
+    /// Default `-mfpu` (32-bit Arm only).
+    pub fpu: Option<String>,
+    /// Default `-mfloat-abi` (32-bit Arm only).
+    pub float_abi: Option<String>,
+    /// Default instruction set, `arm` or `thumb` (32-bit Arm only).
+    pub mode: Option<String>,
+    /// Default `-mabi`.
+    pub abi: Option<String>,
+}
+
+/// Everything known about an installed toolchain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Info {
+    pub target: String,
+    pub gcc_version: GccVersion,
+    /// Package string, e.g. `Linaro GCC 7.5-2019.12`.
+    pub gcc_package: Option<String>,
+    pub binutils_version: String,
+    /// glibc version of the sysroot; `None` for bare-metal toolchains.
+    pub glibc_version: Option<String>,
+    pub defaults: Defaults,
+    /// Sysroot as printed by the driver; `None` when it has none.
+    pub sysroot: Option<PathBuf>,
+    /// Dynamic linker used for default links, e.g. `/lib/ld-linux-aarch64.so.1`.
+    pub dynamic_linker: Option<String>,
+    pub multilibs: Vec<Multilib>,
+    pub thread_model: Option<String>,
+    pub configure_args: Vec<String>,
+}
+
+impl Info {
+    /// Builds an [`Info`] from captured command output.
+    pub fn from_outputs(outputs: &Outputs) -> Result<Self, Error> {
+        let verbose = parse::verbose(&outputs.verbose)?;
+        let with = |name: &str| verbose.configured_with(name).map(str::to_owned);
+        let defaults = Defaults {
+            arch: with("arch"),
+            cpu: with("cpu"),
+            tune: with("tune"),
+            fpu: with("fpu"),
+            float_abi: with("float"),
+            mode: with("mode"),
+            abi: with("abi"),
+        };
+        let sysroot = outputs.sysroot.trim();
+        let specs = parse::specs(&outputs.specs);
+        Ok(Self {
+            target: verbose.target,
+            gcc_version: verbose.version,
+            gcc_package: verbose.package,
+            binutils_version: parse::ld_version(&outputs.ld_version)?,
+            glibc_version: outputs.features_h.as_deref().and_then(parse::glibc_version),
+            defaults,
+            sysroot: (!sysroot.is_empty()).then(|| PathBuf::from(sysroot)),
+            dynamic_linker: parse::dynamic_linker(&specs),
+            multilibs: parse::multi_lib(&outputs.multi_lib)?,
+            thread_model: verbose.thread_model,
+            configure_args: verbose.configure_args,
+        })
+    }
+}
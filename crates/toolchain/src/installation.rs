@@ -0,0 +1,148 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use linaro_manifest::Triple;
+
+use crate::{Error, Info, Outputs};
+
+/// A toolchain unpacked on disk, identified by its `bin/<triple>-gcc` driver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Installation {
+    root: PathBuf,
+    triple: Triple,
+}
+
+impl Installation {
+    /// Opens the toolchain rooted at `root`, discovering its target triple
+    /// from the driver binaries in `root/bin`.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, Error> {
+        let root = root.into();
+        let bin = root.join("bin");
+        let mut drivers = Vec::new();
+        for entry in fs::read_dir(&bin).map_err(|source| Error::Io {
+            path: bin.clone(),
+            source,
+        })? {
+            let entry = entry.map_err(|source| Error::Io {
+                path: bin.clone(),
+                source,
+            })?;
+            let name = entry.file_name();
+            let Some(triple) = name
+                .to_str()
+                .and_then(|n| n.strip_suffix("-gcc"))
+                .and_then(|t| t.parse::<Triple>().ok())
+            else {
+                continue;
+            };
+            drivers.push(triple);
+        }
+        drivers.sort();
+        match drivers.len() {
+            0 => Err(Error::NoDriver(bin)),
+            1 => Ok(Self {
+                root,
+                triple: drivers.remove(0),
+            }),
+            _ => Err(Error::AmbiguousDriver {
+                dir: bin,
+                drivers: drivers.iter().map(|t| format!("{t}-gcc")).collect(),
+            }),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn triple(&self) -> &Triple {
+        &self.triple
+    }
+
+    /// Path of the prefixed tool `name`, e.g. `tool("objcopy")` is
+    /// `<root>/bin/<triple>-objcopy`.
+    pub fn tool(&self, name: &str) -> PathBuf {
+        self.root
+            .join("bin")
+            .join(format!("{}{name}", self.triple.tool_prefix()))
+    }
+
+    /// The sysroot the driver links against, or `None` for toolchains
+    /// without one (bare metal, or a compiler using the host's headers).
+    pub fn sysroot(&self) -> Result<Option<PathBuf>, Error> {
+        let sysroot = run(&self.tool("gcc"), ["-print-sysroot"])?.0;
+        Ok(match sysroot.trim() {
+            "" => None,
+            dir => Some(fs::canonicalize(dir).unwrap_or_else(|_| dir.into())),
+        })
+    }
+
+    /// The directory holding the GCC runtime shared libraries (`libgcc_s`,
+    /// `libstdc++`, ...) for the default multilib, or `None` when the
+    /// toolchain has no shared libgcc, as for bare metal.
+    pub fn runtime_dir(&self) -> Result<Option<PathBuf>, Error> {
+        let path = run(&self.tool("gcc"), ["-print-file-name=libgcc_s.so.1"])?.0;
+        // The driver prints the bare name back when it has no such file.
+        let path = Path::new(path.trim());
+        Ok(path.is_absolute().then(|| {
+            let dir = path.parent().unwrap_or(path);
+            fs::canonicalize(dir).unwrap_or_else(|_| dir.to_owned())
+        }))
+    }
+
+    /// Runs the drivers and captures their output.
+    pub fn outputs(&self) -> Result<Outputs, Error> {
+        let gcc = self.tool("gcc");
+        let sysroot = run(&gcc, ["-print-sysroot"])?.0;
+        let features_h = match sysroot.trim() {
+            "" => None,
+            dir => fs::read_to_string(Path::new(dir).join("usr/include/features.h")).ok(),
+        };
+        Ok(Outputs {
+            verbose: run(&gcc, ["-v"])?.1,
+            specs: run(&gcc, ["-dumpspecs"])?.0,
+            multi_lib: run(&gcc, ["-print-multi-lib"])?.0,
+            sysroot,
+            ld_version: run(&self.tool("ld"), ["--version"])?.0,
+            features_h,
+        })
+    }
+
+    /// Runs the drivers and collects what they report.
+    pub fn introspect(&self) -> Result<Info, Error> {
+        let mut info = Info::from_outputs(&self.outputs()?)?;
+        if let Some(sysroot) = &mut info.sysroot {
+            if let Ok(canonical) = fs::canonicalize(&*sysroot) {
+                *sysroot = canonical;
+            }
+        }
+        Ok(info)
+    }
+}
+
+/// Runs `program` with the C locale, returning its stdout and stderr.
+fn run<I, S>(program: &Path, args: I) -> Result<(String, String), Error>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = Command::new(program)
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|source| Error::Run {
+            program: program.to_owned(),
+            source,
+        })?;
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    if !output.status.success() {
+        return Err(Error::Failed {
+            program: program.to_owned(),
+            status: output.status,
+            stderr: stderr.trim().to_owned(),
+        });
+    }
+    Ok((String::from_utf8_lossy(&output.stdout).into_owned(), stderr))
+}
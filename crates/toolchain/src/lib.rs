@@ -0,0 +1,16 @@
+//! Introspection of an installed Linaro toolchain.
+//!
+//! [`Installation`] locates the driver binaries in a toolchain directory and
+//! [`Installation::introspect`] runs them to build an [`Info`]. The parsers
+//! in [`parse`] work on captured text, so everything except running the
+//! drivers can be exercised without a cross compiler present.
+
+mod error;
+mod info;
+mod installation;
+pub mod parse;
+
+pub use error::Error;
+pub use info::{Defaults, Info, Outputs};
+pub use installation::Installation;
+pub use parse::Multilib;
@@ -0,0 +1,288 @@
+//! Parsers for the text printed by GCC drivers and binutils.
+//!
+//! Each function takes the captured output of one command, which is how the
+//! fixtures under `tests/fixtures` exercise them.
+
+use std::collections::BTreeMap;
+
+use linaro_manifest::GccVersion;
+use serde::Serialize;
+
+use crate::Error;
+
+/// What `gcc -v` reports about the compiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verbose {
+    /// The `Target:` line, e.g. `aarch64-linux-gnu`.
+    pub target: String,
+    pub version: GccVersion,
+    /// Package string in parentheses, e.g. `Linaro GCC 7.5-2019.12`.
+    pub package: Option<String>,
+    /// Arguments GCC was configured with, unquoted.
+    pub configure_args: Vec<String>,
+    pub thread_model: Option<String>,
+}
+
+impl Verbose {
+    /// Value of a `--with-<name>=<value>` configure option.
+    pub fn configured_with(&self, name: &str) -> Option<&str> {
+        let prefix = format!("--with-{name}=");
+        self.configure_args
+            .iter()
+            .find_map(|arg| arg.strip_prefix(prefix.as_str()))
+    }
+}
+
+/// Parses the stderr of `gcc -v`.
+pub fn verbose(text: &str) -> Result<Verbose, Error> {
+    let err = |reason: &str| Error::Parse {
+        what: "gcc -v",
+        reason: reason.to_owned(),
+    };
+    let mut target = None;
+    let mut version = None;
+    let mut package = None;
+    let mut configure_args = Vec::new();
+    let mut thread_model = None;
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("Target: ") {
+            target = Some(rest.trim().to_owned());
+        } else if let Some(rest) = line.strip_prefix("Configured with: ") {
+            // The first word is the path of the configure script itself.
+            configure_args = shell_words(rest).into_iter().skip(1).collect();
+        } else if let Some(rest) = line.strip_prefix("Thread model: ") {
+            thread_model = Some(rest.trim().to_owned());
+        } else if let Some(rest) = line.strip_prefix("gcc version ") {
+            let (number, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+            version = Some(
+                number
+                    .parse::<GccVersion>()
+                    .map_err(|reason| err(&reason))?,
+            );
+            let rest = rest.trim();
+            package = rest
+                .strip_prefix('(')
+                .and_then(|r| r.split_once(')'))
+                .map(|(p, _)| p.to_owned());
+        }
+    }
+    Ok(Verbose {
+        target: target.ok_or_else(|| err("no `Target:` line"))?,
+        version: version.ok_or_else(|| err("no `gcc version` line"))?,
+        package,
+        configure_args,
+        thread_model,
+    })
+}
+
+/// Splits a shell command line into words, honouring single and double
+/// quotes and backslash escapes.
+fn shell_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                }
+                in_word = true;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Parses the output of `gcc -dumpspecs` into a map from spec name to its
+/// (possibly multi-line) value.
+pub fn specs(text: &str) -> BTreeMap<String, String> {
+    let mut specs = BTreeMap::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+    for line in text.lines() {
+        if let Some(name) = line.strip_prefix('*').and_then(|l| l.strip_suffix(':')) {
+            if let Some((name, value)) = current.take() {
+                specs.insert(name, value.join("\n"));
+            }
+            current = Some((name.to_owned(), Vec::new()));
+        } else if line.is_empty() {
+            if let Some((name, value)) = current.take() {
+                specs.insert(name, value.join("\n"));
+            }
+        } else if let Some((_, value)) = current.as_mut() {
+            value.push(line);
+        }
+    }
+    if let Some((name, value)) = current {
+        specs.insert(name, value.join("\n"));
+    }
+    specs
+}
+
+/// Expands the conditional constructs of a spec string as the driver would
+/// when invoked without any options or input files.
+///
+/// `%{S:X}` and `%{S}` expand to nothing, `%{!S:X}` to `X`, and
+/// `%{S:X;:Y}` to `Y`. Other `%` sequences are left untouched.
+pub fn expand_spec_defaults(spec: &str) -> String {
+    let mut out = String::new();
+    let mut rest = spec;
+    while let Some(start) = rest.find("%{") {
+        out.push_str(&rest[..start]);
+        let body_start = start + 2;
+        let Some(len) = matching_brace(&rest[body_start..]) else {
+            // Unbalanced: keep the remainder verbatim.
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let body = &rest[body_start..body_start + len];
+        out.push_str(&expand_conditional(body));
+        rest = &rest[body_start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Length of the text before the `}` closing an already opened `%{`.
+fn matching_brace(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits `text` at `sep` characters outside nested braces.
+fn split_top_level(text: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            c if c == sep && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn expand_conditional(body: &str) -> String {
+    for alternative in split_top_level(body, ';') {
+        let Some((condition, value)) = alternative.split_once(':') else {
+            // `%{S}`, `%{S*}`, `%{<S}`: substitutes a switch that is absent.
+            return String::new();
+        };
+        let holds = condition.is_empty()
+            || condition
+                .split('|')
+                .any(|any| any.split('&').all(|atom| atom.starts_with('!')));
+        if holds {
+            return expand_spec_defaults(value);
+        }
+    }
+    String::new()
+}
+
+/// The dynamic linker a default link passes to `ld`, taken from the `link`
+/// spec.
+pub fn dynamic_linker(specs: &BTreeMap<String, String>) -> Option<String> {
+    let link = expand_spec_defaults(specs.get("link")?);
+    let mut words = link.split_whitespace();
+    words.find(|w| *w == "-dynamic-linker")?;
+    words.next().map(str::to_owned)
+}
+
+/// One line of `gcc -print-multi-lib`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Multilib {
+    /// Library directory relative to the default one; `.` for the default.
+    pub dir: String,
+    /// Options selecting this multilib, e.g. `["-mthumb", "-mfloat-abi=hard"]`.
+    pub flags: Vec<String>,
+}
+
+/// Parses the output of `gcc -print-multi-lib`.
+pub fn multi_lib(text: &str) -> Result<Vec<Multilib>, Error> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| {
+            let (dir, flags) = line.split_once(';').ok_or_else(|| Error::Parse {
+                what: "gcc -print-multi-lib",
+                reason: format!("no `;` in `{line}`"),
+            })?;
+            Ok(Multilib {
+                dir: dir.to_owned(),
+                flags: flags
+                    .split('@')
+                    .filter(|f| !f.is_empty())
+                    .map(|f| format!("-{f}"))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Extracts the binutils version from the first line of `ld --version`,
+/// e.g. `2.28.2.20170706` from
+/// `GNU ld (Linaro_Binutils-2019.12) 2.28.2.20170706`.
+pub fn ld_version(text: &str) -> Result<String, Error> {
+    text.lines()
+        .next()
+        .and_then(|l| l.split_whitespace().last())
+        .filter(|v| v.starts_with(|c: char| c.is_ascii_digit()))
+        .map(str::to_owned)
+        .ok_or_else(|| Error::Parse {
+            what: "ld --version",
+            reason: "no version on the first line".to_owned(),
+        })
+}
+
+/// Extracts the glibc version from the sysroot's `<features.h>`, e.g. `2.25`.
+pub fn glibc_version(features_h: &str) -> Option<String> {
+    let define = |name: &str| {
+        features_h.lines().find_map(|line| {
+            let mut words = line.split_whitespace();
+            (words.next() == Some("#define") && words.next() == Some(name))
+                .then(|| words.next())
+                .flatten()
+                .and_then(|v| v.parse::<u32>().ok())
+        })
+    };
+    Some(format!(
+        "{}.{}",
+        define("__GLIBC__")?,
+        define("__GLIBC_MINOR__")?
+    ))
+}
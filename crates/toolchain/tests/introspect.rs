@@ -0,0 +1,168 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use linaro_manifest::GccVersion;
+use linaro_toolchain::parse::{self, Multilib};
+use linaro_toolchain::{Error, Info, Installation, Outputs};
+
+fn fixtures(target: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(target)
+}
+
+fn outputs(target: &str) -> Outputs {
+    let dir = fixtures(target);
+    let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+    Outputs {
+        verbose: read("gcc-v.txt"),
+        specs: read("dumpspecs.txt"),
+        multi_lib: read("print-multi-lib.txt"),
+        sysroot: read("print-sysroot.txt"),
+        ld_version: read("ld-version.txt"),
+        features_h: fs::read_to_string(dir.join("features.h")).ok(),
+    }
+}
+
+#[test]
+fn aarch64_linux_gnu() {
+    let info = Info::from_outputs(&outputs("aarch64-linux-gnu")).unwrap();
+    assert_eq!(info.target, "aarch64-linux-gnu");
+    assert_eq!(info.gcc_version, GccVersion::new(7, 5, 0));
+    assert_eq!(info.gcc_package.as_deref(), Some("Linaro GCC 7.5-2019.12"));
+    assert_eq!(info.binutils_version, "2.28.2.20170706");
+    assert_eq!(info.glibc_version.as_deref(), Some("2.25"));
+    assert_eq!(info.defaults.arch.as_deref(), Some("armv8-a"));
+    assert_eq!(info.defaults.float_abi, None);
+    assert_eq!(
+        info.dynamic_linker.as_deref(),
+        Some("/lib/ld-linux-aarch64.so.1")
+    );
+    assert_eq!(
+        info.sysroot.as_deref(),
+        Some(Path::new(
+            "/opt/gcc-linaro-7.5.0-2019.12-x86_64_aarch64-linux-gnu/bin/../aarch64-linux-gnu/libc"
+        ))
+    );
+    assert_eq!(
+        info.multilibs,
+        [Multilib {
+            dir: ".".into(),
+            flags: vec![]
+        }]
+    );
+    assert_eq!(info.thread_model.as_deref(), Some("posix"));
+    assert!(info
+        .configure_args
+        .iter()
+        .any(|a| a == "--disable-multilib"));
+}
+
+#[test]
+fn arm_linux_gnueabihf() {
+    let info = Info::from_outputs(&outputs("arm-linux-gnueabihf")).unwrap();
+    assert_eq!(info.target, "arm-linux-gnueabihf");
+    assert_eq!(info.defaults.arch.as_deref(), Some("armv7-a"));
+    assert_eq!(info.defaults.tune.as_deref(), Some("cortex-a9"));
+    assert_eq!(info.defaults.fpu.as_deref(), Some("vfpv3-d16"));
+    assert_eq!(info.defaults.float_abi.as_deref(), Some("hard"));
+    assert_eq!(info.defaults.mode.as_deref(), Some("thumb"));
+    assert_eq!(
+        info.dynamic_linker.as_deref(),
+        Some("/lib/ld-linux-armhf.so.3")
+    );
+}
+
+#[test]
+fn arm_eabi_bare_metal() {
+    let info = Info::from_outputs(&outputs("arm-eabi")).unwrap();
+    assert_eq!(info.glibc_version, None);
+    assert_eq!(info.sysroot, None);
+    assert_eq!(info.dynamic_linker, None);
+    assert_eq!(info.multilibs.len(), 7);
+    assert_eq!(info.multilibs[5].dir, "thumb/v7e-m/fpv4-sp/hard");
+    assert_eq!(
+        info.multilibs[5].flags,
+        ["-mthumb", "-march=armv7e-m+fp", "-mfloat-abi=hard"]
+    );
+}
+
+#[test]
+fn spec_defaults() {
+    assert_eq!(parse::expand_spec_defaults("a %{s:b} c"), "a  c");
+    assert_eq!(parse::expand_spec_defaults("%{!s:%{t:x;:y}}"), "y");
+    assert_eq!(parse::expand_spec_defaults("%{s|!t:x}%{s&!t:y}"), "x");
+    assert_eq!(parse::expand_spec_defaults("%{s:;p:x;!q:z}"), "z");
+    assert_eq!(parse::expand_spec_defaults("%(linker) %{o*}"), "%(linker) ");
+}
+
+#[test]
+fn rejects_truncated_output() {
+    let mut outputs = outputs("aarch64-linux-gnu");
+    outputs.verbose = outputs.verbose.replace("Target: ", "");
+    assert!(matches!(
+        Info::from_outputs(&outputs),
+        Err(Error::Parse { what: "gcc -v", .. })
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn introspects_installed_toolchain() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixtures = fixtures("aarch64-linux-gnu");
+    let root = tempfile::tempdir().unwrap();
+    let bin = root.path().join("bin");
+    let sysroot = root.path().join("aarch64-linux-gnu/libc");
+    fs::create_dir_all(&bin).unwrap();
+    fs::create_dir_all(sysroot.join("usr/include")).unwrap();
+    fs::copy(
+        fixtures.join("features.h"),
+        sysroot.join("usr/include/features.h"),
+    )
+    .unwrap();
+
+    let f = fixtures.display();
+    let scripts = [
+        (
+            "aarch64-linux-gnu-gcc",
+            format!(
+                "case \"$1\" in\n\
+                 -v) cat {f}/gcc-v.txt >&2 ;;\n\
+                 -dumpspecs) cat {f}/dumpspecs.txt ;;\n\
+                 -print-multi-lib) cat {f}/print-multi-lib.txt ;;\n\
+                 -print-sysroot) echo {}/bin/../aarch64-linux-gnu/libc ;;\n\
+                 esac",
+                root.path().display()
+            ),
+        ),
+        ("aarch64-linux-gnu-ld", format!("cat {f}/ld-version.txt")),
+        ("aarch64-linux-gnu-gcc-ar", "exit 1".to_owned()),
+    ];
+    for (name, body) in scripts {
+        let path = bin.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let installation = Installation::open(root.path()).unwrap();
+    assert_eq!(installation.triple().as_str(), "aarch64-linux-gnu");
+    assert_eq!(
+        installation.tool("objcopy"),
+        bin.join("aarch64-linux-gnu-objcopy")
+    );
+    let info = installation.introspect().unwrap();
+    assert_eq!(info.glibc_version.as_deref(), Some("2.25"));
+    assert_eq!(info.sysroot, Some(fs::canonicalize(&sysroot).unwrap()));
+}
+
+#[test]
+fn requires_a_driver() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir(root.path().join("bin")).unwrap();
+    assert!(matches!(
+        Installation::open(root.path()),
+        Err(Error::NoDriver(_))
+    ));
+}
@@ -0,0 +1,54 @@
+//! Classifying GCC driver arguments, shared by the invocation [`log`] and
+//! the `linaro-objcache` launcher so that both read a command line the same
+//! way.
+//!
+//! [`log`]: crate::log
+
+use std::path::Path;
+
+/// Options whose value is the next argument.
+pub const WITH_VALUE: &[&str] = &[
+    "-o",
+    "-x",
+    "-I",
+    "-D",
+    "-U",
+    "-include",
+    "-imacros",
+    "-isystem",
+    "-iquote",
+    "-idirafter",
+    "-iprefix",
+    "-iwithprefix",
+    "-iwithprefixbefore",
+    "-isysroot",
+    "-MF",
+    "-MT",
+    "-MQ",
+    "-L",
+    "-l",
+    "-T",
+    "-u",
+    "-z",
+    "-e",
+    "-Xlinker",
+    "-Xassembler",
+    "-Xpreprocessor",
+    "--param",
+    "--sysroot",
+    "-aux-info",
+    "-wrapper",
+];
+
+/// Extensions GCC compiles or assembles.
+pub const SOURCE_EXTENSIONS: &[&str] = &[
+    "c", "cc", "cp", "cxx", "cpp", "CPP", "c++", "C", "i", "ii", "s", "S", "sx",
+];
+
+/// Whether `arg` names a file GCC compiles or assembles, by its extension.
+pub fn is_source(arg: &str) -> bool {
+    Path::new(arg)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| SOURCE_EXTENSIONS.contains(&e))
+}
@@ -0,0 +1,29 @@
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("malformed wrapper config {}: {reason}", path.display())]
+    Config { path: PathBuf, reason: String },
+    #[error("{}:{line}: malformed invocation: {reason}", path.display())]
+    Log {
+        path: PathBuf,
+        line: usize,
+        reason: String,
+    },
+    #[error("{tool} is not in the toolchain ({} does not exist)", path.display())]
+    UnknownTool { tool: String, path: PathBuf },
+    #[error("cannot tell which tool to run from `{0}`")]
+    NoTool(String),
+    #[error(transparent)]
+    Toolchain(#[from] linaro_toolchain::Error),
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::Io { path, source }
+    }
+}
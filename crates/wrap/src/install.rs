@@ -0,0 +1,97 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use linaro_toolchain::Installation;
+
+use crate::{Config, Error, CONFIG_FILE};
+
+/// What [`install`] set up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Installed {
+    pub dir: PathBuf,
+    /// The wrapper names, e.g. `aarch64-linux-gnu-gcc`.
+    pub tools: Vec<String>,
+    pub config: Config,
+}
+
+/// Installs a wrapper in `dir` for every `bin/<triple>-*` tool of
+/// `installation`, as a hard link to (or, across file systems, a copy of)
+/// the `linaro-wrap` executable `wrapper`, and writes [`CONFIG_FILE`]. The
+/// `scrub` and `flags` of an existing configuration are kept.
+pub fn install(
+    installation: &Installation,
+    dir: &Path,
+    wrapper: &Path,
+) -> Result<Installed, Error> {
+    fs::create_dir_all(dir).map_err(Error::io(dir))?;
+    let canonical = |path: &Path| fs::canonicalize(path).map_err(Error::io(path));
+    let root = canonical(installation.root())?;
+    let toolchain = relative(&canonical(dir)?, &root);
+    let sysroot = installation
+        .sysroot()?
+        .map(|s| s.strip_prefix(&root).map(Path::to_owned).unwrap_or(s));
+
+    let config_path = dir.join(CONFIG_FILE);
+    let triple = installation.triple().clone();
+    let config = match Config::load(&config_path) {
+        Ok(old) => Config {
+            toolchain,
+            triple,
+            sysroot,
+            ..old
+        },
+        Err(Error::Io { source, .. }) if source.kind() == io::ErrorKind::NotFound => Config {
+            sysroot,
+            ..Config::new(toolchain, triple)
+        },
+        Err(err) => return Err(err),
+    };
+
+    let bin = installation.root().join("bin");
+    let prefix = config.triple.tool_prefix();
+    let mut tools = Vec::new();
+    for entry in fs::read_dir(&bin).map_err(Error::io(&bin))? {
+        let entry = entry.map_err(Error::io(&bin))?;
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        if name.starts_with(&prefix) {
+            tools.push(name);
+        }
+    }
+    tools.sort();
+    for name in &tools {
+        let link = dir.join(name);
+        match fs::remove_file(&link) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(Error::io(&link)(err)),
+            _ => {}
+        }
+        if fs::hard_link(wrapper, &link).is_err() {
+            fs::copy(wrapper, &link).map_err(Error::io(&link))?;
+        }
+    }
+    fs::write(&config_path, config.to_toml()).map_err(Error::io(&config_path))?;
+
+    Ok(Installed {
+        dir: dir.to_owned(),
+        tools,
+        config,
+    })
+}
+
+/// The path of `to` relative to the directory `from`; both are canonical.
+fn relative(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut path: PathBuf = from[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .collect();
+    path.extend(&to[common..]);
+    if path.as_os_str().is_empty() {
+        path.push(".");
+    }
+    path
+}
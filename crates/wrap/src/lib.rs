@@ -0,0 +1,243 @@
+//! Wrapper executables that make a vendored toolchain relocatable and
+//! hermetic.
+//!
+//! One program, `linaro-wrap`, is [`install`]ed under the name of every tool
+//! of a toolchain (`aarch64-linux-gnu-gcc`, `aarch64-linux-gnu-objcopy`, …)
+//! next to a [`CONFIG_FILE`]:
+//!
+//! ```toml
+//! toolchain = "../../x86_64/aarch64-linux-gnu/7.5.0"
+//! triple = "aarch64-linux-gnu"
+//! sysroot = "aarch64-linux-gnu/libc"
+//! scrub = ["CPATH", "LIBRARY_PATH"]
+//!
+//! [flags]
+//! gcc = ["-ffile-prefix-map=/build=."]
+//! ```
+//!
+//! When run, a wrapper finds the real tool relative to its own directory, so
+//! the tree works wherever it is checked out. Compiler drivers and linkers
+//! get `--sysroot` (unless the caller passes one) and then the tool's
+//! `flags`, ahead of the caller's arguments; the `scrub` variables, which
+//! would let the host's headers and libraries leak into the build, are
+//! removed from the environment.
+//!
+//! With a `log` file configured, or [`log::LOG_VAR`] set, each run is also
+//! appended to an invocation [`log`], from which
+//! [`log::compile_commands`] derives a `compile_commands.json`.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use linaro_manifest::Triple;
+use serde::{Deserialize, Serialize};
+
+pub mod argv;
+mod error;
+mod install;
+pub mod log;
+
+pub use error::Error;
+pub use install::{install, Installed};
+
+/// Name of the configuration file read by wrappers in the same directory.
+pub const CONFIG_FILE: &str = "linaro-wrap.toml";
+
+/// Variables scrubbed by default: GCC's and the linker's search paths, and
+/// the dynamic loader's.
+pub const DEFAULT_SCRUB: &[&str] = &[
+    "CPATH",
+    "C_INCLUDE_PATH",
+    "CPLUS_INCLUDE_PATH",
+    "OBJC_INCLUDE_PATH",
+    "LIBRARY_PATH",
+    "COMPILER_PATH",
+    "GCC_EXEC_PREFIX",
+    "GCC_COMPARE_DEBUG",
+    "DEPENDENCIES_OUTPUT",
+    "SUNPRO_DEPENDENCIES",
+    "LD_RUN_PATH",
+    "LD_LIBRARY_PATH",
+];
+
+/// Tools that accept `--sysroot`.
+const SYSROOT_TOOLS: &[&str] = &["gcc", "g++", "c++", "cpp", "ld", "ld.bfd", "ld.gold"];
+
+/// The contents of [`CONFIG_FILE`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The toolchain root, relative to the configuration file.
+    pub toolchain: PathBuf,
+    pub triple: Triple,
+    /// The sysroot passed to compiler drivers and linkers, relative to the
+    /// toolchain root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sysroot: Option<PathBuf>,
+    /// Environment variables removed before the tool runs.
+    #[serde(default = "default_scrub")]
+    pub scrub: Vec<String>,
+    /// File invocations are appended to, relative to the configuration
+    /// file; [`log::LOG_VAR`] overrides it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<PathBuf>,
+    /// Variables whose values are recorded in the log.
+    #[serde(default = "default_log_env")]
+    pub log_env: Vec<String>,
+    /// Flags inserted before the caller's arguments, by tool name without
+    /// the triple (`gcc`, `ld`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub flags: BTreeMap<String, Vec<String>>,
+}
+
+fn default_scrub() -> Vec<String> {
+    DEFAULT_SCRUB.iter().map(|&v| v.to_owned()).collect()
+}
+
+fn default_log_env() -> Vec<String> {
+    log::DEFAULT_LOG_ENV.iter().map(|&v| v.to_owned()).collect()
+}
+
+impl Config {
+    /// A configuration with no sysroot or flags, scrubbing [`DEFAULT_SCRUB`].
+    pub fn new(toolchain: impl Into<PathBuf>, triple: Triple) -> Self {
+        Self {
+            toolchain: toolchain.into(),
+            triple,
+            sysroot: None,
+            scrub: default_scrub(),
+            log: None,
+            log_env: default_log_env(),
+            flags: BTreeMap::new(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(Error::io(path))?;
+        toml::from_str(&text).map_err(|err| Error::Config {
+            path: path.to_owned(),
+            reason: err.to_string(),
+        })
+    }
+
+    pub fn to_toml(&self) -> String {
+        let body = toml::to_string(self).expect("wrapper configs serialize");
+        format!(
+            "# Read by the linaro-wrap executables in this directory; paths are\n\
+             # relative to it. Regenerate with `linaro-prebuilts wrappers`, which\n\
+             # keeps `scrub`, `flags` and the logging settings.\n\n{body}"
+        )
+    }
+}
+
+/// A wrapper directory and its configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wrapper {
+    dir: PathBuf,
+    config: Config,
+}
+
+impl Wrapper {
+    pub fn new(dir: impl Into<PathBuf>, config: Config) -> Self {
+        Self {
+            dir: dir.into(),
+            config,
+        }
+    }
+
+    /// The wrapper for the executable at `exe`, configured by the
+    /// [`CONFIG_FILE`] beside it.
+    pub fn locate(exe: &Path) -> Result<Self, Error> {
+        let dir = exe.parent().unwrap_or(Path::new("."));
+        Ok(Self::new(dir, Config::load(dir.join(CONFIG_FILE))?))
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The toolchain root.
+    pub fn toolchain(&self) -> PathBuf {
+        normalize(&self.dir.join(&self.config.toolchain))
+    }
+
+    /// The tool a wrapper invoked as `invoked` stands for: the name without
+    /// the triple prefix, so `aarch64-linux-gnu-gcc` and a `gcc` link to it
+    /// both run `gcc`.
+    pub fn tool_name<'a>(&self, invoked: &'a str) -> &'a str {
+        invoked
+            .strip_prefix(&self.config.triple.tool_prefix())
+            .unwrap_or(invoked)
+    }
+
+    /// Where invocations are logged, if anywhere: [`log::LOG_VAR`] if set,
+    /// else the configured `log`.
+    pub fn log_path(&self) -> Option<PathBuf> {
+        match std::env::var_os(log::LOG_VAR) {
+            Some(path) if !path.is_empty() => Some(path.into()),
+            _ => self.config.log.as_ref().map(|log| self.dir.join(log)),
+        }
+    }
+
+    /// The command running the real `tool` with `args`.
+    pub fn command<I>(&self, tool: &str, args: I) -> Result<Command, Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString>,
+    {
+        let toolchain = self.toolchain();
+        let path = toolchain
+            .join("bin")
+            .join(format!("{}{tool}", self.config.triple.tool_prefix()));
+        if tool.is_empty() || tool.contains('/') || !path.is_file() {
+            return Err(Error::UnknownTool {
+                tool: tool.to_owned(),
+                path,
+            });
+        }
+        let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+
+        let mut command = Command::new(&path);
+        if let Some(sysroot) = &self.config.sysroot {
+            let given = args.iter().any(|a| {
+                a.to_str()
+                    .is_some_and(|a| a == "--sysroot" || a.starts_with("--sysroot="))
+            });
+            if SYSROOT_TOOLS.contains(&tool) && !given {
+                let mut flag = OsString::from("--sysroot=");
+                flag.push(toolchain.join(sysroot));
+                command.arg(flag);
+            }
+        }
+        if let Some(flags) = self.config.flags.get(tool) {
+            command.args(flags);
+        }
+        command.args(args);
+        for var in &self.config.scrub {
+            command.env_remove(var);
+        }
+        Ok(command)
+    }
+}
+
+/// `path` with `.` components dropped and `..` applied where possible,
+/// without touching the file system.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                out.pop();
+            }
+            component => out.push(component),
+        }
+    }
+    out
+}
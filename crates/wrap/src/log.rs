@@ -0,0 +1,208 @@
+//! Invocation logs, and the compilation database derived from them.
+//!
+//! A log holds one JSON [`Invocation`] per line, appended by every wrapper
+//! writing to it, so one file can collect a whole parallel build.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::argv::{is_source, WITH_VALUE};
+use crate::{Error, Wrapper};
+
+/// Environment variable naming the log file, overriding the configuration.
+/// It should be absolute, as builds run tools from many directories.
+pub const LOG_VAR: &str = "LINARO_WRAP_LOG";
+
+/// Variables recorded with each invocation by default.
+pub const DEFAULT_LOG_ENV: &[&str] = &[
+    "PATH",
+    "ARCH",
+    "CROSS_COMPILE",
+    "SOURCE_DATE_EPOCH",
+    "LANG",
+    "LC_ALL",
+];
+
+/// Tools whose invocations can compile sources.
+const COMPILERS: &[&str] = &["gcc", "g++", "c++", "cc"];
+
+/// One run of a wrapped tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Invocation {
+    /// The tool name without the triple, e.g. `gcc`.
+    pub tool: String,
+    /// The real tool and its arguments, including those the wrapper added.
+    pub arguments: Vec<String>,
+    pub directory: PathBuf,
+    /// The logged variables that were set, after scrubbing.
+    pub env: BTreeMap<String, String>,
+    /// Scrubbed variables that had been set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scrubbed: Vec<String>,
+    /// Start time, in milliseconds since the Unix epoch.
+    pub started_ms: u64,
+    pub duration_ms: u64,
+    /// The exit status; `None` if the tool was killed by `signal`.
+    pub status: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<i32>,
+}
+
+/// The outcome of [`Wrapper::run_logged`].
+#[derive(Debug)]
+pub struct Logged {
+    pub status: ExitStatus,
+    /// Why the invocation could not be logged; the tool ran regardless.
+    pub log_error: Option<Error>,
+}
+
+impl Wrapper {
+    /// Runs the real `tool` with `args` to completion and appends the
+    /// invocation to `log`.
+    pub fn run_logged<I>(&self, tool: &str, args: I, log: &Path) -> Result<Logged, Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString>,
+    {
+        let mut command = self.command(tool, args)?;
+        let directory = env::current_dir().unwrap_or_default();
+        let started_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let start = Instant::now();
+        let status = command.status().map_err(Error::io(command.get_program()))?;
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        let config = self.config();
+        let scrubbed = |name: &String| config.scrub.contains(name);
+        let invocation = Invocation {
+            tool: tool.to_owned(),
+            arguments: std::iter::once(command.get_program())
+                .chain(command.get_args())
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect(),
+            directory,
+            env: config
+                .log_env
+                .iter()
+                .filter(|name| !scrubbed(name))
+                .filter_map(|name| Some((name.clone(), env::var(name).ok()?)))
+                .collect(),
+            scrubbed: config
+                .scrub
+                .iter()
+                .filter(|name| env::var_os(name).is_some())
+                .cloned()
+                .collect(),
+            started_ms,
+            duration_ms,
+            status: status.code(),
+            signal: status.signal(),
+        };
+        Ok(Logged {
+            status,
+            log_error: append(log, &invocation).err(),
+        })
+    }
+}
+
+/// Appends `invocation` to the log at `path`. The line is written with a
+/// single `write`, so concurrent wrappers do not interleave.
+pub fn append(path: &Path, invocation: &Invocation) -> Result<(), Error> {
+    let mut line = serde_json::to_vec(invocation).expect("invocations serialize");
+    line.push(b'\n');
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&line))
+        .map_err(Error::io(path))
+}
+
+/// Reads the log at `path`. Blank lines are skipped.
+pub fn read(path: &Path) -> Result<Vec<Invocation>, Error> {
+    let text = fs::read_to_string(path).map_err(Error::io(path))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|err| Error::Log {
+                path: path.to_owned(),
+                line: index + 1,
+                reason: err.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// An entry of a `compile_commands.json` compilation database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompileCommand {
+    pub directory: PathBuf,
+    /// The source file, as given to the compiler.
+    pub file: String,
+    pub arguments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+/// The compilation database for the compiler invocations among
+/// `invocations`: one entry per source file, from the last invocation
+/// compiling it, ordered by path. Preprocessing-only runs (`-E`, `-M`,
+/// `-MM`) and other tools are left out.
+pub fn compile_commands(invocations: &[Invocation]) -> Vec<CompileCommand> {
+    let mut commands = BTreeMap::new();
+    for invocation in invocations {
+        if !COMPILERS.contains(&invocation.tool.as_str()) {
+            continue;
+        }
+        let args = invocation.arguments.get(1..).unwrap_or_default();
+        if args
+            .iter()
+            .any(|a| matches!(a.as_str(), "-E" | "-M" | "-MM"))
+        {
+            continue;
+        }
+        let mut sources = Vec::new();
+        let mut output = None;
+        let mut language = false;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if WITH_VALUE.contains(&arg.as_str()) {
+                let value = iter.next();
+                match arg.as_str() {
+                    "-o" => output = value.cloned(),
+                    "-x" => language = value.is_some_and(|v| v != "none"),
+                    _ => {}
+                }
+            } else if arg.starts_with('-') {
+                if let Some(value) = arg.strip_prefix("-x") {
+                    language = value != "none";
+                }
+            } else if language || is_source(arg) {
+                sources.push(arg.clone());
+            }
+        }
+        for file in sources {
+            let key = crate::normalize(&invocation.directory.join(&file));
+            let command = CompileCommand {
+                directory: invocation.directory.clone(),
+                file,
+                arguments: invocation.arguments.clone(),
+                output: output.clone(),
+            };
+            commands.insert(key, command);
+        }
+    }
+    commands.into_values().collect()
+}
@@ -0,0 +1,61 @@
+//! `linaro-wrap`: runs the toolchain tool it is named after, as configured
+//! by the `linaro-wrap.toml` beside it (see the `linaro_wrap` library).
+//!
+//! Failures of the wrapper itself exit with status 127, as a shell does
+//! when it cannot run a command. Otherwise the real tool replaces this
+//! process, or, when invocations are logged, runs as a child whose status
+//! the wrapper passes on (128 plus the signal number if it was killed).
+
+use std::convert::Infallible;
+use std::env;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::Path;
+use std::process::ExitCode;
+
+use linaro_manifest::error_chain;
+use linaro_wrap::{Error, Wrapper};
+
+const EXIT_WRAPPER: u8 = 127;
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(never) => match never {},
+        Err(err) => {
+            eprintln!("linaro-wrap: {}", error_chain(&err));
+            ExitCode::from(EXIT_WRAPPER)
+        }
+    }
+}
+
+fn run() -> Result<Infallible, Error> {
+    let mut args = env::args_os();
+    let argv0 = args.next().unwrap_or_default();
+    let invoked = Path::new(&argv0)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    if invoked.is_empty() || invoked == env!("CARGO_PKG_NAME") {
+        return Err(Error::NoTool(argv0.to_string_lossy().into_owned()));
+    }
+    let exe = env::current_exe().map_err(|source| Error::Io {
+        path: "/proc/self/exe".into(),
+        source,
+    })?;
+    let wrapper = Wrapper::locate(&exe)?;
+    let tool = wrapper.tool_name(invoked);
+    if let Some(log) = wrapper.log_path() {
+        let logged = wrapper.run_logged(tool, args, &log)?;
+        if let Some(err) = logged.log_error {
+            eprintln!("linaro-wrap: warning: not logged: {}", error_chain(&err));
+        }
+        let status = logged.status;
+        let code = status.code().or(status.signal().map(|s| 128 + s));
+        std::process::exit(code.unwrap_or(EXIT_WRAPPER.into()));
+    }
+    let mut command = wrapper.command(tool, args)?;
+    let source = command.exec();
+    Err(Error::Io {
+        path: command.get_program().into(),
+        source,
+    })
+}
@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use linaro_golden::check_golden;
+use linaro_wrap::log::{self, compile_commands, Invocation};
+use linaro_wrap::Error;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn derives_compile_commands() {
+    let invocations = log::read(&fixture("invocations.jsonl")).unwrap();
+    assert_eq!(invocations.len(), 9);
+    let commands = compile_commands(&invocations);
+    let files: Vec<_> = commands
+        .iter()
+        .map(|c| (c.directory.to_str().unwrap(), c.file.as_str()))
+        .collect();
+    assert_eq!(
+        files,
+        [
+            ("/build/app", "gen/table.inc"),
+            ("/build/app", "hello.c"),
+            ("/build/app/out", "../src/app.cpp"),
+            ("/build/app", "util.c"),
+            ("/build/linux", "arch/arm64/kernel/head.S"),
+            ("/build/linux", "init/main.c"),
+        ]
+    );
+    // The later, failed compile of init/main.c wins.
+    assert!(commands[5].arguments.contains(&"-g".to_owned()));
+    assert_eq!(commands[5].output.as_deref(), Some("init/main.o"));
+
+    let json = serde_json::to_string_pretty(&commands).unwrap() + "\n";
+    check_golden!("compile_commands.json", &json);
+}
+
+#[test]
+fn appends_and_reads_logs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("build.log");
+    let invocations = log::read(&fixture("invocations.jsonl")).unwrap();
+    for invocation in &invocations[..2] {
+        log::append(&path, invocation).unwrap();
+    }
+    assert_eq!(log::read(&path).unwrap(), &invocations[..2]);
+
+    let mut text = fs::read_to_string(&path).unwrap();
+    text.push_str("{\"tool\":\"gcc\"\n");
+    fs::write(&path, text).unwrap();
+    let err = log::read(&path).unwrap_err();
+    assert!(matches!(err, Error::Log { line: 3, .. }), "{err}");
+
+    let stray: Result<Invocation, _> = serde_json::from_str("{\"tool\":\"gcc\",\"argv\":[]}");
+    assert!(stray.is_err());
+}
@@ -0,0 +1,261 @@
+use std::fs;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use linaro_toolchain::Installation;
+use linaro_wrap::log::{self, LOG_VAR};
+use linaro_wrap::{install, Config, Error, Wrapper, CONFIG_FILE};
+
+const WRAPPER: &str = env!("CARGO_BIN_EXE_linaro-wrap");
+
+/// A stand-in for a real tool: prints its name, arguments and some of its
+/// environment; `gcc -print-sysroot` prints the toolchain's libc, and
+/// `--fail` and `--crash` make it exit with status 3 or SIGTERM.
+const STUB: &str = r#"#!/bin/sh
+if [ "$1" = -print-sysroot ]; then cd "$(dirname "$0")/../x86_64-linux-gnu/libc" && pwd; exit; fi
+for arg; do
+  case "$arg" in --fail) echo failed >&2; exit 3 ;; --crash) kill -TERM $$ ;; esac
+done
+echo "tool=${0##*/}"
+for arg; do echo "arg=$arg"; done
+echo "CPATH=${CPATH-unset}"
+echo "HOME=${HOME-unset}"
+"#;
+
+/// A fake x86-64 toolchain at `root` with gcc, g++ and objcopy.
+fn toolchain(root: &Path) -> Installation {
+    fs::create_dir_all(root.join("bin")).unwrap();
+    fs::create_dir_all(root.join("x86_64-linux-gnu/libc/usr/include")).unwrap();
+    for tool in ["gcc", "g++", "objcopy"] {
+        let path = root.join(format!("bin/x86_64-linux-gnu-{tool}"));
+        fs::write(&path, STUB).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    Installation::open(root).unwrap()
+}
+
+fn run(wrapper: &Path, args: &[&str]) -> (i32, String, String) {
+    let output = Command::new(wrapper)
+        .args(args)
+        .env("CPATH", "/usr/include")
+        .env("HOME", "/home/builder")
+        .output()
+        .unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn builds_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    toolchain(&dir.path().join("toolchain"));
+    let mut config = Config::new("../toolchain", "x86_64-linux-gnu".parse().unwrap());
+    config.sysroot = Some("x86_64-linux-gnu/libc".into());
+    config.flags.insert("gcc".into(), vec!["-O2".into()]);
+    let wrapper = Wrapper::new(dir.path().join("wrappers"), config);
+    assert_eq!(wrapper.toolchain(), dir.path().join("toolchain"));
+    assert_eq!(wrapper.tool_name("x86_64-linux-gnu-g++"), "g++");
+    assert_eq!(wrapper.tool_name("cc"), "cc");
+
+    let command = wrapper.command("gcc", ["-c", "a.c"]).unwrap();
+    assert_eq!(
+        command.get_program(),
+        dir.path().join("toolchain/bin/x86_64-linux-gnu-gcc")
+    );
+    let sysroot = format!(
+        "--sysroot={}",
+        dir.path().join("toolchain/x86_64-linux-gnu/libc").display()
+    );
+    let args: Vec<_> = command.get_args().map(|a| a.to_str().unwrap()).collect();
+    assert_eq!(args, [sysroot.as_str(), "-O2", "-c", "a.c"]);
+    let removed: Vec<_> = command
+        .get_envs()
+        .filter(|(_, value)| value.is_none())
+        .map(|(name, _)| name.to_str().unwrap())
+        .collect();
+    assert!(removed.contains(&"CPATH"), "{removed:?}");
+    assert!(removed.contains(&"LIBRARY_PATH"), "{removed:?}");
+
+    // The caller's sysroot wins; tools without one get none.
+    let command = wrapper
+        .command("gcc", ["--sysroot", "/other", "-c", "a.c"])
+        .unwrap();
+    assert_eq!(command.get_args().count(), 5);
+    let command = wrapper.command("objcopy", ["-O", "binary"]).unwrap();
+    assert_eq!(command.get_args().count(), 2);
+
+    for tool in ["gdb", "../bin/x86_64-linux-gnu-gcc", ""] {
+        assert!(
+            matches!(
+                wrapper.command(tool, ["-v"]),
+                Err(Error::UnknownTool { .. })
+            ),
+            "{tool}"
+        );
+    }
+}
+
+#[test]
+fn parses_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(CONFIG_FILE);
+    fs::write(
+        &path,
+        "toolchain = \"../tc\"\ntriple = \"aarch64-linux-gnu\"\n[flags]\n\"g++\" = [\"-std=c++14\"]\n",
+    )
+    .unwrap();
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.sysroot, None);
+    assert_eq!(config.scrub[0], "CPATH");
+    assert_eq!(config.flags["g++"], ["-std=c++14"]);
+    let again: Config = toml::from_str(&config.to_toml()).unwrap();
+    assert_eq!(again, config);
+
+    fs::write(&path, "toolchain = \"../tc\"\ntriple = \"aarch64\"\n").unwrap();
+    assert!(matches!(Config::load(&path), Err(Error::Config { .. })));
+}
+
+#[test]
+fn installs_relocatable_wrappers() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = dir.path().join("tree");
+    let installation = toolchain(&tree.join("prebuilts/x86_64/x86_64-linux-gnu/7.5.0"));
+    let out = tree.join("wrappers/x86_64-linux-gnu");
+    let installed = install(&installation, &out, Path::new(WRAPPER)).unwrap();
+    assert_eq!(
+        installed.tools,
+        [
+            "x86_64-linux-gnu-g++",
+            "x86_64-linux-gnu-gcc",
+            "x86_64-linux-gnu-objcopy"
+        ]
+    );
+    assert_eq!(
+        installed.config.toolchain,
+        Path::new("../../prebuilts/x86_64/x86_64-linux-gnu/7.5.0")
+    );
+    assert_eq!(
+        installed.config.sysroot.as_deref(),
+        Some(Path::new("x86_64-linux-gnu/libc"))
+    );
+
+    // Hand-added flags survive reinstalling.
+    let mut config = Config::load(out.join(CONFIG_FILE)).unwrap();
+    config.flags.insert("gcc".into(), vec!["-g0".into()]);
+    fs::write(out.join(CONFIG_FILE), config.to_toml()).unwrap();
+    install(&installation, &out, Path::new(WRAPPER)).unwrap();
+
+    // The whole tree moves; the wrappers follow.
+    let moved = dir.path().join("moved");
+    fs::rename(&tree, &moved).unwrap();
+    let out = moved.join("wrappers/x86_64-linux-gnu");
+    let (code, stdout, stderr) = run(&out.join("x86_64-linux-gnu-gcc"), &["-c", "a.c"]);
+    assert_eq!(code, 0, "{stderr}");
+    let sysroot = moved.join("prebuilts/x86_64/x86_64-linux-gnu/7.5.0/x86_64-linux-gnu/libc");
+    assert_eq!(
+        stdout,
+        format!(
+            "tool=x86_64-linux-gnu-gcc\narg=--sysroot={}\narg=-g0\narg=-c\narg=a.c\n\
+             CPATH=unset\nHOME=/home/builder\n",
+            sysroot.display()
+        )
+    );
+
+    // Unprefixed links work too.
+    symlink("x86_64-linux-gnu-objcopy", out.join("objcopy")).unwrap();
+    let (code, stdout, _) = run(&out.join("objcopy"), &["-O", "binary"]);
+    assert_eq!(code, 0);
+    assert!(stdout.starts_with("tool=x86_64-linux-gnu-objcopy\narg=-O\narg=binary\n"));
+}
+
+#[test]
+fn reports_wrapper_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    let installation = toolchain(&dir.path().join("toolchain"));
+    let out = dir.path().join("wrappers");
+    install(&installation, &out, Path::new(WRAPPER)).unwrap();
+
+    let gdb: PathBuf = out.join("x86_64-linux-gnu-gdb");
+    fs::hard_link(out.join("x86_64-linux-gnu-gcc"), &gdb).unwrap();
+    let (code, _, stderr) = run(&gdb, &[]);
+    assert_eq!(code, 127);
+    assert!(
+        stderr.starts_with("linaro-wrap: gdb is not in the toolchain"),
+        "{stderr}"
+    );
+
+    fs::remove_file(out.join(CONFIG_FILE)).unwrap();
+    let (code, _, stderr) = run(&out.join("x86_64-linux-gnu-gcc"), &[]);
+    assert_eq!(code, 127);
+    assert!(stderr.contains(CONFIG_FILE), "{stderr}");
+
+    let (code, _, stderr) = run(Path::new(WRAPPER), &["gcc"]);
+    assert_eq!(code, 127);
+    assert!(stderr.contains("cannot tell which tool to run"), "{stderr}");
+}
+
+#[test]
+fn logs_invocations() {
+    let dir = tempfile::tempdir().unwrap();
+    let installation = toolchain(&dir.path().join("toolchain"));
+    let out = dir.path().join("wrappers");
+    install(&installation, &out, Path::new(WRAPPER)).unwrap();
+    let gcc = out.join("x86_64-linux-gnu-gcc");
+
+    // Configured in the file, relative to it.
+    let mut config = Config::load(out.join(CONFIG_FILE)).unwrap();
+    config.log = Some("build.log".into());
+    config.log_env = vec!["HOME".into(), "CPATH".into(), "UNSET".into()];
+    fs::write(out.join(CONFIG_FILE), config.to_toml()).unwrap();
+    let (code, stdout, _) = run(&gcc, &["-c", "a.c"]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("arg=a.c\nCPATH=unset\n"), "{stdout}");
+    let (code, _, stderr) = run(&gcc, &["--fail"]);
+    assert_eq!((code, stderr.as_str()), (3, "failed\n"));
+    let (code, _, _) = run(&gcc, &["--crash"]);
+    assert_eq!(code, 128 + 15);
+
+    let invocations = log::read(&out.join("build.log")).unwrap();
+    assert_eq!(invocations.len(), 3);
+    let first = &invocations[0];
+    assert_eq!(first.tool, "gcc");
+    assert_eq!(
+        first.arguments[0],
+        dir.path()
+            .join("toolchain/bin/x86_64-linux-gnu-gcc")
+            .display()
+            .to_string()
+    );
+    assert!(first.arguments[1].starts_with("--sysroot="));
+    assert_eq!(&first.arguments[2..], ["-c", "a.c"]);
+    assert_eq!(first.directory, std::env::current_dir().unwrap());
+    assert_eq!(
+        first.env.iter().collect::<Vec<_>>(),
+        [(&"HOME".to_owned(), &"/home/builder".to_owned())]
+    );
+    assert!(first.scrubbed.contains(&"CPATH".to_owned()));
+    assert_eq!((first.status, first.signal), (Some(0), None));
+    assert_eq!(invocations[1].status, Some(3));
+    assert_eq!(
+        (invocations[2].status, invocations[2].signal),
+        (None, Some(15))
+    );
+
+    // The variable takes precedence; an unwritable log does not fail the build.
+    let output = Command::new(&gcc)
+        .arg("-v")
+        .env(LOG_VAR, dir.path().join("missing/build.log"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("linaro-wrap: warning: not logged: "),
+        "{stderr}"
+    );
+    assert_eq!(log::read(&out.join("build.log")).unwrap().len(), 3);
+}